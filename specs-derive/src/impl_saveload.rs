@@ -103,6 +103,31 @@ pub fn impl_saveload(ast: &mut DeriveInput) -> TokenStream {
 struct FieldMetaData {
     field: Field,
     skip_field: bool,
+    entities_field: bool,
+}
+
+impl FieldMetaData {
+    /// The expression converting `value` (a reference) into its `Data`.
+    fn convert_into(&self, value: TokenStream) -> TokenStream {
+        if self.skip_field {
+            quote! { Clone::clone(#value) }
+        } else if self.entities_field {
+            quote! { ConvertEntities::convert_into(#value, &mut ids) }
+        } else {
+            quote! { ConvertSaveload::convert_into(#value, &mut ids)? }
+        }
+    }
+
+    /// The expression converting `data` back from its `Data`.
+    fn convert_from(&self, data: TokenStream) -> TokenStream {
+        if self.skip_field {
+            data
+        } else if self.entities_field {
+            quote! { ConvertEntities::convert_from(#data, &mut ids) }
+        } else {
+            quote! { ConvertSaveload::convert_from(#data, &mut ids)? }
+        }
+    }
 }
 
 /// Implements all elements of saveload common to structs of any type
@@ -185,14 +210,10 @@ fn saveload_named_struct(
     };
 
     let field_ser = saveload_fields.iter().map(|field_meta| {
-        let field = &field_meta.field;
-        let field_ident = &field.ident;
+        let field_ident = &field_meta.field.ident;
+        let value = field_meta.convert_into(quote! { &self.#field_ident });
 
-        if field_meta.skip_field {
-            quote! { #field_ident: self.#field_ident.clone() }
-        } else {
-            quote! { #field_ident: ConvertSaveload::convert_into(&self.#field_ident, &mut ids)? }
-        }
+        quote! { #field_ident: #value }
     });
 
     let ser = quote! {
@@ -204,14 +225,10 @@ fn saveload_named_struct(
     let field_de = saveload_fields
         .iter()
         .map(|field_meta| {
-            let field = &field_meta.field;
-            let field_ident = &field.ident;
+            let field_ident = &field_meta.field.ident;
+            let value = field_meta.convert_from(quote! { data.#field_ident });
 
-            if field_meta.skip_field {
-                quote! { #field_ident: data.#field_ident }
-            } else {
-                quote! { #field_ident: ConvertSaveload::convert_from(data.#field_ident, &mut ids)? }
-            }
+            quote! { #field_ident: #value }
         })
         .collect::<Vec<_>>();
 
@@ -281,13 +298,7 @@ fn saveload_tuple_struct(
     let field_ser = saveload_fields
         .iter()
         .zip(field_ids.iter())
-        .map(|(field_meta, field_id)| {
-            if field_meta.skip_field {
-                quote! { self.#field_id.clone() }
-            } else {
-                quote! { ConvertSaveload::convert_into(&self.#field_id, &mut ids)? }
-            }
-        })
+        .map(|(field_meta, field_id)| field_meta.convert_into(quote! { &self.#field_id }))
         .collect::<Vec<_>>();
 
     let ser = quote! {
@@ -299,13 +310,7 @@ fn saveload_tuple_struct(
     let field_de = saveload_fields
         .iter()
         .zip(field_ids)
-        .map(|(field_meta, field_id)| {
-            if field_meta.skip_field {
-                quote! { data.#field_id }
-            } else {
-                quote! { ConvertSaveload::convert_from(data.#field_id, &mut ids)? }
-            }
-        })
+        .map(|(field_meta, field_id)| field_meta.convert_from(quote! { data.#field_id }))
         .collect::<Vec<_>>();
 
     let de = quote! {
@@ -331,6 +336,7 @@ where
             FieldMetaData {
                 field: resolved,
                 skip_field: field_should_skip(&f),
+                entities_field: field_holds_entities(f),
             }
         })
         .collect()
@@ -412,12 +418,9 @@ fn saveload_enum(data: &DataEnum, name: &Ident, generics: &Generics) -> Saveload
 
                 let field_ser = saveload_fields.iter().map(|field_meta| {
                     let field_ident = &field_meta.field.ident;
+                    let value = field_meta.convert_into(quote! { #field_ident });
 
-                    if field_meta.skip_field {
-                        quote!{ #field_ident: #field_ident.clone() }
-                    } else {
-                        quote!{ #field_ident: ConvertSaveload::convert_into(#field_ident, &mut ids)? }
-                    }
+                    quote!{ #field_ident: #value }
                 });
 
                 let names = fields
@@ -433,12 +436,9 @@ fn saveload_enum(data: &DataEnum, name: &Ident, generics: &Generics) -> Saveload
 
                 let field_de = saveload_fields.iter().map(|field_meta| {
                     let field_ident = &field_meta.field.ident;
+                    let value = field_meta.convert_from(quote! { #field_ident });
 
-                    if field_meta.skip_field {
-                        quote!{ #field_ident: #field_ident }
-                    } else {
-                        quote!{ #field_ident: ConvertSaveload::convert_from(#field_ident, &mut ids)? }
-                    }
+                    quote!{ #field_ident: #value }
                 })
                 .collect::<Vec<_>>();
 
@@ -459,13 +459,7 @@ fn saveload_enum(data: &DataEnum, name: &Ident, generics: &Generics) -> Saveload
                 let field_ser = saveload_fields
                     .iter()
                     .zip(field_ids.iter())
-                    .map(|(field_meta, field_ident)| {
-                        if field_meta.skip_field {
-                            quote! { #field_ident.clone() }
-                        } else {
-                            quote! { ConvertSaveload::convert_into(#field_ident, &mut ids)? }
-                        }
-                    })
+                    .map(|(field_meta, field_ident)| field_meta.convert_into(quote! { #field_ident }))
                     .collect::<Vec<_>>();
 
                 big_match_ser = quote! {
@@ -476,13 +470,7 @@ fn saveload_enum(data: &DataEnum, name: &Ident, generics: &Generics) -> Saveload
                 let field_de = saveload_fields
                     .iter()
                     .zip(field_ids.iter())
-                    .map(|(field_meta, field_ident)| {
-                        if field_meta.skip_field {
-                            quote! { #field_ident.clone() }
-                        } else {
-                            quote! { ConvertSaveload::convert_from(#field_ident, &mut ids)? }
-                        }
-                    })
+                    .map(|(field_meta, field_ident)| field_meta.convert_from(quote! { #field_ident }))
                     .collect::<Vec<_>>();
 
                 big_match_de = quote! {
@@ -555,8 +543,19 @@ fn field_should_skip(field: &Field) -> bool {
     field.attrs.iter().any(attribute_is_skip)
 }
 
+fn attribute_is_entities(attribute: &Attribute) -> bool {
+    attribute.path.is_ident("convert_save_load_entities")
+}
+
+fn field_holds_entities(field: &Field) -> bool {
+    field.attrs.iter().any(attribute_is_entities)
+}
+
 fn replace_field(field: &mut Field) {
-    if !field_should_skip(field) {
+    if field_holds_entities(field) {
+        let ty = &field.ty;
+        field.ty = parse_quote!(<#ty as ConvertEntities<MA>>::Data);
+    } else if !field_should_skip(field) {
         replace_entity_type(&mut field.ty);
     }
 
@@ -613,7 +612,7 @@ fn replace_attributes(attrs: &mut Vec<Attribute>) {
     let output_attrs = attrs
         .iter()
         .filter_map(|attr| {
-            if attr.path.is_ident("convert_save_load_skip_convert") {
+            if attribute_is_skip(attr) || attribute_is_entities(attr) {
                 None
             } else if attr.path.is_ident("convert_save_load_attr") {
                 match attr.parse_args_with(single_parse_outer_from_args) {
@@ -1,5 +1,5 @@
-//! Implements the `#[derive(Component)]`, `#[derive(Saveload)]` macro and
-//! `#[component]` attribute for [Specs][sp].
+//! Implements the `#[derive(Component)]`, `#[derive(Saveload)]`,
+//! `#[derive(Bundle)]` macro and `#[component]` attribute for [Specs][sp].
 //!
 //! [sp]: https://slide-rs.github.io/specs-website/
 
@@ -15,7 +15,7 @@ extern crate syn;
 use proc_macro::TokenStream;
 use syn::{
     parse::{Parse, ParseStream, Result},
-    DeriveInput, Path,
+    Data, DeriveInput, Path,
 };
 
 mod impl_saveload;
@@ -75,21 +75,101 @@ fn impl_component(ast: &DeriveInput) -> proc_macro2::TokenStream {
     }
 }
 
+/// Custom derive macro for the `Bundle` trait, which adds every field of a
+/// struct to the entity as a component.
+///
+/// Fields which are bundles themselves need the `#[bundle]` attribute.
+///
+/// Requires `Builder` and `Bundle` to be in scope.
+///
+/// ## Example
+///
+/// ```rust,ignore
+/// use specs::world::{Builder, Bundle};
+///
+/// #[derive(Bundle)]
+/// struct BodyBundle {
+///     pos: Pos,
+///     vel: Vel,
+/// }
+///
+/// #[derive(Bundle)]
+/// struct PlayerBundle {
+///     #[bundle]
+///     body: BodyBundle,
+///     health: Health,
+/// }
+///
+/// world.create_entity().with_bundle(PlayerBundle::default()).build();
+/// ```
+#[proc_macro_derive(Bundle, attributes(bundle))]
+pub fn bundle(input: TokenStream) -> TokenStream {
+    let ast = syn::parse(input).unwrap();
+    let gen = impl_bundle(&ast);
+    gen.into()
+}
+
+fn impl_bundle(ast: &DeriveInput) -> proc_macro2::TokenStream {
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let fields = match ast.data {
+        Data::Struct(ref data) => &data.fields,
+        _ => panic!("Only structs can derive `Bundle`"),
+    };
+    let adds = fields.iter().enumerate().map(|(i, field)| {
+        let member = match field.ident {
+            Some(ref ident) => quote!(#ident),
+            None => {
+                let index = syn::Index::from(i);
+                quote!(#index)
+            }
+        };
+        if field.attrs.iter().any(|attr| attr.path.is_ident("bundle")) {
+            quote!(.with_bundle(self.#member))
+        } else {
+            quote!(.with(self.#member))
+        }
+    });
+
+    quote! {
+        impl #impl_generics Bundle for #name #ty_generics #where_clause {
+            fn add_to_entity<B: Builder>(self, builder: B) -> B {
+                builder #(#adds)*
+            }
+        }
+    }
+}
+
 /// Custom derive macro for the `ConvertSaveload` trait.
 ///
 /// Requires `Entity`, `ConvertSaveload`, `Marker` to be in a scope
 ///
+/// Fields holding containers of entities like `Vec<Entity>` need the
+/// `#[convert_save_load_entities]` attribute, which converts them with
+/// `ConvertEntities` (that has to be in scope as well).
+///
 /// ## Example
 ///
 /// ```rust,ignore
-/// use specs::{Entity, saveload::{ConvertSaveload, Marker}};
+/// use specs::{Entity, saveload::{ConvertEntities, ConvertSaveload, Marker}};
 ///
 /// #[derive(ConvertSaveload)]
 /// struct Target(Entity);
+///
+/// #[derive(ConvertSaveload)]
+/// struct Targets {
+///     #[convert_save_load_entities]
+///     all: Vec<Entity>,
+/// }
 /// ```
 #[proc_macro_derive(
     ConvertSaveload,
-    attributes(convert_save_load_attr, convert_save_load_skip_convert)
+    attributes(
+        convert_save_load_attr,
+        convert_save_load_skip_convert,
+        convert_save_load_entities
+    )
 )]
 pub fn saveload(input: TokenStream) -> TokenStream {
     use impl_saveload::impl_saveload;
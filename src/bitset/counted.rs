@@ -0,0 +1,142 @@
+use std::iter::FromIterator;
+
+use hibitset::{BitSet, BitSetLike};
+
+use crate::{join::Join, world::Index};
+
+/// A `BitSet` which keeps track of the number of indices it contains, so
+/// `len` is O(1).
+///
+/// `hibitset`'s `BitSet` doesn't store counts in its layers, so this counts
+/// the indices as they're added and removed. Joins over any bitset can count
+/// their remaining indices with `ExactSizeIterator::len`, which only visits
+/// the words which have bits set.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::{bitset::CountedBitSet, prelude::*};
+///
+/// let mut dirty = CountedBitSet::new();
+/// dirty.add(3);
+/// dirty.add(5);
+/// dirty.add(3);
+/// assert_eq!(dirty.len(), 2);
+///
+/// dirty.remove(3);
+/// assert_eq!(dirty.len(), 1);
+/// assert_eq!((&dirty).join().collect::<Vec<_>>(), vec![5]);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CountedBitSet {
+    set: BitSet,
+    len: usize,
+}
+
+impl CountedBitSet {
+    /// Creates an empty `CountedBitSet`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds `id` to the set. Returns `true` if it was already in the set.
+    pub fn add(&mut self, id: Index) -> bool {
+        let contained = self.set.add(id);
+        if !contained {
+            self.len += 1;
+        }
+
+        contained
+    }
+
+    /// Removes `id` from the set. Returns `true` if it was in the set.
+    pub fn remove(&mut self, id: Index) -> bool {
+        let removed = self.set.remove(id);
+        if removed {
+            self.len -= 1;
+        }
+
+        removed
+    }
+
+    /// Returns `true` if `id` is in the set.
+    pub fn contains(&self, id: Index) -> bool {
+        self.set.contains(id)
+    }
+
+    /// Returns the number of indices in the set.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all indices from the set.
+    pub fn clear(&mut self) {
+        self.set.clear();
+        self.len = 0;
+    }
+
+    /// Returns the underlying `BitSet`.
+    pub fn as_bitset(&self) -> &BitSet {
+        &self.set
+    }
+
+    /// Returns the underlying `BitSet`, dropping the count.
+    pub fn into_bitset(self) -> BitSet {
+        self.set
+    }
+}
+
+impl From<BitSet> for CountedBitSet {
+    /// Counts the indices of `set`, visiting the words which have bits set.
+    fn from(set: BitSet) -> Self {
+        let len = (&set).join().len();
+
+        CountedBitSet { set, len }
+    }
+}
+
+impl FromIterator<Index> for CountedBitSet {
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = Index>,
+    {
+        let mut set = CountedBitSet::new();
+        for id in iter {
+            set.add(id);
+        }
+
+        set
+    }
+}
+
+impl BitSetLike for CountedBitSet {
+    #[inline]
+    fn layer3(&self) -> usize {
+        self.set.layer3()
+    }
+
+    #[inline]
+    fn layer2(&self, i: usize) -> usize {
+        self.set.layer2(i)
+    }
+
+    #[inline]
+    fn layer1(&self, i: usize) -> usize {
+        self.set.layer1(i)
+    }
+
+    #[inline]
+    fn layer0(&self, i: usize) -> usize {
+        self.set.layer0(i)
+    }
+
+    #[inline]
+    fn contains(&self, i: Index) -> bool {
+        self.set.contains(i)
+    }
+}
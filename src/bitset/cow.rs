@@ -0,0 +1,195 @@
+use std::{iter::FromIterator, sync::Arc};
+
+use hibitset::BitSetLike;
+
+use crate::{
+    bitset::{ext::CAPACITY, BITS},
+    world::Index,
+};
+
+/// Mask of the bits of an index within one word.
+const WORD: usize = (1 << BITS) - 1;
+
+/// A bitset whose snapshots share their bottom layer until it's changed,
+/// e.g. for keeping the masks of the last frames around for rollback.
+///
+/// It has the same layers as a `BitSet`, but the bottom layer is split into
+/// reference counted blocks, one for every word of the layer above. Taking a
+/// snapshot with `snapshot` only copies the upper layers, which are 64
+/// times smaller, and adding or removing an index copies at most the block
+/// it is in, if that's shared with another snapshot.
+///
+/// # Panics
+///
+/// Like `BitSet::add`, adding an index past the capacity of a `BitSet`
+/// panics.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::{bitset::CowBitSet, prelude::*};
+///
+/// let mut selection: CowBitSet = (0..1000).collect();
+/// let mut history = Vec::new();
+/// for frame in 0..10 {
+///     history.push(selection.snapshot());
+///     selection.remove(frame);
+/// }
+///
+/// assert!(!selection.contains(3));
+/// assert!(history[4].contains(4) && !history[4].contains(3));
+/// assert_eq!((&history[0]).join().count(), 1000);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CowBitSet {
+    layer3: usize,
+    layer2: Vec<usize>,
+    layer1: Vec<usize>,
+    // One block of `layer0` words for every word of `layer1`, `None` if all
+    // of its words are empty.
+    layer0: Vec<Option<Arc<Vec<usize>>>>,
+}
+
+impl CowBitSet {
+    /// Creates an empty `CowBitSet`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns a copy of this set which shares the blocks of the bottom
+    /// layer with it until either of them changes.
+    pub fn snapshot(&self) -> Self {
+        self.clone()
+    }
+
+    /// Adds `id` to the set. Returns `true` if it was already in the set.
+    pub fn add(&mut self, id: Index) -> bool {
+        assert!(
+            id < CAPACITY,
+            "Expected index to be less then {}, found {}",
+            CAPACITY,
+            id
+        );
+        if self.contains(id) {
+            return true;
+        }
+
+        let (i0, i1, i2) = offsets(id);
+        fill_up(&mut self.layer2, i2);
+        fill_up(&mut self.layer1, i1);
+        if self.layer0.len() <= i1 {
+            self.layer0.resize(i1 + 1, None);
+        }
+
+        let block = self.layer0[i1].get_or_insert_with(|| Arc::new(vec![0; 1 << BITS]));
+        let word = &mut Arc::make_mut(block)[i0 & WORD];
+        let was_empty = *word == 0;
+        *word |= 1 << (id as usize & WORD);
+        if was_empty {
+            let was_empty = self.layer1[i1] == 0;
+            self.layer1[i1] |= 1 << (i0 & WORD);
+            if was_empty {
+                let was_empty = self.layer2[i2] == 0;
+                self.layer2[i2] |= 1 << (i1 & WORD);
+                if was_empty {
+                    self.layer3 |= 1 << (i2 & WORD);
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Removes `id` from the set. Returns `true` if it was in the set.
+    pub fn remove(&mut self, id: Index) -> bool {
+        if !self.contains(id) {
+            return false;
+        }
+
+        let (i0, i1, i2) = offsets(id);
+        let block = self.layer0[i1].as_mut().expect("block of a contained index");
+        let word = &mut Arc::make_mut(block)[i0 & WORD];
+        *word &= !(1 << (id as usize & WORD));
+        if *word == 0 {
+            self.layer1[i1] &= !(1 << (i0 & WORD));
+            if self.layer1[i1] == 0 {
+                self.layer0[i1] = None;
+                self.layer2[i2] &= !(1 << (i1 & WORD));
+                if self.layer2[i2] == 0 {
+                    self.layer3 &= !(1 << (i2 & WORD));
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Returns `true` if `id` is in the set.
+    pub fn contains(&self, id: Index) -> bool {
+        let i0 = id as usize >> BITS;
+        self.layer0(i0) & (1 << (id as usize & WORD)) != 0
+    }
+
+    /// Removes all indices from the set.
+    pub fn clear(&mut self) {
+        *self = CowBitSet::new();
+    }
+}
+
+impl FromIterator<Index> for CowBitSet {
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = Index>,
+    {
+        let mut set = CowBitSet::new();
+        for id in iter {
+            set.add(id);
+        }
+
+        set
+    }
+}
+
+impl BitSetLike for CowBitSet {
+    #[inline]
+    fn layer3(&self) -> usize {
+        self.layer3
+    }
+
+    #[inline]
+    fn layer2(&self, i: usize) -> usize {
+        self.layer2.get(i).cloned().unwrap_or(0)
+    }
+
+    #[inline]
+    fn layer1(&self, i: usize) -> usize {
+        self.layer1.get(i).cloned().unwrap_or(0)
+    }
+
+    #[inline]
+    fn layer0(&self, i: usize) -> usize {
+        match self.layer0.get(i >> BITS) {
+            Some(Some(block)) => block[i & WORD],
+            _ => 0,
+        }
+    }
+
+    #[inline]
+    fn contains(&self, i: Index) -> bool {
+        CowBitSet::contains(self, i)
+    }
+}
+
+/// Returns the indices of the words of `layer0`, `layer1` and `layer2` which
+/// `id` is in.
+fn offsets(id: Index) -> (usize, usize, usize) {
+    let id = id as usize;
+
+    (id >> BITS, id >> (2 * BITS), id >> (3 * BITS))
+}
+
+fn fill_up(layer: &mut Vec<usize>, i: usize) {
+    if layer.len() <= i {
+        layer.resize(i + 1, 0);
+    }
+}
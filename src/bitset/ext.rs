@@ -0,0 +1,275 @@
+use std::ops::Range;
+
+use hibitset::{BitIter, BitSet, BitSetAnd, BitSetLike, BitSetNot};
+
+use crate::{bitset::BITS, world::Index};
+
+/// The number of indices a `BitSet` can hold.
+pub(crate) const CAPACITY: Index = 1 << (BITS * 4);
+
+/// In-place set operations on `BitSet`s.
+///
+/// These update the set instead of collecting a lazy combinator like
+/// `BitSetAnd` into a new one, and only visit the words of the layers which
+/// have bits set. That makes them cheap enough to keep a cached set of
+/// entities up to date incrementally.
+///
+/// Unlike `&=`, removing indices also clears the bits of the upper layers
+/// for words which become empty, so `is_empty` stays correct.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::prelude::*;
+///
+/// let mut cached: BitSet = (0..10).collect();
+/// let added: BitSet = (20..25).collect();
+/// let removed: BitSet = (5..8).collect();
+///
+/// cached.union_with(&added);
+/// cached.difference_with(&removed);
+/// cached.clear_range(0..2);
+/// assert_eq!(
+///     (&cached).join().collect::<Vec<_>>(),
+///     vec![2, 3, 4, 8, 9, 20, 21, 22, 23, 24]
+/// );
+///
+/// let even: BitSet = (0..30).filter(|id| id % 2 == 0).collect();
+/// cached.intersect_with(&even);
+/// assert_eq!((&cached).join().collect::<Vec<_>>(), vec![2, 4, 8, 20, 22, 24]);
+/// ```
+pub trait BitSetExt {
+    /// Adds all indices of `other` to this set.
+    fn union_with(&mut self, other: &BitSet);
+
+    /// Removes all indices which aren't in `other` from this set.
+    fn intersect_with<B: BitSetLike>(&mut self, other: &B);
+
+    /// Removes all indices of `other` from this set.
+    fn difference_with<B: BitSetLike>(&mut self, other: &B);
+
+    /// Removes all indices in `range` from this set.
+    fn clear_range(&mut self, range: Range<Index>);
+
+    /// Adds all indices in `range` to this set, setting whole words at a
+    /// time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` ends past the indices a `BitSet` can hold, like
+    /// `BitSet::add`.
+    fn add_range(&mut self, range: Range<Index>);
+
+    /// Removes all indices in `range` from this set, and returns `true` if
+    /// any of them was in the set, like `BitSet::remove`.
+    fn remove_range(&mut self, range: Range<Index>) -> bool;
+
+    /// Returns an iterator over the indices of this set in `range`, which
+    /// skips the words of the layers outside of it.
+    fn iter_range(&self, range: Range<Index>) -> BitIter<BitSetAnd<&BitSet, BitSetRange>>;
+}
+
+impl BitSetExt for BitSet {
+    fn union_with(&mut self, other: &BitSet) {
+        // The layers of a `BitSet` only have bits set for non-empty words,
+        // so or-ing them keeps that true.
+        *self |= other;
+    }
+
+    fn intersect_with<B: BitSetLike>(&mut self, other: &B) {
+        self.difference_with(&BitSetNot(other));
+    }
+
+    fn difference_with<B: BitSetLike>(&mut self, other: &B) {
+        remove_all(self, other);
+    }
+
+    fn clear_range(&mut self, range: Range<Index>) {
+        remove_all(self, &BitSetRange(range));
+    }
+
+    fn add_range(&mut self, range: Range<Index>) {
+        assert!(
+            range.end <= CAPACITY,
+            "Expected index to be less then {}, found {}",
+            CAPACITY,
+            range.end
+        );
+        // The layers of a `BitSetRange` only have bits set for non-empty
+        // words, like the ones of a `BitSet`.
+        *self |= &BitSetRange(range);
+    }
+
+    fn remove_range(&mut self, range: Range<Index>) -> bool {
+        remove_all(self, &BitSetRange(range)) != 0
+    }
+
+    fn iter_range(&self, range: Range<Index>) -> BitIter<BitSetAnd<&BitSet, BitSetRange>> {
+        BitSetAnd(self, BitSetRange(range)).iter()
+    }
+}
+
+/// Removes the indices of `other` from `set`, returning how many there were.
+fn remove_all<B: BitSetLike>(set: &mut BitSet, other: &B) -> usize {
+    // Walk the layers of both sets top-down, so only the words of `set` are
+    // visited, even if `other` is inverted. Removing bits only touches the
+    // words below the ones being walked, so they are read as we go.
+    let mut removed = 0;
+    for i2 in bits(set.layer3() & other.layer3()) {
+        for i1 in bits(set.layer2(i2) & other.layer2(i2)).map(|i| (i2 << BITS) | i) {
+            for i0 in bits(set.layer1(i1) & other.layer1(i1)).map(|i| (i1 << BITS) | i) {
+                let word = set.layer0(i0) & other.layer0(i0);
+                if word != 0 {
+                    // `BitSet` doesn't expose its layers, but xor-ing the
+                    // bits to remove masks them out of the word and clears
+                    // the upper layers if it becomes empty.
+                    *set ^= &Word(i0, word);
+                    removed += word.count_ones() as usize;
+                }
+            }
+        }
+    }
+
+    removed
+}
+
+/// Returns an iterator over the positions of the set bits of `word`.
+fn bits(mut word: usize) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if word == 0 {
+            return None;
+        }
+        let bit = word.trailing_zeros() as usize;
+        word &= word - 1;
+        Some(bit)
+    })
+}
+
+/// A `BitSetLike` holding the non-empty bits of word `.0` of layer 0.
+struct Word(usize, usize);
+
+impl Word {
+    /// Returns the word of `level` above the word of layer 0, which has the
+    /// bit of the word below set.
+    fn upper(&self, level: usize, i: usize) -> usize {
+        let below = self.0 >> (BITS * (level - 1));
+        if i == below >> BITS {
+            1 << (below & ((1 << BITS) - 1))
+        } else {
+            0
+        }
+    }
+}
+
+impl BitSetLike for Word {
+    #[inline]
+    fn layer3(&self) -> usize {
+        self.upper(3, 0)
+    }
+
+    #[inline]
+    fn layer2(&self, i: usize) -> usize {
+        self.upper(2, i)
+    }
+
+    #[inline]
+    fn layer1(&self, i: usize) -> usize {
+        self.upper(1, i)
+    }
+
+    #[inline]
+    fn layer0(&self, i: usize) -> usize {
+        if i == self.0 {
+            self.1
+        } else {
+            0
+        }
+    }
+
+    #[inline]
+    fn contains(&self, i: Index) -> bool {
+        self.layer0(i as usize >> BITS) & (1 << (i as usize & ((1 << BITS) - 1))) != 0
+    }
+}
+
+/// A `BitSetLike` containing the indices of a range, e.g. the entities of
+/// a chunk in a partitioning scheme which hands out entity ids by region.
+///
+/// Its layers are computed on the fly, so joining over it or intersecting
+/// it with another set skips the words outside of the range.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::{bitset::BitSetRange, prelude::*};
+///
+/// struct Pos(f32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Pos>();
+/// for i in 0..100 {
+///     world.create_entity().with(Pos(i as f32)).build();
+/// }
+///
+/// let pos = world.read_storage::<Pos>();
+/// let chunk: f32 = (&pos, BitSetRange(10..20)).join().map(|(pos, _)| pos.0).sum();
+/// assert_eq!(chunk, 145.0);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BitSetRange(pub Range<Index>);
+
+impl BitSetRange {
+    /// Returns the word `i` of `level`, whose bits stand for
+    /// `1 << (BITS * level)` indices each.
+    fn word(&self, level: usize, i: usize) -> usize {
+        let (start, end) = (self.0.start as usize, self.0.end as usize);
+        if start >= end {
+            return 0;
+        }
+
+        let shift = BITS * level;
+        let first = start >> shift;
+        let last = (end - 1) >> shift;
+        let low = i << BITS;
+        let high = low + (1 << BITS) - 1;
+        if last < low || first > high {
+            return 0;
+        }
+
+        let from = first.max(low) - low;
+        let to = last.min(high) - low;
+
+        (!0 >> ((1 << BITS) - 1 - to)) & (!0 << from)
+    }
+}
+
+impl BitSetLike for BitSetRange {
+    #[inline]
+    fn layer3(&self) -> usize {
+        self.word(3, 0)
+    }
+
+    #[inline]
+    fn layer2(&self, i: usize) -> usize {
+        self.word(2, i)
+    }
+
+    #[inline]
+    fn layer1(&self, i: usize) -> usize {
+        self.word(1, i)
+    }
+
+    #[inline]
+    fn layer0(&self, i: usize) -> usize {
+        self.word(0, i)
+    }
+
+    #[inline]
+    fn contains(&self, i: Index) -> bool {
+        self.0.start <= i && i < self.0.end
+    }
+}
@@ -0,0 +1,158 @@
+//! Implementations and structures related to bitsets.
+//!
+//! Normally used for `Join`s and filtering entities.
+//!
+//! The lazy combinators `BitSetAnd`, `BitSetOr`, `BitSetXor` and `BitSetNot`
+//! compose bitsets into expressions which are evaluated word by word while
+//! joining, without allocating intermediate sets:
+//!
+//! ```
+//! use specs::{
+//!     bitset::{BitSetAnd, BitSetNot},
+//!     prelude::*,
+//! };
+//!
+//! #[derive(Default)]
+//! struct Dead;
+//!
+//! impl Component for Dead {
+//!     type Storage = NullStorage<Self>;
+//! }
+//!
+//! let mut world = World::new();
+//! world.register::<Dead>();
+//! let entities: Vec<_> = (0..6).map(|_| world.create_entity().build()).collect();
+//! world.write_storage().insert(entities[2], Dead).unwrap();
+//!
+//! let selection: BitSet = entities[..4].iter().map(|e| e.id()).collect();
+//! let visible: BitSet = entities[1..].iter().map(|e| e.id()).collect();
+//! let dead = world.read_storage::<Dead>();
+//!
+//! let targets = BitSetAnd(BitSetAnd(&selection, &visible), BitSetNot(dead.mask()));
+//! let targets: Vec<_> = (&world.entities(), targets).join().map(|(e, _)| e).collect();
+//! assert_eq!(targets, vec![entities[1], entities[3]]);
+//! ```
+//!
+//! All bitsets can be joined over, yielding the indices they contain. An
+//! `AtomicBitSet` can also be added to through a shared reference with
+//! `add_atomic`, so parallel systems can collect entities without a `Mutex`.
+//!
+//! ## Examples
+//!
+//! ```
+//! # #[cfg(feature = "parallel")]
+//! # {
+//! use specs::{bitset::AtomicBitSet, prelude::*};
+//!
+//! struct Health(i32);
+//!
+//! impl Component for Health {
+//!     type Storage = VecStorage<Self>;
+//! }
+//!
+//! let mut world = World::new();
+//! world.register::<Health>();
+//! for i in 0..100 {
+//!     world.create_entity().with(Health(i % 10)).build();
+//! }
+//!
+//! let dying = AtomicBitSet::new();
+//! let (entities, health) = (world.entities(), world.read_storage::<Health>());
+//! (&entities, &health).par_join().for_each(|(entity, health)| {
+//!     if health.0 == 0 {
+//!         dying.add_atomic(entity.id());
+//!     }
+//! });
+//!
+//! assert_eq!((&dying).join().count(), 10);
+//! for (entity, _) in (&entities, &dying).join() {
+//!     assert_eq!(health.get(entity).unwrap().0, 0);
+//! }
+//! # }
+//! ```
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+
+pub use hibitset::{AtomicBitSet, BitSet, BitSetAll, BitSetAnd, BitSetLike, BitSetNot, BitSetOr, BitSetXor};
+
+use crate::join::Join;
+#[cfg(feature = "parallel")]
+use crate::join::ParJoin;
+use crate::world::Index;
+
+pub use self::{
+    counted::CountedBitSet,
+    cow::CowBitSet,
+    ext::{BitSetExt, BitSetRange},
+    split::split_ranges,
+};
+
+mod counted;
+mod cow;
+mod ext;
+#[cfg(feature = "serde")]
+pub mod runs;
+mod split;
+
+/// Base two log of the number of bits in a `usize`, and so of the number of
+/// words of a layer below one word of the layer above.
+pub(crate) const BITS: usize = (std::mem::size_of::<usize>() * 8).trailing_zeros() as usize;
+
+/// Iterates over the positions of the set bits of `word`.
+pub(crate) fn bits(mut word: usize) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if word == 0 {
+            None
+        } else {
+            let bit = word.trailing_zeros() as usize;
+            word &= word - 1;
+            Some(bit)
+        }
+    })
+}
+
+macro_rules! define_bit_join {
+    ( impl < ( $( $lifetime:tt )* ) ( $( $arg:ident ),* ) > for $bitset:ty ) => {
+        impl<$( $lifetime, )* $( $arg ),*> Join for $bitset
+            where $( $arg: BitSetLike ),*
+        {
+            type Type = Index;
+            type Value = ();
+            type Mask = $bitset;
+
+            // SAFETY: This just moves a `BitSet`; invariants of `Join` are fulfilled, since `Self::Value` cannot be mutated.
+            unsafe fn open(self) -> (Self::Mask, Self::Value) {
+                (self, ())
+            }
+
+            // SAFETY: No unsafe code and no invariants to meet.
+            unsafe fn get(_: &mut Self::Value, id: Index) -> Self::Type {
+                id
+            }
+        }
+
+        #[cfg(feature = "parallel")]
+        unsafe impl<$( $lifetime, )* $( $arg ),*> ParJoin for $bitset
+            where $( $arg: BitSetLike ),*
+        { }
+    }
+}
+
+define_bit_join!(impl<()()> for BitSet);
+define_bit_join!(impl<('a)()> for &'a BitSet);
+define_bit_join!(impl<()()> for AtomicBitSet);
+define_bit_join!(impl<()()> for CountedBitSet);
+define_bit_join!(impl<('a)()> for &'a CountedBitSet);
+define_bit_join!(impl<()()> for CowBitSet);
+define_bit_join!(impl<('a)()> for &'a CowBitSet);
+define_bit_join!(impl<()()> for BitSetRange);
+define_bit_join!(impl<('a)()> for &'a BitSetRange);
+define_bit_join!(impl<('a)()> for &'a AtomicBitSet);
+define_bit_join!(impl<()(A)> for BitSetNot<A>);
+define_bit_join!(impl<('a)(A)> for &'a BitSetNot<A>);
+define_bit_join!(impl<()(A, B)> for BitSetAnd<A, B>);
+define_bit_join!(impl<('a)(A, B)> for &'a BitSetAnd<A, B>);
+define_bit_join!(impl<()(A, B)> for BitSetOr<A, B>);
+define_bit_join!(impl<('a)(A, B)> for &'a BitSetOr<A, B>);
+define_bit_join!(impl<()(A, B)> for BitSetXor<A, B>);
+define_bit_join!(impl<('a)()> for &'a dyn BitSetLike);
@@ -0,0 +1,78 @@
+//! Serialization of `BitSet`s as runs of consecutive indices, for use with
+//! `#[serde(with = "specs::bitset::runs")]`.
+//!
+//! A set is written as a sequence of `(start, len)` pairs, so sets of
+//! neighbouring entities, like a selection or the entities of a region,
+//! stay small.
+//!
+//! **Note:** The indices are written as they are. They only refer to the
+//! same entities in the world they were saved from; to save entities for
+//! another world, save their markers instead, see `saveload`.
+//!
+//! ## Examples
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use specs::BitSet;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Selection {
+//!     #[serde(with = "specs::bitset::runs")]
+//!     selected: BitSet,
+//! }
+//!
+//! let mut selected = BitSet::new();
+//! for id in (0..4).chain(10..12) {
+//!     selected.add(id);
+//! }
+//!
+//! let json = serde_json::to_string(&Selection { selected }).unwrap();
+//! assert_eq!(json, r#"{"selected":[[0,4],[10,2]]}"#);
+//!
+//! let selection: Selection = serde_json::from_str(&json).unwrap();
+//! assert!(selection.selected.contains(11));
+//!
+//! // Indices a `BitSet` can't hold are an error.
+//! assert!(serde_json::from_str::<Selection>(r#"{"selected":[[4294967295,2]]}"#).is_err());
+//! ```
+
+use hibitset::{BitSet, BitSetLike};
+use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+use crate::{bitset::ext::CAPACITY, world::Index};
+
+/// Serializes `set` as runs of consecutive indices.
+pub fn serialize<S>(set: &BitSet, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut runs: Vec<(Index, Index)> = Vec::new();
+    for id in set.iter() {
+        match runs.last_mut() {
+            Some((start, len)) if *start + *len == id => *len += 1,
+            _ => runs.push((id, 1)),
+        }
+    }
+
+    serializer.collect_seq(runs)
+}
+
+/// Deserializes a `BitSet` written by `serialize`.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<BitSet, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let runs = Vec::<(Index, Index)>::deserialize(deserializer)?;
+    let mut set = BitSet::new();
+    for (start, len) in runs {
+        let end = start
+            .checked_add(len)
+            .filter(|&end| end <= CAPACITY)
+            .ok_or_else(|| D::Error::custom("bitset run out of range"))?;
+        for id in start..end {
+            set.add(id);
+        }
+    }
+
+    Ok(set)
+}
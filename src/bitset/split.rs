@@ -0,0 +1,88 @@
+use std::ops::Range;
+
+use hibitset::BitSetLike;
+
+use crate::{
+    bitset::{bits, BITS},
+    world::Index,
+};
+
+/// Splits the indices of `set` into up to `parts` disjoint ranges, e.g. for
+/// handing them to different threads.
+///
+/// Starting at the top layer, the words which have bits set are split into
+/// their set bits one layer further down, until there are at least `parts`
+/// of them or the second-lowest layer is reached. These are then grouped
+/// into `parts` ranges of neighbouring words. Only the upper layers are
+/// visited, so splitting is cheap. `par_join` splits its mask the same way
+/// while it runs.
+///
+/// Every index of `set` is in one of the ranges, which can be joined over
+/// with `BitSetRange`. The ranges are sorted and may also contain indices
+/// which aren't in `set`.
+///
+/// # Panics
+///
+/// Panics if `parts` is 0.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::{
+///     bitset::{split_ranges, BitSetAnd, BitSetNot, BitSetRange},
+///     prelude::*,
+/// };
+///
+/// let alive: BitSet = (0..10_000).collect();
+/// let dead: BitSet = (0..10_000).filter(|id| id % 3 == 0).collect();
+/// let set = BitSetAnd(&alive, BitSetNot(&dead));
+///
+/// let ranges = split_ranges(&set, 4);
+/// assert_eq!(ranges.len(), 4);
+///
+/// let threads: Vec<_> = ranges
+///     .into_iter()
+///     .map(|range| {
+///         let (alive, dead) = (alive.clone(), dead.clone());
+///         std::thread::spawn(move || {
+///             let set = BitSetAnd(&alive, BitSetNot(&dead));
+///             (set, BitSetRange(range)).join().count()
+///         })
+///     })
+///     .collect();
+/// let count: usize = threads.into_iter().map(|t| t.join().unwrap()).sum();
+/// assert_eq!(count, 10_000 - 3_334);
+/// ```
+pub fn split_ranges<B>(set: &B, parts: usize) -> Vec<Range<Index>>
+where
+    B: BitSetLike,
+{
+    assert!(parts != 0, "number of parts must be non-zero");
+
+    // The indices of the words of layer `level - 1` which have bits set.
+    let mut level = 3;
+    let mut words: Vec<usize> = bits(set.layer3()).collect();
+    while words.len() < parts && level > 1 {
+        words = words
+            .into_iter()
+            .flat_map(|word| {
+                bits(set.get_from_layer(level - 1, word)).map(move |bit| (word << BITS) | bit)
+            })
+            .collect();
+        level -= 1;
+    }
+
+    // Each word stands for the indices below one bit of `level`.
+    let shift = BITS * level;
+    let per_part = 1 + words.len().saturating_sub(1) / parts;
+
+    words
+        .chunks(per_part)
+        .map(|chunk| {
+            let first = chunk[0] << shift;
+            let last = (chunk[chunk.len() - 1] + 1) << shift;
+
+            first as Index..last as Index
+        })
+        .collect()
+}
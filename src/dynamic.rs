@@ -0,0 +1,255 @@
+//! Component types which are only known at runtime, e.g. the ones defined by
+//! a script or in an editor.
+//!
+//! A dynamic component type is registered by name with `register`, and each
+//! of its components is a `serde_json::Value`, usually an object with named
+//! fields. Dynamic storages are fetched by name with `read` and `write`, can
+//! be joined like `Storage`s and lose the components of deleted entities.
+//! With the `serde` feature enabled, they are saved and loaded with
+//! `SaveloadRegistry::register_dynamic`.
+//!
+//! ## Examples
+//!
+//! ```
+//! use serde_json::json;
+//! use specs::{dynamic, prelude::*};
+//!
+//! let mut world = World::new();
+//! dynamic::register(&mut world, "quest");
+//!
+//! let npc = world.create_entity().build();
+//! dynamic::write(&world, "quest")
+//!     .unwrap()
+//!     .insert(npc, json!({ "id": 7, "done": false }))
+//!     .unwrap();
+//!
+//! {
+//!     let quests = dynamic::read(&world, "quest").unwrap();
+//!     assert_eq!(quests.get(npc).unwrap()["id"], 7);
+//!     assert_eq!((&world.entities(), &quests).join().count(), 1);
+//! }
+//!
+//! world.delete_entity(npc).unwrap();
+//! assert!(dynamic::read(&world, "quest").unwrap().is_empty());
+//! ```
+
+use std::{
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+};
+
+use hibitset::{BitSet, BitSetLike};
+use serde_json::Value;
+use shred::{Fetch, FetchMut, ResourceId, World};
+
+use crate::{
+    error::{Error, WrongGeneration},
+    join::Join,
+    world::{EntitiesRes, Entity, Index},
+};
+
+/// The registered dynamic component types, mapping their names to the ids
+/// of their `DynamicData` resources.
+#[derive(Debug, Default)]
+pub struct DynamicComponents {
+    ids: HashMap<String, u64>,
+}
+
+impl DynamicComponents {
+    /// Returns `true` if a dynamic component type is registered as `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.ids.contains_key(name)
+    }
+
+    /// Returns the names of all registered dynamic component types.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.ids.keys().map(String::as_str)
+    }
+
+    fn resource_id(&self, name: &str) -> Option<ResourceId> {
+        self.ids.get(name).map(|&id| data_id(id))
+    }
+}
+
+/// The components of one dynamic component type, accessed through a
+/// `DynamicStorage`.
+#[derive(Debug, Default)]
+pub struct DynamicData {
+    mask: BitSet,
+    components: HashMap<Index, Value>,
+}
+
+fn data_id(id: u64) -> ResourceId {
+    ResourceId::new_with_dynamic_id::<DynamicData>(id)
+}
+
+/// The storage of a dynamic component type, checking the generations of
+/// the entities like `Storage` does.
+pub struct DynamicStorage<'e, D> {
+    entities: Fetch<'e, EntitiesRes>,
+    data: D,
+}
+
+/// A dynamic storage with read access.
+pub type ReadDynamic<'e> = DynamicStorage<'e, Fetch<'e, DynamicData>>;
+
+/// A dynamic storage with write access.
+pub type WriteDynamic<'e> = DynamicStorage<'e, FetchMut<'e, DynamicData>>;
+
+/// Registers a dynamic component type as `name`. Registering a name again
+/// keeps its components.
+pub fn register(world: &mut World, name: &str) {
+    let id = {
+        let mut components = world
+            .entry::<DynamicComponents>()
+            .or_insert_with(Default::default);
+        let next = components.ids.len() as u64 + 1;
+        *components.ids.entry(name.to_owned()).or_insert(next)
+    };
+
+    if !world.has_value_raw(data_id(id)) {
+        world.insert_by_id(data_id(id), DynamicData::default());
+    }
+}
+
+/// Fetches the storage of the dynamic component type `name` for reading,
+/// or `None` if it isn't registered.
+///
+/// # Panics
+///
+/// Panics if the storage is borrowed mutably.
+pub fn read<'e>(world: &'e World, name: &str) -> Option<ReadDynamic<'e>> {
+    let id = world.try_fetch::<DynamicComponents>()?.resource_id(name)?;
+
+    Some(DynamicStorage {
+        entities: world.fetch(),
+        data: world.try_fetch_by_id(id)?,
+    })
+}
+
+/// Fetches the storage of the dynamic component type `name` for writing,
+/// or `None` if it isn't registered.
+///
+/// # Panics
+///
+/// Panics if the storage is already borrowed.
+pub fn write<'e>(world: &'e World, name: &str) -> Option<WriteDynamic<'e>> {
+    let id = world.try_fetch::<DynamicComponents>()?.resource_id(name)?;
+
+    Some(DynamicStorage {
+        entities: world.fetch(),
+        data: world.try_fetch_mut_by_id(id)?,
+    })
+}
+
+/// Drops the dynamic components of `deleted` entities.
+pub(crate) fn forget(world: &World, deleted: &[Entity]) {
+    let components = match world.try_fetch::<DynamicComponents>() {
+        Some(components) => components,
+        None => return,
+    };
+
+    for &id in components.ids.values() {
+        if let Some(mut data) = world.try_fetch_mut_by_id::<DynamicData>(data_id(id)) {
+            for entity in deleted {
+                if data.mask.remove(entity.id()) {
+                    data.components.remove(&entity.id());
+                }
+            }
+        }
+    }
+}
+
+impl<'e, D> DynamicStorage<'e, D>
+where
+    D: Deref<Target = DynamicData>,
+{
+    /// Returns the component of `e`, if it has one.
+    pub fn get(&self, e: Entity) -> Option<&Value> {
+        if self.data.mask.contains(e.id()) && self.entities.is_alive(e) {
+            self.data.components.get(&e.id())
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if `e` has a component.
+    pub fn contains(&self, e: Entity) -> bool {
+        self.get(e).is_some()
+    }
+
+    /// Returns the bitset of the entities with a component.
+    pub fn mask(&self) -> &BitSet {
+        &self.data.mask
+    }
+
+    /// Returns the number of components.
+    pub fn count(&self) -> usize {
+        self.data.components.len()
+    }
+
+    /// Returns `true` if there are no components.
+    pub fn is_empty(&self) -> bool {
+        self.data.mask.is_empty()
+    }
+}
+
+impl<'e, D> DynamicStorage<'e, D>
+where
+    D: DerefMut<Target = DynamicData>,
+{
+    /// Returns the component of `e` mutably, if it has one.
+    pub fn get_mut(&mut self, e: Entity) -> Option<&mut Value> {
+        if self.data.mask.contains(e.id()) && self.entities.is_alive(e) {
+            self.data.components.get_mut(&e.id())
+        } else {
+            None
+        }
+    }
+
+    /// Inserts a component for `e`, returning the component it replaced.
+    pub fn insert(&mut self, e: Entity, v: Value) -> Result<Option<Value>, Error> {
+        if !self.entities.is_alive(e) {
+            return Err(Error::WrongGeneration(WrongGeneration {
+                action: "insert dynamic component for entity",
+                actual_gen: self.entities.entity(e.id()).gen(),
+                entity: e,
+            }));
+        }
+
+        self.data.mask.add(e.id());
+        Ok(self.data.components.insert(e.id(), v))
+    }
+
+    /// Removes the component of `e`, if it has one.
+    pub fn remove(&mut self, e: Entity) -> Option<Value> {
+        if self.entities.is_alive(e) && self.data.mask.remove(e.id()) {
+            self.data.components.remove(&e.id())
+        } else {
+            None
+        }
+    }
+
+    /// Removes all components.
+    pub fn clear(&mut self) {
+        self.data.mask.clear();
+        self.data.components.clear();
+    }
+}
+
+impl<'a, 'e, D> Join for &'a DynamicStorage<'e, D>
+where
+    D: Deref<Target = DynamicData>,
+{
+    type Mask = &'a BitSet;
+    type Type = &'a Value;
+    type Value = &'a HashMap<Index, Value>;
+
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        (&self.data.mask, &self.data.components)
+    }
+
+    unsafe fn get(v: &mut Self::Value, i: Index) -> &'a Value {
+        &v[&i]
+    }
+}
@@ -0,0 +1,513 @@
+//! Parent/child relations between entities.
+//!
+//! An entity becomes the child of another one by getting a `Parent`
+//! component. The `HierarchyMaintenanceSystem` keeps the `Hierarchy`
+//! resource up to date with the changes of the `Parent` storage: it maps
+//! parents to their children, sorts the children so parents come before
+//! their children, and publishes `HierarchyEvent`s when parents change.
+//! The `PropagationSystem` uses them to compute components from the ones of
+//! the parents, e.g. world transforms.
+//!
+//! `WorldExt::delete_hierarchy` deletes an entity together with its
+//! descendants. With `HierarchyMaintenanceSystem::cascade_deletion`, every
+//! deletion does.
+//!
+//! ## Examples
+//!
+//! ```
+//! use specs::{
+//!     hierarchy::{Hierarchy, HierarchyMaintenanceSystem, Parent},
+//!     prelude::*,
+//! };
+//!
+//! let mut world = World::new();
+//! let mut dispatcher = DispatcherBuilder::new()
+//!     .with(HierarchyMaintenanceSystem::new(), "hierarchy", &[])
+//!     .build();
+//! dispatcher.setup(&mut world);
+//!
+//! let car = world.create_entity().build();
+//! let wheel = world.create_entity().with(Parent(car)).build();
+//! let bolt = world.create_entity().with(Parent(wheel)).build();
+//! dispatcher.dispatch(&world);
+//!
+//! let hierarchy = world.read_resource::<Hierarchy>();
+//! assert_eq!(hierarchy.children(car), &[wheel]);
+//! assert_eq!(hierarchy.parent(bolt), Some(wheel));
+//! assert_eq!(hierarchy.all(), &[wheel, bolt]);
+//! ```
+
+use std::{
+    collections::{HashMap, HashSet},
+    marker::PhantomData,
+};
+
+use shrev::{EventChannel, EventIterator, ReaderId};
+
+use crate::{
+    prelude::*,
+    world::{EntitiesRes, Finalizers, Index},
+};
+
+/// Makes the entity the child of another entity, which is tracked by the
+/// `Hierarchy`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Parent(pub Entity);
+
+impl Component for Parent {
+    type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+}
+
+/// A change of the `Hierarchy`, published by the
+/// `HierarchyMaintenanceSystem`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HierarchyEvent {
+    /// The entity got a `Parent`, or its parent changed.
+    Modified(Entity),
+    /// The entity lost its `Parent`, e.g. because it was deleted.
+    Removed(Entity),
+}
+
+/// The parent/child relations of the entities with a `Parent`, kept up to
+/// date by the `HierarchyMaintenanceSystem`.
+///
+/// Entities whose parents form a cycle are left out of `all`.
+#[derive(Debug, Default)]
+pub struct Hierarchy {
+    /// The child and its parent, by the index of the child.
+    parents: HashMap<Index, (Entity, Entity)>,
+    children: HashMap<Entity, Vec<Entity>>,
+    sorted: Vec<Entity>,
+    events: EventChannel<HierarchyEvent>,
+}
+
+impl Hierarchy {
+    /// Creates an empty hierarchy.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the parent of `child`, if it has one.
+    pub fn parent(&self, child: Entity) -> Option<Entity> {
+        match self.parents.get(&child.id()) {
+            Some(&(entity, parent)) if entity == child => Some(parent),
+            _ => None,
+        }
+    }
+
+    /// Returns the children of `parent`, in the order they got their
+    /// `Parent`.
+    pub fn children(&self, parent: Entity) -> &[Entity] {
+        self.children.get(&parent).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the children of `parent`, their children and so on, sorted
+    /// like `all`.
+    pub fn descendants(&self, parent: Entity) -> Vec<Entity> {
+        descendants(&self.children, &[parent])
+    }
+
+    /// Returns all entities with a parent, sorted so that every entity
+    /// comes after its parent, e.g. for propagating transforms.
+    pub fn all(&self) -> &[Entity] {
+        &self.sorted
+    }
+
+    /// Starts reading the changes of the hierarchy. Like the readers of
+    /// tracked storages, it should be read every frame, otherwise the
+    /// events pile up waiting for it.
+    pub fn register_reader(&mut self) -> ReaderId<HierarchyEvent> {
+        self.events.register_reader()
+    }
+
+    /// Returns the changes since `reader` was last read.
+    pub fn read(&self, reader: &mut ReaderId<HierarchyEvent>) -> EventIterator<'_, HierarchyEvent> {
+        self.events.read(reader)
+    }
+
+    /// Makes `child` a child of `parent`, returning `true` if that changed
+    /// anything.
+    fn attach(&mut self, child: Entity, parent: Entity) -> bool {
+        if let Some(&(old_child, old_parent)) = self.parents.get(&child.id()) {
+            if old_child == child && old_parent == parent {
+                return false;
+            }
+            self.unlink(old_child, old_parent);
+            if old_child != child {
+                self.events.single_write(HierarchyEvent::Removed(old_child));
+            }
+        }
+
+        self.parents.insert(child.id(), (child, parent));
+        self.children.entry(parent).or_default().push(child);
+        self.events.single_write(HierarchyEvent::Modified(child));
+
+        true
+    }
+
+    /// Removes the parent of the entity with index `id`, returning `true`
+    /// if it had one.
+    fn detach(&mut self, id: Index) -> bool {
+        match self.parents.remove(&id) {
+            Some((child, parent)) => {
+                self.unlink(child, parent);
+                self.events.single_write(HierarchyEvent::Removed(child));
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn unlink(&mut self, child: Entity, parent: Entity) {
+        if let Some(children) = self.children.get_mut(&parent) {
+            children.retain(|&e| e != child);
+            if children.is_empty() {
+                self.children.remove(&parent);
+            }
+        }
+    }
+
+    fn sort(&mut self) {
+        let mut roots: Vec<Entity> = self
+            .children
+            .keys()
+            .filter(|&&e| self.parent(e).is_none())
+            .cloned()
+            .collect();
+        roots.sort();
+
+        self.sorted.clear();
+        for root in roots {
+            self.sorted.extend_from_slice(&self.children[&root]);
+        }
+        let mut i = 0;
+        while i < self.sorted.len() {
+            if let Some(children) = self.children.get(&self.sorted[i]) {
+                self.sorted.extend_from_slice(children);
+            }
+            i += 1;
+        }
+
+        if self.sorted.len() < self.parents.len() {
+            log::warn!(
+                "{} entities have cyclic parents and are left out of the hierarchy",
+                self.parents.len() - self.sorted.len()
+            );
+        }
+    }
+}
+
+/// Returns the descendants of `roots`, children before grandchildren.
+fn descendants(children: &HashMap<Entity, Vec<Entity>>, roots: &[Entity]) -> Vec<Entity> {
+    let mut visited: HashSet<Entity> = roots.iter().cloned().collect();
+    let mut descendants = Vec::new();
+    let mut parents = roots.to_vec();
+    let mut i = 0;
+    while i < parents.len() {
+        for &child in children.get(&parents[i]).map_or(&[][..], Vec::as_slice) {
+            if visited.insert(child) {
+                descendants.push(child);
+                parents.push(child);
+            }
+        }
+        i += 1;
+    }
+
+    descendants
+}
+
+/// Returns the descendants of `roots` according to the `Parent` storage,
+/// which may be newer than the `Hierarchy`.
+pub(crate) fn subtree(
+    entities: &EntitiesRes,
+    parents: &ReadStorage<Parent>,
+    roots: &[Entity],
+) -> Vec<Entity> {
+    let mut children: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    for (child, parent) in (entities, parents).join() {
+        children.entry(parent.0).or_default().push(child);
+    }
+
+    descendants(&children, roots)
+}
+
+/// Deletes the descendants of the `deleted` entities, see
+/// `HierarchyMaintenanceSystem::cascade_deletion`.
+fn delete_descendants(world: &World, deleted: &[Entity]) {
+    let entities = world.entities();
+    for child in subtree(&entities, &world.read_storage::<Parent>(), deleted) {
+        if !entities.is_pending_deletion(child) {
+            entities
+                .delete(child)
+                .expect("Bug: joined entities are alive");
+        }
+    }
+}
+
+/// Keeps the `Hierarchy` up to date with the `Parent` storage.
+///
+/// The hierarchy reflects the parents as of the last time this system ran,
+/// so it should run after the systems changing parents. Entities deleted
+/// with `Entities::delete` leave the hierarchy after the next
+/// `World::maintain`.
+#[derive(Default)]
+pub struct HierarchyMaintenanceSystem {
+    reader: Option<ReaderId<ComponentEvent>>,
+    cascade: bool,
+}
+
+impl HierarchyMaintenanceSystem {
+    /// Creates a new `HierarchyMaintenanceSystem`, which starts tracking the
+    /// `Parent` storage in `System::setup`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// If enabled, deleting an entity deletes its descendants as well.
+    /// Disabled by default.
+    ///
+    /// The descendants are deleted by a finalizer added in `System::setup`,
+    /// see `Finalizers::on_delete`. Entities deleted with `Entities::delete`
+    /// are deleted with their descendants by the next `World::maintain`.
+    /// The descendants of entities deleted with `WorldExt::delete_entity`
+    /// are deleted with `Entities::delete`; use
+    /// `WorldExt::delete_hierarchy` to delete them right away.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::{
+    ///     hierarchy::{HierarchyMaintenanceSystem, Parent},
+    ///     prelude::*,
+    /// };
+    ///
+    /// let mut world = World::new();
+    /// let mut dispatcher = DispatcherBuilder::new()
+    ///     .with(
+    ///         HierarchyMaintenanceSystem::new().cascade_deletion(true),
+    ///         "hierarchy",
+    ///         &[],
+    ///     )
+    ///     .build();
+    /// dispatcher.setup(&mut world);
+    ///
+    /// let vehicle = world.create_entity().build();
+    /// let turret = world.create_entity().with(Parent(vehicle)).build();
+    /// let barrel = world.create_entity().with(Parent(turret)).build();
+    ///
+    /// world.entities().delete(vehicle).unwrap();
+    /// world.maintain();
+    /// assert!(!world.is_alive(turret));
+    /// assert!(!world.is_alive(barrel));
+    /// ```
+    pub fn cascade_deletion(mut self, cascade: bool) -> Self {
+        self.cascade = cascade;
+        self
+    }
+}
+
+impl<'a> System<'a> for HierarchyMaintenanceSystem {
+    type SystemData = (
+        Read<'a, EntitiesRes>,
+        ReadStorage<'a, Parent>,
+        Write<'a, Hierarchy>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(WriteStorage::<Parent>::fetch(world).register_reader());
+        if self.cascade {
+            world
+                .entry::<Finalizers>()
+                .or_insert_with(Default::default)
+                .on_delete(delete_descendants);
+        }
+    }
+
+    fn run(&mut self, (entities, parents, mut hierarchy): Self::SystemData) {
+        let reader = self
+            .reader
+            .as_mut()
+            .expect("`HierarchyMaintenanceSystem::setup` was not called");
+
+        let mut changed = false;
+        for event in parents.channel().read(reader) {
+            changed |= match *event {
+                ComponentEvent::Removed(id) => hierarchy.detach(id),
+                ComponentEvent::Inserted(id) | ComponentEvent::Modified(id) => {
+                    let child = entities.entity(id);
+                    match parents.get(child) {
+                        Some(parent) => hierarchy.attach(child, parent.0),
+                        None => false,
+                    }
+                }
+            };
+        }
+
+        if changed {
+            hierarchy.sort();
+        }
+    }
+}
+
+/// Computes a global component `G` from a local component `L` of every
+/// entity and the `G` of its parent, e.g. world transforms from local ones.
+///
+/// `combine` gets the `G` of the parent, or `None` for entities without a
+/// parent or whose parent has no `G`, and the `L` of the entity. Only the
+/// entities whose `L` or parent changed since the last run, and their
+/// descendants, are updated, using the events of the tracked `L` storage and
+/// the `Hierarchy`. Entities which lose their `L` lose their `G`.
+///
+/// This system should run after the `HierarchyMaintenanceSystem`, and it
+/// only sees the changes made after `System::setup`.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::{
+///     hierarchy::{HierarchyMaintenanceSystem, Parent, PropagationSystem},
+///     prelude::*,
+/// };
+///
+/// struct Local(f32);
+///
+/// impl Component for Local {
+///     type Storage = FlaggedStorage<Self>;
+/// }
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Global(f32);
+///
+/// impl Component for Global {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// let mut world = World::new();
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(HierarchyMaintenanceSystem::new(), "hierarchy", &[])
+///     .with(
+///         PropagationSystem::new(|parent: Option<&Global>, local: &Local| {
+///             Global(parent.map_or(0.0, |p| p.0) + local.0)
+///         }),
+///         "propagation",
+///         &["hierarchy"],
+///     )
+///     .build();
+/// dispatcher.setup(&mut world);
+///
+/// let ship = world.create_entity().with(Local(10.0)).build();
+/// let cannon = world
+///     .create_entity()
+///     .with(Local(1.5))
+///     .with(Parent(ship))
+///     .build();
+/// dispatcher.dispatch(&world);
+/// assert_eq!(world.read_storage::<Global>().get(cannon), Some(&Global(11.5)));
+///
+/// world.write_storage::<Local>().get_mut(ship).unwrap().0 = 20.0;
+/// dispatcher.dispatch(&world);
+/// assert_eq!(world.read_storage::<Global>().get(cannon), Some(&Global(21.5)));
+/// ```
+pub struct PropagationSystem<L, G, F> {
+    combine: F,
+    local_reader: Option<ReaderId<ComponentEvent>>,
+    hierarchy_reader: Option<ReaderId<HierarchyEvent>>,
+    dirty: BitSet,
+    marker: PhantomData<fn(&L) -> G>,
+}
+
+impl<L, G, F> PropagationSystem<L, G, F>
+where
+    F: Fn(Option<&G>, &L) -> G,
+{
+    /// Creates a new `PropagationSystem` computing `G`s with `combine`.
+    pub fn new(combine: F) -> Self {
+        PropagationSystem {
+            combine,
+            local_reader: None,
+            hierarchy_reader: None,
+            dirty: BitSet::new(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, L, G, F> System<'a> for PropagationSystem<L, G, F>
+where
+    L: Component,
+    L::Storage: Tracked,
+    G: Component,
+    F: Fn(Option<&G>, &L) -> G,
+{
+    type SystemData = (
+        Read<'a, EntitiesRes>,
+        ReadStorage<'a, L>,
+        WriteStorage<'a, G>,
+        Read<'a, Hierarchy>,
+    );
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.local_reader = Some(WriteStorage::<L>::fetch(world).register_reader());
+        self.hierarchy_reader = Some(world.write_resource::<Hierarchy>().register_reader());
+    }
+
+    fn run(&mut self, (entities, locals, mut globals, hierarchy): Self::SystemData) {
+        const NOT_SET_UP: &str = "`PropagationSystem::setup` was not called";
+
+        self.dirty.clear();
+        for event in locals.channel().read(self.local_reader.as_mut().expect(NOT_SET_UP)) {
+            match *event {
+                ComponentEvent::Inserted(id)
+                | ComponentEvent::Modified(id)
+                | ComponentEvent::Removed(id) => self.dirty.add(id),
+            };
+        }
+        for event in hierarchy.read(self.hierarchy_reader.as_mut().expect(NOT_SET_UP)) {
+            match *event {
+                HierarchyEvent::Modified(e) | HierarchyEvent::Removed(e) => {
+                    self.dirty.add(e.id())
+                }
+            };
+        }
+
+        // Entities without a parent, and the ones which lost their `L`.
+        for (entity, local, _) in (&entities, locals.maybe(), &self.dirty).join() {
+            if hierarchy.parent(entity).is_some() {
+                continue;
+            }
+            match local {
+                Some(local) => {
+                    let global = (self.combine)(None, local);
+                    globals.insert(entity, global).expect("Bug: joined entity is alive");
+                }
+                None => {
+                    globals.remove(entity);
+                }
+            }
+        }
+
+        // Parents come before their children, so a dirty parent marks its
+        // children dirty before they are visited.
+        for &entity in hierarchy.all() {
+            let parent = hierarchy.parent(entity).expect("Bug: sorted entity has no parent");
+            if !self.dirty.contains(entity.id()) && !self.dirty.contains(parent.id()) {
+                continue;
+            }
+            self.dirty.add(entity.id());
+            if !entities.is_alive(entity) {
+                continue;
+            }
+            match locals.get(entity) {
+                Some(local) => {
+                    let global = (self.combine)(globals.get(parent), local);
+                    globals.insert(entity, global).expect("Bug: entity is alive");
+                }
+                None => {
+                    globals.remove(entity);
+                }
+            }
+        }
+    }
+}
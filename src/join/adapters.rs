@@ -0,0 +1,215 @@
+use std::borrow::Cow;
+
+use hibitset::BitSetLike;
+
+#[cfg(feature = "parallel")]
+use crate::join::ParJoin;
+use crate::{join::Join, world::Index};
+
+/// A `Join`-able structure that only yields the indices `filter` returns
+/// `true` for.
+///
+/// For usage see [`Join::filter_ids()`].
+///
+/// [`Join::filter_ids()`]: ../join/trait.Join.html#method.filter_ids
+pub struct FilterJoin<J, F> {
+    join: J,
+    filter: F,
+}
+
+impl<J, F> FilterJoin<J, F> {
+    pub(crate) fn new(join: J, filter: F) -> Self {
+        FilterJoin { join, filter }
+    }
+}
+
+impl<J, F> Join for FilterJoin<J, F>
+where
+    J: Join,
+    F: Fn(Index) -> bool,
+{
+    type Mask = FilterMask<J::Mask, F>;
+    type Type = J::Type;
+    type Value = J::Value;
+
+    // SAFETY: The mask only contains indices of the mask of `J`, so it's
+    // as correct as the one of `J`.
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        let (mask, value) = self.join.open();
+        let mask = FilterMask {
+            mask,
+            filter: self.filter,
+        };
+
+        (mask, value)
+    }
+
+    // SAFETY: `id` was checked against our mask, which is a subset of the
+    // mask of `J`.
+    unsafe fn get(value: &mut Self::Value, id: Index) -> Self::Type {
+        J::get(value, id)
+    }
+
+    #[inline]
+    fn is_unconstrained() -> bool {
+        J::is_unconstrained()
+    }
+}
+
+// SAFETY: This only wraps `J`, which implements `ParJoin`. The filter is
+// called from multiple threads, so it has to be `Sync`.
+#[cfg(feature = "parallel")]
+unsafe impl<J, F> ParJoin for FilterJoin<J, F>
+where
+    J: ParJoin,
+    F: Fn(Index) -> bool + Sync,
+{
+}
+
+/// The mask of a `FilterJoin`, containing the indices of `M` for which the
+/// filter returns `true`.
+pub struct FilterMask<M, F> {
+    mask: M,
+    filter: F,
+}
+
+impl<M, F> BitSetLike for FilterMask<M, F>
+where
+    M: BitSetLike,
+    F: Fn(Index) -> bool,
+{
+    // The upper layers may have bits set for words of `layer0` the filter
+    // emptied, which iterating skips.
+    #[inline]
+    fn layer3(&self) -> usize {
+        self.mask.layer3()
+    }
+
+    #[inline]
+    fn layer2(&self, i: usize) -> usize {
+        self.mask.layer2(i)
+    }
+
+    #[inline]
+    fn layer1(&self, i: usize) -> usize {
+        self.mask.layer1(i)
+    }
+
+    fn layer0(&self, i: usize) -> usize {
+        let shift = usize::trailing_zeros(std::mem::size_of::<usize>() * 8);
+        let mut word = self.mask.layer0(i);
+        let mut bits = word;
+        while bits != 0 {
+            let bit = bits.trailing_zeros();
+            bits &= bits - 1;
+            if !(self.filter)(((i << shift) as Index) | bit) {
+                word &= !(1 << bit);
+            }
+        }
+
+        word
+    }
+
+    #[inline]
+    fn contains(&self, i: Index) -> bool {
+        self.mask.contains(i) && (self.filter)(i)
+    }
+}
+
+/// A `Join`-able structure that yields the components of `J` passed through
+/// `map`.
+///
+/// For usage see [`Join::map_join()`].
+///
+/// [`Join::map_join()`]: ../join/trait.Join.html#method.map_join
+pub struct MapJoin<J, F> {
+    join: J,
+    map: F,
+}
+
+impl<J, F> MapJoin<J, F> {
+    pub(crate) fn new(join: J, map: F) -> Self {
+        MapJoin { join, map }
+    }
+}
+
+impl<J, F, T> Join for MapJoin<J, F>
+where
+    J: Join,
+    F: Fn(J::Type) -> T,
+{
+    type Mask = J::Mask;
+    type Type = T;
+    type Value = (J::Value, F);
+
+    // SAFETY: This wraps `J` without changing its mask.
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        let (mask, value) = self.join.open();
+
+        (mask, (value, self.map))
+    }
+
+    // SAFETY: The mask is the one of `J`, so `id` was checked against it.
+    unsafe fn get((value, map): &mut Self::Value, id: Index) -> Self::Type {
+        map(J::get(value, id))
+    }
+
+    #[inline]
+    fn is_unconstrained() -> bool {
+        J::is_unconstrained()
+    }
+}
+
+// SAFETY: This only wraps `J`, which implements `ParJoin`. `map` is called
+// from multiple threads, so it has to be `Sync`.
+#[cfg(feature = "parallel")]
+unsafe impl<J, F, T> ParJoin for MapJoin<J, F>
+where
+    J: ParJoin,
+    F: Fn(J::Type) -> T + Sync,
+{
+}
+
+/// An `Iterator` over a join in the order of a `SortedStorage`.
+///
+/// For usage see [`Join::sorted_join()`].
+///
+/// [`Join::sorted_join()`]: ../join/trait.Join.html#method.sorted_join
+#[must_use]
+pub struct SortedJoinIter<'a, J: Join> {
+    ids: Cow<'a, [Index]>,
+    pos: usize,
+    mask: J::Mask,
+    values: J::Value,
+}
+
+impl<'a, J: Join> SortedJoinIter<'a, J> {
+    pub(crate) fn new(join: J, ids: Cow<'a, [Index]>) -> Self {
+        // SAFETY: We only `get` the indices which are in the mask.
+        let (mask, values) = unsafe { join.open() };
+
+        SortedJoinIter {
+            ids,
+            pos: 0,
+            mask,
+            values,
+        }
+    }
+}
+
+impl<'a, J: Join> Iterator for SortedJoinIter<'a, J> {
+    type Item = J::Type;
+
+    fn next(&mut self) -> Option<J::Type> {
+        while let Some(&id) = self.ids.get(self.pos) {
+            self.pos += 1;
+            if self.mask.contains(id) {
+                // SAFETY: We checked the mask, and the indices are distinct,
+                // so mutable references don't alias.
+                return Some(unsafe { J::get(&mut self.values, id) });
+            }
+        }
+
+        None
+    }
+}
@@ -0,0 +1,106 @@
+use hibitset::BitSetLike;
+
+use crate::{
+    bitset::{bits, BITS},
+    world::Index,
+};
+
+const LAYERS: usize = 4;
+
+/// An `Iterator` over the indices of a mask, like `hibitset::BitIter`, which
+/// can also tell how many indices are left.
+///
+/// `BitIter` doesn't expose its mask, so it can't be counted after the
+/// iteration started.
+#[derive(Clone)]
+pub(crate) struct Keys<M> {
+    mask: M,
+    // The bits of each layer which haven't been visited yet.
+    masks: [usize; LAYERS],
+    // The index of the first bit of the current word of each layer below the
+    // top one.
+    prefix: [u32; LAYERS - 1],
+}
+
+impl<M: BitSetLike> Keys<M> {
+    pub(crate) fn new(mask: M) -> Self {
+        let top = mask.layer3();
+
+        Keys {
+            mask,
+            masks: [0, 0, 0, top],
+            prefix: [0; LAYERS - 1],
+        }
+    }
+
+    #[inline]
+    pub(crate) fn contains(&self, id: Index) -> bool {
+        self.mask.contains(id)
+    }
+
+    /// Counts the indices which haven't been yielded yet.
+    ///
+    /// This visits every word of the mask which has bits set, so it's linear
+    /// in the number of set words rather than constant, even for a
+    /// `CountedBitSet`. That is still about 64 times less work than
+    /// iterating a dense mask.
+    pub(crate) fn len(&self) -> usize {
+        let mut len = self.masks[0].count_ones() as usize;
+        for level in 1..LAYERS {
+            let prefix = self.prefix.get(level).cloned().unwrap_or(0);
+            len += bits(self.masks[level])
+                .map(|bit| self.count(level - 1, prefix as usize | bit))
+                .sum::<usize>();
+        }
+
+        len
+    }
+
+    /// Counts the bits in the word `idx` of `level` and all words below it.
+    fn count(&self, level: usize, idx: usize) -> usize {
+        let word = self.mask.get_from_layer(level, idx);
+        if level == 0 {
+            word.count_ones() as usize
+        } else {
+            bits(word)
+                .map(|bit| self.count(level - 1, (idx << BITS) | bit))
+                .sum()
+        }
+    }
+}
+
+impl<M: BitSetLike> Iterator for Keys<M> {
+    type Item = Index;
+
+    fn next(&mut self) -> Option<Index> {
+        'find: loop {
+            for level in 0..LAYERS {
+                let word = self.masks[level];
+                if word == 0 {
+                    continue;
+                }
+
+                // Take the first set bit off the word.
+                let bit = word.trailing_zeros();
+                self.masks[level] &= word - 1;
+                let idx = self.prefix.get(level).cloned().unwrap_or(0) | bit;
+                if level == 0 {
+                    return Some(idx);
+                }
+
+                // Descend into the word of the layer below.
+                self.masks[level - 1] = self.mask.get_from_layer(level - 1, idx as usize);
+                self.prefix[level - 1] = idx << BITS;
+                continue 'find;
+            }
+
+            return None;
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+
+        (len, Some(len))
+    }
+}
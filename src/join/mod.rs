@@ -1,15 +1,24 @@
 //! Joining of components for iteration over entities with specific components.
 
-use hibitset::{BitIter, BitSetAll, BitSetAnd, BitSetLike};
+use hibitset::{BitSetAll, BitSetAnd, BitSetLike};
 use shred::{Fetch, FetchMut, Read, ReadExpect, Resource, Write, WriteExpect};
 use std::ops::{Deref, DerefMut};
 use tuple_utils::Split;
 
-use crate::world::{Entities, Entity, Index};
+use crate::{
+    storage::{MaskedStorage, Sorted, Storage},
+    world::{Component, Entities, Entity, Index},
+};
 
+use self::keys::Keys;
+
+mod adapters;
+mod keys;
 #[cfg(feature = "parallel")]
 mod par_join;
 
+pub use self::adapters::{FilterJoin, FilterMask, MapJoin, SortedJoinIter};
+
 #[cfg(feature = "parallel")]
 pub use self::par_join::{JoinParIter, ParJoin};
 
@@ -67,6 +76,7 @@ bitset_and! {A, B, C, D, E, F, G, H, I, J, K, L, M}
 bitset_and! {A, B, C, D, E, F, G, H, I, J, K, L, M, N}
 bitset_and! {A, B, C, D, E, F, G, H, I, J, K, L, M, N, O}
 bitset_and! {A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P}
+bitset_and! {A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q}
 
 /// The purpose of the `Join` trait is to provide a way
 /// to access multiple storages at the same time with
@@ -214,6 +224,89 @@ pub trait Join {
         MaybeJoin(self)
     }
 
+    /// Returns a `Join`-able structure which only yields the indices
+    /// `filter` returns `true` for.
+    ///
+    /// The filter is applied to the mask, so the result can be joined with
+    /// more storages, filtered again or joined in parallel, without
+    /// collecting the matching entities first. It only gets the index of an
+    /// entity, as the components are accessed after the mask is known; use
+    /// `Entities::entity` to get the `Entity`.
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # struct Pos(i32); impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// # struct Vel(i32); impl Component for Vel { type Storage = VecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// world.register::<Vel>();
+    /// for i in 0..6 {
+    ///     world.create_entity().with(Pos(0)).with(Vel(i)).build();
+    /// }
+    ///
+    /// let entities = world.entities();
+    /// let mut pos = world.write_storage::<Pos>();
+    /// let vel = world.read_storage::<Vel>();
+    /// // Only move every other entity this frame.
+    /// let odd = (&mut pos).filter_ids(|id| id % 2 == 1);
+    /// for (pos, vel) in (odd, &vel).join() {
+    ///     pos.0 += vel.0;
+    /// }
+    ///
+    /// let moved: Vec<_> = (&entities, &pos).join().map(|(e, pos)| (e.id(), pos.0)).collect();
+    /// assert_eq!(moved, vec![(0, 0), (1, 1), (2, 0), (3, 3), (4, 0), (5, 5)]);
+    /// ```
+    fn filter_ids<F>(self, filter: F) -> FilterJoin<Self, F>
+    where
+        Self: Sized,
+        F: Fn(Index) -> bool,
+    {
+        FilterJoin::new(self, filter)
+    }
+
+    /// Returns a `Join`-able structure which yields the components passed
+    /// through `map`, e.g. to pick the fields a loop needs or to convert
+    /// them, while staying joinable with other storages.
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # struct Pos(i32, i32); impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// # struct Mass(i32); impl Component for Mass { type Storage = VecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// world.register::<Mass>();
+    /// world.create_entity().with(Pos(1, 2)).with(Mass(3)).build();
+    /// world.create_entity().with(Pos(4, 5)).build();
+    ///
+    /// let pos = world.read_storage::<Pos>();
+    /// let mass = world.read_storage::<Mass>();
+    /// let x = (&pos).map_join(|pos| pos.0);
+    /// let weighted: Vec<i32> = (x, &mass).join().map(|(x, mass)| x * mass.0).collect();
+    /// assert_eq!(weighted, vec![3]);
+    /// ```
+    fn map_join<F, T>(self, map: F) -> MapJoin<Self, F>
+    where
+        Self: Sized,
+        F: Fn(Self::Type) -> T,
+    {
+        MapJoin::new(self, map)
+    }
+
+    /// Creates an iterator over the join which yields the components in
+    /// ascending order of the components of `keys`, a storage wrapped in a
+    /// `SortedStorage`. Entities without a key are skipped.
+    ///
+    /// See `SortedStorage` for an example.
+    fn sorted_join<'a, 'e, K, D>(self, keys: &'a Storage<'e, K, D>) -> SortedJoinIter<'a, Self>
+    where
+        Self: Sized,
+        K: Component,
+        K::Storage: Sorted,
+        D: Deref<Target = MaskedStorage<K>>,
+    {
+        SortedJoinIter::new(self, keys.sorted_ids())
+    }
+
     /// Open this join by returning the mask and the storages.
     ///
     /// # Safety
@@ -295,10 +388,153 @@ where
 #[cfg(feature = "parallel")]
 unsafe impl<T> ParJoin for MaybeJoin<T> where T: ParJoin {}
 
+/// A `Join`-able wrapper around any `BitSetLike`, yielding the indices it
+/// contains.
+///
+/// The bitsets of `hibitset` can be joined over directly. This makes bitsets
+/// of other crates or your own, like the result of a spatial query, part of a
+/// join, so they can be intersected with storages without collecting them
+/// into a `BitSet` first.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::{hibitset::BitSetLike, join::BitSetJoin, prelude::*, world::Index};
+///
+/// /// The entities in a cell of a grid, as the lowest layer of a bitset.
+/// struct Cell(u64);
+///
+/// impl BitSetLike for Cell {
+///     fn layer3(&self) -> usize {
+///         self.layer2(0).min(1)
+///     }
+///     fn layer2(&self, i: usize) -> usize {
+///         self.layer1(i).min(1)
+///     }
+///     fn layer1(&self, i: usize) -> usize {
+///         self.layer0(i).min(1)
+///     }
+///     fn layer0(&self, i: usize) -> usize {
+///         if i == 0 { self.0 as usize } else { 0 }
+///     }
+///     fn contains(&self, i: Index) -> bool {
+///         i < 64 && self.0 & 1 << i != 0
+///     }
+/// }
+///
+/// struct Health(u32);
+///
+/// impl Component for Health {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Health>();
+/// for _ in 0..4 {
+///     world.create_entity().with(Health(10)).build();
+/// }
+///
+/// // An explosion hits the entities 1 and 3.
+/// let mut health = world.write_storage::<Health>();
+/// for (health, _) in (&mut health, BitSetJoin(Cell(0b1010))).join() {
+///     health.0 -= 5;
+/// }
+/// let left: Vec<u32> = (&health).join().map(|health| health.0).collect();
+/// assert_eq!(left, vec![10, 5, 10, 5]);
+/// ```
+pub struct BitSetJoin<B>(pub B);
+
+impl<B> BitSetLike for BitSetJoin<B>
+where
+    B: BitSetLike,
+{
+    #[inline]
+    fn layer3(&self) -> usize {
+        self.0.layer3()
+    }
+
+    #[inline]
+    fn layer2(&self, i: usize) -> usize {
+        self.0.layer2(i)
+    }
+
+    #[inline]
+    fn layer1(&self, i: usize) -> usize {
+        self.0.layer1(i)
+    }
+
+    #[inline]
+    fn layer0(&self, i: usize) -> usize {
+        self.0.layer0(i)
+    }
+
+    #[inline]
+    fn contains(&self, i: Index) -> bool {
+        self.0.contains(i)
+    }
+}
+
+impl<B> Join for BitSetJoin<B>
+where
+    B: BitSetLike,
+{
+    type Mask = Self;
+    type Type = Index;
+    type Value = ();
+
+    // SAFETY: This just moves the bitset; invariants of `Join` are fulfilled,
+    // since `Self::Value` cannot be mutated.
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        (self, ())
+    }
+
+    // SAFETY: No unsafe code and no invariants to meet.
+    unsafe fn get(_: &mut Self::Value, id: Index) -> Self::Type {
+        id
+    }
+}
+
+// SAFETY: `get` doesn't access any memory.
+#[cfg(feature = "parallel")]
+unsafe impl<B> ParJoin for BitSetJoin<B> where B: BitSetLike {}
+
 /// `JoinIter` is an `Iterator` over a group of `Storages`.
+///
+/// Its `size_hint` is exact, so `collect` allocates only once, and
+/// `ExactSizeIterator::len` counts the remaining values from the bits of the
+/// mask without fetching them. Both visit every non-empty word of the mask,
+/// so they take time linear in the size of the mask. `count` still fetches
+/// every value, because joins like the one over `Storage::drain` rely on
+/// that.
+///
+/// ## Example
+///
+/// ```
+/// # use specs::prelude::*;
+/// # struct Pos; impl Component for Pos { type Storage = VecStorage<Self>; }
+/// # struct Vel; impl Component for Vel { type Storage = VecStorage<Self>; }
+/// let mut world = World::new();
+/// world.register::<Pos>();
+/// world.register::<Vel>();
+/// for i in 0..10 {
+///     let builder = world.create_entity().with(Pos);
+///     if i % 2 == 0 {
+///         builder.with(Vel).build();
+///     } else {
+///         builder.build();
+///     }
+/// }
+///
+/// let pos = world.read_storage::<Pos>();
+/// let vel = world.read_storage::<Vel>();
+/// let mut join = (&pos, &vel).join();
+/// assert_eq!(join.len(), 5);
+/// join.next();
+/// assert_eq!(join.size_hint(), (4, Some(4)));
+/// ```
 #[must_use]
 pub struct JoinIter<J: Join> {
-    keys: BitIter<J::Mask>,
+    keys: Keys<J::Mask>,
     values: J::Value,
 }
 
@@ -315,7 +551,7 @@ impl<J: Join> JoinIter<J> {
         // exposing them.
         let (keys, values) = unsafe { j.open() };
         JoinIter {
-            keys: keys.iter(),
+            keys: Keys::new(keys),
             values,
         }
     }
@@ -395,6 +631,72 @@ impl<J: Join> JoinIter<J> {
             None
         }
     }
+
+    /// Returns an iterator over batches of up to `size` joined values, e.g.
+    /// for handing fixed-size batches to SIMD kernels or GPU upload buffers.
+    /// Only the last batch may be smaller.
+    ///
+    /// Every batch is collected into a new `Vec`. Components stored in
+    /// `PackedStorage`s can be processed in batches without copying them
+    /// instead, see `PackedGroup`, whose slices can be split with
+    /// `slice::chunks`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// # struct Pos(f32); impl Component for Pos { type Storage = VecStorage<Self>; }
+    /// # struct Vel(f32); impl Component for Vel { type Storage = VecStorage<Self>; }
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// world.register::<Vel>();
+    /// for i in 0..10 {
+    ///     world.create_entity().with(Pos(0.0)).with(Vel(i as f32)).build();
+    /// }
+    ///
+    /// let mut pos = world.write_storage::<Pos>();
+    /// let vel = world.read_storage::<Vel>();
+    /// let mut sizes = Vec::new();
+    /// for batch in (&mut pos, &vel).join().chunks(4) {
+    ///     sizes.push(batch.len());
+    ///     for (pos, vel) in batch {
+    ///         pos.0 += vel.0;
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(sizes, vec![4, 4, 2]);
+    /// assert_eq!((&pos).join().map(|pos| pos.0).sum::<f32>(), 45.0);
+    /// ```
+    pub fn chunks(self, size: usize) -> JoinChunks<J> {
+        assert!(size != 0, "chunk size must be non-zero");
+
+        JoinChunks { iter: self, size }
+    }
+}
+
+/// An `Iterator` over batches of joined values, created by
+/// `JoinIter::chunks`.
+#[must_use]
+pub struct JoinChunks<J: Join> {
+    iter: JoinIter<J>,
+    size: usize,
+}
+
+impl<J: Join> std::iter::Iterator for JoinChunks<J> {
+    type Item = Vec<J::Type>;
+
+    fn next(&mut self) -> Option<Vec<J::Type>> {
+        let first = self.iter.next()?;
+        let mut chunk = Vec::with_capacity(self.size);
+        chunk.push(first);
+        chunk.extend(self.iter.by_ref().take(self.size - 1));
+
+        Some(chunk)
+    }
 }
 
 impl<J: Join> std::iter::Iterator for JoinIter<J> {
@@ -407,8 +709,14 @@ impl<J: Join> std::iter::Iterator for JoinIter<J> {
             .next()
             .map(|idx| unsafe { J::get(&mut self.values, idx) })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
 }
 
+impl<J: Join> std::iter::ExactSizeIterator for JoinIter<J> {}
+
 /// Clones the `JoinIter`.
 ///
 /// # Examples
@@ -12,6 +12,47 @@ use rayon::iter::{
 /// to access multiple storages in parallel at the same time with
 /// the merged bit set.
 ///
+/// The merged bit set is split across rayon's thread pool, so every thread
+/// works on distinct entities. Storages can be joined mutably in parallel
+/// if their `UnprotectedStorage` implements `DistinctStorage`; the storages
+/// which record changes, like `FlaggedStorage` and `TickedStorage`, don't.
+/// Only available with the `parallel` feature.
+///
+/// ## Examples
+///
+/// ```
+/// use rayon::iter::ParallelIterator;
+/// use specs::prelude::*;
+///
+/// struct Pos(f32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// struct Vel(f32);
+///
+/// impl Component for Vel {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Pos>();
+/// world.register::<Vel>();
+/// for i in 0..1000 {
+///     world.create_entity().with(Pos(0.0)).with(Vel(i as f32)).build();
+/// }
+///
+/// let vel = world.read_storage::<Vel>();
+/// let mut pos = world.write_storage::<Pos>();
+/// (&vel, &mut pos).par_join().for_each(|(vel, pos)| {
+///     pos.0 += vel.0;
+/// });
+///
+/// let sum: f32 = (&pos).par_join().map(|pos| pos.0).sum();
+/// assert_eq!(sum, 499_500.0);
+/// ```
+///
 /// # Safety
 ///
 /// The implementation of `ParallelIterator` for `ParJoin` makes multiple
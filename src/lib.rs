@@ -197,11 +197,15 @@ pub extern crate uuid;
 #[cfg(feature = "serde")]
 pub mod saveload;
 
-mod bitset;
+pub mod bitset;
 pub mod changeset;
+#[cfg(feature = "serde_json")]
+pub mod dynamic;
 pub mod error;
+pub mod hierarchy;
 pub mod join;
 pub mod prelude;
+pub mod relation;
 pub mod storage;
 pub mod world;
 
@@ -217,7 +221,7 @@ pub use shrev::ReaderId;
 pub use shred::AsyncDispatcher;
 
 #[cfg(feature = "specs-derive")]
-pub use specs_derive::{Component, ConvertSaveload};
+pub use specs_derive::{Bundle, Component, ConvertSaveload};
 
 #[cfg(feature = "parallel")]
 pub use crate::join::ParJoin;
@@ -228,7 +232,7 @@ pub use crate::{
         DefaultVecStorage, DenseVecStorage, FlaggedStorage, HashMapStorage, NullStorage,
         ReadStorage, Storage, Tracked, VecStorage, WriteStorage,
     },
-    world::{Builder, Component, Entities, Entity, EntityBuilder, LazyUpdate, WorldExt},
+    world::{Builder, Bundle, Component, Entities, Entity, EntityBuilder, LazyUpdate, WorldExt},
 };
 
 #[cfg(feature = "nightly")]
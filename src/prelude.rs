@@ -5,6 +5,7 @@
 pub use crate::join::Join;
 #[cfg(feature = "parallel")]
 pub use crate::join::ParJoin;
+pub use crate::bitset::BitSetExt;
 pub use hibitset::BitSet;
 pub use shred::{
     Accessor, Dispatcher, DispatcherBuilder, Read, ReadExpect, Resource, ResourceId, RunNow,
@@ -23,5 +24,5 @@ pub use crate::{
         ComponentEvent, DefaultVecStorage, DenseVecStorage, FlaggedStorage, HashMapStorage,
         NullStorage, ReadStorage, Storage, Tracked, VecStorage, WriteStorage,
     },
-    world::{Builder, Component, Entities, Entity, EntityBuilder, LazyUpdate, WorldExt},
+    world::{Builder, Bundle, Component, Entities, Entity, EntityBuilder, LazyUpdate, WorldExt},
 };
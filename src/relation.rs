@@ -0,0 +1,417 @@
+//! Typed relations between entities, e.g. "a likes b" or "a targets b".
+//!
+//! The relations of kind `R` an entity has to other entities are stored in
+//! its `Relations<R>` component, as edges from the entity, the subject, to
+//! the other ones, the objects, each with a value of type `R`. Unlike
+//! `Vec<Entity>` components, relations can be queried from both sides, lose
+//! the edges to deleted objects once registered with
+//! `WorldExt::register_relation`, and are saved with the objects mapped to
+//! their markers.
+//!
+//! ## Examples
+//!
+//! ```
+//! use specs::{prelude::*, relation::Relations};
+//!
+//! #[derive(Default)]
+//! struct Likes;
+//!
+//! struct Health(u32);
+//!
+//! impl Component for Health {
+//!     type Storage = VecStorage<Self>;
+//! }
+//!
+//! let mut world = World::new();
+//! world.register::<Health>();
+//! world.register_relation::<Likes>();
+//!
+//! let a = world.create_entity().build();
+//! let b = world.create_entity().with(Health(10)).build();
+//! let c = world.create_entity().build();
+//! world.relate::<Likes>(a, b).unwrap();
+//! world.relate::<Likes>(a, c).unwrap();
+//! world.relate::<Likes>(c, b).unwrap();
+//!
+//! // All pairs where the subject likes an object with `Health`.
+//! let likes = world.read_storage::<Relations<Likes>>();
+//! let health = world.read_storage::<Health>();
+//! let pairs: Vec<_> = likes
+//!     .pairs()
+//!     .filter_map(|(subject, object, _)| health.get(object).map(|h| (subject, object, h.0)))
+//!     .collect();
+//! assert_eq!(pairs, vec![(a, b, 10), (c, b, 10)]);
+//! assert_eq!(likes.sources(b).collect::<Vec<_>>(), vec![a, c]);
+//! ```
+
+use std::{
+    collections::VecDeque,
+    ops::{Deref, DerefMut},
+};
+
+use hibitset::BitSet;
+
+use crate::{
+    error::{Error, WrongGeneration},
+    join::Join,
+    storage::{DenseVecStorage, MaskedStorage, Storage},
+    world::{Component, Entity, World, WorldExt},
+};
+
+/// The relations of kind `R` from an entity to other entities, with a value
+/// of type `R` for each of them.
+///
+/// The edges are usually changed through the methods of the storage, see
+/// `Storage::relate`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Relations<R> {
+    edges: Vec<(Entity, R)>,
+}
+
+impl<R> Relations<R> {
+    /// Creates an empty set of relations.
+    pub fn new() -> Self {
+        Relations { edges: Vec::new() }
+    }
+
+    /// Returns the objects of the relations and their values, in the order
+    /// they were related, including dead objects which weren't removed yet.
+    pub fn edges(&self) -> &[(Entity, R)] {
+        &self.edges
+    }
+
+    /// Returns the value of the relation to `object`, if there is one.
+    pub fn get(&self, object: Entity) -> Option<&R> {
+        self.edges.iter().find(|e| e.0 == object).map(|e| &e.1)
+    }
+
+    /// Returns the number of relations.
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Returns `true` if there are no relations.
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    /// Relates the subject to `object`, returning the value it replaced.
+    fn insert(&mut self, object: Entity, value: R) -> Option<R> {
+        match self.edges.iter_mut().find(|e| e.0 == object) {
+            Some(edge) => Some(std::mem::replace(&mut edge.1, value)),
+            None => {
+                self.edges.push((object, value));
+                None
+            }
+        }
+    }
+
+    /// Removes the relation to `object`, returning its value.
+    fn remove(&mut self, object: Entity) -> Option<R> {
+        let index = self.edges.iter().position(|e| e.0 == object)?;
+        Some(self.edges.remove(index).1)
+    }
+}
+
+impl<R> Default for Relations<R> {
+    fn default() -> Self {
+        Relations::new()
+    }
+}
+
+impl<R> Component for Relations<R>
+where
+    R: Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<'e, R, D> Storage<'e, Relations<R>, D>
+where
+    R: Send + Sync + 'static,
+    D: Deref<Target = MaskedStorage<Relations<R>>>,
+{
+    /// Returns all relations between live entities as
+    /// `(subject, object, value)`, ordered by subject.
+    pub fn pairs(&self) -> impl Iterator<Item = (Entity, Entity, &R)> {
+        let entities = self.fetched_entities();
+        (entities, self).join().flat_map(move |(subject, relations)| {
+            relations
+                .edges
+                .iter()
+                .filter(move |e| entities.is_alive(e.0))
+                .map(move |e| (subject, e.0, &e.1))
+        })
+    }
+
+    /// Returns the live objects `subject` is related to.
+    pub fn targets(&self, subject: Entity) -> impl Iterator<Item = Entity> + '_ {
+        let entities = self.fetched_entities();
+        self.get(subject)
+            .map_or(&[][..], |relations| &relations.edges)
+            .iter()
+            .map(|e| e.0)
+            .filter(move |&object| entities.is_alive(object))
+    }
+
+    /// Returns the subjects related to `object`, ordered by subject. This
+    /// looks through all relations.
+    pub fn sources(&self, object: Entity) -> impl Iterator<Item = Entity> + '_ {
+        (self.fetched_entities(), self)
+            .join()
+            .filter(move |(_, relations)| relations.get(object).is_some())
+            .map(|(subject, _)| subject)
+    }
+
+    /// Returns the value of the relation from `subject` to `object`, if
+    /// both are alive and related.
+    pub fn related(&self, subject: Entity, object: Entity) -> Option<&R> {
+        if !self.fetched_entities().is_alive(object) {
+            return None;
+        }
+
+        self.get(subject).and_then(|relations| relations.get(object))
+    }
+
+    /// Returns the entities reachable from `start` by following relations,
+    /// nearest first. Every entity is visited once, even if the relations
+    /// have cycles, and `start` itself isn't returned.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::{prelude::*, relation::Relations};
+    ///
+    /// #[derive(Default)]
+    /// struct Owns;
+    ///
+    /// let mut world = World::new();
+    /// world.register_relation::<Owns>();
+    ///
+    /// let guild = world.create_entity().build();
+    /// let player = world.create_entity().build();
+    /// let chest = world.create_entity().build();
+    /// let sword = world.create_entity().build();
+    /// world.relate::<Owns>(guild, player).unwrap();
+    /// world.relate::<Owns>(player, chest).unwrap();
+    /// world.relate::<Owns>(chest, sword).unwrap();
+    /// world.relate::<Owns>(guild, sword).unwrap();
+    ///
+    /// let owns = world.read_storage::<Relations<Owns>>();
+    /// let bfs: Vec<_> = owns.breadth_first(guild).collect();
+    /// assert_eq!(bfs, vec![player, sword, chest]);
+    /// let dfs: Vec<_> = owns.depth_first(guild).collect();
+    /// assert_eq!(dfs, vec![player, chest, sword]);
+    /// assert!(owns.reachable(player).contains(sword.id()));
+    /// ```
+    pub fn breadth_first(&self, start: Entity) -> Traverse<'_, 'e, R, D> {
+        Traverse::new(self, start, false)
+    }
+
+    /// Returns the entities reachable from `start` by following relations,
+    /// following the first relation of an entity as deep as possible before
+    /// the next one. Every entity is visited once, even if the relations
+    /// have cycles, and `start` itself isn't returned.
+    pub fn depth_first(&self, start: Entity) -> Traverse<'_, 'e, R, D> {
+        Traverse::new(self, start, true)
+    }
+
+    /// Returns the indices of the entities reachable from `start`, e.g. for
+    /// joining them with other storages. Like the traversals, it doesn't
+    /// contain `start`.
+    pub fn reachable(&self, start: Entity) -> BitSet {
+        let mut reachable = BitSet::new();
+        for entity in self.breadth_first(start) {
+            reachable.add(entity.id());
+        }
+
+        reachable
+    }
+}
+
+/// An iterator over the entities reachable from an entity, see
+/// `Storage::breadth_first` and `Storage::depth_first`.
+pub struct Traverse<'s, 'e, R, D> {
+    storage: &'s Storage<'e, Relations<R>, D>,
+    visited: BitSet,
+    pending: VecDeque<Entity>,
+    depth_first: bool,
+}
+
+impl<'s, 'e, R, D> Traverse<'s, 'e, R, D>
+where
+    R: Send + Sync + 'static,
+    D: Deref<Target = MaskedStorage<Relations<R>>>,
+{
+    fn new(storage: &'s Storage<'e, Relations<R>, D>, start: Entity, depth_first: bool) -> Self {
+        let mut traverse = Traverse {
+            storage,
+            visited: BitSet::new(),
+            pending: VecDeque::new(),
+            depth_first,
+        };
+        traverse.visited.add(start.id());
+        traverse.push_targets(start);
+
+        traverse
+    }
+
+    /// Queues the unvisited objects of `subject`. Breadth first, entities
+    /// are marked as visited when they are queued, depth first when they
+    /// are returned.
+    fn push_targets(&mut self, subject: Entity) {
+        if self.depth_first {
+            let targets: Vec<_> = self.storage.targets(subject).collect();
+            for object in targets.into_iter().rev() {
+                if !self.visited.contains(object.id()) {
+                    self.pending.push_back(object);
+                }
+            }
+        } else {
+            for object in self.storage.targets(subject) {
+                if !self.visited.add(object.id()) {
+                    self.pending.push_back(object);
+                }
+            }
+        }
+    }
+}
+
+impl<'s, 'e, R, D> Iterator for Traverse<'s, 'e, R, D>
+where
+    R: Send + Sync + 'static,
+    D: Deref<Target = MaskedStorage<Relations<R>>>,
+{
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Entity> {
+        let entity = if self.depth_first {
+            loop {
+                let entity = self.pending.pop_back()?;
+                if !self.visited.add(entity.id()) {
+                    break entity;
+                }
+            }
+        } else {
+            self.pending.pop_front()?
+        };
+        self.push_targets(entity);
+
+        Some(entity)
+    }
+}
+
+impl<'e, R, D> Storage<'e, Relations<R>, D>
+where
+    R: Send + Sync + 'static,
+    D: DerefMut<Target = MaskedStorage<Relations<R>>>,
+{
+    /// Relates `subject` to `object` with `value`, returning the value it
+    /// replaced. Fails if either entity is dead.
+    pub fn relate(&mut self, subject: Entity, object: Entity, value: R) -> Result<Option<R>, Error> {
+        let entities = self.fetched_entities();
+        if !entities.is_alive(object) {
+            return Err(Error::WrongGeneration(WrongGeneration {
+                action: "relate to entity",
+                actual_gen: entities.entity(object.id()).gen(),
+                entity: object,
+            }));
+        }
+
+        if let Some(relations) = self.get_mut(subject) {
+            return Ok(relations.insert(object, value));
+        }
+        let mut relations = Relations::new();
+        relations.insert(object, value);
+        self.insert(subject, relations).map(|_| None)
+    }
+
+    /// Removes the relation from `subject` to `object`, returning its
+    /// value. The `Relations` component is removed with the last relation.
+    pub fn unrelate(&mut self, subject: Entity, object: Entity) -> Option<R> {
+        let (value, empty) = {
+            let relations = self.get_mut(subject)?;
+            (relations.remove(object), relations.is_empty())
+        };
+        if empty {
+            self.remove(subject);
+        }
+
+        value
+    }
+}
+
+/// Removes the relations to the `deleted` entities, see
+/// `WorldExt::register_relation`.
+pub(crate) fn forget_objects<R>(world: &World, deleted: &[Entity])
+where
+    R: Send + Sync + 'static,
+{
+    let mut storage = world.write_storage::<Relations<R>>();
+    if storage.is_empty() {
+        return;
+    }
+
+    let mut ids = BitSet::new();
+    for entity in deleted {
+        ids.add(entity.id());
+    }
+    // The deleted entities are still alive, so the current generation of a
+    // deleted index is the one of the deleted entity.
+    let entities = world.entities();
+    let is_deleted = |e: Entity| ids.contains(e.id()) && entities.entity(e.id()) == e;
+    let mut empty = Vec::new();
+    for (subject, relations) in (&entities, &mut storage).join() {
+        relations.edges.retain(|e| !is_deleted(e.0));
+        if relations.is_empty() {
+            empty.push(subject);
+        }
+    }
+    for subject in empty {
+        storage.remove(subject);
+    }
+}
+
+#[cfg(feature = "serde")]
+mod saveload {
+    use std::convert::Infallible;
+
+    use serde::{de::DeserializeOwned, Serialize};
+
+    use super::Relations;
+    use crate::{saveload::ConvertSaveload, world::Entity};
+
+    /// The objects are saved as their markers, leaving out the ones without
+    /// a marker, like `ConvertEntities` does.
+    impl<R, M> ConvertSaveload<M> for Relations<R>
+    where
+        R: Clone + Serialize + DeserializeOwned,
+        M: Serialize + DeserializeOwned,
+    {
+        type Data = Vec<(M, R)>;
+        type Error = Infallible;
+
+        fn convert_into<F>(&self, mut ids: F) -> Result<Self::Data, Self::Error>
+        where
+            F: FnMut(Entity) -> Option<M>,
+        {
+            Ok(self
+                .edges
+                .iter()
+                .filter_map(|(object, value)| ids(*object).map(|m| (m, value.clone())))
+                .collect())
+        }
+
+        fn convert_from<F>(data: Self::Data, mut ids: F) -> Result<Self, Self::Error>
+        where
+            F: FnMut(M) -> Option<Entity>,
+        {
+            Ok(Relations {
+                edges: data
+                    .into_iter()
+                    .filter_map(|(marker, value)| ids(marker).map(|e| (e, value)))
+                    .collect(),
+            })
+        }
+    }
+}
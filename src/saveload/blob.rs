@@ -0,0 +1,153 @@
+//! Copying single entities in and out of a world as self-contained blobs.
+//!
+//! A blob doesn't use the markers of the world it was exported from: the
+//! entities inside it are numbered from zero, so importing it always creates
+//! fresh entities. This makes blobs suitable for copy and paste, or for
+//! sending an entity to another world.
+
+use std::{collections::HashMap, fmt::Display};
+
+use serde::ser;
+use shred::SystemData;
+
+use crate::{
+    saveload::{
+        de::DeserializeComponents,
+        marker::{Marker, SimpleMarker},
+        ser::SerializeComponents,
+        stream::StreamError,
+        EntityData,
+    },
+    world::{Entity, World, WorldExt},
+};
+
+/// Serializes `entity` with the components of the storages `S` into a
+/// `bincode` blob, which can be loaded with `import_entity`.
+///
+/// `T` is the marker type whose `ConvertSaveload` implementations are used,
+/// i.e. the components are converted as if saved with `SimpleMarker<T>`.
+/// If `include_references` is set, all live entities reachable through
+/// entity references are exported as well; otherwise such references are
+/// dropped.
+///
+/// ## Examples
+///
+/// ```
+/// # extern crate specs;
+/// # #[macro_use] extern crate serde;
+/// use std::convert::Infallible;
+///
+/// use specs::{
+///     prelude::*,
+///     saveload::{export_entity, import_entity},
+/// };
+///
+/// #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// struct Pos(f32, f32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// struct Save;
+///
+/// let mut world = World::new();
+/// world.register::<Pos>();
+/// let entity = world.create_entity().with(Pos(1.0, 2.0)).build();
+///
+/// let blob =
+///     export_entity::<Infallible, Save, (ReadStorage<Pos>,)>(&world, entity, false).unwrap();
+/// let copy =
+///     import_entity::<Infallible, Save, (WriteStorage<Pos>,)>(&world, &blob).unwrap();
+/// world.maintain();
+///
+/// assert_ne!(entity, copy);
+/// assert_eq!(world.read_storage::<Pos>().get(copy), Some(&Pos(1.0, 2.0)));
+/// ```
+pub fn export_entity<'a, E, T, S>(
+    world: &'a World,
+    entity: Entity,
+    include_references: bool,
+) -> Result<Vec<u8>, StreamError>
+where
+    E: Display,
+    T: 'static + ?Sized + Send + Sync,
+    S: SystemData<'a> + SerializeComponents<E, SimpleMarker<T>>,
+{
+    let storages = world.system_data::<S>();
+    let entities = world.entities();
+
+    let mut order = vec![entity];
+    let mut indices = HashMap::new();
+    indices.insert(entity, 0u64);
+    let mut data = Vec::new();
+    while data.len() < order.len() {
+        let index = data.len();
+        let current = order[index];
+        let ids = |referenced: Entity| {
+            if let Some(&index) = indices.get(&referenced) {
+                return Some(SimpleMarker::new(index));
+            }
+            if !include_references || !entities.is_alive(referenced) {
+                return None;
+            }
+            let index = order.len() as u64;
+            indices.insert(referenced, index);
+            order.push(referenced);
+            Some(SimpleMarker::new(index))
+        };
+        let components = storages
+            .serialize_entity(current, ids)
+            .map_err(<bincode::Error as ser::Error>::custom)?;
+        data.push(EntityData::<SimpleMarker<T>, S::Data> {
+            marker: SimpleMarker::new(index as u64),
+            components,
+        });
+    }
+
+    Ok(bincode::serialize(&data)?)
+}
+
+/// Creates new entities from a blob written by `export_entity` and returns
+/// the one that was exported; the others are the entities it referenced.
+///
+/// The entities are created atomically, so `World::maintain` has to be
+/// called before they show up in joins. If a component fails to load, all
+/// created entities are deleted again.
+pub fn import_entity<'a, E, T, S>(world: &'a World, blob: &[u8]) -> Result<Entity, StreamError>
+where
+    E: Display,
+    T: 'static + ?Sized + Send + Sync,
+    S: SystemData<'a> + DeserializeComponents<E, SimpleMarker<T>>,
+{
+    let data: Vec<EntityData<SimpleMarker<T>, S::Data>> = bincode::deserialize(blob)?;
+    let root = match data.first() {
+        Some(root) => root.marker.id(),
+        None => {
+            return Err(StreamError::from(<bincode::Error as ser::Error>::custom(
+                "blob contains no entity",
+            )))
+        }
+    };
+
+    let entities = world.entities();
+    let mut storages = world.system_data::<S>();
+    let created: HashMap<u64, Entity> = data
+        .iter()
+        .map(|entity_data| (entity_data.marker.id(), entities.create()))
+        .collect();
+
+    for entity_data in data {
+        let entity = created[&entity_data.marker.id()];
+        let ids = |marker: SimpleMarker<T>| created.get(&marker.id()).cloned();
+        if let Err(e) = storages.deserialize_entity(entity, entity_data.components, ids) {
+            for &entity in created.values() {
+                let _ = entities.delete(entity);
+            }
+
+            return Err(<bincode::Error as ser::Error>::custom(e).into());
+        }
+    }
+
+    Ok(created[&root])
+}
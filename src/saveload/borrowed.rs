@@ -0,0 +1,250 @@
+//! Deserialization into components from data borrowed from the input.
+//!
+//! `ConvertSaveload::Data` has to be `DeserializeOwned`, so every string or
+//! byte buffer is copied out of the input before a component is built from
+//! it. Formats which deserialize from a slice, like `bincode`, can instead
+//! lend such data for the lifetime `'de` of the input; components opt into
+//! that with `ConvertSaveloadBorrowed`.
+
+use std::{
+    any::type_name,
+    fmt::{self, Display, Formatter},
+    marker::PhantomData,
+};
+
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+
+use crate::{
+    saveload::{
+        error::Error,
+        marker::{Marker, MarkerAllocator},
+        EntityData,
+    },
+    storage::{GenericWriteStorage, WriteStorage},
+    world::{Component, EntitiesRes, Entity},
+};
+
+/// Converts a type (usually a `Component`) from a deserialized form which
+/// may borrow from the input, see `DeserializeComponentsBorrowed`.
+///
+/// Unlike `ConvertSaveload` there is no blanket implementation; types
+/// without borrowed data can use `type Data = Self`.
+///
+/// ## Examples
+///
+/// ```
+/// use std::convert::Infallible;
+///
+/// use specs::{
+///     prelude::*,
+///     saveload::{ConvertSaveloadBorrowed, Marker},
+/// };
+///
+/// struct Name(String);
+///
+/// impl Component for Name {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// impl<'de, M: Marker> ConvertSaveloadBorrowed<'de, M> for Name {
+///     type Data = &'de str;
+///     type Error = Infallible;
+///
+///     fn convert_from<F>(data: &'de str, _ids: F) -> Result<Self, Infallible>
+///     where
+///         F: FnMut(M) -> Option<Entity>,
+///     {
+///         Ok(Name(data.to_owned()))
+///     }
+/// }
+/// ```
+pub trait ConvertSaveloadBorrowed<'de, M>: Sized {
+    /// The deserialized form, which may borrow from the input.
+    type Data: Deserialize<'de>;
+
+    /// The error returned by a failed conversion.
+    type Error;
+
+    /// Builds the value from its deserialized form using a marker -> entity
+    /// mapping.
+    fn convert_from<F>(data: Self::Data, ids: F) -> Result<Self, Self::Error>
+    where
+        F: FnMut(M) -> Option<Entity>;
+}
+
+/// A tuple of storages which can be loaded from input borrowed for `'de`.
+///
+/// The input has the same layout as the one of
+/// `DeserializeComponents::deserialize`, so it can be written with
+/// `SerializeComponents::serialize`. Implemented for tuples of storages
+/// whose components implement `ConvertSaveloadBorrowed`.
+pub trait DeserializeComponentsBorrowed<'de, E, M>: Sized
+where
+    E: Display,
+    M: Marker,
+{
+    /// The data representation of the components, borrowing from the input.
+    type Data: Deserialize<'de>;
+
+    /// Loads the components of `entity` from `components`, replacing the
+    /// ones it has and removing the ones missing from `components`.
+    fn deserialize_entity<F>(
+        &mut self,
+        entity: Entity,
+        components: Self::Data,
+        ids: F,
+    ) -> Result<(), Error<E>>
+    where
+        F: FnMut(M) -> Option<Entity>;
+
+    /// Deserialize entities according to markers, like
+    /// `DeserializeComponents::deserialize`.
+    fn deserialize_borrowed<'a: 'b, 'b, D>(
+        &'b mut self,
+        entities: &'b EntitiesRes,
+        markers: &'b mut WriteStorage<'a, M>,
+        allocator: &'b mut M::Allocator,
+        deserializer: D,
+    ) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(VisitBorrowed::<E, M, Self> {
+            allocator,
+            entities,
+            markers,
+            storages: self,
+            pd: PhantomData,
+        })
+    }
+}
+
+struct VisitBorrowed<'a: 'b, 'b, E, M: Marker, S: 'b> {
+    allocator: &'b mut M::Allocator,
+    entities: &'b EntitiesRes,
+    markers: &'b mut WriteStorage<'a, M>,
+    storages: &'b mut S,
+    pd: PhantomData<E>,
+}
+
+impl<'de, 'a: 'b, 'b, E, M, S> Visitor<'de> for VisitBorrowed<'a, 'b, E, M, S>
+where
+    E: Display,
+    M: Marker,
+    S: DeserializeComponentsBorrowed<'de, E, M>,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "Sequence of serialized entities")
+    }
+
+    fn visit_seq<SEQ>(self, mut seq: SEQ) -> Result<(), SEQ::Error>
+    where
+        SEQ: SeqAccess<'de>,
+    {
+        for index in 0.. {
+            let data = seq
+                .next_element::<EntityData<M, S::Data>>()
+                .map_err(|e| de::Error::custom(Error::new(e).with_index(index)))?;
+            let data = match data {
+                Some(data) => data,
+                None => break,
+            };
+
+            let marker = data.marker.clone();
+            let entity = self
+                .allocator
+                .retrieve_entity(data.marker, self.markers, self.entities);
+            let allocator = &mut *self.allocator;
+            let markers = &mut *self.markers;
+            let entities = self.entities;
+            let ids = |marker: M| Some(allocator.retrieve_entity(marker, markers, entities));
+            self.storages
+                .deserialize_entity(entity, data.components, ids)
+                .map_err(|e| de::Error::custom(e.with_index(index).with_marker(&marker)))?;
+        }
+
+        Ok(())
+    }
+}
+
+macro_rules! deserialize_components_borrowed {
+    ($($comp:ident => $sto:ident,)*) => {
+        impl<'de, E, M, $($sto,)*> DeserializeComponentsBorrowed<'de, E, M> for ($($sto,)*)
+        where
+            E: Display,
+            M: Marker,
+            $(
+                $sto: GenericWriteStorage,
+                <$sto as GenericWriteStorage>::Component:
+                    ConvertSaveloadBorrowed<'de, M> + Component,
+                E: From<<
+                    <$sto as GenericWriteStorage>::Component as ConvertSaveloadBorrowed<'de, M>
+                >::Error>,
+            )*
+        {
+            type Data = ($(
+                Option<
+                    <<$sto as GenericWriteStorage>::Component as ConvertSaveloadBorrowed<'de, M>>::Data
+                >,)*
+            );
+
+            #[allow(unused)]
+            fn deserialize_entity<F>(
+                &mut self,
+                entity: Entity,
+                components: Self::Data,
+                mut ids: F,
+            ) -> Result<(), Error<E>>
+            where
+                F: FnMut(M) -> Option<Entity>
+            {
+                #[allow(bad_style)]
+                let ($(ref mut $sto,)*) = *self;
+                #[allow(bad_style)]
+                let ($($comp,)*) = components;
+                $(
+                    if let Some(component) = $comp {
+                        let component =
+                            ConvertSaveloadBorrowed::<'de, M>::convert_from(component, &mut ids)
+                                .map_err(|e| {
+                                    Error::new(E::from(e)).with_component(
+                                        type_name::<<$sto as GenericWriteStorage>::Component>(),
+                                    )
+                                })?;
+                        $sto.insert(entity, component);
+                    } else {
+                        $sto.remove(entity);
+                    }
+                )*
+                Ok(())
+            }
+        }
+
+        deserialize_components_borrowed!(@pop $($comp => $sto,)*);
+    };
+    (@pop) => {};
+    (@pop $head0:ident => $head1:ident, $($tail0:ident => $tail1:ident,)*) => {
+        deserialize_components_borrowed!($($tail0 => $tail1,)*);
+    };
+}
+
+deserialize_components_borrowed!(
+    CA => SA,
+    CB => SB,
+    CC => SC,
+    CD => SD,
+    CE => SE,
+    CF => SF,
+    CG => SG,
+    CH => SH,
+    CI => SI,
+    CJ => SJ,
+    CK => SK,
+    CL => SL,
+    CN => SN,
+    CM => SM,
+    CO => SO,
+    CP => SP,
+);
@@ -0,0 +1,195 @@
+//! Compressing the streams used by `save_to_writer` and `load_from_reader`.
+
+use std::io::{self, Read, Write};
+
+/// A compression algorithm for save streams.
+///
+/// Wrap the writer handed to `save_to_writer` with `Compression::writer` and
+/// the reader handed to `load_from_reader` with `Compression::reader`.
+///
+/// ## Examples
+///
+/// ```
+/// # extern crate specs;
+/// # #[macro_use] extern crate serde;
+/// use std::convert::Infallible;
+///
+/// use specs::{
+///     error::Error,
+///     prelude::*,
+///     saveload::{
+///         load_from_reader, save_to_writer, Compression, Format, MarkedBuilder, SimpleMarker,
+///         SimpleMarkerAllocator,
+///     },
+/// };
+///
+/// #[derive(Clone, Serialize, Deserialize)]
+/// struct Pos(f32, f32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// struct Save;
+///
+/// let mut world = World::new();
+/// world.register::<Pos>();
+/// world.register::<SimpleMarker<Save>>();
+/// world.insert(SimpleMarkerAllocator::<Save>::new());
+/// world.create_entity().with(Pos(1.0, 2.0)).marked::<SimpleMarker<Save>>().build();
+/// # let compression = Compression::None;
+/// # #[cfg(feature = "flate2")]
+/// let compression = Compression::Deflate;
+///
+/// let mut writer = compression.writer(Vec::new());
+/// world.exec(
+///     |(entities, pos, markers): (Entities, ReadStorage<Pos>, ReadStorage<SimpleMarker<Save>>)| {
+///         save_to_writer::<Infallible, _, _, _>(
+///             Format::Json,
+///             &(&pos,),
+///             &entities,
+///             &markers,
+///             &mut writer,
+///         )
+///         .unwrap();
+///     },
+/// );
+/// let buffer = writer.finish().unwrap();
+///
+/// world.exec(
+///     |(entities, pos, mut markers, mut allocator): (
+///         Entities,
+///         WriteStorage<Pos>,
+///         WriteStorage<SimpleMarker<Save>>,
+///         Write<SimpleMarkerAllocator<Save>>,
+///     )| {
+///         load_from_reader::<Error, _, _, _>(
+///             Format::Json,
+///             &mut (pos,),
+///             &entities,
+///             &mut markers,
+///             &mut allocator,
+///             compression.reader(&buffer[..]),
+///         )
+///         .unwrap();
+///     },
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    /// The stream is passed through unchanged.
+    None,
+    /// LZ4 frames, written by `lz4_flex`. Fast, with a moderate ratio.
+    #[cfg(feature = "lz4_flex")]
+    Lz4,
+    /// Deflate, written by `flate2`. Slower, with a better ratio.
+    #[cfg(feature = "flate2")]
+    Deflate,
+}
+
+impl Compression {
+    /// Wraps `writer` so everything written to it is compressed.
+    pub fn writer<W: Write>(self, writer: W) -> CompressedWriter<W> {
+        let inner = match self {
+            Compression::None => WriterKind::None(writer),
+            #[cfg(feature = "lz4_flex")]
+            Compression::Lz4 => WriterKind::Lz4(lz4_flex::frame::FrameEncoder::new(writer)),
+            #[cfg(feature = "flate2")]
+            Compression::Deflate => WriterKind::Deflate(flate2::write::DeflateEncoder::new(
+                writer,
+                flate2::Compression::default(),
+            )),
+        };
+
+        CompressedWriter { inner }
+    }
+
+    /// Wraps `reader` so everything read from it is decompressed.
+    pub fn reader<R: Read>(self, reader: R) -> DecompressedReader<R> {
+        let inner = match self {
+            Compression::None => ReaderKind::None(reader),
+            #[cfg(feature = "lz4_flex")]
+            Compression::Lz4 => ReaderKind::Lz4(lz4_flex::frame::FrameDecoder::new(reader)),
+            #[cfg(feature = "flate2")]
+            Compression::Deflate => ReaderKind::Deflate(flate2::read::DeflateDecoder::new(reader)),
+        };
+
+        DecompressedReader { inner }
+    }
+}
+
+/// A writer compressing into `W`, created by `Compression::writer`.
+///
+/// `finish` has to be called once everything is written, otherwise the
+/// stream may be cut off.
+pub struct CompressedWriter<W: Write> {
+    inner: WriterKind<W>,
+}
+
+enum WriterKind<W: Write> {
+    None(W),
+    #[cfg(feature = "lz4_flex")]
+    Lz4(lz4_flex::frame::FrameEncoder<W>),
+    #[cfg(feature = "flate2")]
+    Deflate(flate2::write::DeflateEncoder<W>),
+}
+
+impl<W: Write> CompressedWriter<W> {
+    /// Writes the end of the compressed stream and returns the inner writer.
+    pub fn finish(self) -> io::Result<W> {
+        match self.inner {
+            WriterKind::None(mut writer) => writer.flush().map(|_| writer),
+            #[cfg(feature = "lz4_flex")]
+            WriterKind::Lz4(encoder) => encoder.finish().map_err(io::Error::from),
+            #[cfg(feature = "flate2")]
+            WriterKind::Deflate(encoder) => encoder.finish(),
+        }
+    }
+}
+
+impl<W: Write> Write for CompressedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.inner {
+            WriterKind::None(writer) => writer.write(buf),
+            #[cfg(feature = "lz4_flex")]
+            WriterKind::Lz4(encoder) => encoder.write(buf),
+            #[cfg(feature = "flate2")]
+            WriterKind::Deflate(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.inner {
+            WriterKind::None(writer) => writer.flush(),
+            #[cfg(feature = "lz4_flex")]
+            WriterKind::Lz4(encoder) => encoder.flush(),
+            #[cfg(feature = "flate2")]
+            WriterKind::Deflate(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// A reader decompressing from `R`, created by `Compression::reader`.
+pub struct DecompressedReader<R: Read> {
+    inner: ReaderKind<R>,
+}
+
+enum ReaderKind<R: Read> {
+    None(R),
+    #[cfg(feature = "lz4_flex")]
+    Lz4(lz4_flex::frame::FrameDecoder<R>),
+    #[cfg(feature = "flate2")]
+    Deflate(flate2::read::DeflateDecoder<R>),
+}
+
+impl<R: Read> Read for DecompressedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match &mut self.inner {
+            ReaderKind::None(reader) => reader.read(buf),
+            #[cfg(feature = "lz4_flex")]
+            ReaderKind::Lz4(decoder) => decoder.read(buf),
+            #[cfg(feature = "flate2")]
+            ReaderKind::Deflate(decoder) => decoder.read(buf),
+        }
+    }
+}
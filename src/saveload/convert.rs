@@ -0,0 +1,126 @@
+//! Conversions for containers of `Entity`.
+
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, Hash},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::world::Entity;
+
+/// Like `ConvertSaveload`, but for containers of `Entity`.
+///
+/// `ConvertSaveload` is implemented for every `Serialize` and
+/// `DeserializeOwned` type, which rules out implementing it for types like
+/// `Vec<Entity>`. This trait fills that gap by mapping each inner `Entity`
+/// through the `ids` closure. Entities that can't be mapped are left out.
+///
+/// With `#[derive(ConvertSaveload)]`, mark such fields with
+/// `#[convert_save_load_entities]`; manual `ConvertSaveload` implementations
+/// can call it directly.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::{prelude::*, saveload::ConvertEntities};
+///
+/// let mut world = World::new();
+/// let a = world.create_entity().build();
+/// let b = world.create_entity().build();
+///
+/// let markers: Vec<u64> = ConvertEntities::convert_into(&vec![a, b], |e: Entity| {
+///     if e == a {
+///         Some(1)
+///     } else {
+///         None
+///     }
+/// });
+/// assert_eq!(markers, vec![1]);
+/// ```
+pub trait ConvertEntities<M>: Sized {
+    /// (De)Serializable data representation, with every `Entity` replaced by
+    /// `M`.
+    type Data: Serialize + DeserializeOwned;
+
+    /// Convert this container from its deserializable form (`Data`) using a
+    /// marker to entity mapping function.
+    fn convert_from<F>(data: Self::Data, ids: F) -> Self
+    where
+        F: FnMut(M) -> Option<Entity>;
+
+    /// Convert this container into its serializable form (`Data`) using an
+    /// entity to marker mapping function.
+    fn convert_into<F>(&self, ids: F) -> Self::Data
+    where
+        F: FnMut(Entity) -> Option<M>;
+}
+
+impl<M> ConvertEntities<M> for Option<Entity>
+where
+    M: Serialize + DeserializeOwned,
+{
+    type Data = Option<M>;
+
+    fn convert_from<F>(data: Self::Data, ids: F) -> Self
+    where
+        F: FnMut(M) -> Option<Entity>,
+    {
+        data.and_then(ids)
+    }
+
+    fn convert_into<F>(&self, ids: F) -> Self::Data
+    where
+        F: FnMut(Entity) -> Option<M>,
+    {
+        self.and_then(ids)
+    }
+}
+
+impl<M> ConvertEntities<M> for Vec<Entity>
+where
+    M: Serialize + DeserializeOwned,
+{
+    type Data = Vec<M>;
+
+    fn convert_from<F>(data: Self::Data, ids: F) -> Self
+    where
+        F: FnMut(M) -> Option<Entity>,
+    {
+        data.into_iter().filter_map(ids).collect()
+    }
+
+    fn convert_into<F>(&self, ids: F) -> Self::Data
+    where
+        F: FnMut(Entity) -> Option<M>,
+    {
+        self.iter().cloned().filter_map(ids).collect()
+    }
+}
+
+impl<K, M, S> ConvertEntities<M> for HashMap<K, Entity, S>
+where
+    K: Clone + Eq + Hash + Serialize + DeserializeOwned,
+    M: Serialize + DeserializeOwned,
+    S: BuildHasher + Default,
+{
+    type Data = HashMap<K, M>;
+
+    fn convert_from<F>(data: Self::Data, mut ids: F) -> Self
+    where
+        F: FnMut(M) -> Option<Entity>,
+    {
+        data.into_iter()
+            .filter_map(|(key, marker)| ids(marker).map(|entity| (key, entity)))
+            .collect()
+    }
+
+    fn convert_into<F>(&self, mut ids: F) -> Self::Data
+    where
+        F: FnMut(Entity) -> Option<M>,
+    {
+        self.iter()
+            .filter_map(|(key, &entity)| ids(entity).map(|marker| (key.clone(), marker)))
+            .collect()
+    }
+}
@@ -1,17 +1,29 @@
 use std::{
+    any::type_name,
+    collections::{HashMap, HashSet},
     fmt::{self, Display, Formatter},
     marker::PhantomData,
 };
 
-use serde::de::{
-    self, Deserialize, DeserializeOwned, DeserializeSeed, Deserializer, SeqAccess, Visitor,
+use serde::{
+    de::{
+        self, DeserializeOwned, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess,
+        Visitor,
+    },
+    Deserialize,
 };
 
 use super::ConvertSaveload;
 use crate::{
     saveload::{
-        marker::{Marker, MarkerAllocator},
-        EntityData,
+        error::Error,
+        marker::{Marker, MarkerAllocator, PersistentAllocator},
+        options::{
+            DeserializeOptions, DeserializeReport, MergePolicy, UnknownMarkerPolicy,
+            ValidationReport,
+        },
+        progress::Progress,
+        Column, ColumnData, EntityData,
     },
     storage::{GenericWriteStorage, WriteStorage},
     world::{Component, EntitiesRes, Entity},
@@ -27,6 +39,12 @@ where
     /// The data representation that a component group gets deserialized to.
     type Data: DeserializeOwned;
 
+    /// The column-oriented data representation, one `Column` per storage.
+    type Columns: DeserializeOwned;
+
+    /// The number of component storages, i.e. the length of `Data`.
+    const COMPONENTS: usize;
+
     /// Loads `Component`s to entity from `Data` deserializable representation
     fn deserialize_entity<F>(
         &mut self,
@@ -34,9 +52,63 @@ where
         components: Self::Data,
         ids: F,
     ) -> Result<(), E>
+    where
+        F: FnMut(M) -> Option<Entity>,
+    {
+        self.deserialize_entity_with(entity, components, MergePolicy::Overwrite, ids)
+            .map_err(|e| e.source)
+    }
+
+    /// Loads `Component`s to entity from `Data` deserializable representation,
+    /// merging them with the existing components according to `policy`.
+    ///
+    /// `MergePolicy::ErrorOnConflict` is treated like
+    /// `MergePolicy::KeepExisting` here; use `conflicts` to check for
+    /// conflicts beforehand. Errors name the component that failed.
+    fn deserialize_entity_with<F>(
+        &mut self,
+        entity: Entity,
+        components: Self::Data,
+        policy: MergePolicy,
+        ids: F,
+    ) -> Result<(), Error<E>>
     where
         F: FnMut(M) -> Option<Entity>;
 
+    /// Converts `components` like `deserialize_entity` without inserting
+    /// them anywhere. Errors name the component that failed.
+    fn validate_data<F>(components: Self::Data, ids: F) -> Result<(), Error<E>>
+    where
+        F: FnMut(M) -> Option<Entity>;
+
+    /// Returns `true` if `entity` already has any of the components in
+    /// `components`.
+    fn conflicts(&self, entity: Entity, components: &Self::Data) -> bool;
+
+    /// Loads `Component`s from columns to `entities`, where the indices of
+    /// each column refer to positions in `entities`. Entities that are
+    /// missing from a column lose that component.
+    ///
+    /// Fails without loading anything if a column doesn't have one value per
+    /// index or an index is out of range.
+    fn deserialize_columns_data<F>(
+        &mut self,
+        entities: &[Entity],
+        columns: Self::Columns,
+        ids: F,
+    ) -> Result<(), Error<String>>
+    where
+        F: FnMut(M) -> Option<Entity>;
+
+    /// Reads `Data` element by element from `seq`. Elements missing at the
+    /// end are an error if `strict`, and absent components otherwise.
+    /// Remaining elements are left in `seq`.
+    ///
+    /// Errors name the component that failed.
+    fn deserialize_data_seq<'de, A>(seq: &mut A, strict: bool) -> Result<Self::Data, A::Error>
+    where
+        A: SeqAccess<'de>;
+
     /// Deserialize entities according to markers.
     fn deserialize<'a: 'b, 'b, 'de, D>(
         &'b mut self,
@@ -48,23 +120,174 @@ where
     where
         D: Deserializer<'de>,
     {
+        self.deserialize_with(
+            entities,
+            markers,
+            allocator,
+            &DeserializeOptions::default(),
+            deserializer,
+        )
+        .map(|_| ())
+    }
+
+    /// Deserialize entities according to markers, using `options` to decide
+    /// how to handle data that doesn't match the storages.
+    ///
+    /// Returns a report of everything that was skipped.
+    fn deserialize_with<'a: 'b, 'b, 'de, D>(
+        &'b mut self,
+        entities: &'b EntitiesRes,
+        markers: &'b mut WriteStorage<'a, M>,
+        allocator: &'b mut M::Allocator,
+        options: &'b DeserializeOptions,
+        deserializer: D,
+    ) -> Result<DeserializeReport<M>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.deserialize_with_progress(
+            entities,
+            markers,
+            allocator,
+            options,
+            &mut Progress::new(),
+            deserializer,
+        )
+    }
+
+    /// Like `deserialize_with`, but reports every deserialized entity to
+    /// `progress` and stops with a `Cancelled` error once its token is
+    /// cancelled.
+    fn deserialize_with_progress<'a: 'b, 'b, 'de, D>(
+        &'b mut self,
+        entities: &'b EntitiesRes,
+        markers: &'b mut WriteStorage<'a, M>,
+        allocator: &'b mut M::Allocator,
+        options: &'b DeserializeOptions,
+        progress: &'b mut Progress,
+        deserializer: D,
+    ) -> Result<DeserializeReport<M>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut report = DeserializeReport::default();
         deserializer.deserialize_seq(VisitEntities::<E, M, Self> {
             allocator,
             entities,
             markers,
             storages: self,
+            options,
+            progress,
+            report: &mut report,
             pd: PhantomData,
-        })
+        })?;
+
+        Ok(report)
+    }
+
+    /// Deserialize entities written by
+    /// `SerializeComponents::serialize_with_allocator` according to markers
+    /// and restore the saved state of `allocator`.
+    fn deserialize_with_allocator<'a: 'b, 'b, 'de, D>(
+        &'b mut self,
+        entities: &'b EntitiesRes,
+        markers: &'b mut WriteStorage<'a, M>,
+        allocator: &'b mut M::Allocator,
+        deserializer: D,
+    ) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+        M::Allocator: PersistentAllocator<M>,
+    {
+        let options = DeserializeOptions::default();
+        let mut report = DeserializeReport::default();
+        deserializer.deserialize_struct(
+            "SaveData",
+            &["allocator", "entities"],
+            VisitSaveData::<E, M, Self> {
+                allocator,
+                entities,
+                markers,
+                storages: self,
+                options: &options,
+                progress: &mut Progress::new(),
+                report: &mut report,
+                pd: PhantomData,
+            },
+        )
+    }
+
+    /// Applies a diff written by `SerializeComponents::serialize_diff`:
+    /// deletes the entities with removed markers and loads the changed
+    /// entities according to markers.
+    ///
+    /// Fails before changing anything if the diff isn't based on snapshot
+    /// `base`, see `DiffTracker`.
+    fn apply_diff<'a: 'b, 'b, 'de, D>(
+        &'b mut self,
+        entities: &'b EntitiesRes,
+        markers: &'b mut WriteStorage<'a, M>,
+        allocator: &'b mut M::Allocator,
+        base: u64,
+        deserializer: D,
+    ) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let options = DeserializeOptions::default();
+        let mut report = DeserializeReport::default();
+        deserializer.deserialize_struct(
+            "DiffData",
+            &["base", "removed", "entities"],
+            VisitDiffData::<E, M, Self> {
+                base,
+                allocator,
+                entities,
+                markers,
+                storages: self,
+                options: &options,
+                progress: &mut Progress::new(),
+                report: &mut report,
+                pd: PhantomData,
+            },
+        )
+    }
+
+    /// Deserialize entities written by `SerializeComponents::serialize_columns`
+    /// according to markers.
+    fn deserialize_columns<'a: 'b, 'b, 'de, D>(
+        &'b mut self,
+        entities: &'b EntitiesRes,
+        markers: &'b mut WriteStorage<'a, M>,
+        allocator: &'b mut M::Allocator,
+        deserializer: D,
+    ) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let data = ColumnData::<M, Self::Columns>::deserialize(deserializer)?;
+        let resolved: Vec<Entity> = data
+            .markers
+            .into_iter()
+            .map(|marker| allocator.retrieve_entity(marker, markers, entities))
+            .collect();
+        let ids = |marker: M| Some(allocator.retrieve_entity(marker, markers, entities));
+
+        self.deserialize_columns_data(&resolved, data.columns, ids)
+            .map_err(de::Error::custom)
     }
 }
 
 /// Wrapper for `Entity` and tuple of `WriteStorage`s that implements
 /// `serde::Deserialize`.
 struct DeserializeEntity<'a: 'b, 'b, E, M: Marker, S: 'b> {
+    index: usize,
     allocator: &'b mut M::Allocator,
     entities: &'b EntitiesRes,
     storages: &'b mut S,
     markers: &'b mut WriteStorage<'a, M>,
+    options: &'b DeserializeOptions,
+    report: &'b mut DeserializeReport<M>,
     pd: PhantomData<E>,
 }
 
@@ -81,20 +304,84 @@ where
         D: Deserializer<'de>,
     {
         let DeserializeEntity {
+            index,
             entities,
             storages,
             markers,
             allocator,
+            options,
+            report,
             ..
         } = self;
-        let data = EntityData::<M, S::Data>::deserialize(deserializer)?;
-        let entity = allocator.retrieve_entity(data.marker, markers, entities);
-        let ids = |marker: M| Some(allocator.retrieve_entity(marker, markers, entities));
+        let skip_unknown = options.skip_unknown_components;
+        let (data, unknown) = EntityDataSeed::<E, M, S>(skip_unknown, PhantomData).deserialize(deserializer)?;
+        if unknown > 0 {
+            report.unknown_components.push((data.marker.clone(), unknown));
+        }
+        let marker = data.marker.clone();
+        match apply_entity(entities, storages, markers, allocator, options, report, data) {
+            Ok(()) => Ok(()),
+            Err(e) if options.collect_apply_errors => {
+                report.errors.push(e.with_index(index).with_marker(&marker));
+                Ok(())
+            }
+            Err(e) => Err(de::Error::custom(e.with_marker(&marker))),
+        }
+    }
+}
 
-        storages
-            .deserialize_entity(entity, data.components, ids)
-            .map_err(de::Error::custom)
+/// Loads the decoded `data` of an entity into the storages.
+fn apply_entity<'a, E, M, S>(
+    entities: &EntitiesRes,
+    storages: &mut S,
+    markers: &mut WriteStorage<'a, M>,
+    allocator: &mut M::Allocator,
+    options: &DeserializeOptions,
+    report: &mut DeserializeReport<M>,
+    data: EntityData<M, S::Data>,
+) -> Result<(), Error<String>>
+where
+    E: Display,
+    M: Marker,
+    S: DeserializeComponents<E, M>,
+{
+    let entity = match options.unknown_markers {
+        UnknownMarkerPolicy::Create => allocator.retrieve_entity(data.marker, markers, entities),
+        UnknownMarkerPolicy::Skip => {
+            let marker = data.marker.clone();
+            match allocator.retrieve_existing_entity(data.marker, markers) {
+                Some(entity) => entity,
+                None => {
+                    report.skipped_markers.push(marker);
+                    return Ok(());
+                }
+            }
+        }
+        UnknownMarkerPolicy::Error => allocator
+            .retrieve_existing_entity(data.marker, markers)
+            .ok_or_else(|| Error::new("no entity with this marker exists".to_owned()))?,
+    };
+    let policy = options.merge_policy;
+    if policy == MergePolicy::ErrorOnConflict && storages.conflicts(entity, &data.components) {
+        return Err(Error::new(
+            "the entity already has some of the deserialized components".to_owned(),
+        ));
     }
+    let create = options.unknown_markers == UnknownMarkerPolicy::Create;
+    let ids = |marker: M| {
+        if create {
+            Some(allocator.retrieve_entity(marker, markers, entities))
+        } else {
+            allocator.retrieve_existing_entity(marker, markers)
+        }
+    };
+
+    storages
+        .deserialize_entity_with(entity, data.components, policy, ids)
+        .map_err(|e| e.map_source(|source| source.to_string()))?;
+    report.entities += 1;
+
+    Ok(())
 }
 
 /// Wrapper for `Entities` and tuple of `WriteStorage`s that implements
@@ -104,6 +391,9 @@ struct VisitEntities<'a: 'b, 'b, E, M: Marker, S: 'b> {
     entities: &'b EntitiesRes,
     markers: &'b mut WriteStorage<'a, M>,
     storages: &'b mut S,
+    options: &'b DeserializeOptions,
+    progress: &'b mut Progress,
+    report: &'b mut DeserializeReport<M>,
     pd: PhantomData<E>,
 }
 
@@ -123,19 +413,545 @@ where
     where
         SEQ: SeqAccess<'de>,
     {
-        loop {
-            let ret = seq.next_element_seed(DeserializeEntity {
-                entities: self.entities,
-                storages: self.storages,
-                markers: self.markers,
-                allocator: self.allocator,
-                pd: self.pd,
-            })?;
+        let total = seq.size_hint();
+        for index in 0.. {
+            self.progress.check().map_err(de::Error::custom)?;
+            let ret = seq
+                .next_element_seed(DeserializeEntity {
+                    index,
+                    entities: self.entities,
+                    storages: self.storages,
+                    markers: self.markers,
+                    allocator: self.allocator,
+                    options: self.options,
+                    report: self.report,
+                    pd: self.pd,
+                })
+                .map_err(|e| de::Error::custom(Error::new(e).with_index(index)))?;
 
             if ret.is_none() {
-                break Ok(());
+                break;
             }
+            self.progress.entity_done(index + 1, total);
         }
+
+        Ok(())
+    }
+}
+
+impl<'de, 'a: 'b, 'b, E, M, S> DeserializeSeed<'de> for VisitEntities<'a, 'b, E, M, S>
+where
+    E: Display,
+    M: Marker,
+    S: DeserializeComponents<E, M>,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+/// Runs the whole decoding of `DeserializeComponents::deserialize` on
+/// `deserializer` without touching any storage, so a save can be checked
+/// before loading it into a live world.
+///
+/// `S` is the tuple of storages the save would be loaded with. Malformed
+/// data is returned as an error; problems with otherwise well-formed
+/// entities, like failing `ConvertSaveload` conversions, duplicate markers
+/// and references to entities which aren't part of the save, are listed in
+/// the returned `ValidationReport`.
+///
+/// ## Examples
+///
+/// ```
+/// # extern crate ron;
+/// use serde::{Deserialize, Serialize};
+/// use specs::{
+///     error::Error,
+///     prelude::*,
+///     saveload::{validate, SimpleMarker},
+/// };
+///
+/// #[derive(Clone, Deserialize, Serialize)]
+/// struct Pos(i32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// struct Save;
+///
+/// let serial = "[(marker: (0), components: (Some((5)),))]";
+/// let mut de = ron::de::Deserializer::from_str(serial).unwrap();
+/// let report =
+///     validate::<Error, SimpleMarker<Save>, (WriteStorage<Pos>,), _>(&mut de).unwrap();
+/// assert!(report.is_valid());
+/// assert_eq!(report.entities, 1);
+/// ```
+pub fn validate<'de, E, M, S, D>(deserializer: D) -> Result<ValidationReport<M>, D::Error>
+where
+    E: Display,
+    M: Marker,
+    S: DeserializeComponents<E, M>,
+    D: Deserializer<'de>,
+{
+    let mut report = ValidationReport::default();
+    deserializer.deserialize_seq(ValidateEntities::<E, M, S> {
+        report: &mut report,
+        pd: PhantomData,
+    })?;
+
+    Ok(report)
+}
+
+/// Visitor for `validate`.
+struct ValidateEntities<'b, E, M, S> {
+    report: &'b mut ValidationReport<M>,
+    pd: PhantomData<(E, S)>,
+}
+
+impl<'de, 'b, E, M, S> Visitor<'de> for ValidateEntities<'b, E, M, S>
+where
+    E: Display,
+    M: Marker,
+    S: DeserializeComponents<E, M>,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "Sequence of serialized entities")
+    }
+
+    fn visit_seq<SEQ>(self, mut seq: SEQ) -> Result<(), SEQ::Error>
+    where
+        SEQ: SeqAccess<'de>,
+    {
+        // Markers are resolved to entities of a scratch `EntitiesRes`, so
+        // conversions see the same entity for the same marker.
+        let scratch = EntitiesRes::default();
+        let mut mapping: HashMap<M, Entity> = HashMap::new();
+        let mut defined = HashSet::new();
+        let mut referenced = HashSet::new();
+        for index in 0.. {
+            let element = seq
+                .next_element_seed(EntityDataSeed::<E, M, S>(false, PhantomData))
+                .map_err(|e| de::Error::custom(Error::new(e).with_index(index)))?;
+            let data = match element {
+                Some((data, _)) => data,
+                None => break,
+            };
+            let marker = data.marker;
+            if !defined.insert(marker.clone()) {
+                self.report.errors.push(
+                    Error::new("the marker appears more than once".to_owned())
+                        .with_index(index)
+                        .with_marker(&marker),
+                );
+                continue;
+            }
+            let ids = |marker: M| {
+                referenced.insert(marker.clone());
+                Some(*mapping.entry(marker).or_insert_with(|| scratch.create()))
+            };
+
+            match S::validate_data(data.components, ids) {
+                Ok(()) => self.report.entities += 1,
+                Err(e) => self.report.errors.push(
+                    e.map_source(|source| source.to_string())
+                        .with_index(index)
+                        .with_marker(&marker),
+                ),
+            }
+        }
+        self.report.dangling_references = referenced.difference(&defined).cloned().collect();
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum SaveDataField {
+    Allocator,
+    Entities,
+    #[serde(other)]
+    Other,
+}
+
+/// Wrapper for `Entities` and tuple of `WriteStorage`s that deserializes a
+/// `SaveData`, restoring the allocator state it contains.
+struct VisitSaveData<'a: 'b, 'b, E, M: Marker, S: 'b> {
+    allocator: &'b mut M::Allocator,
+    entities: &'b EntitiesRes,
+    markers: &'b mut WriteStorage<'a, M>,
+    storages: &'b mut S,
+    options: &'b DeserializeOptions,
+    progress: &'b mut Progress,
+    report: &'b mut DeserializeReport<M>,
+    pd: PhantomData<E>,
+}
+
+impl<'a: 'b, 'b, E, M: Marker, S> VisitSaveData<'a, 'b, E, M, S> {
+    fn visit_entities(&mut self) -> VisitEntities<'a, '_, E, M, S> {
+        VisitEntities {
+            allocator: self.allocator,
+            entities: self.entities,
+            markers: self.markers,
+            storages: self.storages,
+            options: self.options,
+            progress: self.progress,
+            report: self.report,
+            pd: PhantomData,
+        }
+    }
+}
+
+impl<'de, 'a: 'b, 'b, E, M, S> Visitor<'de> for VisitSaveData<'a, 'b, E, M, S>
+where
+    E: Display,
+    M: Marker,
+    M::Allocator: PersistentAllocator<M>,
+    S: DeserializeComponents<E, M>,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "struct SaveData")
+    }
+
+    fn visit_seq<SEQ>(mut self, mut seq: SEQ) -> Result<(), SEQ::Error>
+    where
+        SEQ: SeqAccess<'de>,
+    {
+        let state = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &"struct SaveData with 2 elements"))?;
+        self.allocator.restore(state);
+        seq.next_element_seed(self.visit_entities())?
+            .ok_or_else(|| de::Error::invalid_length(1, &"struct SaveData with 2 elements"))
+    }
+
+    fn visit_map<MAP>(mut self, mut map: MAP) -> Result<(), MAP::Error>
+    where
+        MAP: MapAccess<'de>,
+    {
+        let mut allocator = false;
+        let mut entities = false;
+        while let Some(field) = map.next_key()? {
+            match field {
+                SaveDataField::Allocator => {
+                    let state = map.next_value()?;
+                    self.allocator.restore(state);
+                    allocator = true;
+                }
+                SaveDataField::Entities => {
+                    map.next_value_seed(self.visit_entities())?;
+                    entities = true;
+                }
+                SaveDataField::Other => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        if !allocator {
+            return Err(de::Error::missing_field("allocator"));
+        }
+        if !entities {
+            return Err(de::Error::missing_field("entities"));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum DiffDataField {
+    Base,
+    Removed,
+    Entities,
+    #[serde(other)]
+    Other,
+}
+
+/// Wrapper for `Entities` and tuple of `WriteStorage`s that deserializes a
+/// `DiffData` based on snapshot `base`.
+struct VisitDiffData<'a: 'b, 'b, E, M: Marker, S: 'b> {
+    base: u64,
+    allocator: &'b mut M::Allocator,
+    entities: &'b EntitiesRes,
+    markers: &'b mut WriteStorage<'a, M>,
+    storages: &'b mut S,
+    options: &'b DeserializeOptions,
+    progress: &'b mut Progress,
+    report: &'b mut DeserializeReport<M>,
+    pd: PhantomData<E>,
+}
+
+impl<'a: 'b, 'b, E, M: Marker, S> VisitDiffData<'a, 'b, E, M, S> {
+    fn check_base<ERR: de::Error>(&self, base: u64) -> Result<(), ERR> {
+        if base == self.base {
+            Ok(())
+        } else {
+            Err(de::Error::custom(format!(
+                "diff is based on snapshot {}, but the world is at snapshot {}",
+                base, self.base
+            )))
+        }
+    }
+
+    fn remove(&mut self, removed: Vec<M>) {
+        for marker in removed {
+            let entity = self
+                .allocator
+                .retrieve_existing_entity(marker.clone(), self.markers);
+            if let Some(entity) = entity {
+                let _ = self.entities.delete(entity);
+            }
+            self.allocator.retire(&marker);
+        }
+    }
+
+    fn visit_entities(&mut self) -> VisitEntities<'a, '_, E, M, S> {
+        VisitEntities {
+            allocator: self.allocator,
+            entities: self.entities,
+            markers: self.markers,
+            storages: self.storages,
+            options: self.options,
+            progress: self.progress,
+            report: self.report,
+            pd: PhantomData,
+        }
+    }
+}
+
+impl<'de, 'a: 'b, 'b, E, M, S> Visitor<'de> for VisitDiffData<'a, 'b, E, M, S>
+where
+    E: Display,
+    M: Marker,
+    S: DeserializeComponents<E, M>,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "struct DiffData")
+    }
+
+    fn visit_seq<SEQ>(mut self, mut seq: SEQ) -> Result<(), SEQ::Error>
+    where
+        SEQ: SeqAccess<'de>,
+    {
+        let expected = &"struct DiffData with 3 elements";
+        let base = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, expected))?;
+        self.check_base(base)?;
+        let removed = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, expected))?;
+        self.remove(removed);
+        seq.next_element_seed(self.visit_entities())?
+            .ok_or_else(|| de::Error::invalid_length(2, expected))
+    }
+
+    fn visit_map<MAP>(mut self, mut map: MAP) -> Result<(), MAP::Error>
+    where
+        MAP: MapAccess<'de>,
+    {
+        let mut base = false;
+        let mut entities = false;
+        while let Some(field) = map.next_key()? {
+            match field {
+                DiffDataField::Base => {
+                    self.check_base(map.next_value()?)?;
+                    base = true;
+                }
+                DiffDataField::Removed if base => {
+                    let removed = map.next_value()?;
+                    self.remove(removed);
+                }
+                DiffDataField::Entities if base => {
+                    map.next_value_seed(self.visit_entities())?;
+                    entities = true;
+                }
+                DiffDataField::Removed | DiffDataField::Entities => {
+                    return Err(de::Error::custom("`base` has to come first in a DiffData"));
+                }
+                DiffDataField::Other => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        if !base {
+            return Err(de::Error::missing_field("base"));
+        }
+        if !entities {
+            return Err(de::Error::missing_field("entities"));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum EntityDataField {
+    Marker,
+    Components,
+    #[serde(other)]
+    Other,
+}
+
+/// Deserializes `EntityData` like its derived implementation, but adds the
+/// marker and component to errors. If the flag is set, trailing component
+/// entries which don't belong to any storage are skipped; their number is
+/// returned along with the data.
+struct EntityDataSeed<E, M, S>(bool, PhantomData<(E, M, S)>);
+
+impl<'de, E, M, S> DeserializeSeed<'de> for EntityDataSeed<E, M, S>
+where
+    E: Display,
+    M: Marker,
+    S: DeserializeComponents<E, M>,
+{
+    type Value = (EntityData<M, S::Data>, usize);
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct("EntityData", &["marker", "components"], self)
+    }
+}
+
+impl<'de, E, M, S> Visitor<'de> for EntityDataSeed<E, M, S>
+where
+    E: Display,
+    M: Marker,
+    S: DeserializeComponents<E, M>,
+{
+    type Value = (EntityData<M, S::Data>, usize);
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "struct EntityData")
+    }
+
+    fn visit_seq<SEQ>(self, mut seq: SEQ) -> Result<Self::Value, SEQ::Error>
+    where
+        SEQ: SeqAccess<'de>,
+    {
+        let marker: M = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let (components, unknown) = seq
+            .next_element_seed(ComponentsSeed::<E, M, S>(self.0, PhantomData))
+            .map_err(|e| de::Error::custom(Error::new(e).with_marker(&marker)))?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+        Ok((EntityData { marker, components }, unknown))
+    }
+
+    fn visit_map<MAP>(self, mut map: MAP) -> Result<Self::Value, MAP::Error>
+    where
+        MAP: MapAccess<'de>,
+    {
+        let mut marker: Option<M> = None;
+        let mut components = None;
+        while let Some(field) = map.next_key()? {
+            match field {
+                EntityDataField::Marker => marker = Some(map.next_value()?),
+                EntityDataField::Components => {
+                    let seed = ComponentsSeed::<E, M, S>(self.0, PhantomData);
+                    components = Some(map.next_value_seed(seed).map_err(|e| match marker {
+                        Some(ref marker) => de::Error::custom(Error::new(e).with_marker(marker)),
+                        None => e,
+                    })?)
+                }
+                EntityDataField::Other => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        let marker = marker.ok_or_else(|| de::Error::missing_field("marker"))?;
+        let (components, unknown) =
+            components.ok_or_else(|| de::Error::missing_field("components"))?;
+
+        Ok((EntityData { marker, components }, unknown))
+    }
+}
+
+/// Deserializes the component tuple of an entity. If the flag is set,
+/// entries past the known storages are counted and skipped, otherwise they
+/// are an error.
+struct ComponentsSeed<E, M, S>(bool, PhantomData<(E, M, S)>);
+
+impl<'de, E, M, S> DeserializeSeed<'de> for ComponentsSeed<E, M, S>
+where
+    E: Display,
+    M: Marker,
+    S: DeserializeComponents<E, M>,
+{
+    type Value = (S::Data, usize);
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_tuple(S::COMPONENTS, self)
+    }
+}
+
+impl<'de, E, M, S> Visitor<'de> for ComponentsSeed<E, M, S>
+where
+    E: Display,
+    M: Marker,
+    S: DeserializeComponents<E, M>,
+{
+    type Value = (S::Data, usize);
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "a tuple of components")
+    }
+
+    fn visit_seq<SEQ>(self, mut seq: SEQ) -> Result<Self::Value, SEQ::Error>
+    where
+        SEQ: SeqAccess<'de>,
+    {
+        let skip_unknown = self.0;
+        let data = S::deserialize_data_seq(&mut seq, !skip_unknown)?;
+        let mut unknown = 0;
+        while seq.next_element::<IgnoredAny>()?.is_some() {
+            if !skip_unknown {
+                return Err(de::Error::invalid_length(S::COMPONENTS + 1, &self));
+            }
+            unknown += 1;
+        }
+
+        Ok((data, unknown))
+    }
+}
+
+/// Checks that `column` has one value per index and that its indices refer
+/// to one of `len` entities.
+fn validate_column<D>(column: &Column<D>, len: usize) -> Result<(), String> {
+    if column.indices.len() != column.values.len() {
+        return Err(format!(
+            "column has {} indices but {} values",
+            column.indices.len(),
+            column.values.len(),
+        ));
+    }
+    match column.indices.iter().find(|&&index| index as usize >= len) {
+        Some(index) => Err(format!(
+            "column index {} is out of range for {} entities",
+            index, len,
+        )),
+        None => Ok(()),
     }
 }
 
@@ -158,14 +974,48 @@ macro_rules! deserialize_components {
                     <<$sto as GenericWriteStorage>::Component as ConvertSaveload<M>>::Data
                 >,)*
             );
+            type Columns = ($(
+                Column<
+                    <<$sto as GenericWriteStorage>::Component as ConvertSaveload<M>>::Data
+                >,)*
+            );
+
+            const COMPONENTS: usize = <[&str]>::len(&[$(stringify!($sto),)*]);
 
             #[allow(unused)]
-            fn deserialize_entity<F>(
+            fn deserialize_data_seq<'de, A>(seq: &mut A, strict: bool) -> Result<Self::Data, A::Error>
+            where
+                A: SeqAccess<'de>
+            {
+                let mut index = 0;
+                Ok(($(
+                    {
+                        let component = type_name::<<$sto as GenericWriteStorage>::Component>();
+                        let element = seq.next_element::<Option<
+                            <<$sto as GenericWriteStorage>::Component as ConvertSaveload<M>>::Data
+                        >>().map_err(|e| {
+                            de::Error::custom(Error::new(e).with_component(component))
+                        })?;
+                        if strict && element.is_none() {
+                            return Err(de::Error::invalid_length(
+                                index,
+                                &"a tuple with an entry for every component",
+                            ));
+                        }
+                        index += 1;
+                        element.unwrap_or(None)
+                    },
+                )*))
+            }
+
+            #[allow(unused)]
+            fn deserialize_entity_with<F>(
                 &mut self,
                 entity: Entity,
                 components: Self::Data,
+                policy: MergePolicy,
                 mut ids: F,
-            ) -> Result<(), E>
+            ) -> Result<(), Error<E>>
             where
                 F: FnMut(M) -> Option<Entity>
             {
@@ -173,15 +1023,99 @@ macro_rules! deserialize_components {
                 let ($(ref mut $sto,)*) = *self;
                 #[allow(bad_style)]
                 let ($($comp,)*) = components;
+                let overwrite = policy == MergePolicy::Overwrite;
+                let replace = overwrite || policy == MergePolicy::Patch;
                 $(
                     if let Some(component) = $comp {
-                        $sto.insert(entity, ConvertSaveload::<M>::convert_from(component, &mut ids)?);
-                    } else {
+                        if replace || !$sto.contains(entity) {
+                            let component = ConvertSaveload::<M>::convert_from(component, &mut ids)
+                                .map_err(|e| {
+                                    Error::new(E::from(e)).with_component(
+                                        type_name::<<$sto as GenericWriteStorage>::Component>(),
+                                    )
+                                })?;
+                            $sto.insert(entity, component);
+                        }
+                    } else if overwrite {
                         $sto.remove(entity);
                     }
                 )*
                 Ok(())
             }
+
+            #[allow(unused)]
+            fn validate_data<F>(components: Self::Data, mut ids: F) -> Result<(), Error<E>>
+            where
+                F: FnMut(M) -> Option<Entity>
+            {
+                #[allow(bad_style)]
+                let ($($comp,)*) = components;
+                $(
+                    if let Some(component) = $comp {
+                        <<$sto as GenericWriteStorage>::Component as ConvertSaveload<M>>::convert_from(
+                            component,
+                            &mut ids,
+                        )
+                        .map_err(|e| {
+                            Error::new(E::from(e)).with_component(
+                                type_name::<<$sto as GenericWriteStorage>::Component>(),
+                            )
+                        })?;
+                    }
+                )*
+                Ok(())
+            }
+
+            #[allow(unused)]
+            fn conflicts(&self, entity: Entity, components: &Self::Data) -> bool {
+                #[allow(bad_style)]
+                let ($(ref $sto,)*) = *self;
+                #[allow(bad_style)]
+                let ($(ref $comp,)*) = *components;
+
+                false $(|| ($comp.is_some() && $sto.contains(entity)))*
+            }
+
+            #[allow(unused)]
+            fn deserialize_columns_data<F>(
+                &mut self,
+                entities: &[Entity],
+                columns: Self::Columns,
+                mut ids: F,
+            ) -> Result<(), Error<String>>
+            where
+                F: FnMut(M) -> Option<Entity>
+            {
+                #[allow(bad_style)]
+                let ($(ref mut $sto,)*) = *self;
+                #[allow(bad_style)]
+                let ($($comp,)*) = columns;
+                $(
+                    validate_column(&$comp, entities.len()).map_err(|e| {
+                        Error::new(e)
+                            .with_component(type_name::<<$sto as GenericWriteStorage>::Component>())
+                    })?;
+                )*
+                $(
+                    let mut present = vec![false; entities.len()];
+                    for (index, component) in $comp.indices.into_iter().zip($comp.values) {
+                        let component = ConvertSaveload::<M>::convert_from(component, &mut ids)
+                            .map_err(|e| {
+                                Error::new(E::from(e).to_string()).with_component(
+                                    type_name::<<$sto as GenericWriteStorage>::Component>(),
+                                )
+                            })?;
+                        present[index as usize] = true;
+                        $sto.insert(entities[index as usize], component);
+                    }
+                    for (&entity, present) in entities.iter().zip(present) {
+                        if !present {
+                            $sto.remove(entity);
+                        }
+                    }
+                )*
+                Ok(())
+            }
         }
 
         deserialize_components!(@pop $($comp => $sto,)*);
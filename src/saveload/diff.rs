@@ -0,0 +1,196 @@
+//! Tracking which marked entities changed between saves.
+
+use std::{
+    any::TypeId,
+    collections::{HashMap, HashSet},
+};
+
+use hibitset::BitSet;
+use shrev::ReaderId;
+
+use crate::{
+    join::Join,
+    saveload::marker::Marker,
+    storage::{ComponentEvent, ReadStorage, Tracked, WriteStorage},
+    world::{Component, EntitiesRes, Entity},
+};
+
+/// The changes since the last snapshot of a `DiffTracker`, to be written
+/// with `SerializeComponents::serialize_diff`.
+#[derive(Clone, Debug)]
+pub struct Diff<M> {
+    /// The snapshot the changes are relative to.
+    pub base: u64,
+    /// The ids of the entities whose components changed.
+    pub changed: BitSet,
+    /// The markers of entities that were deleted or unmarked.
+    pub removed: Vec<M>,
+}
+
+/// Tracks which entities marked with `M` changed since the last save, so
+/// frequent saves only have to write those.
+///
+/// Every `commit` starts a new snapshot, numbered from `1`; snapshot `0` is
+/// the empty world, so the first diff contains all marked entities. A diff
+/// with `base` `n` is loaded with `DeserializeComponents::apply_diff` into
+/// a world at snapshot `n`, which is at snapshot `n + 1` afterwards.
+///
+/// Changes to components are picked up from the events of flagged storages
+/// registered with `track` and read with `record`; changes to other storages
+/// have to be reported with `mark_dirty`. Newly marked entities are always
+/// part of the diff.
+///
+/// ## Examples
+///
+/// ```
+/// # extern crate ron;
+/// use std::convert::Infallible;
+///
+/// use serde::{Deserialize, Serialize};
+/// use specs::{
+///     prelude::*,
+///     saveload::{
+///         DiffTracker, MarkedBuilder, SerializeComponents, SimpleMarker, SimpleMarkerAllocator,
+///     },
+/// };
+///
+/// #[derive(Clone, Deserialize, Serialize)]
+/// struct Pos(i32);
+///
+/// impl Component for Pos {
+///     type Storage = FlaggedStorage<Self>;
+/// }
+///
+/// struct Save;
+///
+/// let mut world = World::new();
+/// world.register::<Pos>();
+/// world.register::<SimpleMarker<Save>>();
+/// world.insert(SimpleMarkerAllocator::<Save>::new());
+///
+/// let mut tracker = DiffTracker::<SimpleMarker<Save>>::new();
+/// tracker.track(&mut world.write_storage::<Pos>());
+/// world.create_entity().with(Pos(1)).marked::<SimpleMarker<Save>>().build();
+///
+/// let pos = world.read_storage::<Pos>();
+/// let markers = world.read_storage::<SimpleMarker<Save>>();
+/// tracker.record(&pos);
+/// let diff = tracker.diff(&world.entities(), &markers);
+/// let mut ser = ron::ser::Serializer::new(None, false);
+/// SerializeComponents::<Infallible, _>::serialize_diff(
+///     &(&pos,),
+///     &world.entities(),
+///     &markers,
+///     &diff,
+///     &mut ser,
+/// )
+/// .unwrap();
+/// tracker.commit(&world.entities(), &markers);
+/// assert_eq!(tracker.snapshot(), 1);
+/// ```
+pub struct DiffTracker<M> {
+    snapshot: u64,
+    dirty: BitSet,
+    saved: HashSet<M>,
+    readers: HashMap<TypeId, ReaderId<ComponentEvent>>,
+}
+
+impl<M: Marker> DiffTracker<M> {
+    /// Creates a tracker at snapshot `0`.
+    pub fn new() -> Self {
+        DiffTracker {
+            snapshot: 0,
+            dirty: BitSet::new(),
+            saved: HashSet::new(),
+            readers: HashMap::new(),
+        }
+    }
+
+    /// The current snapshot, i.e. the number of commits so far.
+    pub fn snapshot(&self) -> u64 {
+        self.snapshot
+    }
+
+    /// Starts tracking the changes to the components in `storage`.
+    pub fn track<C>(&mut self, storage: &mut WriteStorage<C>)
+    where
+        C: Component,
+        C::Storage: Tracked,
+    {
+        let reader = storage.register_reader();
+        self.readers.insert(TypeId::of::<C>(), reader);
+    }
+
+    /// Marks the entities with events in the tracked `storage` as changed.
+    /// This has to be called for every tracked storage before `diff`,
+    /// otherwise events pile up in the storage.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the storage isn't tracked.
+    pub fn record<C>(&mut self, storage: &ReadStorage<C>)
+    where
+        C: Component,
+        C::Storage: Tracked,
+    {
+        let reader = self
+            .readers
+            .get_mut(&TypeId::of::<C>())
+            .expect("`DiffTracker::record` called with a storage that isn't tracked");
+        for event in storage.channel().read(reader) {
+            match *event {
+                ComponentEvent::Inserted(id)
+                | ComponentEvent::Modified(id)
+                | ComponentEvent::Removed(id) => {
+                    self.dirty.add(id);
+                }
+            }
+        }
+    }
+
+    /// Marks `entity` as changed.
+    pub fn mark_dirty(&mut self, entity: Entity) {
+        self.dirty.add(entity.id());
+    }
+
+    /// Returns the changes since the current snapshot.
+    pub fn diff(&self, entities: &EntitiesRes, markers: &ReadStorage<M>) -> Diff<M> {
+        let mut changed = BitSet::new();
+        let mut current = HashSet::new();
+        for (entity, marker) in (entities, markers).join() {
+            if self.dirty.contains(entity.id()) || !self.saved.contains(marker) {
+                changed.add(entity.id());
+            }
+            current.insert(marker);
+        }
+        let removed = self
+            .saved
+            .iter()
+            .filter(|marker| !current.contains(marker))
+            .cloned()
+            .collect();
+
+        Diff {
+            base: self.snapshot,
+            changed,
+            removed,
+        }
+    }
+
+    /// Starts a new snapshot after the changes returned by `diff`, or the
+    /// whole world, were saved.
+    pub fn commit(&mut self, entities: &EntitiesRes, markers: &ReadStorage<M>) {
+        self.snapshot += 1;
+        self.dirty.clear();
+        self.saved = (entities, markers)
+            .join()
+            .map(|(_, marker)| marker.clone())
+            .collect();
+    }
+}
+
+impl<M: Marker> Default for DiffTracker<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,92 @@
+//! Deserialization errors with context.
+
+use std::{
+    error::Error as StdError,
+    fmt::{Debug, Display, Formatter, Result as FmtResult},
+};
+
+/// An error which occurred while deserializing an entity, together with
+/// where it happened.
+///
+/// `DeserializeComponents::deserialize` reports these through the
+/// deserializer's error type, so its message reads like
+/// `entity #2: marker SimpleMarker(5, ..): component game::Pos: invalid type`.
+#[derive(Clone, Debug)]
+pub struct Error<E> {
+    /// The position of the entity in the serialized sequence.
+    pub index: Option<usize>,
+    /// The `Debug` representation of the entity's marker.
+    pub marker: Option<String>,
+    /// The type name of the component that failed.
+    pub component: Option<&'static str>,
+    /// The underlying error.
+    pub source: E,
+}
+
+impl<E> Error<E> {
+    /// Creates an error without any context.
+    pub fn new(source: E) -> Self {
+        Error {
+            index: None,
+            marker: None,
+            component: None,
+            source,
+        }
+    }
+
+    /// Sets the position of the entity in the serialized sequence.
+    pub fn with_index(mut self, index: usize) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Sets the marker of the entity.
+    pub fn with_marker<M: Debug>(mut self, marker: &M) -> Self {
+        self.marker = Some(format!("{:?}", marker));
+        self
+    }
+
+    /// Sets the component which failed.
+    pub fn with_component(mut self, component: &'static str) -> Self {
+        self.component = Some(component);
+        self
+    }
+
+    /// Converts the underlying error, keeping the context.
+    pub fn map_source<T, F>(self, f: F) -> Error<T>
+    where
+        F: FnOnce(E) -> T,
+    {
+        Error {
+            index: self.index,
+            marker: self.marker,
+            component: self.component,
+            source: f(self.source),
+        }
+    }
+}
+
+impl<E: Display> Display for Error<E> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        if let Some(index) = self.index {
+            write!(f, "entity #{}: ", index)?;
+        }
+        if let Some(ref marker) = self.marker {
+            write!(f, "marker {}: ", marker)?;
+        }
+        if let Some(component) = self.component {
+            write!(f, "component {}: ", component)?;
+        }
+
+        write!(f, "{}", self.source)
+    }
+}
+
+impl<E> StdError for Error<E>
+where
+    E: StdError + 'static,
+{
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&self.source)
+    }
+}
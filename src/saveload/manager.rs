@@ -0,0 +1,354 @@
+//! Named save slots on disk and a system which saves into them.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    fs::{self, File},
+    io,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::{
+    prelude::*,
+    saveload::{
+        de::DeserializeComponents,
+        marker::Marker,
+        ser::SerializeComponents,
+        stream::{load_from_reader, save_to_writer, Format, StreamError},
+    },
+    world::EntitiesRes,
+};
+
+/// A save slot known to a `SaveManager`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SaveSlot {
+    /// The name of the slot.
+    pub name: String,
+    /// The file the slot is saved to.
+    pub path: PathBuf,
+    /// When the slot was last written, if it exists.
+    pub saved_at: Option<SystemTime>,
+}
+
+/// A resource keeping track of named save slots, all stored as files in one
+/// directory.
+///
+/// Saves can be written directly with `save`, or requested with
+/// `request_save` and left to an `AutoSaveSystem`.
+///
+/// ## Examples
+///
+/// ```
+/// # extern crate specs;
+/// # #[macro_use] extern crate serde;
+/// use std::convert::Infallible;
+///
+/// use specs::{
+///     prelude::*,
+///     saveload::{Format, MarkedBuilder, SaveManager, SimpleMarker, SimpleMarkerAllocator},
+/// };
+///
+/// #[derive(Clone, Serialize, Deserialize)]
+/// struct Pos(f32, f32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// struct Save;
+///
+/// let mut world = World::new();
+/// world.register::<Pos>();
+/// world.register::<SimpleMarker<Save>>();
+/// world.insert(SimpleMarkerAllocator::<Save>::new());
+/// world.create_entity().with(Pos(1.0, 2.0)).marked::<SimpleMarker<Save>>().build();
+///
+/// let directory = std::env::temp_dir().join("specs-save-manager-doc");
+/// let mut manager = SaveManager::new(&directory, Format::Json);
+/// let (entities, pos, markers) = world.system_data::<(
+///     Entities,
+///     ReadStorage<Pos>,
+///     ReadStorage<SimpleMarker<Save>>,
+/// )>();
+/// manager
+///     .save::<Infallible, _, _>("quicksave", &(&pos,), &entities, &markers)
+///     .unwrap();
+/// assert!(manager.slot("quicksave").unwrap().saved_at.is_some());
+/// # std::fs::remove_dir_all(&directory).unwrap();
+/// ```
+pub struct SaveManager {
+    directory: PathBuf,
+    format: Format,
+    slots: BTreeMap<String, SaveSlot>,
+    requested: Vec<String>,
+    last_error: Option<StreamError>,
+}
+
+impl SaveManager {
+    /// Creates a manager which saves into `directory` with `format`. The
+    /// directory is created on the first save.
+    pub fn new<P: Into<PathBuf>>(directory: P, format: Format) -> Self {
+        SaveManager {
+            directory: directory.into(),
+            format,
+            slots: BTreeMap::new(),
+            requested: Vec::new(),
+            last_error: None,
+        }
+    }
+
+    /// The directory holding the save files.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// The format of the save files.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Returns the slot called `name`, if it was saved or found by `scan`.
+    pub fn slot(&self, name: &str) -> Option<&SaveSlot> {
+        self.slots.get(name)
+    }
+
+    /// Returns all known slots, ordered by name.
+    pub fn slots(&self) -> impl Iterator<Item = &SaveSlot> {
+        self.slots.values()
+    }
+
+    /// Returns the file the slot called `name` is saved to.
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.directory
+            .join(format!("{}.{}", name, extension(self.format)))
+    }
+
+    /// Adds the save files already in the directory to the known slots. A
+    /// missing directory has no slots.
+    pub fn scan(&mut self) -> io::Result<()> {
+        let entries = match fs::read_dir(&self.directory) {
+            Ok(entries) => entries,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(extension(self.format)) {
+                continue;
+            }
+            let name = match path.file_stem().and_then(|name| name.to_str()) {
+                Some(name) => name.to_owned(),
+                None => continue,
+            };
+            let saved_at = fs::metadata(&path)?.modified().ok();
+            self.slots.insert(
+                name.clone(),
+                SaveSlot {
+                    name,
+                    path,
+                    saved_at,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Asks the `AutoSaveSystem`s to save into the slot called `name` the
+    /// next time they run.
+    pub fn request_save(&mut self, name: &str) {
+        if !self.requested.iter().any(|requested| requested == name) {
+            self.requested.push(name.to_owned());
+        }
+    }
+
+    /// Returns the error of the last failed save done by an
+    /// `AutoSaveSystem`, clearing it.
+    pub fn take_error(&mut self) -> Option<StreamError> {
+        self.last_error.take()
+    }
+
+    /// Saves all marked entities into the slot called `name`, like
+    /// `save_to_writer`.
+    ///
+    /// The save is written to a temporary file first, so an existing save is
+    /// only replaced once the new one is complete.
+    pub fn save<E, M, S>(
+        &mut self,
+        name: &str,
+        storages: &S,
+        entities: &EntitiesRes,
+        markers: &ReadStorage<M>,
+    ) -> Result<(), StreamError>
+    where
+        E: Display,
+        M: Marker,
+        S: SerializeComponents<E, M>,
+    {
+        let path = self.path(name);
+        let partial = path.with_extension("partial");
+        fs::create_dir_all(&self.directory)?;
+        save_to_writer(self.format, storages, entities, markers, File::create(&partial)?)?;
+        fs::rename(&partial, &path)?;
+
+        self.slots.insert(
+            name.to_owned(),
+            SaveSlot {
+                name: name.to_owned(),
+                path,
+                saved_at: Some(SystemTime::now()),
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Loads the slot called `name` into the given storages, like
+    /// `load_from_reader`.
+    pub fn load<'a, E, M, S>(
+        &self,
+        name: &str,
+        storages: &mut S,
+        entities: &EntitiesRes,
+        markers: &mut WriteStorage<'a, M>,
+        allocator: &mut M::Allocator,
+    ) -> Result<(), StreamError>
+    where
+        E: Display,
+        M: Marker,
+        S: DeserializeComponents<E, M>,
+    {
+        let file = File::open(self.path(name))?;
+
+        load_from_reader(self.format, storages, entities, markers, allocator, file)
+    }
+}
+
+fn extension(format: Format) -> &'static str {
+    match format {
+        #[cfg(feature = "serde_json")]
+        Format::Json => "json",
+        #[cfg(feature = "bincode")]
+        Format::Bincode => "bin",
+    }
+}
+
+/// A tuple of component types whose `ReadStorage`s an `AutoSaveSystem`
+/// fetches, e.g. `(Pos, Vel)`.
+pub trait ComponentStorages<'a> {
+    /// The tuple of `ReadStorage`s.
+    type Storages: SystemData<'a>;
+}
+
+macro_rules! component_storages {
+    ($($comp:ident,)*) => {
+        impl<'a, $($comp,)*> ComponentStorages<'a> for ($($comp,)*)
+        where
+            $($comp: Component,)*
+        {
+            type Storages = ($(ReadStorage<'a, $comp>,)*);
+        }
+
+        component_storages!(@pop $($comp,)*);
+    };
+    (@pop) => {};
+    (@pop $head:ident, $($tail:ident,)*) => {
+        component_storages!($($tail,)*);
+    };
+}
+
+component_storages!(CA, CB, CC, CD, CE, CF, CG, CH, CI, CJ, CK, CL, CM, CN, CO, CP,);
+
+/// Saves the components `C` of all entities marked with `M` through the
+/// `SaveManager` resource, into one slot every `interval` and into every
+/// slot passed to `SaveManager::request_save`.
+///
+/// Failed saves are kept in the manager, see `SaveManager::take_error`.
+///
+/// ## Examples
+///
+/// ```
+/// # extern crate specs;
+/// # #[macro_use] extern crate serde;
+/// use std::{convert::Infallible, time::Duration};
+///
+/// use specs::{
+///     prelude::*,
+///     saveload::{AutoSaveSystem, SimpleMarker},
+/// };
+///
+/// #[derive(Clone, Serialize, Deserialize)]
+/// struct Pos(f32, f32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// struct Save;
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(
+///         AutoSaveSystem::<Infallible, SimpleMarker<Save>, (Pos,)>::new(
+///             "autosave",
+///             Some(Duration::from_secs(300)),
+///         ),
+///         "autosave",
+///         &[],
+///     )
+///     .build();
+/// # let _ = &mut dispatcher;
+/// ```
+pub struct AutoSaveSystem<E, M, C> {
+    slot: String,
+    interval: Option<Duration>,
+    last_save: Instant,
+    marker: PhantomData<(E, M, C)>,
+}
+
+impl<E, M, C> AutoSaveSystem<E, M, C> {
+    /// Creates a system which saves into `slot` every `interval`, or only on
+    /// request if `interval` is `None`.
+    pub fn new(slot: &str, interval: Option<Duration>) -> Self {
+        AutoSaveSystem {
+            slot: slot.to_owned(),
+            interval,
+            last_save: Instant::now(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, E, M, C> System<'a> for AutoSaveSystem<E, M, C>
+where
+    E: Display,
+    M: Marker,
+    C: ComponentStorages<'a>,
+    C::Storages: SerializeComponents<E, M>,
+{
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, M>,
+        WriteExpect<'a, SaveManager>,
+        C::Storages,
+    );
+
+    fn run(&mut self, (entities, markers, mut manager, storages): Self::SystemData) {
+        let mut slots = std::mem::take(&mut manager.requested);
+        if let Some(interval) = self.interval {
+            if self.last_save.elapsed() >= interval && !slots.contains(&self.slot) {
+                slots.push(self.slot.clone());
+            }
+        }
+        if slots.contains(&self.slot) {
+            self.last_save = Instant::now();
+        }
+
+        for slot in slots {
+            if let Err(e) = manager.save(&slot, &storages, &entities, &markers) {
+                manager.last_error = Some(e);
+            }
+        }
+    }
+}
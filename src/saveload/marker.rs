@@ -11,7 +11,7 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{
     prelude::*,
-    world::{EntitiesRes, EntityResBuilder, LazyBuilder},
+    world::{EntitiesRes, EntityResBuilder, LazyBuilder, Reset},
 };
 
 /// A common trait for `EntityBuilder` and `LazyBuilder` with a marker function,
@@ -19,6 +19,14 @@ use crate::{
 pub trait MarkedBuilder {
     /// Add a `Marker` to the entity by fetching the associated allocator.
     ///
+    /// This marks entities while they're created, so they don't need to be
+    /// marked in a separate pass before saving.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the marker isn't registered or its allocator isn't added
+    /// to the `World`. For `LazyBuilder`, this happens on `World::maintain`.
+    ///
     /// ## Examples
     ///
     /// ```
@@ -40,7 +48,10 @@ pub trait MarkedBuilder {
     /// world.register::<SimpleMarker<NetworkSync>>();
     /// world.insert(SimpleMarkerAllocator::<NetworkSync>::new());
     ///
-    /// mark_entity(world.create_entity());
+    /// let entity = mark_entity(world.create_entity());
+    /// assert!(world
+    ///     .read_storage::<SimpleMarker<NetworkSync>>()
+    ///     .contains(entity));
     /// ```
     fn marked<M: Marker>(self) -> Self;
 }
@@ -339,6 +350,20 @@ pub trait MarkerAllocator<M: Marker>: Resource {
         entity
     }
 
+    /// Like `retrieve_entity`, but returns `None` instead of creating a new
+    /// entity if no entity has a marker with the same id.
+    fn retrieve_existing_entity(
+        &mut self,
+        marker: M,
+        storage: &mut WriteStorage<M>,
+    ) -> Option<Entity> {
+        let entity = self.retrieve_entity_internal(marker.id())?;
+        let mut marker_comp = storage.get_mut(entity)?;
+        marker_comp.update(marker);
+
+        Some(entity)
+    }
+
     /// Create new unique marker `M` and attach it to entity.
     /// Or get old marker if this entity is already marked.
     /// If entity is dead then this will return `None`.
@@ -361,39 +386,143 @@ pub trait MarkerAllocator<M: Marker>: Resource {
         Some((storage.get(entity).unwrap(), new))
     }
 
+    /// Forgets the entity mapped to `marker`, e.g. because it was deleted.
+    ///
+    /// The default implementation does nothing and leaves cleanup to
+    /// `maintain`.
+    fn retire(&mut self, _marker: &M) {}
+
     /// Maintain internal data. Cleanup if necessary.
+    ///
+    /// `MaintainMarkers` can be added to a dispatcher to call this
+    /// regularly, so the mappings of deleted entities don't pile up.
     fn maintain(&mut self, _entities: &EntitiesRes, _storage: &ReadStorage<M>);
 }
 
+/// A `MarkerAllocator` whose state can be stored along with the saved
+/// entities.
+///
+/// Restoring the state makes sure ids allocated after loading never collide
+/// with ids that were in use when saving, even if those entities weren't
+/// loaded. See `SerializeComponents::serialize_with_allocator`.
+pub trait PersistentAllocator<M: Marker>: MarkerAllocator<M> {
+    /// The serializable state, e.g. the next id to hand out.
+    type State: Serialize + DeserializeOwned;
+
+    /// Returns the current state.
+    fn state(&self) -> Self::State;
+
+    /// Merges `state` into this allocator, so it won't hand out any id that
+    /// was in use when `state` was saved.
+    fn restore(&mut self, state: Self::State);
+}
+
+/// An identifier type usable with `SimpleMarker` and `SimpleMarkerAllocator`.
+///
+/// Implemented for `u32`, `u64`, `u128` and `String`. Generated `String` ids
+/// are decimal numbers; human-readable ids can be passed to
+/// `MarkerAllocator::allocate` explicitly.
+pub trait MarkerId:
+    Clone + Debug + Eq + Hash + DeserializeOwned + Serialize + Send + Sync + 'static
+{
+    /// The first id handed out by a new allocator.
+    fn first() -> Self;
+
+    /// The id handed out after `self`.
+    fn next(&self) -> Self;
+
+    /// Called for every explicitly allocated `id`, so `index` (the next id
+    /// to hand out) can be moved past it.
+    fn reserve(index: &mut Self, id: &Self);
+}
+
+macro_rules! impl_marker_id {
+    ($($ty:ty),*) => {
+        $(
+            impl MarkerId for $ty {
+                fn first() -> Self {
+                    0
+                }
+
+                fn next(&self) -> Self {
+                    self + 1
+                }
+
+                fn reserve(index: &mut Self, id: &Self) {
+                    if *id >= *index {
+                        *index = id + 1;
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_marker_id!(u32, u64, u128);
+
+impl MarkerId for String {
+    fn first() -> Self {
+        "0".to_owned()
+    }
+
+    fn next(&self) -> Self {
+        self.parse::<u64>().map_or(0, |index| index + 1).to_string()
+    }
+
+    fn reserve(index: &mut Self, id: &Self) {
+        if let (Ok(current), Ok(id)) = (index.parse::<u64>(), id.parse::<u64>()) {
+            if id >= current {
+                *index = (id + 1).to_string();
+            }
+        }
+    }
+}
+
 /// Basic marker implementation usable for saving and loading, uses `u64` as
-/// identifier
+/// identifier by default; see `MarkerId` for the other identifier types.
 #[derive(Serialize, Deserialize)]
 #[repr(transparent)]
-pub struct SimpleMarker<T: ?Sized>(u64, #[serde(skip)] PhantomData<T>);
+pub struct SimpleMarker<T: ?Sized, I = u64>(I, #[serde(skip)] PhantomData<T>);
+
+/// A `SimpleMarker` with `u32` identifiers.
+pub type U32Marker<T> = SimpleMarker<T, u32>;
+
+/// A `SimpleMarker` with `u128` identifiers.
+pub type U128Marker<T> = SimpleMarker<T, u128>;
+
+/// A `SimpleMarker` with `String` identifiers.
+pub type StringMarker<T> = SimpleMarker<T, String>;
 
-impl<T: ?Sized> Clone for SimpleMarker<T> {
+impl<T: ?Sized, I> SimpleMarker<T, I> {
+    /// Creates a marker with the given id, bypassing any allocator.
+    pub(crate) fn new(id: I) -> Self {
+        SimpleMarker(id, PhantomData)
+    }
+}
+
+impl<T: ?Sized, I: Clone> Clone for SimpleMarker<T, I> {
     fn clone(&self) -> Self {
-        *self
+        SimpleMarker(self.0.clone(), PhantomData)
     }
 }
 
-impl<T: ?Sized> Copy for SimpleMarker<T> {}
+impl<T: ?Sized, I: Copy> Copy for SimpleMarker<T, I> {}
 
-impl<T: ?Sized> PartialEq for SimpleMarker<T> {
+impl<T: ?Sized, I: PartialEq> PartialEq for SimpleMarker<T, I> {
     fn eq(&self, other: &Self) -> bool {
         self.0 == other.0
     }
 }
 
-impl<T: ?Sized> Eq for SimpleMarker<T> {}
+impl<T: ?Sized, I: Eq> Eq for SimpleMarker<T, I> {}
 
-impl<T: ?Sized> Hash for SimpleMarker<T> {
+impl<T: ?Sized, I: Hash> Hash for SimpleMarker<T, I> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.0.hash(state);
     }
 }
 
-impl<T: ?Sized> Debug for SimpleMarker<T> {
+impl<T: ?Sized, I: Debug> Debug for SimpleMarker<T, I> {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt.debug_tuple("SimpleMarker")
             .field(&self.0)
@@ -402,33 +531,35 @@ impl<T: ?Sized> Debug for SimpleMarker<T> {
     }
 }
 
-impl<T> Component for SimpleMarker<T>
+impl<T, I> Component for SimpleMarker<T, I>
 where
     T: 'static + ?Sized + Send + Sync,
+    I: MarkerId,
 {
     type Storage = DenseVecStorage<Self>;
 }
 
-impl<T> Marker for SimpleMarker<T>
+impl<T, I> Marker for SimpleMarker<T, I>
 where
     T: 'static + ?Sized + Send + Sync,
+    I: MarkerId,
 {
-    type Allocator = SimpleMarkerAllocator<T>;
-    type Identifier = u64;
+    type Allocator = SimpleMarkerAllocator<T, I>;
+    type Identifier = I;
 
-    fn id(&self) -> u64 {
-        self.0
+    fn id(&self) -> I {
+        self.0.clone()
     }
 }
 
-/// Basic marker allocator, uses `u64` as identifier
-pub struct SimpleMarkerAllocator<T: ?Sized> {
-    index: u64,
-    mapping: HashMap<u64, Entity>,
+/// Basic marker allocator, uses `u64` as identifier by default
+pub struct SimpleMarkerAllocator<T: ?Sized, I = u64> {
+    index: I,
+    mapping: HashMap<I, Entity>,
     _phantom_data: PhantomData<T>,
 }
 
-impl<T: ?Sized> Debug for SimpleMarkerAllocator<T> {
+impl<T: ?Sized, I: Debug> Debug for SimpleMarkerAllocator<T, I> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         fmt.debug_struct("SimpleMarkerAllocator")
             .field("index", &self.index)
@@ -438,58 +569,74 @@ impl<T: ?Sized> Debug for SimpleMarkerAllocator<T> {
     }
 }
 
-impl<T: ?Sized> Clone for SimpleMarkerAllocator<T> {
+impl<T: ?Sized, I: Clone> Clone for SimpleMarkerAllocator<T, I> {
     fn clone(&self) -> Self {
         Self {
-            index: self.index,
+            index: self.index.clone(),
             mapping: self.mapping.clone(),
             _phantom_data: PhantomData,
         }
     }
 }
 
-impl<T: ?Sized> Default for SimpleMarkerAllocator<T> {
+impl<T: ?Sized, I: MarkerId> Default for SimpleMarkerAllocator<T, I> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<T: ?Sized> SimpleMarkerAllocator<T> {
+impl<T: ?Sized, I: MarkerId> SimpleMarkerAllocator<T, I> {
     /// Create new `SimpleMarkerAllocator` which will yield `SimpleMarker`s
-    /// starting with `0`
+    /// starting with `MarkerId::first` (`0` for `u64`)
     pub fn new() -> Self {
         Self {
-            index: 0,
+            index: I::first(),
             mapping: HashMap::new(),
             _phantom_data: PhantomData,
         }
     }
 }
 
-impl<T> MarkerAllocator<SimpleMarker<T>> for SimpleMarkerAllocator<T>
+impl<T: ?Sized, I: MarkerId> Reset for SimpleMarkerAllocator<T, I> {
+    fn reset(&mut self) {
+        self.index = I::first();
+        self.mapping.clear();
+    }
+}
+
+impl<T, I> MarkerAllocator<SimpleMarker<T, I>> for SimpleMarkerAllocator<T, I>
 where
     T: 'static + ?Sized + Send + Sync,
+    I: MarkerId,
 {
-    fn allocate(&mut self, entity: Entity, id: Option<u64>) -> SimpleMarker<T> {
+    fn allocate(&mut self, entity: Entity, id: Option<I>) -> SimpleMarker<T, I> {
         let marker = if let Some(id) = id {
-            if id >= self.index {
-                self.index = id + 1;
-            }
+            I::reserve(&mut self.index, &id);
             SimpleMarker(id, PhantomData)
         } else {
-            self.index += 1;
-            SimpleMarker(self.index - 1, PhantomData)
+            // Explicit ids aren't necessarily ordered (e.g. strings), so skip
+            // any that are taken already.
+            while self.mapping.contains_key(&self.index) {
+                self.index = self.index.next();
+            }
+            let id = self.index.clone();
+            self.index = id.next();
+            SimpleMarker(id, PhantomData)
         };
         self.mapping.insert(marker.id(), entity);
 
         marker
     }
 
-    fn retrieve_entity_internal(&self, id: u64) -> Option<Entity> {
+    fn retrieve_entity_internal(&self, id: I) -> Option<Entity> {
         self.mapping.get(&id).cloned()
     }
 
-    fn maintain(&mut self, entities: &EntitiesRes, storage: &ReadStorage<SimpleMarker<T>>) {
+    fn retire(&mut self, marker: &SimpleMarker<T, I>) {
+        self.mapping.remove(&marker.0);
+    }
+
+    fn maintain(&mut self, entities: &EntitiesRes, storage: &ReadStorage<SimpleMarker<T, I>>) {
         // FIXME: may be too slow
         self.mapping = (entities, storage)
             .join()
@@ -497,3 +644,24 @@ where
             .collect();
     }
 }
+
+impl<T, I> PersistentAllocator<SimpleMarker<T, I>> for SimpleMarkerAllocator<T, I>
+where
+    T: 'static + ?Sized + Send + Sync,
+    I: MarkerId,
+{
+    type State = I;
+
+    fn state(&self) -> I {
+        self.index.clone()
+    }
+
+    fn restore(&mut self, index: I) {
+        // `reserve` only moves the index if the saved one is at least as big.
+        let mut current = self.index.clone();
+        I::reserve(&mut current, &index);
+        if current != self.index {
+            self.index = index;
+        }
+    }
+}
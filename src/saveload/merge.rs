@@ -0,0 +1,163 @@
+//! Merging saved entities into a world which already has marked entities.
+
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{
+    prelude::SystemData,
+    saveload::{
+        options::{ConflictPolicy, MergeReport},
+        DeserializeComponents, EntityData, Error, Marker, MarkerAllocator, MergePolicy,
+    },
+    world::{World, WorldExt},
+};
+
+/// Loads the entities of another save into a world, resolving markers which
+/// are taken already.
+///
+/// ## Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use specs::{
+///     prelude::*,
+///     saveload::{ConflictPolicy, EntityData, MarkedBuilder, MergeWorld, SimpleMarker,
+///         SimpleMarkerAllocator},
+/// };
+///
+/// #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+/// struct Name(String);
+///
+/// impl Component for Name {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// struct Net;
+/// type NetMarker = SimpleMarker<Net>;
+///
+/// let mut world = World::new();
+/// world.register::<Name>();
+/// world.register::<NetMarker>();
+/// world.insert(SimpleMarkerAllocator::<Net>::new());
+/// world.create_entity().with(Name("local".into())).marked::<NetMarker>().build();
+///
+/// // Another save which used the same marker id.
+/// let save: Vec<EntityData<NetMarker, (Option<Name>,)>> =
+///     serde_json::from_str(r#"[{"marker": [0], "components": ["incoming"]}]"#).unwrap();
+///
+/// let report = world
+///     .merge_from::<_, NetMarker, (WriteStorage<Name>,)>(save, ConflictPolicy::Rename)
+///     .unwrap_or_else(|e: specs::saveload::Error<std::convert::Infallible>| panic!("{}", e));
+/// assert_eq!(report.renamed.len(), 1);
+/// assert_eq!(world.read_storage::<Name>().join().count(), 2);
+/// ```
+pub trait MergeWorld {
+    /// Loads the entities of `save` with the storages `S`, creating new
+    /// entities for markers the world doesn't know yet. `policy` decides what
+    /// happens to entities whose marker already belongs to an entity.
+    ///
+    /// References between the entities of `save` follow their entities, so
+    /// they point to the renamed entities with `ConflictPolicy::Rename` and
+    /// to the local ones with `ConflictPolicy::PreferLocal`. Loaded entities
+    /// end up exactly as they were saved, like with `MergePolicy::Overwrite`.
+    ///
+    /// Loading stops at the first entity that fails; the entities before it
+    /// stay loaded.
+    fn merge_from<'a, E, M, S>(
+        &'a self,
+        save: Vec<EntityData<M, S::Data>>,
+        policy: ConflictPolicy,
+    ) -> Result<MergeReport<M>, Error<E>>
+    where
+        E: Display,
+        M: Marker,
+        S: SystemData<'a> + DeserializeComponents<E, M>;
+}
+
+impl MergeWorld for World {
+    fn merge_from<'a, E, M, S>(
+        &'a self,
+        save: Vec<EntityData<M, S::Data>>,
+        policy: ConflictPolicy,
+    ) -> Result<MergeReport<M>, Error<E>>
+    where
+        E: Display,
+        M: Marker,
+        S: SystemData<'a> + DeserializeComponents<E, M>,
+    {
+        let entities = self.entities();
+        let mut markers = self.write_storage::<M>();
+        let mut allocator = self.write_resource::<M::Allocator>();
+        let mut storages = self.system_data::<S>();
+
+        // Conflicts are found before anything is allocated, so the markers
+        // handed out for renamed entities can't be taken for conflicts.
+        let conflicts: Vec<bool> = save
+            .iter()
+            .map(|data| {
+                allocator
+                    .retrieve_entity_internal(data.marker.id())
+                    .and_then(|entity| markers.get(entity))
+                    .is_some()
+            })
+            .collect();
+
+        // Incoming markers are claimed first, so renaming can't hand them out.
+        let mut report = MergeReport::default();
+        let mut mapping = HashMap::new();
+        let mut targets = Vec::with_capacity(save.len());
+        for (data, &conflict) in save.iter().zip(&conflicts) {
+            if conflict {
+                report.conflicts.push(data.marker.clone());
+            }
+            let target = if conflict && policy != ConflictPolicy::PreferIncoming {
+                None
+            } else {
+                Some(allocator.retrieve_entity(data.marker.clone(), &mut markers, &entities))
+            };
+            if let Some(entity) = target {
+                mapping.insert(data.marker.clone(), entity);
+            }
+            targets.push(target);
+        }
+
+        for (data, target) in save.iter().zip(&mut targets) {
+            if target.is_some() {
+                continue;
+            }
+            let entity = match policy {
+                ConflictPolicy::Rename => {
+                    let entity = entities.create();
+                    let (marker, _) = allocator
+                        .mark(entity, &mut markers)
+                        .expect("a freshly created entity is alive");
+                    report.renamed.push((data.marker.clone(), marker.clone()));
+                    *target = Some(entity);
+
+                    entity
+                }
+                _ => allocator
+                    .retrieve_entity_internal(data.marker.id())
+                    .expect("conflicting markers belong to an entity"),
+            };
+            mapping.insert(data.marker.clone(), entity);
+        }
+
+        let mut ids = |marker: M| match mapping.get(&marker) {
+            Some(&entity) => Some(entity),
+            None => Some(allocator.retrieve_entity(marker, &mut markers, &entities)),
+        };
+        for (index, (data, target)) in save.into_iter().zip(targets).enumerate() {
+            let entity = match target {
+                Some(entity) => entity,
+                None => continue,
+            };
+            let marker = data.marker;
+            storages
+                .deserialize_entity_with(entity, data.components, MergePolicy::Overwrite, &mut ids)
+                .map_err(|e| e.with_index(index).with_marker(&marker))?;
+            report.entities += 1;
+        }
+
+        Ok(report)
+    }
+}
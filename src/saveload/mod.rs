@@ -23,6 +23,23 @@
 //! of these ids is what `MarkerAllocator`s are responsible for. For an example,
 //! see the docs for the `Marker` trait.
 //!
+//! With the `uuid_entity` feature, `UuidMarker` identifies entities by random
+//! v4 UUIDs, so saves from different machines or sessions can be merged
+//! without id collisions. Otherwise `MergeWorld::merge_from` loads another
+//! save and resolves the markers both have according to a `ConflictPolicy`.
+//!
+//! ## Streaming
+//!
+//! With the `serde_json` or `bincode` feature enabled, `save_to_writer` and
+//! `load_from_reader` write entities to / read them from any `io::Write` /
+//! `io::Read` without buffering the whole world in memory.
+//!
+//! ## Scenes
+//!
+//! With the `ron` and `serde_json` features enabled, `write_scene` and
+//! `read_scene` store entities as RON files with named components, which are
+//! meant to be edited by hand. Children are nested under their parents.
+//!
 
 use std::convert::Infallible;
 
@@ -30,20 +47,87 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::world::Entity;
 
+#[cfg(feature = "bincode")]
+mod blob;
+mod borrowed;
+#[cfg(any(feature = "lz4_flex", feature = "flate2"))]
+mod compression;
+mod convert;
 mod de;
+mod diff;
+mod error;
+#[cfg(any(feature = "serde_json", feature = "bincode"))]
+mod manager;
 mod marker;
+mod merge;
+mod options;
+mod prefab;
+mod progress;
+#[cfg(feature = "serde_json")]
+mod registry;
+#[cfg(all(feature = "ron", feature = "serde_json"))]
+mod scene;
 mod ser;
+mod snapshot;
+mod system;
+#[cfg(any(feature = "serde_json", feature = "bincode"))]
+mod stream;
 #[cfg(test)]
 mod tests;
 #[cfg(feature = "uuid_entity")]
 mod uuid;
+#[cfg(feature = "serde_json")]
+mod world_diff;
 
+#[cfg(feature = "bincode")]
+pub use self::blob::{export_entity, import_entity};
+#[cfg(any(feature = "lz4_flex", feature = "flate2"))]
+pub use self::compression::{CompressedWriter, Compression, DecompressedReader};
+#[cfg(any(feature = "serde_json", feature = "bincode"))]
+pub use self::manager::{AutoSaveSystem, ComponentStorages, SaveManager, SaveSlot};
+#[cfg(feature = "parallel")]
+pub use self::ser::ParSerializeComponents;
+#[cfg(feature = "serde_json")]
+pub use self::registry::{
+    ComponentMap, DynamicDeserialize, DynamicSerialize, SaveloadRegistry, UnknownComponent,
+    UnknownComponents,
+};
+#[cfg(all(feature = "ron", feature = "serde_json"))]
+pub use self::scene::{
+    read_scene, read_scene_into, write_scene, write_subtree, Scene, SceneEntity, SceneError,
+};
+#[cfg(any(feature = "serde_json", feature = "bincode"))]
+pub use self::stream::{
+    load_from_reader, load_from_reader_checked, save_to_writer, save_to_writer_checked, Format,
+    StreamError,
+};
 #[cfg(feature = "uuid_entity")]
 pub use self::uuid::{UuidMarker, UuidMarkerAllocator};
+#[cfg(feature = "serde_json")]
+pub use self::world_diff::{diff, ComponentChange, WorldDiff};
 pub use self::{
-    de::DeserializeComponents,
-    marker::{MarkedBuilder, Marker, MarkerAllocator, SimpleMarker, SimpleMarkerAllocator},
+    borrowed::{ConvertSaveloadBorrowed, DeserializeComponentsBorrowed},
+    convert::ConvertEntities,
+    de::{validate, DeserializeComponents},
+    diff::{Diff, DiffTracker},
+    error::Error,
+    marker::{
+        MarkedBuilder, Marker, MarkerAllocator, MarkerId, PersistentAllocator, SimpleMarker,
+        SimpleMarkerAllocator, StringMarker, U128Marker, U32Marker,
+    },
+    merge::MergeWorld,
+    options::{
+        ConflictPolicy, DeserializeOptions, DeserializeReport, MergePolicy, MergeReport,
+        UnknownMarkerPolicy, ValidationReport,
+    },
+    prefab::{
+        InstantiatePrefab, Prefab, PrefabChild, PrefabComponents, PrefabEntity, PrefabLibrary,
+        PrefabMarker, PrefabReloadSystem, PrefabSource, Template, TemplateData, TemplateLibrary,
+    },
+    progress::{CancellationToken, Cancelled, Progress},
     ser::SerializeComponents,
+    snapshot::{SceneStack, Snapshot, SnapshotRegistry},
+    system::{MaintainMarkers, MarkSystem},
 };
 
 /// A struct used for deserializing entity data.
@@ -55,6 +139,76 @@ pub struct EntityData<M, D> {
     pub components: D,
 }
 
+/// A struct used for (de)serializing entities together with the state of
+/// their `MarkerAllocator`, see `PersistentAllocator`.
+#[derive(Serialize, Deserialize)]
+pub struct SaveData<A, D> {
+    /// The `PersistentAllocator::State` of the allocator.
+    pub allocator: A,
+    /// The entities, in the same format `SerializeComponents::serialize`
+    /// writes.
+    pub entities: D,
+}
+
+/// A struct used for (de)serializing the entities that changed since an
+/// earlier save, see `DiffTracker`.
+#[derive(Serialize, Deserialize)]
+pub struct DiffData<M, D> {
+    /// The snapshot the diff has to be applied to.
+    pub base: u64,
+    /// The markers of the entities that were deleted or unmarked.
+    pub removed: Vec<M>,
+    /// The changed entities, in the same format `SerializeComponents::serialize`
+    /// writes.
+    pub entities: D,
+}
+
+/// A struct used for (de)serializing entities column by column.
+///
+/// Instead of one `EntityData` per entity, the markers of all entities are
+/// written once and every component storage becomes a single `Column`.
+#[derive(Serialize, Deserialize)]
+pub struct ColumnData<M, D> {
+    /// The markers of all serialized entities.
+    pub markers: Vec<M>,
+    /// One `Column` per component storage.
+    pub columns: D,
+}
+
+/// The values of one component storage in a `ColumnData`.
+///
+/// `indices` holds positions into `ColumnData::markers`, in ascending order,
+/// and `values[i]` is the component of the entity at `indices[i]`.
+#[derive(Serialize, Deserialize)]
+pub struct Column<D> {
+    /// The indices of the entities that have this component.
+    pub indices: Vec<u32>,
+    /// The component values, one per index.
+    pub values: Vec<D>,
+}
+
+impl<D> Column<D> {
+    /// Creates an empty column.
+    pub fn new() -> Self {
+        Column {
+            indices: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Appends the component of the entity at `index`.
+    pub fn push(&mut self, index: u32, value: D) {
+        self.indices.push(index);
+        self.values.push(value);
+    }
+}
+
+impl<D> Default for Column<D> {
+    fn default() -> Self {
+        Column::new()
+    }
+}
+
 /// Converts a data type (usually a [`Component`]) into its serializable form
 /// and back to actual data from it's deserialized form.
 ///
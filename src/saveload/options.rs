@@ -0,0 +1,224 @@
+//! Options and reports for `DeserializeComponents::deserialize_with`,
+//! `saveload::validate` and `MergeWorld::merge_from`.
+
+use crate::saveload::Error;
+
+/// Options that control how `DeserializeComponents::deserialize_with`
+/// handles data which doesn't exactly match the storages it's loaded into.
+///
+/// The default options behave like `DeserializeComponents::deserialize`.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::saveload::DeserializeOptions;
+///
+/// let options = DeserializeOptions::new().skip_unknown_components(true);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct DeserializeOptions {
+    pub(crate) skip_unknown_components: bool,
+    pub(crate) merge_policy: MergePolicy,
+    pub(crate) unknown_markers: UnknownMarkerPolicy,
+    pub(crate) collect_apply_errors: bool,
+}
+
+impl DeserializeOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// If enabled, component entries beyond the storages that are being
+    /// deserialized (e.g. written by a newer build with more components) are
+    /// skipped and counted in the `DeserializeReport` instead of failing.
+    /// Entries missing at the end are treated as absent components.
+    ///
+    /// This needs a format which records the length of the component tuple,
+    /// which excludes non-self-describing formats like `bincode`.
+    pub fn skip_unknown_components(mut self, skip: bool) -> Self {
+        self.skip_unknown_components = skip;
+        self
+    }
+
+    /// Sets how components are merged into entities that already exist.
+    /// Defaults to `MergePolicy::Overwrite`.
+    pub fn merge_policy(mut self, policy: MergePolicy) -> Self {
+        self.merge_policy = policy;
+        self
+    }
+
+    /// Sets what happens to entities whose marker isn't known to the
+    /// `MarkerAllocator`. Defaults to `UnknownMarkerPolicy::Create`.
+    ///
+    /// With `Skip` or `Error`, no entities are ever created, which is useful
+    /// to apply authoritative updates to entities that already exist.
+    pub fn unknown_markers(mut self, policy: UnknownMarkerPolicy) -> Self {
+        self.unknown_markers = policy;
+        self
+    }
+
+    /// If enabled, entities which were decoded but fail to be applied to
+    /// the world are listed in the `DeserializeReport`, and loading
+    /// continues with the next entity.
+    ///
+    /// This only covers errors after decoding, like failing
+    /// `ConvertSaveload` conversions, unknown markers with
+    /// `UnknownMarkerPolicy::Error` and merge conflicts; such an entity may
+    /// end up partially loaded. Malformed or mistyped entity data still
+    /// aborts the load, because the deserializer can't resume after it.
+    /// Check a save with `saveload::validate` first to find those.
+    pub fn collect_apply_errors(mut self, collect: bool) -> Self {
+        self.collect_apply_errors = collect;
+        self
+    }
+}
+
+/// Decides what happens when a deserialized entity already has components.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Deserialized components replace existing ones and components that are
+    /// absent from the data are removed, so the entity ends up exactly as
+    /// it was saved.
+    Overwrite,
+    /// Existing components are left untouched; only components the entity
+    /// doesn't have yet are inserted. Nothing is removed.
+    KeepExisting,
+    /// Deserialization fails if the data contains a component the entity
+    /// already has. Otherwise this behaves like `KeepExisting`.
+    ErrorOnConflict,
+    /// Deserialized components replace existing ones, but components absent
+    /// from the data are kept, so the data acts as a patch.
+    Patch,
+}
+
+impl Default for MergePolicy {
+    fn default() -> Self {
+        MergePolicy::Overwrite
+    }
+}
+
+/// Decides what happens to deserialized entities with an unknown marker.
+///
+/// This also applies to entities referenced from components, which resolve to
+/// `None` instead of a new entity unless the policy is `Create`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnknownMarkerPolicy {
+    /// A new entity is created for the marker.
+    Create,
+    /// The entity is skipped and its marker listed in the `DeserializeReport`.
+    Skip,
+    /// Deserialization fails.
+    Error,
+}
+
+impl Default for UnknownMarkerPolicy {
+    fn default() -> Self {
+        UnknownMarkerPolicy::Create
+    }
+}
+
+/// Decides what `MergeWorld::merge_from` does with an incoming entity whose
+/// marker already belongs to an entity of the world.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// The incoming entity is loaded into a new entity with a fresh marker.
+    Rename,
+    /// The incoming entity is skipped and the local one is kept.
+    PreferLocal,
+    /// The incoming entity replaces the components of the local one.
+    PreferIncoming,
+}
+
+impl Default for ConflictPolicy {
+    fn default() -> Self {
+        ConflictPolicy::Rename
+    }
+}
+
+/// Summary of a `DeserializeComponents::deserialize_with` run.
+#[derive(Clone, Debug)]
+pub struct DeserializeReport<M> {
+    /// The number of entities that were deserialized.
+    pub entities: usize,
+    /// The entities for which unknown component entries were skipped,
+    /// together with the number of skipped entries.
+    pub unknown_components: Vec<(M, usize)>,
+    /// The markers of entities that were skipped because they didn't exist,
+    /// see `UnknownMarkerPolicy::Skip`.
+    pub skipped_markers: Vec<M>,
+    /// The errors of entities that failed to load, see
+    /// `DeserializeOptions::collect_apply_errors`.
+    pub errors: Vec<Error<String>>,
+}
+
+impl<M> DeserializeReport<M> {
+    /// Returns `true` if nothing had to be skipped.
+    pub fn is_clean(&self) -> bool {
+        self.unknown_components.is_empty()
+            && self.skipped_markers.is_empty()
+            && self.errors.is_empty()
+    }
+}
+
+impl<M> Default for DeserializeReport<M> {
+    fn default() -> Self {
+        DeserializeReport {
+            entities: 0,
+            unknown_components: Vec::new(),
+            skipped_markers: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+/// Summary of a `saveload::validate` run.
+#[derive(Clone, Debug)]
+pub struct ValidationReport<M> {
+    /// The number of entities that would load without errors.
+    pub entities: usize,
+    /// The errors of entities that would fail to load.
+    pub errors: Vec<Error<String>>,
+    /// Markers which are referenced from components but don't belong to any
+    /// entity in the save. Loading creates empty entities for them.
+    pub dangling_references: Vec<M>,
+}
+
+impl<M> ValidationReport<M> {
+    /// Returns `true` if the save can be loaded without any problems.
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty() && self.dangling_references.is_empty()
+    }
+}
+
+impl<M> Default for ValidationReport<M> {
+    fn default() -> Self {
+        ValidationReport {
+            entities: 0,
+            errors: Vec::new(),
+            dangling_references: Vec::new(),
+        }
+    }
+}
+
+/// Summary of a `MergeWorld::merge_from` run.
+#[derive(Clone, Debug)]
+pub struct MergeReport<M> {
+    /// The number of entities that were loaded.
+    pub entities: usize,
+    /// The incoming markers which already belonged to an entity.
+    pub conflicts: Vec<M>,
+    /// The incoming markers which were replaced with `ConflictPolicy::Rename`,
+    /// together with their new markers.
+    pub renamed: Vec<(M, M)>,
+}
+
+impl<M> Default for MergeReport<M> {
+    fn default() -> Self {
+        MergeReport {
+            entities: 0,
+            conflicts: Vec::new(),
+            renamed: Vec::new(),
+        }
+    }
+}
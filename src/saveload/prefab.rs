@@ -0,0 +1,1023 @@
+//! Templates of entities which can be instantiated any number of times.
+
+use std::{
+    collections::{hash_map::Entry, BTreeMap, HashMap},
+    fmt::Display,
+    marker::PhantomData,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    prelude::*,
+    saveload::{
+        de::DeserializeComponents,
+        error::Error,
+        marker::{Marker, SimpleMarker},
+        options::MergePolicy,
+        ConvertSaveload, EntityData,
+    },
+    world::EntitiesRes,
+};
+
+/// Tag type of `PrefabMarker`.
+pub struct PrefabEntity;
+
+/// The marker entity references inside a `Prefab` are converted with. Its id
+/// is the position at which the referenced entity or child was added to the
+/// prefab.
+pub type PrefabMarker = SimpleMarker<PrefabEntity>;
+
+/// A template of one or more entities, holding the data representation of
+/// their components.
+///
+/// `D` is the `DeserializeComponents::Data` of the storages the prefab is
+/// instantiated with, so a prefab has the same layout as a save written by
+/// `SerializeComponents::serialize` and can be loaded from content files with
+/// serde. Entities inside a prefab refer to each other by `PrefabMarker`s,
+/// which are remapped to the fresh entities on every instantiation.
+///
+/// A prefab can nest other prefabs of a `PrefabLibrary` with `add_child`; its
+/// entities then refer to the first entity of a child by the child's marker,
+/// and a patch stored with the child can refer back to them.
+///
+/// ## Examples
+///
+/// ```
+/// # extern crate specs;
+/// # #[macro_use] extern crate serde;
+/// use std::convert::Infallible;
+///
+/// use specs::{
+///     prelude::*,
+///     saveload::{InstantiatePrefab, Prefab},
+/// };
+///
+/// #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// struct Pos(f32, f32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Pos>();
+///
+/// let mut prefab = Prefab::new();
+/// prefab.add((Some(Pos(0.0, 0.0)),));
+/// prefab.add((Some(Pos(1.0, 0.0)),));
+///
+/// let entities = world
+///     .instantiate::<Infallible, (WriteStorage<Pos>,)>(&prefab)
+///     .unwrap();
+/// world.maintain();
+///
+/// assert_eq!(entities.len(), 2);
+/// assert_eq!(world.read_storage::<Pos>().get(entities[1]), Some(&Pos(1.0, 0.0)));
+/// ```
+#[derive(Serialize, Deserialize)]
+pub struct Prefab<D> {
+    entities: Vec<EntityData<PrefabMarker, D>>,
+    #[serde(default = "Vec::new")]
+    children: Vec<PrefabChild<D>>,
+}
+
+/// A prefab nested in another one, see `Prefab::add_child`.
+#[derive(Serialize, Deserialize)]
+pub struct PrefabChild<D> {
+    /// The marker the entities of the outer prefab refer to the first entity
+    /// of the child with.
+    pub marker: PrefabMarker,
+    /// The name of the child in the `PrefabLibrary`.
+    pub prefab: String,
+    /// Components applied to the first entity of the child with
+    /// `MergePolicy::Patch`, converted with the markers of the outer prefab.
+    pub patch: Option<D>,
+}
+
+impl<D> Prefab<D> {
+    /// Creates a prefab without entities.
+    pub fn new() -> Self {
+        Prefab {
+            entities: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Adds an entity with `components` and returns the marker other
+    /// entities of the prefab can refer to it with.
+    pub fn add(&mut self, components: D) -> PrefabMarker {
+        let marker = self.next_marker();
+        self.entities.push(EntityData {
+            marker,
+            components,
+        });
+
+        marker
+    }
+
+    /// Nests the prefab called `prefab` in the `PrefabLibrary`, which is
+    /// instantiated along with this one. Returns the marker the entities of
+    /// this prefab can refer to the first entity of the child with.
+    ///
+    /// `patch` is applied to the first entity of the child after this prefab
+    /// was loaded, e.g. to point it to its parent.
+    pub fn add_child(&mut self, prefab: &str, patch: Option<D>) -> PrefabMarker {
+        let marker = self.next_marker();
+        self.children.push(PrefabChild {
+            marker,
+            prefab: prefab.to_owned(),
+            patch,
+        });
+
+        marker
+    }
+
+    /// The nested prefabs.
+    pub fn children(&self) -> &[PrefabChild<D>] {
+        &self.children
+    }
+
+    fn next_marker(&self) -> PrefabMarker {
+        SimpleMarker::new((self.entities.len() + self.children.len()) as u64)
+    }
+
+    /// Maps the markers of the entities to the `created` ones.
+    fn ids(&self, created: &[Entity]) -> HashMap<u64, Entity> {
+        self.entities
+            .iter()
+            .zip(created)
+            .map(|(entity_data, &entity)| (entity_data.marker.id(), entity))
+            .collect()
+    }
+
+    /// Loads the components of the prefab and `overrides` into the `created`
+    /// entities. Fails before loading anything if an override refers to an
+    /// entity which isn't in the prefab.
+    fn load<E, S>(
+        &self,
+        storages: &mut S,
+        created: &[Entity],
+        ids: &HashMap<u64, Entity>,
+        overrides: Vec<(PrefabMarker, D)>,
+    ) -> Result<(), Error<String>>
+    where
+        E: Display,
+        S: DeserializeComponents<E, PrefabMarker, Data = D>,
+        D: Clone,
+    {
+        let mut patches = Vec::with_capacity(overrides.len());
+        for (marker, components) in overrides {
+            let entity = match ids.get(&marker.id()) {
+                Some(&entity) => entity,
+                None => {
+                    return Err(Error::new(
+                        "override refers to an entity which isn't in the prefab".to_owned(),
+                    )
+                    .with_marker(&marker))
+                }
+            };
+            patches.push((entity, marker, components, MergePolicy::Patch));
+        }
+        let entity_data = self.entities.iter().zip(created).map(|(entity_data, &entity)| {
+            (
+                entity,
+                entity_data.marker,
+                entity_data.components.clone(),
+                MergePolicy::Overwrite,
+            )
+        });
+
+        for (index, (entity, marker, components, policy)) in
+            entity_data.chain(patches).enumerate()
+        {
+            storages
+                .deserialize_entity_with(entity, components, policy, |marker: PrefabMarker| {
+                    ids.get(&marker.id()).cloned()
+                })
+                .map_err(|e| {
+                    e.with_index(index)
+                        .with_marker(&marker)
+                        .map_source(|source| source.to_string())
+                })?;
+        }
+
+        Ok(())
+    }
+
+    /// Applies the patch of the child with `marker` to its first entity.
+    fn patch_child<E, S>(
+        &self,
+        storages: &mut S,
+        marker: u64,
+        ids: &HashMap<u64, Entity>,
+    ) -> Result<(), Error<String>>
+    where
+        E: Display,
+        S: DeserializeComponents<E, PrefabMarker, Data = D>,
+        D: Clone,
+    {
+        let child = match self.children.iter().find(|child| child.marker.id() == marker) {
+            Some(child) => child,
+            None => return Ok(()),
+        };
+        if let (Some(patch), Some(&root)) = (&child.patch, ids.get(&marker)) {
+            storages
+                .deserialize_entity_with(root, patch.clone(), MergePolicy::Patch, |marker| {
+                    ids.get(&marker.id()).cloned()
+                })
+                .map_err(|e| {
+                    e.with_marker(&child.marker)
+                        .map_source(|source| source.to_string())
+                })?;
+        }
+
+        Ok(())
+    }
+
+    /// The number of entities in the prefab.
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Returns `true` if the prefab has no entities.
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+impl<D> Default for Prefab<D> {
+    fn default() -> Self {
+        Prefab::new()
+    }
+}
+
+/// Creates entities from `Prefab`s; implemented for `World`.
+pub trait InstantiatePrefab {
+    /// Creates fresh entities from `prefab` with the storages `S` and returns
+    /// them in the order of the prefab.
+    ///
+    /// The entities are created atomically, so `World::maintain` has to be
+    /// called before they show up in joins. If a component fails to load,
+    /// all created entities are deleted again.
+    fn instantiate<'a, E, S>(
+        &'a self,
+        prefab: &Prefab<S::Data>,
+    ) -> Result<Vec<Entity>, Error<String>>
+    where
+        E: Display,
+        S: SystemData<'a> + DeserializeComponents<E, PrefabMarker>,
+        S::Data: Clone,
+    {
+        self.instantiate_with::<E, S>(prefab, Vec::new())
+    }
+
+    /// Like `instantiate`, but afterwards applies `overrides` to the entities
+    /// of the prefab their markers refer to, with `MergePolicy::Patch`: the
+    /// components of an override replace the prefab's ones and the others
+    /// are kept. Fails if an override refers to an entity which isn't in
+    /// the prefab.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the prefab has children, which have to be instantiated
+    /// through a `PrefabLibrary`.
+    fn instantiate_with<'a, E, S>(
+        &'a self,
+        prefab: &Prefab<S::Data>,
+        overrides: Vec<(PrefabMarker, S::Data)>,
+    ) -> Result<Vec<Entity>, Error<String>>
+    where
+        E: Display,
+        S: SystemData<'a> + DeserializeComponents<E, PrefabMarker>,
+        S::Data: Clone;
+}
+
+impl InstantiatePrefab for World {
+    fn instantiate_with<'a, E, S>(
+        &'a self,
+        prefab: &Prefab<S::Data>,
+        overrides: Vec<(PrefabMarker, S::Data)>,
+    ) -> Result<Vec<Entity>, Error<String>>
+    where
+        E: Display,
+        S: SystemData<'a> + DeserializeComponents<E, PrefabMarker>,
+        S::Data: Clone,
+    {
+        assert!(
+            prefab.children.is_empty(),
+            "prefabs with children have to be instantiated through a `PrefabLibrary`"
+        );
+        let entities = self.entities();
+        let mut storages = self.system_data::<S>();
+        let created: Vec<Entity> = prefab.entities.iter().map(|_| entities.create()).collect();
+        let ids = prefab.ids(&created);
+
+        if let Err(e) = prefab.load(&mut storages, &created, &ids, overrides) {
+            for &entity in &created {
+                let _ = entities.delete(entity);
+            }
+
+            return Err(e);
+        }
+
+        Ok(created)
+    }
+}
+
+/// Remembers which prefab entity an entity was instantiated from, see
+/// `PrefabLibrary::instantiate`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrefabSource {
+    /// The name of the prefab in the `PrefabLibrary`.
+    pub prefab: String,
+    /// Distinguishes the instances of the same prefab.
+    pub instance: u64,
+    /// The index of the entity in the prefab.
+    pub index: usize,
+    /// If the instance is nested in another prefab, the instance of that
+    /// prefab and the id of the child's `PrefabMarker` in it.
+    pub parent: Option<(u64, u64)>,
+}
+
+impl Component for PrefabSource {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// A resource holding named prefabs whose instances are kept up to date by a
+/// `PrefabReloadSystem`.
+///
+/// Replacing a prefab with `insert`, e.g. after its file changed on disk,
+/// makes the system re-apply it to all of its instances.
+pub struct PrefabLibrary<D> {
+    prefabs: HashMap<String, Prefab<D>>,
+    changed: Vec<String>,
+    next_instance: u64,
+}
+
+impl<D> PrefabLibrary<D> {
+    /// Creates an empty library.
+    pub fn new() -> Self {
+        PrefabLibrary {
+            prefabs: HashMap::new(),
+            changed: Vec::new(),
+            next_instance: 0,
+        }
+    }
+
+    /// Adds the prefab `name`. If it replaces a prefab, the instances of that
+    /// prefab are updated the next time the `PrefabReloadSystem` runs.
+    pub fn insert(&mut self, name: &str, prefab: Prefab<D>) {
+        match self.prefabs.entry(name.to_owned()) {
+            Entry::Occupied(mut entry) => {
+                entry.insert(prefab);
+                self.mark_changed(name);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(prefab);
+            }
+        }
+    }
+
+    /// Returns the prefab `name`.
+    pub fn get(&self, name: &str) -> Option<&Prefab<D>> {
+        self.prefabs.get(name)
+    }
+
+    /// Makes the `PrefabReloadSystem` re-apply the prefab `name`, e.g. after
+    /// modifying it in place.
+    pub fn mark_changed(&mut self, name: &str) {
+        if !self.changed.iter().any(|changed| changed == name) {
+            self.changed.push(name.to_owned());
+        }
+    }
+
+    /// Instantiates the prefab `name` like `InstantiatePrefab::instantiate_with`
+    /// and adds a `PrefabSource` to the new entities.
+    ///
+    /// Children are instantiated recursively before the components of their
+    /// parent are loaded; overrides may refer to their first entities, too.
+    /// Only the entities of the prefab itself are returned.
+    ///
+    /// Fails if there is no prefab `name` or one of its descendants or if a
+    /// prefab contains itself; all created entities are deleted again.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `PrefabSource` isn't registered.
+    pub fn instantiate<'a, E, S>(
+        &mut self,
+        world: &'a World,
+        name: &str,
+        overrides: Vec<(PrefabMarker, D)>,
+    ) -> Result<Vec<Entity>, Error<String>>
+    where
+        E: Display,
+        S: SystemData<'a> + DeserializeComponents<E, PrefabMarker, Data = D>,
+        D: Clone,
+    {
+        let entities = world.entities();
+        let mut spawner = Spawner {
+            prefabs: &self.prefabs,
+            next_instance: &mut self.next_instance,
+            entities: &entities,
+            storages: world.system_data::<S>(),
+            sources: world.write_storage::<PrefabSource>(),
+            stack: Vec::new(),
+            spawned: Vec::new(),
+        };
+
+        let result = spawner.spawn(name, overrides, None);
+        if result.is_err() {
+            for &entity in &spawner.spawned {
+                let _ = entities.delete(entity);
+            }
+        }
+
+        result
+    }
+}
+
+/// The state of a recursive `PrefabLibrary::instantiate`.
+struct Spawner<'a, 'b, D, S> {
+    prefabs: &'b HashMap<String, Prefab<D>>,
+    next_instance: &'b mut u64,
+    entities: &'b EntitiesRes,
+    storages: S,
+    sources: WriteStorage<'a, PrefabSource>,
+    stack: Vec<&'b str>,
+    spawned: Vec<Entity>,
+}
+
+impl<'a, 'b, D, S> Spawner<'a, 'b, D, S> {
+    fn spawn<E>(
+        &mut self,
+        name: &'b str,
+        overrides: Vec<(PrefabMarker, D)>,
+        parent: Option<(u64, u64)>,
+    ) -> Result<Vec<Entity>, Error<String>>
+    where
+        E: Display,
+        S: DeserializeComponents<E, PrefabMarker, Data = D>,
+        D: Clone,
+    {
+        let prefab = match self.prefabs.get(name) {
+            Some(prefab) => prefab,
+            None => return Err(Error::new(format!("no prefab is called `{}`", name))),
+        };
+        if self.stack.contains(&name) {
+            return Err(Error::new(format!("prefab `{}` contains itself", name)));
+        }
+
+        let instance = *self.next_instance;
+        *self.next_instance += 1;
+        let created: Vec<Entity> = prefab
+            .entities
+            .iter()
+            .map(|_| self.entities.create())
+            .collect();
+        self.spawned.extend(&created);
+        for (index, &entity) in created.iter().enumerate() {
+            let source = PrefabSource {
+                prefab: name.to_owned(),
+                instance,
+                index,
+                parent,
+            };
+            // The entities were just created, so they are alive.
+            self.sources.insert(entity, source).unwrap();
+        }
+
+        let mut ids = prefab.ids(&created);
+        self.stack.push(name);
+        for child in &prefab.children {
+            let parent = Some((instance, child.marker.id()));
+            let child_entities = self.spawn(&child.prefab, Vec::new(), parent)?;
+            if let Some(&root) = child_entities.first() {
+                ids.insert(child.marker.id(), root);
+            }
+        }
+        self.stack.pop();
+
+        prefab.load(&mut self.storages, &created, &ids, overrides)?;
+        for child in &prefab.children {
+            prefab.patch_child(&mut self.storages, child.marker.id(), &ids)?;
+        }
+
+        Ok(created)
+    }
+}
+
+impl<D> Default for PrefabLibrary<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A tuple of component types instantiated from prefabs by a
+/// `PrefabReloadSystem`, e.g. `(Pos, Vel)`.
+pub trait PrefabComponents<'a> {
+    /// The data of a prefab entity, `(Option<ConvertSaveload::Data>, ..)`.
+    type Data: Clone + Send + Sync + 'static;
+    /// The tuple of `WriteStorage`s.
+    type Storages: SystemData<'a>;
+}
+
+macro_rules! prefab_components {
+    ($($comp:ident,)*) => {
+        impl<'a, $($comp,)*> PrefabComponents<'a> for ($($comp,)*)
+        where
+            $(
+                $comp: Component + ConvertSaveload<PrefabMarker>,
+                <$comp as ConvertSaveload<PrefabMarker>>::Data: Clone + Send + Sync + 'static,
+            )*
+        {
+            type Data = ($(Option<<$comp as ConvertSaveload<PrefabMarker>>::Data>,)*);
+            type Storages = ($(WriteStorage<'a, $comp>,)*);
+        }
+
+        prefab_components!(@pop $($comp,)*);
+    };
+    (@pop) => {};
+    (@pop $head:ident, $($tail:ident,)*) => {
+        prefab_components!($($tail,)*);
+    };
+}
+
+prefab_components!(CA, CB, CC, CD, CE, CF, CG, CH, CI, CJ, CK, CL, CM, CN, CO, CP,);
+
+/// Re-applies the prefabs of a `PrefabLibrary` which changed to the entities
+/// instantiated from them, keeping the entities and their markers.
+///
+/// The components `C` of every instance are replaced with the prefab's, so
+/// overrides and changes made at runtime are lost. Entities added to a
+/// prefab are created for every instance and entities removed from it are
+/// deleted. Children are kept as they were instantiated; adding or removing
+/// children only affects new instances. Failures are logged and leave the
+/// instance partially updated.
+///
+/// ## Examples
+///
+/// ```
+/// # extern crate specs;
+/// # #[macro_use] extern crate serde;
+/// use std::convert::Infallible;
+///
+/// use specs::{
+///     prelude::*,
+///     saveload::{Prefab, PrefabLibrary, PrefabReloadSystem, PrefabSource},
+/// };
+///
+/// #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// struct Pos(f32, f32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Pos>();
+/// world.register::<PrefabSource>();
+///
+/// let mut library = PrefabLibrary::new();
+/// let mut prefab = Prefab::new();
+/// prefab.add((Some(Pos(0.0, 0.0)),));
+/// library.insert("crate", prefab);
+/// let entity = library
+///     .instantiate::<Infallible, (WriteStorage<Pos>,)>(&world, "crate", Vec::new())
+///     .unwrap()[0];
+/// world.insert(library);
+/// world.maintain();
+///
+/// let mut reload = PrefabReloadSystem::<Infallible, (Pos,)>::new();
+/// let mut prefab = Prefab::new();
+/// prefab.add((Some(Pos(1.0, 1.0)),));
+/// world
+///     .write_resource::<PrefabLibrary<(Option<Pos>,)>>()
+///     .insert("crate", prefab);
+/// reload.run_now(&world);
+///
+/// assert_eq!(world.read_storage::<Pos>().get(entity), Some(&Pos(1.0, 1.0)));
+/// ```
+pub struct PrefabReloadSystem<E, C> {
+    marker: PhantomData<(E, C)>,
+}
+
+impl<E, C> PrefabReloadSystem<E, C> {
+    /// Creates the system.
+    pub fn new() -> Self {
+        PrefabReloadSystem {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<E, C> Default for PrefabReloadSystem<E, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The entities of one instance, collected by a `PrefabReloadSystem`.
+struct Instance {
+    prefab: String,
+    parent: Option<(u64, u64)>,
+    members: HashMap<usize, Entity>,
+}
+
+/// Maps the markers of `prefab` to the `members` of the instance `id`, by
+/// their index in the prefab.
+fn instance_ids<D>(
+    prefab: &Prefab<D>,
+    id: u64,
+    members: &HashMap<usize, Entity>,
+    child_roots: &HashMap<(u64, u64), Entity>,
+) -> HashMap<u64, Entity> {
+    let mut ids: HashMap<u64, Entity> = prefab
+        .entities
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entity_data)| {
+            members
+                .get(&index)
+                .map(|&entity| (entity_data.marker.id(), entity))
+        })
+        .collect();
+    for child in &prefab.children {
+        if let Some(&root) = child_roots.get(&(id, child.marker.id())) {
+            ids.insert(child.marker.id(), root);
+        }
+    }
+
+    ids
+}
+
+impl<'a, E, C> System<'a> for PrefabReloadSystem<E, C>
+where
+    E: Display,
+    C: PrefabComponents<'a>,
+    C::Storages: DeserializeComponents<E, PrefabMarker, Data = C::Data>,
+{
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, PrefabSource>,
+        WriteExpect<'a, PrefabLibrary<C::Data>>,
+        C::Storages,
+    );
+
+    fn run(&mut self, (entities, mut sources, mut library, mut storages): Self::SystemData) {
+        let changed = std::mem::take(&mut library.changed);
+        if changed.is_empty() {
+            return;
+        }
+
+        let mut instances: HashMap<u64, Instance> = HashMap::new();
+        let mut child_roots = HashMap::new();
+        for (entity, source) in (&entities, &sources).join() {
+            instances
+                .entry(source.instance)
+                .or_insert_with(|| Instance {
+                    prefab: source.prefab.clone(),
+                    parent: source.parent,
+                    members: HashMap::new(),
+                })
+                .members
+                .insert(source.index, entity);
+            if let (Some(parent), 0) = (source.parent, source.index) {
+                child_roots.insert(parent, entity);
+            }
+        }
+
+        for name in changed {
+            let prefab = match library.prefabs.get(&name) {
+                Some(prefab) => prefab,
+                None => continue,
+            };
+
+            for (&id, instance) in &instances {
+                if instance.prefab != name {
+                    continue;
+                }
+
+                let mut members = instance.members.clone();
+                members.retain(|&index, &mut entity| {
+                    let kept = index < prefab.len();
+                    if !kept {
+                        // The entity was just joined, so it is alive.
+                        entities.delete(entity).unwrap();
+                    }
+                    kept
+                });
+                let created: Vec<Entity> = (0..prefab.len())
+                    .map(|index| {
+                        *members.entry(index).or_insert_with(|| {
+                            let entity = entities.create();
+                            let source = PrefabSource {
+                                prefab: name.clone(),
+                                instance: id,
+                                index,
+                                parent: instance.parent,
+                            };
+                            sources.insert(entity, source).unwrap();
+                            entity
+                        })
+                    })
+                    .collect();
+
+                let ids = instance_ids(prefab, id, &members, &child_roots);
+                let mut result = prefab.load(&mut storages, &created, &ids, Vec::new());
+                for child in &prefab.children {
+                    result = result.and_then(|_| {
+                        prefab.patch_child(&mut storages, child.marker.id(), &ids)
+                    });
+                }
+                // The patch of the outer prefab has to be applied again, if
+                // any of its entities are still alive.
+                let parent = instance
+                    .parent
+                    .and_then(|(parent_id, marker)| {
+                        instances.get(&parent_id).map(|parent| (parent_id, marker, parent))
+                    });
+                if let Some((parent_id, marker, parent)) = parent {
+                    if let Some(parent_prefab) = library.prefabs.get(&parent.prefab) {
+                        let parent_ids =
+                            instance_ids(parent_prefab, parent_id, &parent.members, &child_roots);
+                        result = result.and_then(|_| {
+                            parent_prefab.patch_child(&mut storages, marker, &parent_ids)
+                        });
+                    }
+                }
+
+                if let Err(e) = result {
+                    log::warn!("Reloading instance {} of prefab `{}` failed: {}", id, name, e);
+                }
+            }
+        }
+    }
+}
+
+/// The component data of a `Template`, which can be merged with the data of
+/// the template it extends.
+///
+/// Implemented for the `DeserializeComponents::Data` of storage tuples, whose
+/// components are keyed by their position, and for maps like `ComponentMap`,
+/// whose components are keyed by name.
+pub trait TemplateData: Clone {
+    /// Identifies a component in the data.
+    type Key: PartialEq + Serialize + DeserializeOwned;
+
+    /// Replaces the components of `self` with the ones present in `overlay`.
+    fn merge(&mut self, overlay: Self);
+
+    /// Removes the component `key`.
+    fn remove(&mut self, key: &Self::Key);
+}
+
+impl<V: Clone> TemplateData for BTreeMap<String, V> {
+    type Key = String;
+
+    fn merge(&mut self, overlay: Self) {
+        self.extend(overlay);
+    }
+
+    fn remove(&mut self, key: &String) {
+        BTreeMap::remove(self, key);
+    }
+}
+
+macro_rules! template_data {
+    ($($comp:ident => $data:ident,)*) => {
+        impl<$($data,)*> TemplateData for ($(Option<$data>,)*)
+        where
+            $($data: Clone,)*
+        {
+            type Key = usize;
+
+            fn merge(&mut self, overlay: Self) {
+                #[allow(bad_style)]
+                let ($(ref mut $data,)*) = *self;
+                #[allow(bad_style)]
+                let ($($comp,)*) = overlay;
+                $(
+                    if $comp.is_some() {
+                        *$data = $comp;
+                    }
+                )*
+            }
+
+            #[allow(unused)]
+            fn remove(&mut self, key: &usize) {
+                #[allow(bad_style)]
+                let ($(ref mut $data,)*) = *self;
+                let mut index = 0;
+                $(
+                    if index == *key {
+                        *$data = None;
+                    }
+                    index += 1;
+                )*
+            }
+        }
+
+        template_data!(@pop $($comp => $data,)*);
+    };
+    (@pop) => {};
+    (@pop $head:ident => $hdata:ident, $($tail:ident => $tdata:ident,)*) => {
+        template_data!($($tail => $tdata,)*);
+    };
+}
+
+template_data!(
+    CA => DA,
+    CB => DB,
+    CC => DC,
+    CD => DD,
+    CE => DE,
+    CF => DF,
+    CG => DG,
+    CH => DH,
+    CI => DI,
+    CJ => DJ,
+    CK => DK,
+    CL => DL,
+    CM => DM,
+    CN => DN,
+    CO => DO,
+    CP => DP,
+);
+
+/// A single entity template which may extend a base template of the same
+/// `TemplateLibrary`.
+///
+/// The components of the base are resolved first, then the components in
+/// `remove` are removed and finally the components present in `components`
+/// replace the base's ones.
+#[derive(Serialize, Deserialize)]
+#[serde(bound(serialize = "D: Serialize", deserialize = "D: Deserialize<'de>"))]
+pub struct Template<D: TemplateData> {
+    /// The name of the template this one extends.
+    #[serde(default)]
+    pub base: Option<String>,
+    /// The components added to or replaced in the base.
+    pub components: D,
+    /// The components of the base this template doesn't have.
+    #[serde(default = "Vec::new")]
+    pub remove: Vec<D::Key>,
+}
+
+impl<D: TemplateData> Template<D> {
+    /// Creates a template with `components` which doesn't extend another one.
+    pub fn new(components: D) -> Self {
+        Template {
+            base: None,
+            components,
+            remove: Vec::new(),
+        }
+    }
+
+    /// Creates a template extending the template `base`, adding or replacing
+    /// `components`.
+    pub fn extend(base: &str, components: D) -> Self {
+        Template {
+            base: Some(base.to_owned()),
+            components,
+            remove: Vec::new(),
+        }
+    }
+
+    /// Removes the component `key` of the base template.
+    pub fn without(mut self, key: D::Key) -> Self {
+        self.remove.push(key);
+        self
+    }
+}
+
+/// Named `Template`s, which are resolved against their bases every time they
+/// are used, so changing a base affects all templates extending it.
+///
+/// ## Examples
+///
+/// ```
+/// # extern crate specs;
+/// # #[macro_use] extern crate serde;
+/// use std::convert::Infallible;
+///
+/// use specs::{
+///     prelude::*,
+///     saveload::{Template, TemplateLibrary},
+/// };
+///
+/// #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// struct Health(u32);
+///
+/// impl Component for Health {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// struct Flying;
+///
+/// impl Component for Flying {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Health>();
+/// world.register::<Flying>();
+///
+/// let mut templates = TemplateLibrary::new();
+/// templates.insert("bird", Template::new((Some(Health(5)), Some(Flying))));
+/// templates.insert("penguin", Template::extend("bird", (Some(Health(8)), None)).without(1));
+///
+/// let penguin = templates
+///     .instantiate::<Infallible, (WriteStorage<Health>, WriteStorage<Flying>)>(&world, "penguin")
+///     .unwrap();
+/// world.maintain();
+///
+/// assert_eq!(world.read_storage::<Health>().get(penguin), Some(&Health(8)));
+/// assert!(!world.read_storage::<Flying>().contains(penguin));
+/// ```
+pub struct TemplateLibrary<D: TemplateData> {
+    templates: HashMap<String, Template<D>>,
+}
+
+impl<D: TemplateData> TemplateLibrary<D> {
+    /// Creates an empty library.
+    pub fn new() -> Self {
+        TemplateLibrary {
+            templates: HashMap::new(),
+        }
+    }
+
+    /// Adds the template `name`, replacing any template with the same name.
+    pub fn insert(&mut self, name: &str, template: Template<D>) {
+        self.templates.insert(name.to_owned(), template);
+    }
+
+    /// Returns the template `name`.
+    pub fn get(&self, name: &str) -> Option<&Template<D>> {
+        self.templates.get(name)
+    }
+
+    /// Returns the components of the template `name` merged with the ones of
+    /// its bases, e.g. to `Prefab::add` them.
+    ///
+    /// Fails if there is no template `name` or one of its bases, or if a
+    /// template extends itself.
+    pub fn resolve(&self, name: &str) -> Result<D, Error<String>> {
+        self.resolve_from(name, &mut Vec::new())
+    }
+
+    fn resolve_from<'b>(
+        &'b self,
+        name: &'b str,
+        stack: &mut Vec<&'b str>,
+    ) -> Result<D, Error<String>> {
+        let template = match self.templates.get(name) {
+            Some(template) => template,
+            None => return Err(Error::new(format!("no template is called `{}`", name))),
+        };
+        if stack.contains(&name) {
+            return Err(Error::new(format!("template `{}` extends itself", name)));
+        }
+
+        let base = match template.base {
+            Some(ref base) => base,
+            None => return Ok(template.components.clone()),
+        };
+        stack.push(name);
+        let mut components = self.resolve_from(base, stack)?;
+        stack.pop();
+
+        for key in &template.remove {
+            components.remove(key);
+        }
+        components.merge(template.components.clone());
+
+        Ok(components)
+    }
+
+    /// Creates a fresh entity from the resolved template `name` with the
+    /// storages `S`, like `InstantiatePrefab::instantiate`. Fails like
+    /// `resolve` if the template can't be resolved.
+    pub fn instantiate<'a, E, S>(
+        &self,
+        world: &'a World,
+        name: &str,
+    ) -> Result<Entity, Error<String>>
+    where
+        E: Display,
+        S: SystemData<'a> + DeserializeComponents<E, PrefabMarker, Data = D>,
+    {
+        let mut prefab = Prefab::new();
+        prefab.add(self.resolve(name)?);
+
+        Ok(world.instantiate::<E, S>(&prefab)?[0])
+    }
+}
+
+impl<D: TemplateData> Default for TemplateLibrary<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,117 @@
+//! Progress reporting and cancellation for long saves and loads.
+
+use std::{
+    error::Error as StdError,
+    fmt::{self, Display, Formatter},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// A flag shared between a save or load and whoever may want to abort it,
+/// e.g. a loading screen running on another thread.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a token which isn't cancelled yet.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Requests the cancellation of every operation using this token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` once `cancel` has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+type Callback = Box<dyn FnMut(usize, Option<usize>) + Send>;
+
+/// Observes `SerializeComponents::serialize_with_progress` and
+/// `DeserializeComponents::deserialize_with_progress`.
+///
+/// The callback is called after every entity with the number of entities
+/// done so far and the total number, if known. Loads only know the total if
+/// the format records the length of the entity sequence, like `bincode`.
+///
+/// A cancelled operation stops before the next entity and fails with a
+/// `Cancelled` error; the entities handled up to then stay loaded.
+///
+/// ## Examples
+///
+/// ```
+/// use std::sync::{
+///     atomic::{AtomicUsize, Ordering},
+///     Arc,
+/// };
+///
+/// use specs::saveload::{CancellationToken, Progress};
+///
+/// let loaded = Arc::new(AtomicUsize::new(0));
+/// let token = CancellationToken::new();
+///
+/// let counter = loaded.clone();
+/// let progress = Progress::new()
+///     .on_entity(move |done, _total| counter.store(done, Ordering::Relaxed))
+///     .cancel_with(token.clone());
+/// # let _ = progress;
+/// ```
+#[derive(Default)]
+pub struct Progress {
+    callback: Option<Callback>,
+    token: Option<CancellationToken>,
+}
+
+impl Progress {
+    /// Creates a `Progress` without callback which can't be cancelled.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the callback called after every entity.
+    pub fn on_entity<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(usize, Option<usize>) + Send + 'static,
+    {
+        self.callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Makes the operation stop once `token` is cancelled.
+    pub fn cancel_with(mut self, token: CancellationToken) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    pub(crate) fn check(&self) -> Result<(), Cancelled> {
+        match self.token {
+            Some(ref token) if token.is_cancelled() => Err(Cancelled),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn entity_done(&mut self, done: usize, total: Option<usize>) {
+        if let Some(ref mut callback) = self.callback {
+            callback(done, total);
+        }
+    }
+}
+
+/// The error a save or load fails with after its `CancellationToken` was
+/// cancelled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl Display for Cancelled {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Operation was cancelled")
+    }
+}
+
+impl StdError for Cancelled {}
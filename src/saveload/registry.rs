@@ -0,0 +1,536 @@
+//! Runtime selection of the components to save and load.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    error::Error as StdError,
+    fmt::{self, Display, Formatter},
+    marker::PhantomData,
+};
+
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde_json::Value;
+
+use crate::{
+    dynamic::{self, ReadDynamic, WriteDynamic},
+    saveload::{
+        error::Error, Column, ConvertSaveload, EntityData, Marker, MarkerAllocator,
+        SerializeComponents,
+    },
+    storage::{DenseVecStorage, ReadStorage, WriteStorage},
+    world::{Component, EntitiesRes, Entity, World, WorldExt},
+};
+
+/// The components of one entity, keyed by their registered name.
+pub type ComponentMap = BTreeMap<String, Value>;
+
+/// A registry of components which can be saved and loaded by name, for
+/// choosing the serialized components at runtime instead of with a tuple of
+/// storages.
+///
+/// Components are converted to `serde_json::Value`s, so the output of
+/// `DynamicSerialize` can only be read back with self-describing formats.
+///
+/// ## Examples
+///
+/// ```
+/// # extern crate ron;
+/// use serde::{Deserialize, Serialize};
+/// use specs::{
+///     prelude::*,
+///     saveload::{
+///         MarkedBuilder, SaveloadRegistry, SerializeComponents, SimpleMarker,
+///         SimpleMarkerAllocator,
+///     },
+/// };
+///
+/// #[derive(Clone, Deserialize, Serialize)]
+/// struct Pos(i32, i32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// #[derive(Clone, Deserialize, Serialize)]
+/// struct Health(u32);
+///
+/// impl Component for Health {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// struct Save;
+/// type SaveMarker = SimpleMarker<Save>;
+///
+/// let mut world = World::new();
+/// world.register::<Pos>();
+/// world.register::<Health>();
+/// world.register::<SaveMarker>();
+/// world.insert(SimpleMarkerAllocator::<Save>::new());
+/// world.create_entity().with(Pos(1, 2)).with(Health(10)).marked::<SaveMarker>().build();
+///
+/// let mut registry = SaveloadRegistry::<SaveMarker>::new();
+/// registry.register::<Pos>("pos");
+/// registry.register::<Health>("health");
+///
+/// let storages = registry.serializer(&world, &["pos"]).unwrap();
+/// let mut ser = ron::ser::Serializer::new(None, true);
+/// SerializeComponents::<String, SaveMarker>::serialize(
+///     &storages,
+///     &world.entities(),
+///     &world.read_storage::<SaveMarker>(),
+///     &mut ser,
+/// )
+/// .unwrap();
+/// assert!(!ser.into_output_string().contains("health"));
+/// ```
+pub struct SaveloadRegistry<M> {
+    components: HashMap<String, Box<dyn RegisteredComponent<M>>>,
+    preserve_unknown: bool,
+}
+
+impl<M: Marker> SaveloadRegistry<M> {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        SaveloadRegistry {
+            components: HashMap::new(),
+            preserve_unknown: false,
+        }
+    }
+
+    /// Registers `C` under `name`, replacing any component registered with
+    /// the same name.
+    pub fn register<C>(&mut self, name: &str)
+    where
+        C: Component + ConvertSaveload<M>,
+        C::Error: Display,
+    {
+        self.components
+            .insert(name.to_owned(), Box::new(Registration::<C>(PhantomData)));
+    }
+
+    /// Registers the dynamic component type `name`, see `specs::dynamic`.
+    /// Its components are saved as they are.
+    pub fn register_dynamic(&mut self, name: &str) {
+        self.components
+            .insert(name.to_owned(), Box::new(DynamicRegistration(name.to_owned())));
+    }
+
+    /// If enabled, components of loaded entities which aren't registered are
+    /// kept in their `UnknownComponents` and written again when saving,
+    /// instead of being dropped. `UnknownComponents` has to be registered in
+    /// the world then.
+    pub fn preserve_unknown(&mut self, preserve: bool) {
+        self.preserve_unknown = preserve;
+    }
+
+    /// Returns `true` if unknown components are preserved, see
+    /// `preserve_unknown`.
+    pub fn preserves_unknown(&self) -> bool {
+        self.preserve_unknown
+    }
+
+    /// Returns the names of all registered components.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.components.keys().map(String::as_str)
+    }
+
+    /// Fetches the storages of the components named in `selection` for
+    /// serialization.
+    pub fn serializer<'a>(
+        &self,
+        world: &'a World,
+        selection: &[&str],
+    ) -> Result<DynamicSerialize<'a, M>, UnknownComponent> {
+        let mut storages = Vec::with_capacity(selection.len());
+        for &name in selection {
+            storages.push((name.to_owned(), self.get(name)?.fetch(world)));
+        }
+
+        let unknown = if self.preserve_unknown {
+            Some(world.read_storage::<UnknownComponents>())
+        } else {
+            None
+        };
+
+        Ok(DynamicSerialize { storages, unknown })
+    }
+
+    /// Fetches the storages of the components named in `selection` for
+    /// deserialization.
+    pub fn deserializer<'a>(
+        &self,
+        world: &'a World,
+        selection: &[&str],
+    ) -> Result<DynamicDeserialize<'a, M>, UnknownComponent> {
+        let mut storages = Vec::with_capacity(selection.len());
+        for &name in selection {
+            storages.push((name.to_owned(), self.get(name)?.fetch_mut(world)));
+        }
+
+        let unknown = if self.preserve_unknown {
+            let registered = self.components.keys().cloned().collect();
+            Some((registered, world.write_storage::<UnknownComponents>()))
+        } else {
+            None
+        };
+
+        Ok(DynamicDeserialize { storages, unknown })
+    }
+
+    fn get(&self, name: &str) -> Result<&dyn RegisteredComponent<M>, UnknownComponent> {
+        self.components
+            .get(name)
+            .map(|component| &**component)
+            .ok_or_else(|| UnknownComponent(name.to_owned()))
+    }
+}
+
+impl<M: Marker> Default for SaveloadRegistry<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Error returned when a selected component isn't registered.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownComponent(pub String);
+
+impl Display for UnknownComponent {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "no component is registered as `{}`", self.0)
+    }
+}
+
+impl StdError for UnknownComponent {}
+
+/// The components of an entity which weren't registered when it was loaded,
+/// in their serialized form, see `SaveloadRegistry::preserve_unknown`.
+///
+/// Entity references inside them stay markers, so they are only valid as
+/// long as the markers are.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UnknownComponents(pub ComponentMap);
+
+impl Component for UnknownComponents {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// The storages of a runtime selection of components, implementing
+/// `SerializeComponents`. Created by `SaveloadRegistry::serializer`.
+///
+/// Each entity's components are written as a map from the registered name to
+/// the component, followed by its `UnknownComponents` if they are preserved.
+pub struct DynamicSerialize<'a, M> {
+    storages: Vec<(String, Box<dyn ErasedRead<M> + 'a>)>,
+    unknown: Option<ReadStorage<'a, UnknownComponents>>,
+}
+
+impl<'a, M: Marker> SerializeComponents<String, M> for DynamicSerialize<'a, M> {
+    type Data = ComponentMap;
+    type Columns = BTreeMap<String, Column<Value>>;
+
+    fn serialize_entity<F>(&self, entity: Entity, mut ids: F) -> Result<ComponentMap, String>
+    where
+        F: FnMut(Entity) -> Option<M>,
+    {
+        let mut components = ComponentMap::new();
+        for (name, storage) in &self.storages {
+            let value = storage
+                .serialize_component(entity, &mut ids)
+                .map_err(|e| format!("component {}: {}", name, e))?;
+            if let Some(value) = value {
+                components.insert(name.clone(), value);
+            }
+        }
+        if let Some(unknown) = self.unknown.as_ref().and_then(|unknown| unknown.get(entity)) {
+            for (name, value) in &unknown.0 {
+                components
+                    .entry(name.clone())
+                    .or_insert_with(|| value.clone());
+            }
+        }
+
+        Ok(components)
+    }
+
+    fn serialize_columns_data<F>(
+        &self,
+        entities: &[Entity],
+        mut ids: F,
+    ) -> Result<Self::Columns, String>
+    where
+        F: FnMut(Entity) -> Option<M>,
+    {
+        let mut columns = BTreeMap::new();
+        for (name, storage) in &self.storages {
+            let mut column = Column::new();
+            for (index, &entity) in entities.iter().enumerate() {
+                let value = storage
+                    .serialize_component(entity, &mut ids)
+                    .map_err(|e| format!("component {}: {}", name, e))?;
+                if let Some(value) = value {
+                    column.push(index as u32, value);
+                }
+            }
+            columns.insert(name.clone(), column);
+        }
+        if let Some(ref unknown) = self.unknown {
+            for (index, &entity) in entities.iter().enumerate() {
+                let components = match unknown.get(entity) {
+                    Some(components) => components,
+                    None => continue,
+                };
+                for (name, value) in &components.0 {
+                    if self.storages.iter().all(|(selected, _)| selected != name) {
+                        columns
+                            .entry(name.clone())
+                            .or_insert_with(Column::new)
+                            .push(index as u32, value.clone());
+                    }
+                }
+            }
+        }
+
+        Ok(columns)
+    }
+}
+
+/// The storages of a runtime selection of components, which loads entities
+/// written by `DynamicSerialize`. Created by
+/// `SaveloadRegistry::deserializer`.
+pub struct DynamicDeserialize<'a, M> {
+    storages: Vec<(String, Box<dyn ErasedWrite<M> + 'a>)>,
+    unknown: Option<(HashSet<String>, WriteStorage<'a, UnknownComponents>)>,
+}
+
+impl<'a, M: Marker> DynamicDeserialize<'a, M> {
+    /// Deserialize entities according to markers, like
+    /// `DeserializeComponents::deserialize`.
+    ///
+    /// Selected components that are missing from an entity are removed from
+    /// it; components that weren't selected are ignored, unless they aren't
+    /// registered and unknown components are preserved.
+    pub fn deserialize<'de, D>(
+        &mut self,
+        entities: &EntitiesRes,
+        markers: &mut WriteStorage<M>,
+        allocator: &mut M::Allocator,
+        deserializer: D,
+    ) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(VisitDynamic {
+            storages: self,
+            entities,
+            markers,
+            allocator,
+        })
+    }
+
+    /// Loads the `components` of a single entity.
+    pub fn deserialize_entity<F>(
+        &mut self,
+        entity: Entity,
+        mut components: ComponentMap,
+        mut ids: F,
+    ) -> Result<(), String>
+    where
+        F: FnMut(M) -> Option<Entity>,
+    {
+        for (name, storage) in &mut self.storages {
+            match components.remove(name.as_str()) {
+                Some(value) => storage
+                    .deserialize_component(entity, value, &mut ids)
+                    .map_err(|e| format!("component {}: {}", name, e))?,
+                None => storage.remove_component(entity),
+            }
+        }
+        if let Some((ref registered, ref mut unknown)) = self.unknown {
+            components.retain(|name, _| !registered.contains(name));
+            if components.is_empty() {
+                unknown.remove(entity);
+            } else {
+                unknown
+                    .insert(entity, UnknownComponents(components))
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct VisitDynamic<'s, 'a, 'm, M: Marker> {
+    storages: &'s mut DynamicDeserialize<'a, M>,
+    entities: &'s EntitiesRes,
+    markers: &'s mut WriteStorage<'m, M>,
+    allocator: &'s mut M::Allocator,
+}
+
+impl<'de, 's, 'a, 'm, M: Marker> Visitor<'de> for VisitDynamic<'s, 'a, 'm, M> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "Sequence of serialized entities")
+    }
+
+    fn visit_seq<SEQ>(self, mut seq: SEQ) -> Result<(), SEQ::Error>
+    where
+        SEQ: SeqAccess<'de>,
+    {
+        let VisitDynamic {
+            storages,
+            entities,
+            markers,
+            allocator,
+        } = self;
+        for index in 0.. {
+            let data = seq
+                .next_element::<EntityData<M, ComponentMap>>()
+                .map_err(|e| de::Error::custom(Error::new(e).with_index(index)))?;
+            let data = match data {
+                Some(data) => data,
+                None => break,
+            };
+            let marker = data.marker.clone();
+            let entity = allocator.retrieve_entity(data.marker, markers, entities);
+            let ids = |marker: M| Some(allocator.retrieve_entity(marker, markers, entities));
+            storages
+                .deserialize_entity(entity, data.components, ids)
+                .map_err(|e| {
+                    de::Error::custom(Error::new(e).with_index(index).with_marker(&marker))
+                })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A component type in a `SaveloadRegistry`.
+trait RegisteredComponent<M>: Send + Sync {
+    fn fetch<'a>(&self, world: &'a World) -> Box<dyn ErasedRead<M> + 'a>;
+
+    fn fetch_mut<'a>(&self, world: &'a World) -> Box<dyn ErasedWrite<M> + 'a>;
+}
+
+struct Registration<C>(PhantomData<fn() -> C>);
+
+impl<M, C> RegisteredComponent<M> for Registration<C>
+where
+    M: Marker,
+    C: Component + ConvertSaveload<M>,
+    C::Error: Display,
+{
+    fn fetch<'a>(&self, world: &'a World) -> Box<dyn ErasedRead<M> + 'a> {
+        Box::new(world.read_storage::<C>())
+    }
+
+    fn fetch_mut<'a>(&self, world: &'a World) -> Box<dyn ErasedWrite<M> + 'a> {
+        Box::new(world.write_storage::<C>())
+    }
+}
+
+struct DynamicRegistration(String);
+
+impl<M> RegisteredComponent<M> for DynamicRegistration {
+    fn fetch<'a>(&self, world: &'a World) -> Box<dyn ErasedRead<M> + 'a> {
+        Box::new(dynamic::read(world, &self.0).expect("Dynamic component type not registered"))
+    }
+
+    fn fetch_mut<'a>(&self, world: &'a World) -> Box<dyn ErasedWrite<M> + 'a> {
+        Box::new(dynamic::write(world, &self.0).expect("Dynamic component type not registered"))
+    }
+}
+
+/// A fetched storage whose components can be converted to `Value`s.
+trait ErasedRead<M> {
+    fn serialize_component(
+        &self,
+        entity: Entity,
+        ids: &mut dyn FnMut(Entity) -> Option<M>,
+    ) -> Result<Option<Value>, String>;
+}
+
+impl<'a, M, C> ErasedRead<M> for ReadStorage<'a, C>
+where
+    C: Component + ConvertSaveload<M>,
+    C::Error: Display,
+{
+    fn serialize_component(
+        &self,
+        entity: Entity,
+        ids: &mut dyn FnMut(Entity) -> Option<M>,
+    ) -> Result<Option<Value>, String> {
+        let component = match self.get(entity) {
+            Some(component) => component,
+            None => return Ok(None),
+        };
+        let data = component.convert_into(ids).map_err(|e| e.to_string())?;
+
+        serde_json::to_value(data)
+            .map(Some)
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// A fetched storage whose components can be loaded from `Value`s.
+trait ErasedWrite<M> {
+    fn deserialize_component(
+        &mut self,
+        entity: Entity,
+        value: Value,
+        ids: &mut dyn FnMut(M) -> Option<Entity>,
+    ) -> Result<(), String>;
+
+    fn remove_component(&mut self, entity: Entity);
+}
+
+impl<'a, M, C> ErasedWrite<M> for WriteStorage<'a, C>
+where
+    C: Component + ConvertSaveload<M>,
+    C::Error: Display,
+{
+    fn deserialize_component(
+        &mut self,
+        entity: Entity,
+        value: Value,
+        ids: &mut dyn FnMut(M) -> Option<Entity>,
+    ) -> Result<(), String> {
+        let data = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        let component = C::convert_from(data, ids).map_err(|e| e.to_string())?;
+        self.insert(entity, component)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    fn remove_component(&mut self, entity: Entity) {
+        self.remove(entity);
+    }
+}
+
+impl<'a, M> ErasedRead<M> for ReadDynamic<'a> {
+    fn serialize_component(
+        &self,
+        entity: Entity,
+        _ids: &mut dyn FnMut(Entity) -> Option<M>,
+    ) -> Result<Option<Value>, String> {
+        Ok(self.get(entity).cloned())
+    }
+}
+
+impl<'a, M> ErasedWrite<M> for WriteDynamic<'a> {
+    fn deserialize_component(
+        &mut self,
+        entity: Entity,
+        value: Value,
+        _ids: &mut dyn FnMut(M) -> Option<Entity>,
+    ) -> Result<(), String> {
+        self.insert(entity, value)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    fn remove_component(&mut self, entity: Entity) {
+        self.remove(entity);
+    }
+}
@@ -0,0 +1,575 @@
+//! Hand-editable scene files in RON.
+//!
+//! A scene lists the marked entities of a world with their components keyed
+//! by the names of a `SaveloadRegistry`:
+//!
+//! ```ron
+//! (
+//!     entities: [
+//!         (
+//!             marker: (0),
+//!             components: {
+//!                 "pos": (x: 1.0, y: 2.0),
+//!                 "name": "player",
+//!             },
+//!         ),
+//!     ],
+//! )
+//! ```
+//!
+//! Components are read like any RON value, so structs may be written as
+//! `(x: 1.0)`, `Pos(x: 1.0)` or `{"x": 1.0}`; `write_scene` uses the last
+//! form. Newtype structs are written as their content, like `"player"` for
+//! `Name(String)`. Missing components are left out of an entity instead of
+//! being written as `None`.
+//!
+//! Entities with a `hierarchy::Parent` are nested under their parent, if it
+//! is marked as well:
+//!
+//! ```ron
+//! (
+//!     entities: [
+//!         (
+//!             marker: (0),
+//!             components: {"name": "car"},
+//!             children: [
+//!                 (marker: (1), components: {"name": "wheel"}),
+//!             ],
+//!         ),
+//!     ],
+//! )
+//! ```
+//!
+//! The `Parent` components aren't written, and loading a scene gives the
+//! nested entities a `Parent` pointing to the entity they are nested under,
+//! so a subtree written with `write_subtree` keeps its structure when it is
+//! loaded into another world, or under another parent with
+//! `read_scene_into`.
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    error::Error as StdError,
+    fmt::{self, Display, Formatter},
+    io::{self, Read, Write},
+};
+
+use ron::{ser::PrettyConfig, Value as RonValue};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+
+use crate::{
+    hierarchy::Parent,
+    join::Join,
+    saveload::{
+        registry::{ComponentMap, SaveloadRegistry, UnknownComponent},
+        Marker, MarkerAllocator, SerializeComponents,
+    },
+    storage::MaskedStorage,
+    world::{Entity, World, WorldExt},
+    BitSet,
+};
+
+/// The contents of a scene file.
+#[derive(Serialize, Deserialize)]
+pub struct Scene<M> {
+    /// The entities of the scene without a parent in the scene.
+    pub entities: Vec<SceneEntity<M>>,
+}
+
+/// An entity of a `Scene`, with its children nested under it.
+#[derive(Serialize, Deserialize)]
+pub struct SceneEntity<M> {
+    /// The marker of the entity.
+    pub marker: M,
+    /// The components of the entity, keyed by their names in the registry.
+    pub components: ComponentMap,
+    /// The entities whose `hierarchy::Parent` is this entity.
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<SceneEntity<M>>,
+}
+
+/// The layout `read_scene` parses. Components are parsed as RON values
+/// first, because `serde_json::Value` can't be deserialized from RON structs.
+#[derive(Deserialize)]
+struct SceneInput<M> {
+    entities: Vec<EntityInput<M>>,
+}
+
+#[derive(Deserialize)]
+struct EntityInput<M> {
+    marker: M,
+    components: BTreeMap<String, RonValue>,
+    #[serde(default = "Vec::new")]
+    children: Vec<EntityInput<M>>,
+}
+
+/// Error returned by `write_scene` and `read_scene`.
+#[derive(Debug)]
+pub enum SceneError {
+    /// Reading from or writing to the underlying stream failed.
+    Io(io::Error),
+    /// The scene couldn't be written as RON.
+    Serialize(ron::ser::Error),
+    /// The scene file isn't valid RON or doesn't have the scene layout.
+    Deserialize(ron::de::Error),
+    /// An entity has a component which isn't registered.
+    UnknownComponent(UnknownComponent),
+    /// A component couldn't be converted.
+    Component(String),
+    /// The root of a subtree isn't marked, see `write_subtree`.
+    Unmarked(Entity),
+    /// The scene has nested entities, but `hierarchy::Parent` isn't
+    /// registered.
+    ParentNotRegistered,
+}
+
+impl Display for SceneError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            SceneError::Io(ref e) => write!(f, "I/O error: {}", e),
+            SceneError::Serialize(ref e) => write!(f, "RON error: {}", e),
+            SceneError::Deserialize(ref e) => write!(f, "RON error: {}", e),
+            SceneError::UnknownComponent(ref e) => write!(f, "{}", e),
+            SceneError::Component(ref e) => write!(f, "{}", e),
+            SceneError::Unmarked(e) => write!(f, "entity {:?} isn't marked", e),
+            SceneError::ParentNotRegistered => write!(
+                f,
+                "`hierarchy::Parent` has to be registered to load nested entities"
+            ),
+        }
+    }
+}
+
+impl StdError for SceneError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            SceneError::Io(ref e) => Some(e),
+            SceneError::Serialize(ref e) => Some(e),
+            SceneError::Deserialize(ref e) => Some(e),
+            SceneError::UnknownComponent(ref e) => Some(e),
+            SceneError::Component(_)
+            | SceneError::Unmarked(_)
+            | SceneError::ParentNotRegistered => None,
+        }
+    }
+}
+
+impl From<io::Error> for SceneError {
+    fn from(e: io::Error) -> Self {
+        SceneError::Io(e)
+    }
+}
+
+impl From<ron::ser::Error> for SceneError {
+    fn from(e: ron::ser::Error) -> Self {
+        SceneError::Serialize(e)
+    }
+}
+
+impl From<ron::de::Error> for SceneError {
+    fn from(e: ron::de::Error) -> Self {
+        SceneError::Deserialize(e)
+    }
+}
+
+impl From<UnknownComponent> for SceneError {
+    fn from(e: UnknownComponent) -> Self {
+        SceneError::UnknownComponent(e)
+    }
+}
+
+/// Writes all entities marked with `M` and their registered components to
+/// `writer` as a pretty-printed scene.
+///
+/// ## Examples
+///
+/// ```
+/// # extern crate specs;
+/// # #[macro_use] extern crate serde;
+/// use specs::{
+///     prelude::*,
+///     saveload::{
+///         read_scene, write_scene, MarkedBuilder, SaveloadRegistry, SimpleMarker,
+///         SimpleMarkerAllocator,
+///     },
+/// };
+///
+/// #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// struct Pos {
+///     x: f32,
+///     y: f32,
+/// }
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// struct Save;
+/// type SaveMarker = SimpleMarker<Save>;
+///
+/// let mut world = World::new();
+/// world.register::<Pos>();
+/// world.register::<SaveMarker>();
+/// world.insert(SimpleMarkerAllocator::<Save>::new());
+///
+/// let mut registry = SaveloadRegistry::<SaveMarker>::new();
+/// registry.register::<Pos>("pos");
+///
+/// let scene = r#"(
+///     entities: [
+///         (marker: (0), components: {"pos": (x: 1.0, y: 2.0)}),
+///     ],
+/// )"#;
+/// read_scene(&world, &registry, scene.as_bytes()).unwrap();
+/// world.maintain();
+///
+/// let mut written = Vec::new();
+/// write_scene(&world, &registry, &mut written).unwrap();
+/// assert!(String::from_utf8(written).unwrap().contains("\"pos\""));
+/// ```
+pub fn write_scene<M, W>(
+    world: &World,
+    registry: &SaveloadRegistry<M>,
+    writer: W,
+) -> Result<(), SceneError>
+where
+    M: Marker,
+    W: Write,
+{
+    write(world, registry, None, writer)
+}
+
+/// Writes `root` and its marked descendants to `writer` like `write_scene`,
+/// with `root` as the only entity at the top of the scene, e.g. to save a
+/// vehicle together with its wheels.
+///
+/// Fails if `root` isn't marked with `M`.
+pub fn write_subtree<M, W>(
+    world: &World,
+    registry: &SaveloadRegistry<M>,
+    root: Entity,
+    writer: W,
+) -> Result<(), SceneError>
+where
+    M: Marker,
+    W: Write,
+{
+    write(world, registry, Some(root), writer)
+}
+
+fn write<M, W>(
+    world: &World,
+    registry: &SaveloadRegistry<M>,
+    root: Option<Entity>,
+    mut writer: W,
+) -> Result<(), SceneError>
+where
+    M: Marker,
+    W: Write,
+{
+    let names: Vec<&str> = registry.names().collect();
+    let storages = registry.serializer(world, &names)?;
+    let entities = world.entities();
+    let markers = world.read_storage::<M>();
+
+    let mut children: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    let mut has_parent = BitSet::new();
+    if world.has_value::<MaskedStorage<Parent>>() {
+        for (child, _, parent) in (&entities, &markers, &world.read_storage::<Parent>()).join() {
+            if markers.contains(parent.0) {
+                children.entry(parent.0).or_default().push(child);
+                has_parent.add(child.id());
+            }
+        }
+    }
+
+    let mut node = |entity: Entity| {
+        let ids = |entity| markers.get(entity).cloned();
+        Ok(SceneEntity {
+            marker: markers
+                .get(entity)
+                .cloned()
+                .expect("Bug: only marked entities are written"),
+            components: storages
+                .serialize_entity(entity, ids)
+                .map_err(SceneError::Component)?,
+            children: Vec::new(),
+        })
+    };
+    let mut scene = Scene {
+        entities: Vec::new(),
+    };
+    let mut written = BitSet::new();
+    match root {
+        Some(root) if !markers.contains(root) => return Err(SceneError::Unmarked(root)),
+        Some(root) => {
+            scene
+                .entities
+                .push(nest(root, &children, &mut written, &mut node)?);
+        }
+        None => {
+            // Entities whose parents form a cycle are written at the top.
+            for &roots_only in &[true, false] {
+                for (entity, _) in (&entities, &markers).join() {
+                    let skip = if roots_only {
+                        has_parent.contains(entity.id())
+                    } else {
+                        written.contains(entity.id())
+                    };
+                    if !skip {
+                        scene
+                            .entities
+                            .push(nest(entity, &children, &mut written, &mut node)?);
+                    }
+                }
+            }
+        }
+    }
+
+    let config = PrettyConfig {
+        enumerate_arrays: false,
+        ..PrettyConfig::default()
+    };
+    writer.write_all(ron::ser::to_string_pretty(&scene, config)?.as_bytes())?;
+
+    Ok(writer.flush()?)
+}
+
+/// Builds the scene entity of `entity` with `node`, with its children which
+/// weren't written yet nested under it.
+fn nest<M, F>(
+    entity: Entity,
+    children: &HashMap<Entity, Vec<Entity>>,
+    written: &mut BitSet,
+    node: &mut F,
+) -> Result<SceneEntity<M>, SceneError>
+where
+    F: FnMut(Entity) -> Result<SceneEntity<M>, SceneError>,
+{
+    written.add(entity.id());
+    let mut scene_entity = node(entity)?;
+    for &child in children.get(&entity).map_or(&[][..], Vec::as_slice) {
+        if !written.contains(child.id()) {
+            let child = nest(child, children, written, node)?;
+            scene_entity.children.push(child);
+        }
+    }
+
+    Ok(scene_entity)
+}
+
+/// Loads the entities of a scene from `reader`, creating entities for
+/// markers that aren't in the world yet.
+///
+/// Registered components missing from an entity are removed from it. A
+/// component name which isn't registered fails the load before anything is
+/// changed, unless the registry preserves unknown components. Nested
+/// entities get a `hierarchy::Parent` pointing to the entity they are
+/// nested under, while the ones at the top of the scene lose theirs. If
+/// `hierarchy::Parent` isn't registered, a scene with nested entities fails
+/// to load with `SceneError::ParentNotRegistered`.
+pub fn read_scene<M, R>(
+    world: &World,
+    registry: &SaveloadRegistry<M>,
+    reader: R,
+) -> Result<(), SceneError>
+where
+    M: Marker,
+    R: Read,
+{
+    read(world, registry, None, reader)
+}
+
+/// Loads the entities of a scene like `read_scene`, making the ones at the
+/// top of the scene children of `parent`, e.g. to put a subtree written
+/// with `write_subtree` somewhere else. This fails with
+/// `SceneError::ParentNotRegistered` if `hierarchy::Parent` isn't
+/// registered.
+///
+/// ## Examples
+///
+/// ```
+/// # extern crate specs;
+/// # #[macro_use] extern crate serde;
+/// use specs::{
+///     hierarchy::Parent,
+///     prelude::*,
+///     saveload::{
+///         read_scene_into, write_subtree, MarkedBuilder, SaveloadRegistry, SimpleMarker,
+///         SimpleMarkerAllocator,
+///     },
+/// };
+///
+/// #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// struct Name(String);
+///
+/// impl Component for Name {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// struct Save;
+/// type SaveMarker = SimpleMarker<Save>;
+///
+/// fn new_world() -> World {
+///     let mut world = World::new();
+///     world.register::<Name>();
+///     world.register::<Parent>();
+///     world.register::<SaveMarker>();
+///     world.insert(SimpleMarkerAllocator::<Save>::new());
+///     world
+/// }
+///
+/// let mut registry = SaveloadRegistry::<SaveMarker>::new();
+/// registry.register::<Name>("name");
+///
+/// let mut world = new_world();
+/// let car = world
+///     .create_entity()
+///     .with(Name("car".into()))
+///     .marked::<SaveMarker>()
+///     .build();
+/// world
+///     .create_entity()
+///     .with(Name("wheel".into()))
+///     .with(Parent(car))
+///     .marked::<SaveMarker>()
+///     .build();
+/// let mut written = Vec::new();
+/// write_subtree(&world, &registry, car, &mut written).unwrap();
+///
+/// let mut garage = new_world();
+/// let slot = garage.create_entity().build();
+/// read_scene_into(&garage, &registry, &written[..], slot).unwrap();
+/// garage.maintain();
+///
+/// let names = garage.read_storage::<Name>();
+/// let parents = garage.read_storage::<Parent>();
+/// let (car, _) = (&garage.entities(), &names)
+///     .join()
+///     .find(|(_, name)| name.0 == "car")
+///     .unwrap();
+/// let (wheel, _) = (&garage.entities(), &names)
+///     .join()
+///     .find(|(_, name)| name.0 == "wheel")
+///     .unwrap();
+/// assert_eq!(parents.get(car), Some(&Parent(slot)));
+/// assert_eq!(parents.get(wheel), Some(&Parent(car)));
+/// ```
+pub fn read_scene_into<M, R>(
+    world: &World,
+    registry: &SaveloadRegistry<M>,
+    reader: R,
+    parent: Entity,
+) -> Result<(), SceneError>
+where
+    M: Marker,
+    R: Read,
+{
+    read(world, registry, Some(parent), reader)
+}
+
+fn read<M, R>(
+    world: &World,
+    registry: &SaveloadRegistry<M>,
+    parent: Option<Entity>,
+    mut reader: R,
+) -> Result<(), SceneError>
+where
+    M: Marker,
+    R: Read,
+{
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    let scene: SceneInput<M> = ron::de::from_str(&input)?;
+
+    let names: Vec<&str> = registry.names().collect();
+    let known = |name: &&String| registry.preserves_unknown() || names.contains(&name.as_str());
+    let mut nested = parent.is_some();
+    let mut unchecked: Vec<&EntityInput<M>> = scene.entities.iter().collect();
+    while let Some(entity_data) = unchecked.pop() {
+        if let Some(name) = entity_data.components.keys().find(|name| !known(name)) {
+            return Err(UnknownComponent(name.clone()).into());
+        }
+        nested |= !entity_data.children.is_empty();
+        unchecked.extend(&entity_data.children);
+    }
+
+    let mut parents = if world.has_value::<MaskedStorage<Parent>>() {
+        Some(world.write_storage::<Parent>())
+    } else if nested {
+        return Err(SceneError::ParentNotRegistered);
+    } else {
+        None
+    };
+    let mut storages = registry.deserializer(world, &names)?;
+    let entities = world.entities();
+    let mut markers = world.write_storage::<M>();
+    let mut allocator = world.write_resource::<M::Allocator>();
+    let mut pending: VecDeque<_> = scene.entities.into_iter().map(|e| (e, parent)).collect();
+    while let Some((entity_data, parent)) = pending.pop_front() {
+        let mut components = ComponentMap::new();
+        for (name, value) in entity_data.components {
+            let value = to_json(value)
+                .map_err(|e| SceneError::Component(format!("component {}: {}", name, e)))?;
+            components.insert(name, value);
+        }
+        let entity = allocator.retrieve_entity(entity_data.marker, &mut markers, &entities);
+        let ids = |marker| Some(allocator.retrieve_entity(marker, &mut markers, &entities));
+        storages
+            .deserialize_entity(entity, components, ids)
+            .map_err(SceneError::Component)?;
+
+        if let Some(ref mut parents) = parents {
+            match parent {
+                Some(parent) => {
+                    parents
+                        .insert(entity, Parent(parent))
+                        .expect("Bug: retrieved entities are alive");
+                }
+                None => {
+                    parents.remove(entity);
+                }
+            }
+        }
+        pending.extend(
+            entity_data
+                .children
+                .into_iter()
+                .map(|child| (child, Some(entity))),
+        );
+    }
+
+    Ok(())
+}
+
+/// Converts a RON value to JSON, keeping integral numbers integers.
+fn to_json(value: RonValue) -> Result<Value, &'static str> {
+    Ok(match value {
+        RonValue::Bool(b) => Value::Bool(b),
+        RonValue::Char(c) => Value::String(c.to_string()),
+        RonValue::Map(map) => {
+            let mut object = Map::new();
+            for (key, value) in map {
+                match key {
+                    RonValue::String(key) => {
+                        object.insert(key, to_json(value)?);
+                    }
+                    _ => return Err("map keys have to be strings"),
+                }
+            }
+            Value::Object(object)
+        }
+        RonValue::Number(n) => {
+            let n = n.get();
+            if n.fract() == 0.0 && n >= std::i64::MIN as f64 && n <= std::i64::MAX as f64 {
+                Value::Number((n as i64).into())
+            } else {
+                Number::from_f64(n).map(Value::Number).ok_or("numbers have to be finite")?
+            }
+        }
+        RonValue::Option(Some(value)) => to_json(*value)?,
+        RonValue::Option(None) | RonValue::Unit => Value::Null,
+        RonValue::String(s) => Value::String(s),
+        RonValue::Seq(seq) => Value::Array(seq.into_iter().map(to_json).collect::<Result<_, _>>()?),
+    })
+}
@@ -1,13 +1,16 @@
-use std::fmt::Display;
+use std::{fmt::Display, marker::PhantomData};
 
+use hibitset::BitSet;
 use serde::ser::{self, Serialize, SerializeSeq, Serializer};
 
 use super::ConvertSaveload;
 use crate::{
     join::Join,
     saveload::{
-        marker::{Marker, MarkerAllocator},
-        EntityData,
+        diff::Diff,
+        marker::{Marker, MarkerAllocator, PersistentAllocator},
+        progress::Progress,
+        Column, ColumnData, DiffData, EntityData, SaveData,
     },
     storage::{GenericReadStorage, ReadStorage, WriteStorage},
     world::{Component, EntitiesRes, Entity},
@@ -21,12 +24,23 @@ where
     /// The data representation of the components.
     type Data: Serialize;
 
+    /// The column-oriented data representation of the components, one
+    /// `Column` per storage.
+    type Columns: Serialize;
+
     /// Serialize the components of a single entity using a entity -> marker
     /// mapping.
     fn serialize_entity<F>(&self, entity: Entity, ids: F) -> Result<Self::Data, E>
     where
         F: FnMut(Entity) -> Option<M>;
 
+    /// Serialize the components of `entities` column by column using a
+    /// entity -> marker mapping. The position of an entity in `entities` is
+    /// the index written to the columns.
+    fn serialize_columns_data<F>(&self, entities: &[Entity], ids: F) -> Result<Self::Columns, E>
+    where
+        F: FnMut(Entity) -> Option<M>;
+
     /// Serialize components from specified storages
     /// of all marked entities with provided serializer.
     /// When the component gets serialized the closure passed
@@ -44,18 +58,145 @@ where
         E: Display,
         S: Serializer,
     {
-        let count = (entities, markers).join().count();
-        let mut serseq = serializer.serialize_seq(Some(count))?;
+        self.serialize_filtered(entities, markers, |_, _| true, serializer)
+    }
+
+    /// Like `serialize`, but only serializes the marked entities for which
+    /// `filter` returns `true`, e.g. the ones in the current level.
+    ///
+    /// References to marked entities that are filtered out still serialize
+    /// their marker, so loading such a save alone creates empty entities for
+    /// them.
+    ///
+    /// To save the entities in a `BitSet`, use
+    /// `|entity, _| bitset.contains(entity.id())`.
+    fn serialize_filtered<S, F>(
+        &self,
+        entities: &EntitiesRes,
+        markers: &ReadStorage<M>,
+        filter: F,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        E: Display,
+        S: Serializer,
+        F: Fn(Entity, &M) -> bool,
+    {
+        serialize_marked(self, entities, markers, filter, &mut Progress::new(), serializer)
+    }
+
+    /// Like `serialize`, but reports every serialized entity to `progress`
+    /// and stops with a `Cancelled` error once its token is cancelled.
+    fn serialize_with_progress<S>(
+        &self,
+        entities: &EntitiesRes,
+        markers: &ReadStorage<M>,
+        progress: &mut Progress,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        E: Display,
+        S: Serializer,
+    {
+        serialize_marked(self, entities, markers, |_, _| true, progress, serializer)
+    }
+
+    /// Like `serialize`, but wraps the entities in a `SaveData` together
+    /// with the state of `allocator`.
+    ///
+    /// The output has to be read back with
+    /// `DeserializeComponents::deserialize_with_allocator`, which restores
+    /// the allocator so it doesn't hand out ids of entities that were saved
+    /// but not loaded.
+    fn serialize_with_allocator<S>(
+        &self,
+        entities: &EntitiesRes,
+        markers: &ReadStorage<M>,
+        allocator: &M::Allocator,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        Self: Sized,
+        E: Display,
+        S: Serializer,
+        M::Allocator: PersistentAllocator<M>,
+    {
+        SaveData {
+            allocator: allocator.state(),
+            entities: SerializeEntities {
+                storages: self,
+                entities,
+                markers,
+                changed: None,
+                pd: PhantomData,
+            },
+        }
+        .serialize(serializer)
+    }
+
+    /// Serialize the marked entities in `diff.changed` together with the
+    /// removed markers of `diff`, see `DiffTracker`.
+    ///
+    /// The output has to be read back with
+    /// `DeserializeComponents::apply_diff`.
+    fn serialize_diff<S>(
+        &self,
+        entities: &EntitiesRes,
+        markers: &ReadStorage<M>,
+        diff: &Diff<M>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        Self: Sized,
+        E: Display,
+        S: Serializer,
+    {
+        DiffData {
+            base: diff.base,
+            removed: diff.removed.clone(),
+            entities: SerializeEntities {
+                storages: self,
+                entities,
+                markers,
+                changed: Some(&diff.changed),
+                pd: PhantomData,
+            },
+        }
+        .serialize(serializer)
+    }
+
+    /// Serialize components from specified storages
+    /// of all marked entities in the column-oriented `ColumnData` layout.
+    /// All markers are written first, followed by one `Column` per storage,
+    /// which is considerably more compact than `serialize` for big worlds.
+    /// Like `serialize`, this doesn't mark referenced entities.
+    ///
+    /// The output has to be read back with
+    /// `DeserializeComponents::deserialize_columns`.
+    fn serialize_columns<S>(
+        &self,
+        entities: &EntitiesRes,
+        markers: &ReadStorage<M>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        E: Display,
+        S: Serializer,
+    {
+        let (entities, marker_list): (Vec<Entity>, Vec<M>) = (entities, markers)
+            .join()
+            .map(|(e, m)| (e, m.clone()))
+            .unzip();
         let ids = |entity| -> Option<M> { markers.get(entity).cloned() };
-        for (entity, marker) in (entities, markers).join() {
-            serseq.serialize_element(&EntityData::<M, Self::Data> {
-                marker: marker.clone(),
-                components: self
-                    .serialize_entity(entity, &ids)
-                    .map_err(ser::Error::custom)?,
-            })?;
+        let columns = self
+            .serialize_columns_data(&entities, ids)
+            .map_err(ser::Error::custom)?;
+
+        ColumnData {
+            markers: marker_list,
+            columns,
         }
-        serseq.end()
+        .serialize(serializer)
     }
 
     /// Serialize components from specified storages
@@ -109,6 +250,157 @@ where
     }
 }
 
+/// Serializes the marked entities accepted by `filter`, see
+/// `SerializeComponents::serialize_filtered`.
+fn serialize_marked<E, M, T, S, F>(
+    storages: &T,
+    entities: &EntitiesRes,
+    markers: &ReadStorage<M>,
+    filter: F,
+    progress: &mut Progress,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    E: Display,
+    M: Marker,
+    T: SerializeComponents<E, M> + ?Sized,
+    S: Serializer,
+    F: Fn(Entity, &M) -> bool,
+{
+    let count = (entities, markers)
+        .join()
+        .filter(|&(entity, marker)| filter(entity, marker))
+        .count();
+    let mut serseq = serializer.serialize_seq(Some(count))?;
+    let ids = |entity| -> Option<M> { markers.get(entity).cloned() };
+    let mut done = 0;
+    for (entity, marker) in (entities, markers).join() {
+        if !filter(entity, marker) {
+            continue;
+        }
+        progress.check().map_err(ser::Error::custom)?;
+        serseq.serialize_element(&EntityData::<M, T::Data> {
+            marker: marker.clone(),
+            components: storages
+                .serialize_entity(entity, &ids)
+                .map_err(ser::Error::custom)?,
+        })?;
+        done += 1;
+        progress.entity_done(done, Some(count));
+    }
+    serseq.end()
+}
+
+/// Wrapper for a tuple of storages that implements `serde::Serialize` by
+/// calling `SerializeComponents::serialize`, or `serialize_filtered` with
+/// the entities in `changed`.
+struct SerializeEntities<'a, 'b, E, M: Marker, S> {
+    storages: &'a S,
+    entities: &'a EntitiesRes,
+    markers: &'a ReadStorage<'b, M>,
+    changed: Option<&'a BitSet>,
+    pd: PhantomData<E>,
+}
+
+impl<'a, 'b, E, M, S> Serialize for SerializeEntities<'a, 'b, E, M, S>
+where
+    E: Display,
+    M: Marker,
+    S: SerializeComponents<E, M>,
+{
+    fn serialize<SER>(&self, serializer: SER) -> Result<SER::Ok, SER::Error>
+    where
+        SER: Serializer,
+    {
+        match self.changed {
+            Some(changed) => self.storages.serialize_filtered(
+                self.entities,
+                self.markers,
+                |entity, _| changed.contains(entity.id()),
+                serializer,
+            ),
+            None => self
+                .storages
+                .serialize(self.entities, self.markers, serializer),
+        }
+    }
+}
+
+/// Converts the components of `entities` held by `storage` into a `Column`.
+fn serialize_column<E, M, C, S, F>(
+    storage: &S,
+    entities: &[Entity],
+    mut ids: F,
+) -> Result<Column<C::Data>, E>
+where
+    S: GenericReadStorage<Component = C>,
+    C: ConvertSaveload<M>,
+    E: From<C::Error>,
+    F: FnMut(Entity) -> Option<M>,
+{
+    let mut column = Column::new();
+    for (index, &entity) in entities.iter().enumerate() {
+        if let Some(c) = storage.get(entity) {
+            column.push(index as u32, c.convert_into(&mut ids)?);
+        }
+    }
+
+    Ok(column)
+}
+
+/// Parallel version of the column-oriented serialization of
+/// `SerializeComponents`.
+///
+/// Every storage is converted into its `Column` on the rayon thread pool. The
+/// columns are then handed to the serializer in their usual order, so the
+/// output is identical to `serialize_columns` and can be read back with
+/// `DeserializeComponents::deserialize_columns` no matter whether the format
+/// supports out-of-order writes.
+#[cfg(feature = "parallel")]
+pub trait ParSerializeComponents<E, M>: SerializeComponents<E, M>
+where
+    M: Marker,
+{
+    /// Like `SerializeComponents::serialize_columns_data`, but converts each
+    /// storage on its own rayon task.
+    fn par_serialize_columns_data<F>(
+        &self,
+        entities: &[Entity],
+        ids: F,
+    ) -> Result<Self::Columns, E>
+    where
+        F: Fn(Entity) -> Option<M> + Sync;
+
+    /// Like `SerializeComponents::serialize_columns`, but converts the
+    /// storages in parallel.
+    fn par_serialize_columns<S>(
+        &self,
+        entities: &EntitiesRes,
+        markers: &ReadStorage<M>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        E: Display,
+        M: Send + Sync,
+        S: Serializer,
+    {
+        let (entities, marker_list): (Vec<Entity>, Vec<M>) = (entities, markers)
+            .join()
+            .map(|(e, m)| (e, m.clone()))
+            .unzip();
+        let ids = |entity| -> Option<M> { markers.get(entity).cloned() };
+        let columns = self
+            .par_serialize_columns_data(&entities, ids)
+            .map_err(ser::Error::custom)?;
+
+        ColumnData {
+            markers: marker_list,
+            columns,
+        }
+        .serialize(serializer)
+    }
+}
+
 macro_rules! serialize_components {
     ($($comp:ident => $sto:ident,)*) => {
         impl<'a, E, M, $($comp,)* $($sto,)*> SerializeComponents<E, M> for ($($sto,)*)
@@ -121,6 +413,7 @@ macro_rules! serialize_components {
             )*
         {
             type Data = ($(Option<$comp::Data>,)*);
+            type Columns = ($(Column<$comp::Data>,)*);
 
             #[allow(unused)]
             fn serialize_entity<F>(&self, entity: Entity, mut ids: F) -> Result<Self::Data, E>
@@ -134,6 +427,60 @@ macro_rules! serialize_components {
                     $comp.get(entity).map(|c| c.convert_into(&mut ids).map(Some)).unwrap_or(Ok(None))?,
                 )*))
             }
+
+            #[allow(unused)]
+            fn serialize_columns_data<F>(
+                &self,
+                entities: &[Entity],
+                mut ids: F,
+            ) -> Result<Self::Columns, E>
+            where
+                F: FnMut(Entity) -> Option<M>
+            {
+                #[allow(bad_style)]
+                let ($(ref $comp,)*) = *self;
+
+                Ok(($(serialize_column::<E, M, _, _, _>($comp, entities, &mut ids)?,)*))
+            }
+        }
+
+        #[cfg(feature = "parallel")]
+        impl<'a, E, M, $($comp,)* $($sto,)*> ParSerializeComponents<E, M> for ($($sto,)*)
+        where
+            M: Marker,
+            E: Send,
+            $(
+                $sto: GenericReadStorage<Component = $comp> + Sync,
+                $comp: ConvertSaveload<M> + Component,
+                $comp::Data: Send,
+                E: From<<$comp as ConvertSaveload<M>>::Error>,
+            )*
+        {
+            #[allow(unused)]
+            fn par_serialize_columns_data<F>(
+                &self,
+                entities: &[Entity],
+                ids: F,
+            ) -> Result<Self::Columns, E>
+            where
+                F: Fn(Entity) -> Option<M> + Sync
+            {
+                #[allow(bad_style)]
+                let ($(ref $comp,)*) = *self;
+                // The storage type names double as names for the result slots.
+                #[allow(bad_style)]
+                let ($(mut $sto,)*) = ($(None::<Result<Column<$comp::Data>, E>>,)*);
+                let ids = &ids;
+
+                rayon::scope(|scope| {
+                    $(
+                        let slot = &mut $sto;
+                        scope.spawn(move |_| *slot = Some(serialize_column::<E, M, _, _, _>($comp, entities, ids)));
+                    )*
+                });
+
+                Ok(($($sto.expect("column task did not run")?,)*))
+            }
         }
 
         serialize_components!(@pop $($comp => $sto,)*);
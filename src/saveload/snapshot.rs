@@ -0,0 +1,555 @@
+//! In-memory snapshots of marked entities and resources.
+
+use std::{
+    any::type_name,
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    marker::PhantomData,
+};
+
+use hibitset::BitSet;
+use shred::Resource;
+use shrev::ReaderId;
+
+use crate::{
+    join::Join,
+    saveload::{error::Error, ConvertSaveload, Marker, MarkerAllocator},
+    storage::{ComponentEvent, ReadStorage, Tracked},
+    world::{Component, Entity, World, WorldExt},
+};
+
+/// The components and resources captured by a `Snapshot` of entities marked
+/// with `M`. Has to be inserted into the world as a resource before taking a
+/// snapshot.
+///
+/// ## Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use specs::{
+///     prelude::*,
+///     saveload::{MarkedBuilder, SimpleMarker, SimpleMarkerAllocator, Snapshot, SnapshotRegistry},
+/// };
+///
+/// #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+/// struct Pos(i32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// #[derive(Clone, Default)]
+/// struct Turn(u32);
+///
+/// struct Save;
+/// type SaveMarker = SimpleMarker<Save>;
+///
+/// let mut world = World::new();
+/// world.register::<Pos>();
+/// world.register::<SaveMarker>();
+/// world.insert(SimpleMarkerAllocator::<Save>::new());
+/// world.insert(Turn(1));
+/// world.insert(
+///     SnapshotRegistry::<SaveMarker>::new()
+///         .with_component::<Pos>()
+///         .with_resource::<Turn>(),
+/// );
+/// let entity = world.create_entity().with(Pos(1)).marked::<SaveMarker>().build();
+///
+/// let mut snapshot = Snapshot::<SaveMarker>::take(&world).unwrap();
+/// *world.write_storage::<Pos>().get_mut(entity).unwrap() = Pos(5);
+/// world.write_resource::<Turn>().0 = 2;
+///
+/// snapshot.restore(&mut world).unwrap();
+/// assert_eq!(world.read_storage::<Pos>().get(entity), Some(&Pos(1)));
+/// assert_eq!(world.read_resource::<Turn>().0, 1);
+/// ```
+pub struct SnapshotRegistry<M> {
+    components: Vec<Box<dyn RegisteredComponent<M>>>,
+    resources: Vec<Box<dyn RegisteredResource>>,
+}
+
+impl<M: Marker> SnapshotRegistry<M> {
+    /// Creates a registry without components or resources.
+    pub fn new() -> Self {
+        SnapshotRegistry {
+            components: Vec::new(),
+            resources: Vec::new(),
+        }
+    }
+
+    /// Captures the `C` components of marked entities. A restore rewrites
+    /// them for every marked entity.
+    pub fn with_component<C>(mut self) -> Self
+    where
+        C: Component + ConvertSaveload<M>,
+        C::Data: Clone,
+        C::Error: Display,
+    {
+        self.components
+            .push(Box::new(Registration::<C>(PhantomData)));
+        self
+    }
+
+    /// Captures the `C` components of marked entities from a flagged
+    /// storage. A restore only rewrites the components that changed since
+    /// the snapshot was taken or last restored, unless marked entities had
+    /// to be recreated.
+    pub fn with_tracked_component<C>(mut self) -> Self
+    where
+        C: Component + ConvertSaveload<M>,
+        C::Data: Clone,
+        C::Error: Display,
+        C::Storage: Tracked,
+    {
+        self.components
+            .push(Box::new(TrackedRegistration::<C>(PhantomData)));
+        self
+    }
+
+    /// Captures the resource `R` by cloning it.
+    pub fn with_resource<R>(mut self) -> Self
+    where
+        R: Resource + Clone,
+    {
+        self.resources
+            .push(Box::new(ResourceRegistration::<R>(PhantomData)));
+        self
+    }
+}
+
+impl<M: Marker> Default for SnapshotRegistry<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The state of all entities marked with `M`, their registered components
+/// and the registered resources, kept in memory to roll the world back to,
+/// e.g. for undo or for retrying from a checkpoint.
+///
+/// Components are captured in their `ConvertSaveload::Data` form without
+/// serializing them, so entity references stay valid even if the referenced
+/// entities have to be recreated. A snapshot can be restored any number of
+/// times.
+///
+/// Restoring deletes the marked entities created after the snapshot and
+/// recreates the deleted ones; entities without marker are left alone.
+pub struct Snapshot<M> {
+    markers: Vec<M>,
+    components: Vec<Box<dyn CapturedComponent<M>>>,
+    resources: Vec<Box<dyn CapturedResource>>,
+}
+
+impl<M: Marker> Snapshot<M> {
+    /// Captures the marked entities and resources listed in the world's
+    /// `SnapshotRegistry<M>`.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if there is no `SnapshotRegistry<M>` in the world.
+    pub fn take(world: &World) -> Result<Self, Error<String>> {
+        let registry = world.fetch::<SnapshotRegistry<M>>();
+        let markers = world.read_storage::<M>();
+        let components = registry
+            .components
+            .iter()
+            .map(|component| component.capture(world, &markers))
+            .collect::<Result<_, _>>()?;
+        let resources = registry
+            .resources
+            .iter()
+            .map(|resource| resource.capture(world))
+            .collect();
+
+        Ok(Snapshot {
+            markers: (&world.entities(), &markers)
+                .join()
+                .map(|(_, marker)| marker.clone())
+                .collect(),
+            components,
+            resources,
+        })
+    }
+
+    /// Rolls the world back to this snapshot and maintains it.
+    pub fn restore(&mut self, world: &mut World) -> Result<(), Error<String>> {
+        let mut recreated = false;
+        let mut ids = HashMap::with_capacity(self.markers.len());
+        {
+            let entities = world.entities();
+            let mut markers = world.write_storage::<M>();
+            let mut allocator = world.write_resource::<M::Allocator>();
+
+            let kept: HashSet<&M> = self.markers.iter().collect();
+            let removed: Vec<(Entity, M)> = (&entities, &markers)
+                .join()
+                .filter(|(_, marker)| !kept.contains(marker))
+                .map(|(entity, marker)| (entity, marker.clone()))
+                .collect();
+            for (entity, marker) in removed {
+                // The entity was just joined, so it is alive.
+                entities.delete(entity).unwrap();
+                allocator.retire(&marker);
+            }
+
+            for marker in &self.markers {
+                let entity = match allocator.retrieve_existing_entity(marker.clone(), &mut markers)
+                {
+                    Some(entity) => entity,
+                    None => {
+                        recreated = true;
+                        allocator.retrieve_entity(marker.clone(), &mut markers, &entities)
+                    }
+                };
+                ids.insert(marker.clone(), entity);
+            }
+        }
+        world.maintain();
+
+        for component in &mut self.components {
+            component.restore(world, &ids, recreated)?;
+        }
+        for resource in &self.resources {
+            resource.restore(world);
+        }
+
+        Ok(())
+    }
+}
+
+/// A stack of `Snapshot`s for entering and leaving scenes, like menus, pause
+/// screens or sub-levels, which replace the marked entities of the current
+/// scene until they are left again.
+///
+/// ## Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use specs::{
+///     prelude::*,
+///     saveload::{MarkedBuilder, SceneStack, SimpleMarker, SimpleMarkerAllocator, SnapshotRegistry},
+/// };
+///
+/// #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+/// struct Pos(i32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// struct Save;
+/// type SaveMarker = SimpleMarker<Save>;
+///
+/// let mut world = World::new();
+/// world.register::<Pos>();
+/// world.register::<SaveMarker>();
+/// world.insert(SimpleMarkerAllocator::<Save>::new());
+/// world.insert(SnapshotRegistry::<SaveMarker>::new().with_component::<Pos>());
+/// world.create_entity().with(Pos(1)).marked::<SaveMarker>().build();
+///
+/// let mut stack = SceneStack::<SaveMarker>::new();
+/// stack.push(&mut world).unwrap();
+/// assert!(world.read_storage::<Pos>().is_empty());
+/// // Load the pause menu here.
+/// world.create_entity().with(Pos(2)).marked::<SaveMarker>().build();
+///
+/// stack.pop(&mut world).unwrap();
+/// let pos = world.read_storage::<Pos>();
+/// assert_eq!((&pos).join().collect::<Vec<_>>(), vec![&Pos(1)]);
+/// ```
+pub struct SceneStack<M> {
+    scenes: Vec<Snapshot<M>>,
+}
+
+impl<M: Marker> SceneStack<M> {
+    /// Creates an empty stack.
+    pub fn new() -> Self {
+        SceneStack { scenes: Vec::new() }
+    }
+
+    /// The number of scenes which were pushed and not popped yet.
+    pub fn depth(&self) -> usize {
+        self.scenes.len()
+    }
+
+    /// Returns `true` if there is no scene to go back to.
+    pub fn is_empty(&self) -> bool {
+        self.scenes.is_empty()
+    }
+
+    /// Takes a `Snapshot` of the current scene and deletes its marked
+    /// entities, so the next scene can be loaded into the world.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if there is no `SnapshotRegistry<M>` in the world.
+    pub fn push(&mut self, world: &mut World) -> Result<(), Error<String>> {
+        let snapshot = Snapshot::take(world)?;
+        clear::<M>(world);
+        self.scenes.push(snapshot);
+
+        Ok(())
+    }
+
+    /// Deletes the marked entities of the current scene and restores the
+    /// previous one. Returns `Ok(false)` if there is no previous scene.
+    pub fn pop(&mut self, world: &mut World) -> Result<bool, Error<String>> {
+        let mut snapshot = match self.scenes.pop() {
+            Some(snapshot) => snapshot,
+            None => return Ok(false),
+        };
+        clear::<M>(world);
+        snapshot.restore(world)?;
+
+        Ok(true)
+    }
+}
+
+impl<M: Marker> Default for SceneStack<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Deletes all entities marked with `M` and maintains the world.
+fn clear<M: Marker>(world: &mut World) {
+    {
+        let entities = world.entities();
+        let markers = world.read_storage::<M>();
+        let mut allocator = world.write_resource::<M::Allocator>();
+        for (entity, marker) in (&entities, &markers).join() {
+            // The entity was just joined, so it is alive.
+            entities.delete(entity).unwrap();
+            allocator.retire(marker);
+        }
+    }
+    world.maintain();
+}
+
+/// A component type in a `SnapshotRegistry`.
+trait RegisteredComponent<M: Marker>: Send + Sync {
+    fn capture(
+        &self,
+        world: &World,
+        markers: &ReadStorage<M>,
+    ) -> Result<Box<dyn CapturedComponent<M>>, Error<String>>;
+}
+
+/// The components of one type in a `Snapshot`.
+trait CapturedComponent<M> {
+    fn restore(
+        &mut self,
+        world: &World,
+        ids: &HashMap<M, Entity>,
+        full: bool,
+    ) -> Result<(), Error<String>>;
+}
+
+struct Registration<C>(PhantomData<fn() -> C>);
+
+struct TrackedRegistration<C>(PhantomData<fn() -> C>);
+
+struct Captured<M, C: ConvertSaveload<M>> {
+    data: HashMap<M, C::Data>,
+}
+
+struct TrackedCaptured<M, C: ConvertSaveload<M>> {
+    captured: Captured<M, C>,
+    reader: ReaderId<ComponentEvent>,
+}
+
+impl<M, C> Captured<M, C>
+where
+    M: Marker,
+    C: Component + ConvertSaveload<M>,
+    C::Data: Clone,
+    C::Error: Display,
+{
+    fn capture(world: &World, markers: &ReadStorage<M>) -> Result<Self, Error<String>> {
+        let storage = world.read_storage::<C>();
+        let mut data = HashMap::new();
+        for (component, marker) in (&storage, markers).join() {
+            let ids = |entity: Entity| markers.get(entity).cloned();
+            let component = component
+                .convert_into(ids)
+                .map_err(|e| error::<M, C, _>(e, marker))?;
+            data.insert(marker.clone(), component);
+        }
+
+        Ok(Captured { data })
+    }
+
+    /// Resets the component of the marked `entity`.
+    fn restore_entity(
+        &self,
+        world: &World,
+        entity: Entity,
+        marker: &M,
+        ids: &HashMap<M, Entity>,
+    ) -> Result<(), Error<String>> {
+        let mut storage = world.write_storage::<C>();
+        match self.data.get(marker) {
+            Some(data) => {
+                let component = C::convert_from(data.clone(), |marker| ids.get(&marker).cloned())
+                    .map_err(|e| error::<M, C, _>(e, marker))?;
+                storage
+                    .insert(entity, component)
+                    .map_err(|e| error::<M, C, _>(e, marker))?;
+            }
+            None => {
+                storage.remove(entity);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<M, C> CapturedComponent<M> for Captured<M, C>
+where
+    M: Marker,
+    C: Component + ConvertSaveload<M>,
+    C::Data: Clone,
+    C::Error: Display,
+{
+    fn restore(
+        &mut self,
+        world: &World,
+        ids: &HashMap<M, Entity>,
+        _full: bool,
+    ) -> Result<(), Error<String>> {
+        for (marker, &entity) in ids {
+            self.restore_entity(world, entity, marker, ids)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<M, C> CapturedComponent<M> for TrackedCaptured<M, C>
+where
+    M: Marker,
+    C: Component + ConvertSaveload<M>,
+    C::Data: Clone,
+    C::Error: Display,
+    C::Storage: Tracked,
+{
+    fn restore(
+        &mut self,
+        world: &World,
+        ids: &HashMap<M, Entity>,
+        full: bool,
+    ) -> Result<(), Error<String>> {
+        let mut dirty = BitSet::new();
+        for event in world.read_storage::<C>().channel().read(&mut self.reader) {
+            match *event {
+                ComponentEvent::Inserted(id)
+                | ComponentEvent::Modified(id)
+                | ComponentEvent::Removed(id) => {
+                    dirty.add(id);
+                }
+            }
+        }
+
+        if full {
+            self.captured.restore(world, ids, full)?;
+        } else {
+            let entities = world.entities();
+            let markers = world.read_storage::<M>();
+            for id in (&dirty).join() {
+                let entity = entities.entity(id);
+                if !entities.is_alive(entity) {
+                    continue;
+                }
+                if let Some(marker) = markers.get(entity) {
+                    self.captured.restore_entity(world, entity, marker, ids)?;
+                }
+            }
+        }
+
+        // Skip the events caused by the restore itself.
+        world
+            .read_storage::<C>()
+            .channel()
+            .read(&mut self.reader)
+            .for_each(drop);
+
+        Ok(())
+    }
+}
+
+impl<M, C> RegisteredComponent<M> for Registration<C>
+where
+    M: Marker,
+    C: Component + ConvertSaveload<M>,
+    C::Data: Clone,
+    C::Error: Display,
+{
+    fn capture(
+        &self,
+        world: &World,
+        markers: &ReadStorage<M>,
+    ) -> Result<Box<dyn CapturedComponent<M>>, Error<String>> {
+        Ok(Box::new(Captured::<M, C>::capture(world, markers)?))
+    }
+}
+
+impl<M, C> RegisteredComponent<M> for TrackedRegistration<C>
+where
+    M: Marker,
+    C: Component + ConvertSaveload<M>,
+    C::Data: Clone,
+    C::Error: Display,
+    C::Storage: Tracked,
+{
+    fn capture(
+        &self,
+        world: &World,
+        markers: &ReadStorage<M>,
+    ) -> Result<Box<dyn CapturedComponent<M>>, Error<String>> {
+        let reader = world.write_storage::<C>().register_reader();
+
+        Ok(Box::new(TrackedCaptured {
+            captured: Captured::<M, C>::capture(world, markers)?,
+            reader,
+        }))
+    }
+}
+
+fn error<M: Marker, C, E: Display>(e: E, marker: &M) -> Error<String> {
+    Error::new(e.to_string())
+        .with_marker(marker)
+        .with_component(type_name::<C>())
+}
+
+/// A resource type in a `SnapshotRegistry`.
+trait RegisteredResource: Send + Sync {
+    fn capture(&self, world: &World) -> Box<dyn CapturedResource>;
+}
+
+/// A resource in a `Snapshot`.
+trait CapturedResource {
+    fn restore(&self, world: &mut World);
+}
+
+struct ResourceRegistration<R>(PhantomData<fn() -> R>);
+
+impl<R: Resource + Clone> RegisteredResource for ResourceRegistration<R> {
+    fn capture(&self, world: &World) -> Box<dyn CapturedResource> {
+        Box::new(world.try_fetch::<R>().map(|resource| R::clone(&resource)))
+    }
+}
+
+/// A resource which didn't exist when the snapshot was taken is removed on
+/// restore.
+impl<R: Resource + Clone> CapturedResource for Option<R> {
+    fn restore(&self, world: &mut World) {
+        match *self {
+            Some(ref resource) => world.insert(resource.clone()),
+            None => {
+                world.remove::<R>();
+            }
+        }
+    }
+}
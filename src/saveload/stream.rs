@@ -0,0 +1,339 @@
+//! Helpers for saving to an `io::Write` and loading from an `io::Read`.
+//!
+//! These drive a concrete serde format directly over the stream, so entities
+//! are written out (and read back) one at a time instead of the whole world
+//! being buffered in memory first.
+
+use std::{
+    error::Error as StdError,
+    fmt::{self, Display, Formatter},
+    io::{self, BufReader, BufWriter, Read, Write},
+    marker::PhantomData,
+};
+
+use serde::ser::{Serialize, Serializer};
+
+use crate::{
+    saveload::{de::DeserializeComponents, marker::Marker, ser::SerializeComponents},
+    storage::{ReadStorage, WriteStorage},
+    world::EntitiesRes,
+};
+
+/// The format used by `save_to_writer` and `load_from_reader`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// JSON, driven by `serde_json`.
+    #[cfg(feature = "serde_json")]
+    Json,
+    /// The compact binary format of `bincode`.
+    #[cfg(feature = "bincode")]
+    Bincode,
+}
+
+/// Error returned by the streaming helpers.
+#[derive(Debug)]
+pub enum StreamError {
+    /// Reading from or writing to the underlying stream failed.
+    Io(io::Error),
+    /// The JSON (de)serializer failed.
+    #[cfg(feature = "serde_json")]
+    Json(serde_json::Error),
+    /// The bincode (de)serializer failed.
+    #[cfg(feature = "bincode")]
+    Bincode(bincode::Error),
+    /// The checksum trailer of a save written by `save_to_writer_checked` is
+    /// missing or doesn't match the payload.
+    CorruptSave,
+}
+
+impl Display for StreamError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            StreamError::Io(ref e) => write!(f, "I/O error: {}", e),
+            #[cfg(feature = "serde_json")]
+            StreamError::Json(ref e) => write!(f, "JSON error: {}", e),
+            #[cfg(feature = "bincode")]
+            StreamError::Bincode(ref e) => write!(f, "Bincode error: {}", e),
+            StreamError::CorruptSave => write!(f, "Save is corrupt: checksum mismatch"),
+        }
+    }
+}
+
+impl StdError for StreamError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            StreamError::Io(ref e) => Some(e),
+            #[cfg(feature = "serde_json")]
+            StreamError::Json(ref e) => Some(e),
+            #[cfg(feature = "bincode")]
+            StreamError::Bincode(ref e) => Some(e),
+            StreamError::CorruptSave => None,
+        }
+    }
+}
+
+impl From<io::Error> for StreamError {
+    fn from(e: io::Error) -> Self {
+        StreamError::Io(e)
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl From<serde_json::Error> for StreamError {
+    fn from(e: serde_json::Error) -> Self {
+        StreamError::Json(e)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl From<bincode::Error> for StreamError {
+    fn from(e: bincode::Error) -> Self {
+        StreamError::Bincode(e)
+    }
+}
+
+/// Adapter that lets a tuple of storages be handed to a format's
+/// `to_writer`-style entry point.
+struct SaveEntities<'a, 'e, E, M: Marker, S> {
+    storages: &'a S,
+    entities: &'a EntitiesRes,
+    markers: &'a ReadStorage<'e, M>,
+    pd: PhantomData<E>,
+}
+
+impl<'a, 'e, E, M, S> Serialize for SaveEntities<'a, 'e, E, M, S>
+where
+    E: Display,
+    M: Marker,
+    S: SerializeComponents<E, M>,
+{
+    fn serialize<SER>(&self, serializer: SER) -> Result<SER::Ok, SER::Error>
+    where
+        SER: Serializer,
+    {
+        self.storages.serialize(self.entities, self.markers, serializer)
+    }
+}
+
+/// Serializes all marked entities straight into `writer`.
+///
+/// The writer is buffered internally and flushed before returning.
+///
+/// ## Examples
+///
+/// ```
+/// # extern crate specs;
+/// # #[macro_use] extern crate serde;
+/// use std::convert::Infallible;
+///
+/// use specs::{
+///     prelude::*,
+///     saveload::{save_to_writer, Format, MarkedBuilder, SimpleMarker, SimpleMarkerAllocator},
+/// };
+///
+/// #[derive(Clone, Serialize, Deserialize)]
+/// struct Pos(f32, f32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// struct Save;
+///
+/// let mut world = World::new();
+/// world.register::<Pos>();
+/// world.register::<SimpleMarker<Save>>();
+/// world.insert(SimpleMarkerAllocator::<Save>::new());
+/// world.create_entity().with(Pos(1.0, 2.0)).marked::<SimpleMarker<Save>>().build();
+///
+/// let mut buffer = Vec::new();
+/// let (entities, pos, markers) = world.system_data::<(
+///     Entities,
+///     ReadStorage<Pos>,
+///     ReadStorage<SimpleMarker<Save>>,
+/// )>();
+/// save_to_writer::<Infallible, _, _, _>(Format::Json, &(&pos,), &entities, &markers, &mut buffer)
+///     .unwrap();
+/// assert!(!buffer.is_empty());
+/// ```
+pub fn save_to_writer<E, M, S, W>(
+    format: Format,
+    storages: &S,
+    entities: &EntitiesRes,
+    markers: &ReadStorage<M>,
+    writer: W,
+) -> Result<(), StreamError>
+where
+    E: Display,
+    M: Marker,
+    S: SerializeComponents<E, M>,
+    W: Write,
+{
+    let mut writer = BufWriter::new(writer);
+    let save = SaveEntities::<E, M, S> {
+        storages,
+        entities,
+        markers,
+        pd: PhantomData,
+    };
+
+    match format {
+        #[cfg(feature = "serde_json")]
+        Format::Json => serde_json::to_writer(&mut writer, &save)?,
+        #[cfg(feature = "bincode")]
+        Format::Bincode => bincode::serialize_into(&mut writer, &save)?,
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Deserializes entities from `reader` into the given storages, reading the
+/// stream incrementally.
+///
+/// Entities are resolved through `allocator` exactly like
+/// `DeserializeComponents::deserialize` does.
+pub fn load_from_reader<'a, E, M, S, R>(
+    format: Format,
+    storages: &mut S,
+    entities: &EntitiesRes,
+    markers: &mut WriteStorage<'a, M>,
+    allocator: &mut M::Allocator,
+    reader: R,
+) -> Result<(), StreamError>
+where
+    E: Display,
+    M: Marker,
+    S: DeserializeComponents<E, M>,
+    R: Read,
+{
+    let reader = BufReader::new(reader);
+
+    match format {
+        #[cfg(feature = "serde_json")]
+        Format::Json => {
+            let mut de = serde_json::Deserializer::from_reader(reader);
+            storages.deserialize(entities, markers, allocator, &mut de)?;
+            de.end()?;
+        }
+        #[cfg(feature = "bincode")]
+        Format::Bincode => {
+            let mut de = bincode::Deserializer::with_reader(reader, bincode_options());
+            storages.deserialize(entities, markers, allocator, &mut de)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Marks the end of the checksum trailer written by `save_to_writer_checked`.
+const CHECKSUM_MAGIC: &[u8; 4] = b"SPCK";
+
+/// Like `save_to_writer`, but follows the payload with a trailer holding its
+/// CRC-32 checksum. Such saves have to be loaded with
+/// `load_from_reader_checked`.
+pub fn save_to_writer_checked<E, M, S, W>(
+    format: Format,
+    storages: &S,
+    entities: &EntitiesRes,
+    markers: &ReadStorage<M>,
+    writer: W,
+) -> Result<(), StreamError>
+where
+    E: Display,
+    M: Marker,
+    S: SerializeComponents<E, M>,
+    W: Write,
+{
+    let mut writer = ChecksumWriter {
+        inner: writer,
+        checksum: 0,
+    };
+    save_to_writer(format, storages, entities, markers, &mut writer)?;
+
+    let checksum = writer.checksum;
+    writer.inner.write_all(&checksum.to_le_bytes())?;
+    writer.inner.write_all(CHECKSUM_MAGIC)?;
+    writer.inner.flush()?;
+    Ok(())
+}
+
+/// Loads a save written by `save_to_writer_checked`.
+///
+/// The whole save is read and its checksum verified before anything is
+/// deserialized, so a truncated or damaged save returns
+/// `StreamError::CorruptSave` without touching the storages.
+pub fn load_from_reader_checked<'a, E, M, S, R>(
+    format: Format,
+    storages: &mut S,
+    entities: &EntitiesRes,
+    markers: &mut WriteStorage<'a, M>,
+    allocator: &mut M::Allocator,
+    mut reader: R,
+) -> Result<(), StreamError>
+where
+    E: Display,
+    M: Marker,
+    S: DeserializeComponents<E, M>,
+    R: Read,
+{
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    if data.len() < 8 || &data[data.len() - 4..] != CHECKSUM_MAGIC {
+        return Err(StreamError::CorruptSave);
+    }
+
+    let (payload, trailer) = data.split_at(data.len() - 8);
+    let mut checksum = [0; 4];
+    checksum.copy_from_slice(&trailer[..4]);
+    if crc32(0, payload) != u32::from_le_bytes(checksum) {
+        return Err(StreamError::CorruptSave);
+    }
+
+    load_from_reader(format, storages, entities, markers, allocator, payload)
+}
+
+/// Computes the checksum of everything written through it.
+struct ChecksumWriter<W> {
+    inner: W,
+    checksum: u32,
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.checksum = crc32(self.checksum, &buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Continues the CRC-32 (IEEE) `checksum` over `bytes`.
+fn crc32(checksum: u32, bytes: &[u8]) -> u32 {
+    let mut crc = !checksum;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+/// The options `bincode::serialize_into` uses, so that both directions agree.
+#[cfg(feature = "bincode")]
+fn bincode_options() -> impl bincode::Options {
+    use bincode::Options;
+
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+}
@@ -0,0 +1,135 @@
+//! Systems for assigning markers and keeping them up to date.
+
+use std::marker::PhantomData;
+
+use crate::{
+    prelude::*,
+    saveload::{Marker, MarkerAllocator},
+};
+
+/// Calls `MarkerAllocator::maintain` for the allocator of `M` every time it
+/// runs, so the allocator forgets the markers of deleted entities.
+///
+/// Long-running worlds that create and delete marked entities should add
+/// this to their dispatcher.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::{
+///     prelude::*,
+///     saveload::{MaintainMarkers, SimpleMarker},
+/// };
+///
+/// struct NetworkSync;
+///
+/// let mut dispatcher = DispatcherBuilder::new()
+///     .with(
+///         MaintainMarkers::<SimpleMarker<NetworkSync>>::new(),
+///         "maintain_markers",
+///         &[],
+///     )
+///     .build();
+/// # let _ = &mut dispatcher;
+/// ```
+pub struct MaintainMarkers<M>(PhantomData<M>);
+
+impl<M> MaintainMarkers<M> {
+    /// Creates a new `MaintainMarkers` system.
+    pub fn new() -> Self {
+        MaintainMarkers(PhantomData)
+    }
+}
+
+impl<M> Default for MaintainMarkers<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, M: Marker> System<'a> for MaintainMarkers<M> {
+    type SystemData = (Entities<'a>, ReadStorage<'a, M>, WriteExpect<'a, M::Allocator>);
+
+    fn run(&mut self, (entities, markers, mut allocator): Self::SystemData) {
+        allocator.maintain(&entities, &markers);
+    }
+}
+
+/// Marks every entity that doesn't have a marker `M` yet, using the allocator
+/// of `M`. If `C` is a component, only entities with a `C` are marked.
+///
+/// Running this before saving replaces the usual "mark everything" loop.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::{
+///     prelude::*,
+///     saveload::{MarkSystem, SimpleMarker, SimpleMarkerAllocator},
+/// };
+///
+/// struct Persistent;
+///
+/// struct Pos;
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Pos>();
+/// world.register::<SimpleMarker<Persistent>>();
+/// world.insert(SimpleMarkerAllocator::<Persistent>::new());
+/// world.create_entity().with(Pos).build();
+/// world.create_entity().build();
+///
+/// MarkSystem::<SimpleMarker<Persistent>, Pos>::new().run_now(&world);
+/// assert_eq!(world.read_storage::<SimpleMarker<Persistent>>().count(), 1);
+///
+/// MarkSystem::<SimpleMarker<Persistent>>::new().run_now(&world);
+/// assert_eq!(world.read_storage::<SimpleMarker<Persistent>>().count(), 2);
+/// ```
+pub struct MarkSystem<M, C = ()>(PhantomData<(M, C)>);
+
+impl<M, C> MarkSystem<M, C> {
+    /// Creates a new `MarkSystem`.
+    pub fn new() -> Self {
+        MarkSystem(PhantomData)
+    }
+}
+
+impl<M, C> Default for MarkSystem<M, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, M: Marker> System<'a> for MarkSystem<M> {
+    type SystemData = (Entities<'a>, WriteStorage<'a, M>, WriteExpect<'a, M::Allocator>);
+
+    fn run(&mut self, (entities, mut markers, mut allocator): Self::SystemData) {
+        let unmarked: Vec<Entity> = (&entities, !&markers).join().map(|(e, _)| e).collect();
+        for entity in unmarked {
+            allocator.mark(entity, &mut markers);
+        }
+    }
+}
+
+impl<'a, M: Marker, C: Component> System<'a> for MarkSystem<M, C> {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, C>,
+        WriteStorage<'a, M>,
+        WriteExpect<'a, M::Allocator>,
+    );
+
+    fn run(&mut self, (entities, filter, mut markers, mut allocator): Self::SystemData) {
+        let unmarked: Vec<Entity> = (&entities, &filter, !&markers)
+            .join()
+            .map(|(e, _, _)| e)
+            .collect();
+        for entity in unmarked {
+            allocator.mark(entity, &mut markers);
+        }
+    }
+}
@@ -5,23 +5,108 @@ use std::{convert::Infallible, hash::Hash};
 use super::*;
 use crate::{error::Error, prelude::*};
 
-mod marker_test {
+/// The fixture and save helpers shared by the test modules.
+mod common {
     use super::*;
 
     #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
-    struct A(i32);
+    pub struct A(pub i32);
 
     impl Component for A {
         type Storage = VecStorage<Self>;
     }
 
     #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
-    struct B(bool);
+    pub struct B(pub bool);
 
     impl Component for B {
         type Storage = VecStorage<Self>;
     }
 
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+    pub struct Pos(pub i32);
+
+    impl Component for Pos {
+        type Storage = FlaggedStorage<Self>;
+    }
+
+    /// A component referring to another entity.
+    pub struct Target(pub Entity);
+
+    impl Component for Target {
+        type Storage = VecStorage<Self>;
+    }
+
+    impl<M: Marker> ConvertSaveload<M> for Target {
+        type Data = M;
+        type Error = Infallible;
+
+        fn convert_into<F>(&self, ids: F) -> Result<M, Infallible>
+        where
+            F: FnMut(Entity) -> Option<M>,
+        {
+            self.0.convert_into(ids)
+        }
+
+        fn convert_from<F>(data: M, ids: F) -> Result<Self, Infallible>
+        where
+            F: FnMut(M) -> Option<Entity>,
+        {
+            Entity::convert_from(data, ids).map(Target)
+        }
+    }
+
+    pub struct Save;
+
+    pub type SaveMarker = SimpleMarker<Save>;
+
+    /// Creates a world with `A`, `B` and `SaveMarker` registered.
+    pub fn setup() -> World {
+        let mut world = World::new();
+        world.register::<A>();
+        world.register::<B>();
+        world.register::<SaveMarker>();
+        world.insert(SimpleMarkerAllocator::<Save>::new());
+
+        world
+    }
+
+    /// Saves the marked entities of `world` with the storages `S` as RON.
+    pub fn save<'a, S>(world: &'a World) -> String
+    where
+        S: SystemData<'a> + SerializeComponents<Infallible, SaveMarker>,
+    {
+        let mut ser = ron::ser::Serializer::new(None, true);
+        S::fetch(world)
+            .serialize(&world.entities(), &world.read_storage::<SaveMarker>(), &mut ser)
+            .unwrap();
+
+        ser.into_output_string()
+    }
+
+    /// Loads `serial` into `world` with the storages `S`.
+    pub fn load<'a, S>(
+        world: &'a World,
+        serial: &str,
+        options: &DeserializeOptions,
+    ) -> Result<DeserializeReport<SaveMarker>, ron::de::Error>
+    where
+        S: SystemData<'a> + DeserializeComponents<Error, SaveMarker>,
+    {
+        let mut de = ron::de::Deserializer::from_str(serial).unwrap();
+        S::fetch(world).deserialize_with(
+            &world.entities(),
+            &mut world.write_storage::<SaveMarker>(),
+            &mut world.write_resource::<SimpleMarkerAllocator<Save>>(),
+            options,
+            &mut de,
+        )
+    }
+}
+
+mod marker_test {
+    use super::{common::*, *};
+
     struct NetworkSync;
 
     /// Ensure that the marker correctly allocates IDs for entities that come
@@ -30,6 +115,8 @@ mod marker_test {
     #[test]
     fn bumps_index_after_reload() {
         bumps_index_after_reload_internal::<SimpleMarker<NetworkSync>>(SimpleMarkerAllocator::new());
+        bumps_index_after_reload_internal::<U32Marker<NetworkSync>>(SimpleMarkerAllocator::new());
+        bumps_index_after_reload_internal::<StringMarker<NetworkSync>>(SimpleMarkerAllocator::new());
         #[cfg(feature = "uuid_entity")]
         bumps_index_after_reload_internal::<UuidMarker>(UuidMarkerAllocator::new());
     }
@@ -155,6 +242,199 @@ mod marker_test {
         assert_markers_are_unique::<M>(&mut world);
     }
 
+    /// Generated string ids never collide with explicitly allocated ones.
+    #[test]
+    fn string_marker_skips_taken_ids() {
+        let mut world = World::new();
+        world.register::<StringMarker<NetworkSync>>();
+        let a = world.create_entity().build();
+        let b = world.create_entity().build();
+        let c = world.create_entity().build();
+
+        let mut alloc = SimpleMarkerAllocator::<NetworkSync, String>::new();
+        let named = alloc.allocate(a, Some("player".to_owned()));
+        let numbered = alloc.allocate(b, Some("0".to_owned()));
+        let generated = alloc.allocate(c, None);
+
+        assert_eq!(named.id(), "player");
+        assert_eq!(numbered.id(), "0");
+        assert_eq!(generated.id(), "1");
+        assert_eq!(alloc.retrieve_entity_internal("player".to_owned()), Some(a));
+    }
+
+    #[test]
+    fn maintain_markers_forgets_deleted_entities() {
+        type M = SimpleMarker<NetworkSync>;
+
+        let mut world = World::new();
+        world.register::<M>();
+        world.insert(SimpleMarkerAllocator::<NetworkSync>::new());
+        let kept = world.create_entity().marked::<M>().build();
+        let deleted = world.create_entity().marked::<M>().build();
+        let kept_marker = *world.read_storage::<M>().get(kept).unwrap();
+        let deleted_marker = *world.read_storage::<M>().get(deleted).unwrap();
+        world.delete_entity(deleted).unwrap();
+
+        MaintainMarkers::<M>::new().run_now(&world);
+
+        let alloc = world.read_resource::<SimpleMarkerAllocator<NetworkSync>>();
+        assert_eq!(alloc.retrieve_entity_internal(kept_marker.id()), Some(kept));
+        assert_eq!(alloc.retrieve_entity_internal(deleted_marker.id()), None);
+    }
+
+    #[test]
+    fn mark_system_keeps_existing_markers() {
+        type M = SimpleMarker<NetworkSync>;
+
+        let mut world = World::new();
+        world.register::<A>();
+        world.register::<M>();
+        world.insert(SimpleMarkerAllocator::<NetworkSync>::new());
+        let marked = world.create_entity().with(A(1)).marked::<M>().build();
+        let marker = *world.read_storage::<M>().get(marked).unwrap();
+        world.create_entity().with(A(2)).build();
+        world.create_entity().build();
+
+        MarkSystem::<M, A>::new().run_now(&world);
+
+        assert_eq!(world.read_storage::<M>().get(marked), Some(&marker));
+        assert_marked_entity_count::<M>(&mut world, 2);
+        assert_markers_are_unique::<M>(&mut world);
+    }
+
+    /// Ids of entities that were deleted before saving aren't reused after
+    /// loading a save with the allocator state.
+    #[test]
+    fn restores_allocator_state() {
+        type M = SimpleMarker<NetworkSync>;
+
+        let mut world = World::new();
+        world.register::<A>();
+        world.register::<M>();
+        world.insert(SimpleMarkerAllocator::<NetworkSync>::new());
+        world.create_entity().with(A(1)).marked::<M>().build();
+        let deleted = world.create_entity().with(A(2)).marked::<M>().build();
+        world.delete_entity(deleted).unwrap();
+
+        let mut ser = ron::ser::Serializer::new(None, true);
+        world.exec(
+            |(ents, comp_a, markers, alloc): (
+                Entities,
+                ReadStorage<A>,
+                ReadStorage<M>,
+                Read<SimpleMarkerAllocator<NetworkSync>>,
+            )| {
+                SerializeComponents::<Infallible, M>::serialize_with_allocator(
+                    &(&comp_a,),
+                    &ents,
+                    &markers,
+                    &alloc,
+                    &mut ser,
+                )
+                .unwrap();
+            },
+        );
+        let serial = ser.into_output_string();
+
+        let mut world = World::new();
+        world.register::<A>();
+        world.register::<M>();
+        world.insert(SimpleMarkerAllocator::<NetworkSync>::new());
+        let mut de = ron::de::Deserializer::from_str(&serial).unwrap();
+        world.exec(
+            |(ents, comp_a, mut markers, mut alloc): (
+                Entities,
+                WriteStorage<A>,
+                WriteStorage<M>,
+                Write<SimpleMarkerAllocator<NetworkSync>>,
+            )| {
+                DeserializeComponents::<Error, _>::deserialize_with_allocator(
+                    &mut (comp_a,),
+                    &ents,
+                    &mut markers,
+                    &mut alloc,
+                    &mut de,
+                )
+                .unwrap();
+            },
+        );
+        assert_marked_entity_count::<M>(&mut world, 1);
+
+        let entity = world.create_entity().marked::<M>().build();
+        assert_eq!(world.read_storage::<M>().get(entity).unwrap().id(), 2);
+    }
+
+    #[test]
+    fn retire_forgets_marker() {
+        let mut world = World::new();
+        world.register::<SimpleMarker<NetworkSync>>();
+        let entity = world.create_entity().build();
+
+        let mut alloc = SimpleMarkerAllocator::<NetworkSync>::new();
+        let marker = alloc.allocate(entity, None);
+        alloc.retire(&marker);
+
+        assert_eq!(alloc.retrieve_entity_internal(marker.id()), None);
+    }
+
+    /// Saves from independent worlds can be loaded into the same world
+    /// without marker collisions when using `UuidMarker`.
+    #[cfg(feature = "uuid_entity")]
+    #[test]
+    fn merges_uuid_saves_from_different_worlds() {
+        let save = |value: i32| {
+            let mut world = World::new();
+            world.insert(UuidMarkerAllocator::new());
+            world.register::<A>();
+            world.register::<UuidMarker>();
+            world.create_entity().with(A(value)).marked::<UuidMarker>().build();
+
+            let mut ser = ron::ser::Serializer::new(None, true);
+            world.exec(
+                |(ents, comp_a, markers): (Entities, ReadStorage<A>, ReadStorage<UuidMarker>)| {
+                    SerializeComponents::<Infallible, UuidMarker>::serialize(
+                        &(&comp_a,),
+                        &ents,
+                        &markers,
+                        &mut ser,
+                    )
+                    .unwrap();
+                },
+            );
+
+            ser.into_output_string()
+        };
+
+        let mut world = World::new();
+        world.insert(UuidMarkerAllocator::new());
+        world.register::<A>();
+        world.register::<UuidMarker>();
+
+        for serial in &[save(1), save(2)] {
+            let mut de = ron::de::Deserializer::from_str(serial).unwrap();
+            world.exec(
+                |(ents, comp_a, mut markers, mut alloc): (
+                    Entities,
+                    WriteStorage<A>,
+                    WriteStorage<UuidMarker>,
+                    Write<UuidMarkerAllocator>,
+                )| {
+                    DeserializeComponents::<Error, _>::deserialize(
+                        &mut (comp_a,),
+                        &ents,
+                        &mut markers,
+                        &mut alloc,
+                        &mut de,
+                    )
+                    .unwrap();
+                },
+            );
+        }
+
+        assert_marked_entity_count::<UuidMarker>(&mut world, 2);
+        assert_markers_are_unique::<UuidMarker>(&mut world);
+    }
+
     /// Assert that the number of entities marked with `SimpleMarker` is equal
     /// to `count`
     fn assert_marked_entity_count<M: Marker>(world: &mut World, count: usize) {
@@ -184,3 +464,2505 @@ mod marker_test {
         });
     }
 }
+
+#[cfg(any(feature = "serde_json", feature = "bincode"))]
+mod stream_test {
+    use super::{common::*, *};
+
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+    struct Pos(i32, i32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+    }
+
+    fn setup() -> World {
+        let mut world = common::setup();
+        world.register::<Pos>();
+
+        world
+    }
+
+    fn round_trip(format: Format) {
+        let mut world = setup();
+        for i in 0..100 {
+            world
+                .create_entity()
+                .with(Pos(i, -i))
+                .marked::<SaveMarker>()
+                .build();
+        }
+
+        let mut buffer = Vec::new();
+        world.exec(
+            |(ents, pos, markers): (Entities, ReadStorage<Pos>, ReadStorage<SaveMarker>)| {
+                save_to_writer::<Infallible, _, _, _>(
+                    format,
+                    &(&pos,),
+                    &ents,
+                    &markers,
+                    &mut buffer,
+                )
+                .unwrap();
+            },
+        );
+
+        let mut world = setup();
+        world.exec(
+            |(ents, pos, mut markers, mut alloc): (
+                Entities,
+                WriteStorage<Pos>,
+                WriteStorage<SaveMarker>,
+                Write<SimpleMarkerAllocator<Save>>,
+            )| {
+                load_from_reader::<Error, _, _, _>(
+                    format,
+                    &mut (pos,),
+                    &ents,
+                    &mut markers,
+                    &mut alloc,
+                    &buffer[..],
+                )
+                .unwrap();
+            },
+        );
+
+        let pos = world.read_storage::<Pos>();
+        let mut loaded: Vec<_> = pos.join().cloned().collect();
+        loaded.sort_by_key(|p| p.0);
+        assert_eq!(loaded, (0..100).map(|i| Pos(i, -i)).collect::<Vec<_>>());
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn json_round_trip() {
+        round_trip(Format::Json);
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn bincode_round_trip() {
+        round_trip(Format::Bincode);
+    }
+
+    #[cfg(all(feature = "serde_json", any(feature = "lz4_flex", feature = "flate2")))]
+    fn compressed_round_trip(compression: Compression) {
+        let mut world = setup();
+        for i in 0..100 {
+            world.create_entity().with(Pos(i, i)).marked::<SaveMarker>().build();
+        }
+
+        let mut plain = Vec::new();
+        let mut writer = compression.writer(Vec::new());
+        world.exec(
+            |(ents, pos, markers): (Entities, ReadStorage<Pos>, ReadStorage<SaveMarker>)| {
+                for writer in vec![&mut plain as &mut dyn std::io::Write, &mut writer] {
+                    save_to_writer::<Infallible, _, _, _>(
+                        Format::Json,
+                        &(&pos,),
+                        &ents,
+                        &markers,
+                        writer,
+                    )
+                    .unwrap();
+                }
+            },
+        );
+        let compressed = writer.finish().unwrap();
+        assert!(compressed.len() < plain.len() / 2);
+
+        let mut world = setup();
+        world.exec(
+            |(ents, pos, mut markers, mut alloc): (
+                Entities,
+                WriteStorage<Pos>,
+                WriteStorage<SaveMarker>,
+                Write<SimpleMarkerAllocator<Save>>,
+            )| {
+                load_from_reader::<Error, _, _, _>(
+                    Format::Json,
+                    &mut (pos,),
+                    &ents,
+                    &mut markers,
+                    &mut alloc,
+                    compression.reader(&compressed[..]),
+                )
+                .unwrap();
+            },
+        );
+
+        assert_eq!(world.read_storage::<Pos>().count(), 100);
+    }
+
+    #[cfg(all(feature = "serde_json", feature = "lz4_flex"))]
+    #[test]
+    fn lz4_round_trip() {
+        compressed_round_trip(Compression::Lz4);
+    }
+
+    #[cfg(all(feature = "serde_json", feature = "flate2"))]
+    #[test]
+    fn deflate_round_trip() {
+        compressed_round_trip(Compression::Deflate);
+    }
+
+    fn checked_save(world: &mut World) -> Vec<u8> {
+        for i in 0..10 {
+            world.create_entity().with(Pos(i, -i)).marked::<SaveMarker>().build();
+        }
+
+        let mut buffer = Vec::new();
+        world.exec(
+            |(ents, pos, markers): (Entities, ReadStorage<Pos>, ReadStorage<SaveMarker>)| {
+                save_to_writer_checked::<Infallible, _, _, _>(
+                    format(),
+                    &(&pos,),
+                    &ents,
+                    &markers,
+                    &mut buffer,
+                )
+                .unwrap();
+            },
+        );
+
+        buffer
+    }
+
+    fn checked_load(world: &mut World, buffer: &[u8]) -> Result<(), StreamError> {
+        world.exec(
+            |(ents, pos, mut markers, mut alloc): (
+                Entities,
+                WriteStorage<Pos>,
+                WriteStorage<SaveMarker>,
+                Write<SimpleMarkerAllocator<Save>>,
+            )| {
+                load_from_reader_checked::<Error, _, _, _>(
+                    format(),
+                    &mut (pos,),
+                    &ents,
+                    &mut markers,
+                    &mut alloc,
+                    buffer,
+                )
+            },
+        )
+    }
+
+    #[cfg(feature = "serde_json")]
+    fn format() -> Format {
+        Format::Json
+    }
+
+    #[cfg(not(feature = "serde_json"))]
+    fn format() -> Format {
+        Format::Bincode
+    }
+
+    #[test]
+    fn checked_round_trip() {
+        let buffer = checked_save(&mut setup());
+
+        let mut world = setup();
+        checked_load(&mut world, &buffer).unwrap();
+        assert_eq!(world.read_storage::<Pos>().count(), 10);
+    }
+
+    #[test]
+    fn corrupt_save_is_rejected_before_loading() {
+        let mut buffer = checked_save(&mut setup());
+        buffer[10] ^= 1;
+
+        let mut world = setup();
+        assert!(matches!(
+            checked_load(&mut world, &buffer),
+            Err(StreamError::CorruptSave)
+        ));
+        assert!(matches!(
+            checked_load(&mut world, &buffer[..buffer.len() / 2]),
+            Err(StreamError::CorruptSave)
+        ));
+        world.maintain();
+        assert_eq!(world.entities().join().count(), 0);
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn truncated_stream_errors() {
+        let mut world = setup();
+        world.exec(
+            |(ents, pos, mut markers, mut alloc): (
+                Entities,
+                WriteStorage<Pos>,
+                WriteStorage<SaveMarker>,
+                Write<SimpleMarkerAllocator<Save>>,
+            )| {
+                let res = load_from_reader::<Error, _, _, _>(
+                    Format::Json,
+                    &mut (pos,),
+                    &ents,
+                    &mut markers,
+                    &mut alloc,
+                    &b"[{\"marker\":0,"[..],
+                );
+                assert!(matches!(res, Err(StreamError::Json(_))));
+            },
+        );
+    }
+}
+
+mod column_test {
+    use super::{common::*, *};
+
+    fn save(world: &mut World) -> String {
+        let mut ser = ron::ser::Serializer::new(None, true);
+        world.exec(
+            |(ents, a, b, markers): (
+                Entities,
+                ReadStorage<A>,
+                ReadStorage<B>,
+                ReadStorage<SaveMarker>,
+            )| {
+                SerializeComponents::<Infallible, SaveMarker>::serialize_columns(
+                    &(&a, &b),
+                    &ents,
+                    &markers,
+                    &mut ser,
+                )
+                .unwrap();
+            },
+        );
+
+        ser.into_output_string()
+    }
+
+    fn load(world: &mut World, serial: &str) -> Result<(), ron::de::Error> {
+        let mut de = ron::de::Deserializer::from_str(serial).unwrap();
+        world.exec(
+            |(ents, a, b, mut markers, mut alloc): (
+                Entities,
+                WriteStorage<A>,
+                WriteStorage<B>,
+                WriteStorage<SaveMarker>,
+                Write<SimpleMarkerAllocator<Save>>,
+            )| {
+                DeserializeComponents::<Error, _>::deserialize_columns(
+                    &mut (a, b),
+                    &ents,
+                    &mut markers,
+                    &mut alloc,
+                    &mut de,
+                )
+            },
+        )
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut world = setup();
+        world
+            .create_entity()
+            .with(A(1))
+            .with(B(true))
+            .marked::<SaveMarker>()
+            .build();
+        world
+            .create_entity()
+            .with(A(2))
+            .marked::<SaveMarker>()
+            .build();
+        world
+            .create_entity()
+            .with(B(false))
+            .marked::<SaveMarker>()
+            .build();
+        world.create_entity().with(A(4)).build();
+
+        let serial = save(&mut world);
+
+        let mut world = setup();
+        load(&mut world, &serial).unwrap();
+
+        let ents = world.entities();
+        let a = world.read_storage::<A>();
+        let b = world.read_storage::<B>();
+        let markers = world.read_storage::<SaveMarker>();
+        let mut loaded: Vec<_> = (&ents, &markers)
+            .join()
+            .map(|(e, m)| (m.id(), a.get(e).cloned(), b.get(e).cloned()))
+            .collect();
+        loaded.sort_by_key(|l| l.0);
+        assert_eq!(
+            loaded,
+            vec![
+                (0, Some(A(1)), Some(B(true))),
+                (1, Some(A(2)), None),
+                (2, None, Some(B(false))),
+            ]
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_matches_sequential() {
+        let mut world = setup();
+        for i in 0..1000 {
+            let builder = world.create_entity().with(A(i));
+            let builder = if i % 3 == 0 {
+                builder.with(B(i % 2 == 0))
+            } else {
+                builder
+            };
+            builder.marked::<SaveMarker>().build();
+        }
+
+        let sequential = save(&mut world);
+        let mut ser = ron::ser::Serializer::new(None, true);
+        world.exec(
+            |(ents, a, b, markers): (
+                Entities,
+                ReadStorage<A>,
+                ReadStorage<B>,
+                ReadStorage<SaveMarker>,
+            )| {
+                ParSerializeComponents::<Infallible, SaveMarker>::par_serialize_columns(
+                    &(&a, &b),
+                    &ents,
+                    &markers,
+                    &mut ser,
+                )
+                .unwrap();
+            },
+        );
+
+        assert_eq!(ser.into_output_string(), sequential);
+    }
+
+    #[test]
+    fn removes_components_missing_from_column() {
+        let mut world = setup();
+        let entity = world
+            .create_entity()
+            .with(A(1))
+            .with(B(true))
+            .marked::<SaveMarker>()
+            .build();
+
+        let mut saved = setup();
+        saved
+            .create_entity()
+            .with(A(7))
+            .marked::<SaveMarker>()
+            .build();
+        load(&mut world, &save(&mut saved)).unwrap();
+
+        assert_eq!(world.read_storage::<A>().get(entity), Some(&A(7)));
+        assert_eq!(world.read_storage::<B>().get(entity), None);
+    }
+
+    #[test]
+    fn malformed_columns_fail() {
+        let mut world = setup();
+        let e = load(
+            &mut world,
+            "(markers: [(0)], columns: ((indices: [0], values: [(1), (2)]), \
+             (indices: [], values: [])))",
+        )
+        .unwrap_err();
+        assert!(e.to_string().contains("column has 1 indices but 2 values"));
+        assert_eq!(world.read_storage::<A>().count(), 0);
+
+        let e = load(
+            &mut world,
+            "(markers: [(0)], columns: ((indices: [0], values: [(1)]), \
+             (indices: [3], values: [(true)])))",
+        )
+        .unwrap_err();
+        assert!(e.to_string().contains("column index 3 is out of range for 1 entities"));
+        assert_eq!(world.read_storage::<A>().count(), 0);
+    }
+}
+
+mod options_test {
+    use super::{common::*, *};
+
+    /// Saves two entities with both `A` and `B`.
+    fn save_both() -> String {
+        let mut world = setup();
+        world
+            .create_entity()
+            .with(A(1))
+            .with(B(true))
+            .marked::<SaveMarker>()
+            .build();
+        world
+            .create_entity()
+            .with(A(2))
+            .with(B(false))
+            .marked::<SaveMarker>()
+            .build();
+
+        save::<(ReadStorage<A>, ReadStorage<B>)>(&world)
+    }
+
+    /// Loads `serial` into a world that only deserializes `A`.
+    fn load_a_only(
+        serial: &str,
+        options: &DeserializeOptions,
+    ) -> (World, Result<DeserializeReport<SaveMarker>, ron::de::Error>) {
+        let world = setup();
+        let res = load::<(WriteStorage<A>,)>(&world, serial, options);
+
+        (world, res)
+    }
+
+    #[test]
+    fn unknown_components_fail_by_default() {
+        let (_, res) = load_a_only(&save_both(), &DeserializeOptions::new());
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn skip_unknown_components() {
+        let options = DeserializeOptions::new().skip_unknown_components(true);
+        let (world, res) = load_a_only(&save_both(), &options);
+        let report = res.unwrap();
+
+        assert_eq!(report.entities, 2);
+        assert!(!report.is_clean());
+        let mut unknown: Vec<_> = report
+            .unknown_components
+            .iter()
+            .map(|(m, n)| (m.id(), *n))
+            .collect();
+        unknown.sort();
+        assert_eq!(unknown, vec![(0, 1), (1, 1)]);
+
+        let mut a: Vec<_> = world.read_storage::<A>().join().cloned().collect();
+        a.sort_by_key(|a| a.0);
+        assert_eq!(a, vec![A(1), A(2)]);
+    }
+
+    #[test]
+    fn missing_components_are_absent() {
+        let serial = "[(marker: (0), components: ())]";
+        let options = DeserializeOptions::new().skip_unknown_components(true);
+        let (world, res) = load_a_only(serial, &options);
+
+        assert!(res.unwrap().is_clean());
+        assert_eq!(world.read_storage::<SaveMarker>().join().count(), 1);
+        assert_eq!(world.read_storage::<A>().join().count(), 0);
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn skip_unknown_components_json() {
+        let serial = r#"[{"marker":[0],"components":[5,true,"x"]}]"#;
+        let mut world = setup();
+        let mut de = serde_json::Deserializer::from_str(serial);
+        let report = world.exec(
+            |(ents, a, mut markers, mut alloc): (
+                Entities,
+                WriteStorage<A>,
+                WriteStorage<SaveMarker>,
+                Write<SimpleMarkerAllocator<Save>>,
+            )| {
+                DeserializeComponents::<Error, _>::deserialize_with(
+                    &mut (a,),
+                    &ents,
+                    &mut markers,
+                    &mut alloc,
+                    &DeserializeOptions::new().skip_unknown_components(true),
+                    &mut de,
+                )
+                .unwrap()
+            },
+        );
+
+        assert_eq!(report.unknown_components.len(), 1);
+        assert_eq!(report.unknown_components[0].1, 2);
+        assert_eq!(world.read_storage::<A>().join().next(), Some(&A(5)));
+    }
+
+    /// Deserializes both `A` and `B`.
+    type Both<'a> = (WriteStorage<'a, A>, WriteStorage<'a, B>);
+
+    /// Creates a world with an entity carrying marker `0` and `A(10)`.
+    fn populated() -> (World, Entity) {
+        let mut world = setup();
+        let entity = world
+            .create_entity()
+            .with(A(10))
+            .marked::<SaveMarker>()
+            .build();
+
+        (world, entity)
+    }
+
+    #[test]
+    fn merge_overwrite() {
+        let (world, entity) = populated();
+        let serial = "[(marker: (0), components: (Some((1)), None))]";
+        let options = DeserializeOptions::new().merge_policy(MergePolicy::Overwrite);
+        load::<Both>(&world, serial, &options).unwrap();
+        assert_eq!(world.read_storage::<A>().get(entity), Some(&A(1)));
+
+        // Components absent from the save are removed.
+        world.write_storage::<B>().insert(entity, B(true)).unwrap();
+        load::<Both>(&world, serial, &options).unwrap();
+        assert_eq!(world.read_storage::<B>().get(entity), None);
+    }
+
+    #[test]
+    fn merge_keep_existing() {
+        let (world, entity) = populated();
+        let options = DeserializeOptions::new().merge_policy(MergePolicy::KeepExisting);
+        load::<Both>(&world, &save_both(), &options).unwrap();
+
+        assert_eq!(world.read_storage::<A>().get(entity), Some(&A(10)));
+        assert_eq!(world.read_storage::<B>().get(entity), Some(&B(true)));
+        assert_eq!(world.read_storage::<SaveMarker>().join().count(), 2);
+    }
+
+    #[test]
+    fn merge_error_on_conflict() {
+        let options = DeserializeOptions::new().merge_policy(MergePolicy::ErrorOnConflict);
+
+        let (world, entity) = populated();
+        assert!(load::<Both>(&world, &save_both(), &options).is_err());
+        assert_eq!(world.read_storage::<A>().get(entity), Some(&A(10)));
+
+        let (world, entity) = populated();
+        let serial = "[(marker: (0), components: (None, Some((false))))]";
+        load::<Both>(&world, serial, &options).unwrap();
+        assert_eq!(world.read_storage::<A>().get(entity), Some(&A(10)));
+        assert_eq!(world.read_storage::<B>().get(entity), Some(&B(false)));
+    }
+
+    #[test]
+    fn errors_name_entity_marker_and_component() {
+        let world = setup();
+        let serial = "[
+            (marker: (0), components: (Some((1)), None)),
+            (marker: (1), components: (None, Some((3)))),
+        ]";
+        let err = load::<Both>(&world, serial, &DeserializeOptions::new())
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("entity #1: "), "{}", err);
+        assert!(err.contains("marker SimpleMarker(1, "), "{}", err);
+        assert!(err.contains("component specs::saveload::tests::common::B: "), "{}", err);
+    }
+
+    #[test]
+    fn collect_apply_errors_continues_loading() {
+        let (world, entity) = populated();
+        let options = DeserializeOptions::new()
+            .merge_policy(MergePolicy::ErrorOnConflict)
+            .collect_apply_errors(true);
+        let report = load::<Both>(&world, &save_both(), &options).unwrap();
+
+        assert_eq!(report.entities, 1);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].index, Some(0));
+        assert!(!report.is_clean());
+        assert_eq!(world.read_storage::<A>().get(entity), Some(&A(10)));
+        assert_eq!(world.read_storage::<B>().join().count(), 1);
+    }
+
+    #[test]
+    fn collect_apply_errors_aborts_on_malformed_data() {
+        let world = setup();
+        let serial = "[(marker: (0), components: (Some((true)), None))]";
+        let options = DeserializeOptions::new().collect_apply_errors(true);
+        assert!(load::<Both>(&world, serial, &options).is_err());
+    }
+
+    #[test]
+    fn serialize_filtered() {
+        let mut world = setup();
+        let mut level = BitSet::new();
+        for i in 0..4 {
+            let entity = world.create_entity().with(A(i)).marked::<SaveMarker>().build();
+            if i % 2 == 0 {
+                level.add(entity.id());
+            }
+        }
+
+        let mut ser = ron::ser::Serializer::new(None, true);
+        world.exec(
+            |(ents, a, b, markers): (
+                Entities,
+                ReadStorage<A>,
+                ReadStorage<B>,
+                ReadStorage<SaveMarker>,
+            )| {
+                SerializeComponents::<Infallible, SaveMarker>::serialize_filtered(
+                    &(&a, &b),
+                    &ents,
+                    &markers,
+                    |entity, _| level.contains(entity.id()),
+                    &mut ser,
+                )
+                .unwrap();
+            },
+        );
+        let serial = ser.into_output_string();
+
+        let world = setup();
+        load::<Both>(&world, &serial, &DeserializeOptions::new()).unwrap();
+        let mut loaded: Vec<i32> = world.read_storage::<A>().join().map(|a| a.0).collect();
+        loaded.sort();
+        assert_eq!(loaded, vec![0, 2]);
+    }
+
+    #[test]
+    fn unknown_markers_skip() {
+        let (world, entity) = populated();
+        let options = DeserializeOptions::new().unknown_markers(UnknownMarkerPolicy::Skip);
+        let report = load::<Both>(&world, &save_both(), &options).unwrap();
+
+        assert_eq!(report.entities, 1);
+        assert_eq!(report.skipped_markers.len(), 1);
+        assert_eq!(world.read_storage::<A>().get(entity), Some(&A(1)));
+        assert_eq!(world.read_storage::<SaveMarker>().join().count(), 1);
+    }
+
+    #[test]
+    fn unknown_markers_error() {
+        let options = DeserializeOptions::new().unknown_markers(UnknownMarkerPolicy::Error);
+
+        let (world, _) = populated();
+        assert!(load::<Both>(&world, &save_both(), &options).is_err());
+        assert_eq!(world.read_storage::<SaveMarker>().join().count(), 1);
+
+        let (world, entity) = populated();
+        let serial = "[(marker: (0), components: (Some((3)), None))]";
+        load::<Both>(&world, serial, &options).unwrap();
+        assert_eq!(world.read_storage::<A>().get(entity), Some(&A(3)));
+    }
+}
+
+mod convert_test {
+    use std::collections::HashMap;
+
+    use super::{common::*, *};
+
+    struct Links {
+        parent: Option<Entity>,
+        children: Vec<Entity>,
+        named: HashMap<String, Entity>,
+    }
+
+    impl Component for Links {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[derive(Deserialize, Serialize)]
+    struct LinksData {
+        parent: Option<SaveMarker>,
+        children: Vec<SaveMarker>,
+        named: HashMap<String, SaveMarker>,
+    }
+
+    impl ConvertSaveload<SaveMarker> for Links {
+        type Data = LinksData;
+        type Error = Infallible;
+
+        fn convert_into<F>(&self, mut ids: F) -> Result<LinksData, Infallible>
+        where
+            F: FnMut(Entity) -> Option<SaveMarker>,
+        {
+            Ok(LinksData {
+                parent: ConvertEntities::convert_into(&self.parent, &mut ids),
+                children: ConvertEntities::convert_into(&self.children, &mut ids),
+                named: ConvertEntities::convert_into(&self.named, &mut ids),
+            })
+        }
+
+        fn convert_from<F>(data: LinksData, mut ids: F) -> Result<Self, Infallible>
+        where
+            F: FnMut(SaveMarker) -> Option<Entity>,
+        {
+            Ok(Links {
+                parent: ConvertEntities::convert_from(data.parent, &mut ids),
+                children: ConvertEntities::convert_from(data.children, &mut ids),
+                named: ConvertEntities::convert_from(data.named, &mut ids),
+            })
+        }
+    }
+
+    fn setup() -> World {
+        let mut world = common::setup();
+        world.register::<Links>();
+
+        world
+    }
+
+    #[test]
+    fn entity_containers_round_trip() {
+        let mut world = setup();
+        let parent = world.create_entity().marked::<SaveMarker>().build();
+        let child = world.create_entity().marked::<SaveMarker>().build();
+        let unmarked = world.create_entity().build();
+        world
+            .create_entity()
+            .with(Links {
+                parent: Some(parent),
+                children: vec![child, unmarked],
+                named: vec![("child".to_owned(), child)].into_iter().collect(),
+            })
+            .marked::<SaveMarker>()
+            .build();
+        let serial = save::<(ReadStorage<Links>,)>(&world);
+
+        let world = setup();
+        load::<(WriteStorage<Links>,)>(&world, &serial, &DeserializeOptions::new()).unwrap();
+
+        let links = world.read_storage::<Links>();
+        let markers = world.read_storage::<SaveMarker>();
+        let links = links.join().next().unwrap();
+        let id = |entity: Option<Entity>| entity.and_then(|e| markers.get(e)).map(|m| m.id());
+        assert_eq!(id(links.parent), Some(0));
+        assert_eq!(links.children.len(), 1);
+        assert_eq!(id(links.children.get(0).cloned()), Some(1));
+        assert_eq!(id(links.named.get("child").cloned()), Some(1));
+    }
+
+    #[test]
+    fn validate_reports_dangling_references_and_duplicates() {
+        let serial = "[
+            (marker: (0), components: (Some((parent: Some((5)), children: [(1)], named: {})),)),
+            (marker: (1), components: (None,)),
+            (marker: (1), components: (None,)),
+        ]";
+        let mut de = ron::de::Deserializer::from_str(serial).unwrap();
+        let report = validate::<Error, SaveMarker, (WriteStorage<Links>,), _>(&mut de).unwrap();
+
+        assert!(!report.is_valid());
+        assert_eq!(report.entities, 2);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].index, Some(2));
+        assert_eq!(report.dangling_references.len(), 1);
+        assert_eq!(report.dangling_references[0].id(), 5);
+    }
+
+    #[test]
+    fn validate_fails_on_malformed_data() {
+        let serial = "[(marker: (0), components: (Some((parent: 3)),))]";
+        let mut de = ron::de::Deserializer::from_str(serial).unwrap();
+
+        assert!(validate::<Error, SaveMarker, (WriteStorage<Links>,), _>(&mut de).is_err());
+    }
+}
+
+#[cfg(feature = "serde_json")]
+mod registry_test {
+    use super::{common::*, *};
+
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+    struct Pos(i32, i32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+    struct Health(u32);
+
+    impl Component for Health {
+        type Storage = VecStorage<Self>;
+    }
+
+    fn setup() -> (World, SaveloadRegistry<SaveMarker>) {
+        let mut world = common::setup();
+        world.register::<Pos>();
+        world.register::<Health>();
+
+        let mut registry = SaveloadRegistry::new();
+        registry.register::<Pos>("pos");
+        registry.register::<Health>("health");
+
+        (world, registry)
+    }
+
+    #[test]
+    fn selected_components_round_trip() {
+        let (mut world, registry) = setup();
+        world
+            .create_entity()
+            .with(Pos(1, 2))
+            .with(Health(3))
+            .marked::<SaveMarker>()
+            .build();
+        world
+            .create_entity()
+            .with(Health(4))
+            .marked::<SaveMarker>()
+            .build();
+
+        let storages = registry.serializer(&world, &["pos"]).unwrap();
+        let mut ser = serde_json::Serializer::new(Vec::new());
+        SerializeComponents::<String, SaveMarker>::serialize(
+            &storages,
+            &world.entities(),
+            &world.read_storage::<SaveMarker>(),
+            &mut ser,
+        )
+        .unwrap();
+        drop(storages);
+        let serial = String::from_utf8(ser.into_inner()).unwrap();
+        assert!(!serial.contains("health"));
+
+        let (world, registry) = setup();
+        let mut storages = registry.deserializer(&world, &["pos", "health"]).unwrap();
+        let mut de = serde_json::Deserializer::from_str(&serial);
+        storages
+            .deserialize(
+                &world.entities(),
+                &mut world.write_storage::<SaveMarker>(),
+                &mut world.write_resource::<SimpleMarkerAllocator<Save>>(),
+                &mut de,
+            )
+            .unwrap();
+        drop(storages);
+
+        let pos = world.read_storage::<Pos>();
+        let health = world.read_storage::<Health>();
+        assert_eq!(pos.join().cloned().collect::<Vec<_>>(), vec![Pos(1, 2)]);
+        assert_eq!(health.count(), 0);
+    }
+
+    #[test]
+    fn unknown_selection_is_rejected() {
+        let (world, registry) = setup();
+
+        assert_eq!(
+            registry.serializer(&world, &["pos", "velocity"]).err(),
+            Some(UnknownComponent("velocity".to_owned()))
+        );
+    }
+
+    #[test]
+    fn errors_name_the_component() {
+        let (world, registry) = setup();
+        let serial = r#"[{"marker": [0], "components": {"pos": "oops"}}]"#;
+        let mut storages = registry.deserializer(&world, &["pos"]).unwrap();
+        let mut de = serde_json::Deserializer::from_str(serial);
+        let err = storages
+            .deserialize(
+                &world.entities(),
+                &mut world.write_storage::<SaveMarker>(),
+                &mut world.write_resource::<SimpleMarkerAllocator<Save>>(),
+                &mut de,
+            )
+            .unwrap_err();
+
+        assert!(err.to_string().contains("component pos"));
+    }
+
+    #[test]
+    fn dynamic_components_round_trip() {
+        let (mut world, mut registry) = setup();
+        crate::dynamic::register(&mut world, "quest");
+        registry.register_dynamic("quest");
+        let npc = world.create_entity().marked::<SaveMarker>().build();
+        crate::dynamic::write(&world, "quest")
+            .unwrap()
+            .insert(npc, serde_json::json!({ "id": 7 }))
+            .unwrap();
+
+        let storages = registry.serializer(&world, &["quest"]).unwrap();
+        let mut ser = serde_json::Serializer::new(Vec::new());
+        SerializeComponents::<String, SaveMarker>::serialize(
+            &storages,
+            &world.entities(),
+            &world.read_storage::<SaveMarker>(),
+            &mut ser,
+        )
+        .unwrap();
+        drop(storages);
+        let serial = String::from_utf8(ser.into_inner()).unwrap();
+
+        let (mut world, _) = setup();
+        crate::dynamic::register(&mut world, "quest");
+        load(&world, &registry, &serial);
+
+        let quests = crate::dynamic::read(&world, "quest").unwrap();
+        let values: Vec<_> = quests.join().cloned().collect();
+        assert_eq!(values, vec![serde_json::json!({ "id": 7 })]);
+    }
+
+    #[test]
+    fn relations_round_trip() {
+        use crate::relation::Relations;
+
+        let (mut world, mut registry) = setup();
+        world.register_relation::<u32>();
+        registry.register::<Relations<u32>>("likes");
+        let a = world.create_entity().marked::<SaveMarker>().build();
+        let b = world.create_entity().marked::<SaveMarker>().build();
+        let unmarked = world.create_entity().build();
+        {
+            let mut likes = world.write_storage::<Relations<u32>>();
+            likes.relate(a, b, 3).unwrap();
+            likes.relate(a, unmarked, 4).unwrap();
+            likes.relate(b, a, 5).unwrap();
+        }
+
+        let storages = registry.serializer(&world, &["likes"]).unwrap();
+        let mut ser = serde_json::Serializer::new(Vec::new());
+        SerializeComponents::<String, SaveMarker>::serialize(
+            &storages,
+            &world.entities(),
+            &world.read_storage::<SaveMarker>(),
+            &mut ser,
+        )
+        .unwrap();
+        drop(storages);
+        let serial = String::from_utf8(ser.into_inner()).unwrap();
+
+        let (mut world, _) = setup();
+        world.register_relation::<u32>();
+        world.create_entity().build();
+        load(&world, &registry, &serial);
+
+        let markers = world.read_storage::<SaveMarker>();
+        let entity = |id| {
+            (&world.entities(), &markers)
+                .join()
+                .find(|(_, m)| m.id() == id)
+                .unwrap()
+                .0
+        };
+        let (a, b) = (entity(0), entity(1));
+        let likes = world.read_storage::<Relations<u32>>();
+        let pairs: Vec<_> = likes.pairs().map(|(s, o, &v)| (s, o, v)).collect();
+        assert_eq!(pairs.len(), 2);
+        assert!(pairs.contains(&(a, b, 3)));
+        assert!(pairs.contains(&(b, a, 5)));
+    }
+
+    fn load(world: &World, registry: &SaveloadRegistry<SaveMarker>, serial: &str) {
+        let names: Vec<&str> = registry.names().collect();
+        let mut storages = registry.deserializer(world, &names).unwrap();
+        let mut de = serde_json::Deserializer::from_str(serial);
+        storages
+            .deserialize(
+                &world.entities(),
+                &mut world.write_storage::<SaveMarker>(),
+                &mut world.write_resource::<SimpleMarkerAllocator<Save>>(),
+                &mut de,
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn unknown_components_are_preserved() {
+        let (mut world, mut registry) = setup();
+        world.register::<UnknownComponents>();
+        registry.preserve_unknown(true);
+        let serial = r#"[{"marker": [0], "components": {"pos": [1, 2], "ai": {"state": "idle"}}}]"#;
+        load(&world, &registry, serial);
+        world.maintain();
+
+        let entity = world.entities().join().next().unwrap();
+        let unknown = world.read_storage::<UnknownComponents>().get(entity).cloned();
+        assert_eq!(unknown.unwrap().0.keys().collect::<Vec<_>>(), vec!["ai"]);
+
+        let storages = registry.serializer(&world, &["pos"]).unwrap();
+        let saved = storages.serialize_entity(entity, |_| None).unwrap();
+        assert_eq!(saved["ai"], serde_json::json!({"state": "idle"}));
+        assert_eq!(saved["pos"], serde_json::json!([1, 2]));
+        drop(storages);
+
+        load(&world, &registry, r#"[{"marker": [0], "components": {"pos": [1, 2]}}]"#);
+        assert!(world.read_storage::<UnknownComponents>().get(entity).is_none());
+    }
+
+    #[test]
+    fn registered_components_are_not_preserved() {
+        let (mut world, mut registry) = setup();
+        world.register::<UnknownComponents>();
+        registry.preserve_unknown(true);
+        let serial = r#"[{"marker": [0], "components": {"pos": [1, 2], "health": 3}}]"#;
+        let mut storages = registry.deserializer(&world, &["pos"]).unwrap();
+        let mut de = serde_json::Deserializer::from_str(serial);
+        storages
+            .deserialize(
+                &world.entities(),
+                &mut world.write_storage::<SaveMarker>(),
+                &mut world.write_resource::<SimpleMarkerAllocator<Save>>(),
+                &mut de,
+            )
+            .unwrap();
+        drop(storages);
+
+        assert_eq!(world.read_storage::<Health>().count(), 0);
+        assert_eq!(world.read_storage::<UnknownComponents>().count(), 0);
+    }
+}
+
+#[cfg(feature = "bincode")]
+mod blob_test {
+    use super::{common::*, *};
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Child {
+        name: String,
+        parent: Option<Entity>,
+    }
+
+    impl Component for Child {
+        type Storage = VecStorage<Self>;
+    }
+
+    impl ConvertSaveload<SaveMarker> for Child {
+        type Data = (String, Option<SaveMarker>);
+        type Error = Infallible;
+
+        fn convert_into<F>(&self, ids: F) -> Result<Self::Data, Infallible>
+        where
+            F: FnMut(Entity) -> Option<SaveMarker>,
+        {
+            Ok((self.name.clone(), ConvertEntities::convert_into(&self.parent, ids)))
+        }
+
+        fn convert_from<F>(data: Self::Data, ids: F) -> Result<Self, Infallible>
+        where
+            F: FnMut(SaveMarker) -> Option<Entity>,
+        {
+            Ok(Child {
+                name: data.0,
+                parent: ConvertEntities::convert_from(data.1, ids),
+            })
+        }
+    }
+
+    fn setup() -> (World, Entity) {
+        let mut world = World::new();
+        world.register::<Child>();
+        let root = world
+            .create_entity()
+            .with(Child {
+                name: "root".to_owned(),
+                parent: None,
+            })
+            .build();
+        let child = world
+            .create_entity()
+            .with(Child {
+                name: "child".to_owned(),
+                parent: Some(root),
+            })
+            .build();
+
+        (world, child)
+    }
+
+    fn copy(include_references: bool) -> (World, Entity) {
+        let (world, child) = setup();
+        let blob = export_entity::<Infallible, Save, (ReadStorage<Child>,)>(
+            &world,
+            child,
+            include_references,
+        )
+        .unwrap();
+
+        let (mut world, _) = setup();
+        let copy =
+            import_entity::<Infallible, Save, (WriteStorage<Child>,)>(&world, &blob).unwrap();
+        world.maintain();
+
+        (world, copy)
+    }
+
+    #[test]
+    fn import_creates_referenced_entities() {
+        let (world, copy) = copy(true);
+        let children = world.read_storage::<Child>();
+
+        assert_eq!(children.count(), 4);
+        let parent = children.get(copy).unwrap().parent.unwrap();
+        assert_eq!(children.get(parent).unwrap().name, "root");
+        assert!(parent.id() > 1);
+    }
+
+    #[test]
+    fn import_drops_references_when_not_included() {
+        let (world, copy) = copy(false);
+        let children = world.read_storage::<Child>();
+
+        assert_eq!(children.count(), 3);
+        assert_eq!(
+            children.get(copy),
+            Some(&Child {
+                name: "child".to_owned(),
+                parent: None,
+            })
+        );
+    }
+
+    #[test]
+    fn empty_blob_is_rejected() {
+        let world = World::new();
+        let blob = bincode::serialize(&Vec::<()>::new()).unwrap();
+
+        assert!(import_entity::<Infallible, Save, (WriteStorage<Child>,)>(&world, &blob).is_err());
+    }
+}
+
+#[cfg(feature = "bincode")]
+mod manager_test {
+    use std::{fs, path::PathBuf, time::Duration};
+
+    use super::{common::*, *};
+
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+    struct Pos(i32, i32);
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+    }
+
+    type AutoSave = AutoSaveSystem<Infallible, SaveMarker, (Pos,)>;
+
+    fn setup(directory: &PathBuf) -> World {
+        let mut world = common::setup();
+        world.register::<Pos>();
+        world.insert(SaveManager::new(directory, Format::Bincode));
+
+        world
+    }
+
+    fn directory(test: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("specs-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+
+        directory
+    }
+
+    #[test]
+    fn auto_save_on_request_and_interval() {
+        let directory = directory("auto-save");
+        let mut world = setup(&directory);
+        world
+            .create_entity()
+            .with(Pos(1, 2))
+            .marked::<SaveMarker>()
+            .build();
+
+        let mut on_request = AutoSave::new("manual", None);
+        on_request.run_now(&world);
+        assert!(world.read_resource::<SaveManager>().slot("manual").is_none());
+
+        world.write_resource::<SaveManager>().request_save("manual");
+        on_request.run_now(&world);
+        let mut every_run = AutoSave::new("auto", Some(Duration::from_secs(0)));
+        every_run.run_now(&world);
+
+        let mut manager = world.remove::<SaveManager>().unwrap();
+        assert!(manager.take_error().is_none());
+        assert!(manager.slot("manual").unwrap().saved_at.is_some());
+        assert!(manager.path("auto").exists());
+
+        let mut scanned = SaveManager::new(&directory, Format::Bincode);
+        scanned.scan().unwrap();
+        let names: Vec<_> = scanned.slots().map(|slot| slot.name.clone()).collect();
+        assert_eq!(names, vec!["auto".to_owned(), "manual".to_owned()]);
+
+        let mut world = setup(&directory);
+        world.exec(
+            |(ents, pos, mut markers, mut alloc): (
+                Entities,
+                WriteStorage<Pos>,
+                WriteStorage<SaveMarker>,
+                Write<SimpleMarkerAllocator<Save>>,
+            )| {
+                scanned
+                    .load::<Error, _, _>("auto", &mut (pos,), &ents, &mut markers, &mut alloc)
+                    .unwrap();
+            },
+        );
+        let pos = world.read_storage::<Pos>();
+        assert_eq!(pos.join().cloned().collect::<Vec<_>>(), vec![Pos(1, 2)]);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn failed_auto_save_is_kept() {
+        let directory = directory("failed-save");
+        fs::write(&directory, b"not a directory").unwrap();
+        let world = setup(&directory);
+
+        world.write_resource::<SaveManager>().request_save("manual");
+        AutoSave::new("manual", None).run_now(&world);
+
+        assert!(matches!(
+            world.write_resource::<SaveManager>().take_error(),
+            Some(StreamError::Io(_))
+        ));
+        fs::remove_file(&directory).unwrap();
+    }
+}
+
+mod progress_test {
+    use std::sync::{Arc, Mutex};
+
+    use super::{common::*, *};
+
+    fn save(progress: &mut Progress) -> Result<String, ron::ser::Error> {
+        let mut world = setup();
+        for i in 0..3 {
+            world.create_entity().with(A(i)).marked::<SaveMarker>().build();
+        }
+
+        let mut ser = ron::ser::Serializer::new(None, true);
+        world.exec(
+            |(ents, a, markers): (Entities, ReadStorage<A>, ReadStorage<SaveMarker>)| {
+                SerializeComponents::<Infallible, SaveMarker>::serialize_with_progress(
+                    &(&a,),
+                    &ents,
+                    &markers,
+                    progress,
+                    &mut ser,
+                )
+            },
+        )?;
+
+        Ok(ser.into_output_string())
+    }
+
+    fn load(world: &mut World, serial: &str, progress: &mut Progress) -> Result<(), String> {
+        let mut de = ron::de::Deserializer::from_str(serial).unwrap();
+        world.exec(
+            |(ents, a, mut markers, mut alloc): (
+                Entities,
+                WriteStorage<A>,
+                WriteStorage<SaveMarker>,
+                Write<SimpleMarkerAllocator<Save>>,
+            )| {
+                DeserializeComponents::<Error, _>::deserialize_with_progress(
+                    &mut (a,),
+                    &ents,
+                    &mut markers,
+                    &mut alloc,
+                    &DeserializeOptions::new(),
+                    progress,
+                    &mut de,
+                )
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+            },
+        )
+    }
+
+    fn recording() -> (Progress, Arc<Mutex<Vec<(usize, Option<usize>)>>>) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let recorded = calls.clone();
+        let progress = Progress::new().on_entity(move |done, total| {
+            recorded.lock().unwrap().push((done, total));
+        });
+
+        (progress, calls)
+    }
+
+    #[test]
+    fn reports_every_entity() {
+        let (mut progress, calls) = recording();
+        let serial = save(&mut progress).unwrap();
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![(1, Some(3)), (2, Some(3)), (3, Some(3))]
+        );
+
+        let (mut progress, calls) = recording();
+        load(&mut setup(), &serial, &mut progress).unwrap();
+        let done: Vec<usize> = calls.lock().unwrap().iter().map(|&(done, _)| done).collect();
+        assert_eq!(done, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn cancelled_load_stops_between_entities() {
+        let serial = save(&mut Progress::new()).unwrap();
+
+        let token = CancellationToken::new();
+        let cancel = token.clone();
+        let mut progress = Progress::new()
+            .on_entity(move |done, _| {
+                if done == 2 {
+                    cancel.cancel();
+                }
+            })
+            .cancel_with(token);
+        let mut world = setup();
+        let err = load(&mut world, &serial, &mut progress).unwrap_err();
+
+        assert!(err.contains(&Cancelled.to_string()));
+        assert_eq!(world.read_storage::<A>().count(), 2);
+    }
+
+    #[test]
+    fn cancelled_save_fails() {
+        let token = CancellationToken::new();
+        token.cancel();
+
+        assert!(save(&mut Progress::new().cancel_with(token)).is_err());
+    }
+}
+
+#[cfg(feature = "bincode")]
+mod borrowed_test {
+    use super::{common::*, *};
+
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+    struct Name(String);
+
+    impl Component for Name {
+        type Storage = VecStorage<Self>;
+    }
+
+    impl<'de> ConvertSaveloadBorrowed<'de, SaveMarker> for Name {
+        type Data = &'de str;
+        type Error = Infallible;
+
+        fn convert_from<F>(data: &'de str, _ids: F) -> Result<Self, Infallible>
+        where
+            F: FnMut(SaveMarker) -> Option<Entity>,
+        {
+            Ok(Name(data.to_owned()))
+        }
+    }
+
+    fn setup() -> World {
+        let mut world = common::setup();
+        world.register::<Name>();
+
+        world
+    }
+
+    #[test]
+    fn loads_borrowed_data() {
+        let mut world = setup();
+        for name in &["first", "second"] {
+            world
+                .create_entity()
+                .with(Name((*name).to_owned()))
+                .marked::<SaveMarker>()
+                .build();
+        }
+
+        let mut bytes = Vec::new();
+        let mut ser = bincode::Serializer::new(&mut bytes, bincode::DefaultOptions::new());
+        world.exec(
+            |(ents, names, markers): (Entities, ReadStorage<Name>, ReadStorage<SaveMarker>)| {
+                SerializeComponents::<Infallible, SaveMarker>::serialize(
+                    &(&names,),
+                    &ents,
+                    &markers,
+                    &mut ser,
+                )
+                .unwrap();
+            },
+        );
+
+        let mut world = setup();
+        let mut de = bincode::Deserializer::from_slice(&bytes, bincode::DefaultOptions::new());
+        world.exec(
+            |(ents, names, mut markers, mut alloc): (
+                Entities,
+                WriteStorage<Name>,
+                WriteStorage<SaveMarker>,
+                Write<SimpleMarkerAllocator<Save>>,
+            )| {
+                DeserializeComponentsBorrowed::<Infallible, _>::deserialize_borrowed(
+                    &mut (names,),
+                    &ents,
+                    &mut markers,
+                    &mut alloc,
+                    &mut de,
+                )
+                .unwrap();
+            },
+        );
+
+        let mut names: Vec<_> = world.read_storage::<Name>().join().cloned().collect();
+        names.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(names, vec![Name("first".to_owned()), Name("second".to_owned())]);
+    }
+}
+
+mod diff_test {
+    use super::{common::*, *};
+
+    fn setup() -> World {
+        let mut world = common::setup();
+        world.register::<Pos>();
+
+        world
+    }
+
+    fn save_diff(
+        world: &mut World,
+        tracker: &mut DiffTracker<SaveMarker>,
+    ) -> (Diff<SaveMarker>, String) {
+        world.maintain();
+        let pos = world.read_storage::<Pos>();
+        let markers = world.read_storage::<SaveMarker>();
+        tracker.record(&pos);
+        let diff = tracker.diff(&world.entities(), &markers);
+
+        let mut ser = ron::ser::Serializer::new(None, false);
+        SerializeComponents::<Infallible, SaveMarker>::serialize_diff(
+            &(&pos,),
+            &world.entities(),
+            &markers,
+            &diff,
+            &mut ser,
+        )
+        .unwrap();
+        tracker.commit(&world.entities(), &markers);
+
+        (diff, ser.into_output_string())
+    }
+
+    fn apply(world: &mut World, base: u64, serial: &str) -> Result<(), ron::de::Error> {
+        let mut de = ron::de::Deserializer::from_str(serial).unwrap();
+        let result = world.exec(
+            |(ents, pos, mut markers, mut alloc): (
+                Entities,
+                WriteStorage<Pos>,
+                WriteStorage<SaveMarker>,
+                Write<SimpleMarkerAllocator<Save>>,
+            )| {
+                DeserializeComponents::<Error, _>::apply_diff(
+                    &mut (pos,),
+                    &ents,
+                    &mut markers,
+                    &mut alloc,
+                    base,
+                    &mut de,
+                )
+            },
+        );
+        world.maintain();
+
+        result
+    }
+
+    fn positions(world: &World) -> Vec<(u64, i32)> {
+        let markers = world.read_storage::<SaveMarker>();
+        let mut positions: Vec<_> = (&markers, &world.read_storage::<Pos>())
+            .join()
+            .map(|(marker, pos)| (marker.id(), pos.0))
+            .collect();
+        positions.sort();
+
+        positions
+    }
+
+    #[test]
+    fn diffs_only_contain_changes() {
+        let mut world = setup();
+        let mut tracker = DiffTracker::new();
+        tracker.track(&mut world.write_storage::<Pos>());
+        let entities: Vec<_> = (0..3)
+            .map(|i| world.create_entity().with(Pos(i)).marked::<SaveMarker>().build())
+            .collect();
+
+        let (diff, full) = save_diff(&mut world, &mut tracker);
+        assert_eq!(diff.base, 0);
+        assert_eq!((&diff.changed).join().count(), 3);
+        let mut copy = setup();
+        apply(&mut copy, 0, &full).unwrap();
+        assert_eq!(positions(&copy), positions(&world));
+
+        world.write_storage::<Pos>().get_mut(entities[0]).unwrap().0 = 10;
+        world.delete_entity(entities[1]).unwrap();
+        world.create_entity().with(Pos(3)).marked::<SaveMarker>().build();
+
+        let (diff, changes) = save_diff(&mut world, &mut tracker);
+        assert_eq!(diff.base, 1);
+        assert_eq!((&diff.changed).join().count(), 2);
+        assert_eq!(diff.removed.len(), 1);
+        apply(&mut copy, 1, &changes).unwrap();
+        assert_eq!(positions(&copy), vec![(0, 10), (2, 2), (3, 3)]);
+        assert_eq!(positions(&copy), positions(&world));
+    }
+
+    #[test]
+    fn diff_with_wrong_base_is_rejected() {
+        let mut world = setup();
+        let mut tracker = DiffTracker::new();
+        tracker.track(&mut world.write_storage::<Pos>());
+        world.create_entity().with(Pos(0)).marked::<SaveMarker>().build();
+        let (_, full) = save_diff(&mut world, &mut tracker);
+
+        let mut copy = setup();
+        assert!(apply(&mut copy, 1, &full).is_err());
+        assert!(positions(&copy).is_empty());
+    }
+}
+
+mod snapshot_test {
+    use super::{common::*, *};
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Turn(u32);
+
+    fn setup() -> World {
+        let mut world = common::setup();
+        world.register::<Pos>();
+        world.register::<Target>();
+        world.insert(Turn(0));
+        world.insert(
+            SnapshotRegistry::<SaveMarker>::new()
+                .with_tracked_component::<Pos>()
+                .with_component::<Target>()
+                .with_resource::<Turn>(),
+        );
+
+        world
+    }
+
+    fn positions(world: &World) -> Vec<(u64, i32)> {
+        let markers = world.read_storage::<SaveMarker>();
+        let mut positions: Vec<_> = (&markers, &world.read_storage::<Pos>())
+            .join()
+            .map(|(marker, pos)| (marker.id(), pos.0))
+            .collect();
+        positions.sort();
+
+        positions
+    }
+
+    #[test]
+    fn restore_rolls_back_changes() {
+        let mut world = setup();
+        let entities: Vec<_> = (0..3)
+            .map(|i| world.create_entity().with(Pos(i)).marked::<SaveMarker>().build())
+            .collect();
+        let unmarked = world.create_entity().with(Pos(7)).build();
+        let mut snapshot = Snapshot::<SaveMarker>::take(&world).unwrap();
+        let before = positions(&world);
+
+        world.write_storage::<Pos>().get_mut(entities[0]).unwrap().0 = 10;
+        world.write_storage::<Pos>().remove(entities[1]);
+        world.write_storage::<Pos>().get_mut(unmarked).unwrap().0 = 8;
+        world.create_entity().with(Pos(3)).marked::<SaveMarker>().build();
+        *world.write_resource::<Turn>() = Turn(1);
+        world.maintain();
+
+        snapshot.restore(&mut world).unwrap();
+        assert_eq!(positions(&world), before);
+        assert_eq!(world.read_storage::<Pos>().get(unmarked), Some(&Pos(8)));
+        assert_eq!(*world.read_resource::<Turn>(), Turn(0));
+
+        world.write_storage::<Pos>().get_mut(entities[2]).unwrap().0 = 20;
+        snapshot.restore(&mut world).unwrap();
+        assert_eq!(positions(&world), before);
+    }
+
+    #[test]
+    fn restore_recreates_deleted_entities() {
+        let mut world = setup();
+        let target = world.create_entity().with(Pos(1)).marked::<SaveMarker>().build();
+        let seeker = world
+            .create_entity()
+            .with(Pos(2))
+            .with(Target(target))
+            .marked::<SaveMarker>()
+            .build();
+        let mut snapshot = Snapshot::<SaveMarker>::take(&world).unwrap();
+
+        world.delete_entity(target).unwrap();
+        world.maintain();
+
+        snapshot.restore(&mut world).unwrap();
+        assert_eq!(positions(&world), vec![(0, 1), (1, 2)]);
+        let recreated = world.read_storage::<Target>().get(seeker).unwrap().0;
+        assert_ne!(recreated, target);
+        assert_eq!(world.read_storage::<Pos>().get(recreated), Some(&Pos(1)));
+    }
+
+    #[test]
+    fn scene_stack_nests() {
+        let mut world = setup();
+        world.create_entity().with(Pos(1)).marked::<SaveMarker>().build();
+        let mut stack = SceneStack::<SaveMarker>::new();
+
+        stack.push(&mut world).unwrap();
+        assert!(positions(&world).is_empty());
+        world.create_entity().with(Pos(2)).marked::<SaveMarker>().build();
+        *world.write_resource::<Turn>() = Turn(2);
+        stack.push(&mut world).unwrap();
+        world.create_entity().with(Pos(3)).marked::<SaveMarker>().build();
+        world.maintain();
+        assert_eq!(stack.depth(), 2);
+
+        assert!(stack.pop(&mut world).unwrap());
+        assert_eq!(positions(&world)[0].1, 2);
+        assert_eq!(positions(&world).len(), 1);
+        assert_eq!(*world.read_resource::<Turn>(), Turn(2));
+        assert!(stack.pop(&mut world).unwrap());
+        assert_eq!(positions(&world)[0].1, 1);
+        assert_eq!(positions(&world).len(), 1);
+        assert_eq!(*world.read_resource::<Turn>(), Turn(0));
+        assert!(!stack.pop(&mut world).unwrap());
+    }
+}
+
+mod prefab_test {
+    use std::collections::BTreeMap;
+
+    use super::{common::*, *};
+
+    type Storages<'a> = (WriteStorage<'a, Pos>, WriteStorage<'a, Target>);
+
+    fn setup() -> World {
+        let mut world = World::new();
+        world.register::<Pos>();
+        world.register::<Target>();
+
+        world
+    }
+
+    #[test]
+    fn instances_reference_their_own_entities() {
+        let mut world = setup();
+        let mut prefab = Prefab::new();
+        let root = prefab.add((Some(Pos(0)), None));
+        prefab.add((Some(Pos(1)), Some(root)));
+
+        let first = world.instantiate::<Infallible, Storages>(&prefab).unwrap();
+        let second = world.instantiate::<Infallible, Storages>(&prefab).unwrap();
+        world.maintain();
+
+        let parents = world.read_storage::<Target>();
+        assert_eq!(parents.get(first[1]).unwrap().0, first[0]);
+        assert_eq!(parents.get(second[1]).unwrap().0, second[0]);
+        assert_ne!(first[0], second[0]);
+        assert_eq!(world.read_storage::<Pos>().get(second[1]), Some(&Pos(1)));
+    }
+
+    #[test]
+    fn overrides_patch_single_instances() {
+        let mut world = setup();
+        let mut prefab = Prefab::new();
+        let root = prefab.add((Some(Pos(0)), None));
+        let child = prefab.add((Some(Pos(1)), Some(root)));
+
+        let plain = world.instantiate::<Infallible, Storages>(&prefab).unwrap();
+        let moved = world
+            .instantiate_with::<Infallible, Storages>(&prefab, vec![(child, (Some(Pos(5)), None))])
+            .unwrap();
+        world.maintain();
+
+        let pos = world.read_storage::<Pos>();
+        assert_eq!(pos.get(plain[1]), Some(&Pos(1)));
+        assert_eq!(pos.get(moved[1]), Some(&Pos(5)));
+        assert_eq!(pos.get(moved[0]), Some(&Pos(0)));
+        assert_eq!(world.read_storage::<Target>().get(moved[1]).unwrap().0, moved[0]);
+    }
+
+    #[test]
+    fn unknown_override_fails() {
+        let mut world = setup();
+        let mut prefab = Prefab::new();
+        prefab.add((Some(Pos(0)), None));
+
+        let e = world
+            .instantiate_with::<Infallible, Storages>(
+                &prefab,
+                vec![(PrefabMarker::new(7), (Some(Pos(5)), None))],
+            )
+            .unwrap_err();
+        assert!(e.to_string().contains("isn't in the prefab"));
+        assert!(e.to_string().contains("marker SimpleMarker(7"));
+
+        world.maintain();
+        assert_eq!((&world.entities()).join().count(), 0);
+    }
+
+    #[test]
+    fn reload_updates_instances_in_place() {
+        struct Save;
+
+        let mut world = setup();
+        world.register::<PrefabSource>();
+        world.register::<SimpleMarker<Save>>();
+        world.insert(SimpleMarkerAllocator::<Save>::new());
+
+        let mut library = PrefabLibrary::new();
+        let mut prefab = Prefab::new();
+        let root = prefab.add((Some(Pos(0)), None));
+        prefab.add((Some(Pos(1)), Some(root)));
+        library.insert("pair", prefab);
+        let instances: Vec<_> = (0..2)
+            .map(|_| {
+                library
+                    .instantiate::<Infallible, Storages>(&world, "pair", Vec::new())
+                    .unwrap()
+            })
+            .collect();
+        world.insert(library);
+        world.maintain();
+        world.exec(
+            |(mut markers, mut alloc): (
+                WriteStorage<SimpleMarker<Save>>,
+                Write<SimpleMarkerAllocator<Save>>,
+            )| {
+                alloc.mark(instances[0][0], &mut markers);
+            },
+        );
+
+        let mut prefab = Prefab::new();
+        let root = prefab.add((Some(Pos(10)), None));
+        prefab.add((None, Some(root)));
+        prefab.add((Some(Pos(12)), Some(root)));
+        world
+            .write_resource::<PrefabLibrary<<(Pos, Target) as PrefabComponents>::Data>>()
+            .insert("pair", prefab);
+        PrefabReloadSystem::<Infallible, (Pos, Target)>::new().run_now(&world);
+        world.maintain();
+
+        let pos = world.read_storage::<Pos>();
+        let parents = world.read_storage::<Target>();
+        let sources = world.read_storage::<PrefabSource>();
+        for instance in &instances {
+            assert_eq!(pos.get(instance[0]), Some(&Pos(10)));
+            assert_eq!(pos.get(instance[1]), None);
+            assert_eq!(parents.get(instance[1]).unwrap().0, instance[0]);
+            let added = (&world.entities(), &sources, &parents)
+                .join()
+                .find(|(_, source, parent)| source.index == 2 && parent.0 == instance[0])
+                .map(|(entity, _, _)| entity)
+                .unwrap();
+            assert_eq!(pos.get(added), Some(&Pos(12)));
+        }
+        assert!(world
+            .read_storage::<SimpleMarker<Save>>()
+            .contains(instances[0][0]));
+    }
+
+    #[test]
+    fn nested_prefabs_are_wired_up() {
+        let mut world = setup();
+        world.register::<PrefabSource>();
+
+        let mut library = PrefabLibrary::new();
+        let mut wheel = Prefab::new();
+        wheel.add((Some(Pos(1)), None));
+        library.insert("wheel", wheel);
+        let mut car = Prefab::new();
+        let body = car.add((Some(Pos(0)), None));
+        let wheel = car.add_child("wheel", Some((None, Some(body))));
+        car.add((Some(Pos(2)), Some(wheel)));
+        library.insert("car", car);
+
+        let car = library
+            .instantiate::<Infallible, Storages>(&world, "car", Vec::new())
+            .unwrap();
+        world.insert(library);
+        world.maintain();
+        let wheel = world.read_storage::<Target>().get(car[1]).unwrap().0;
+        assert_eq!(world.read_storage::<Target>().get(wheel).unwrap().0, car[0]);
+        assert_eq!(world.read_storage::<Pos>().get(wheel), Some(&Pos(1)));
+
+        let mut prefab = Prefab::new();
+        prefab.add((Some(Pos(5)), None));
+        world
+            .write_resource::<PrefabLibrary<<(Pos, Target) as PrefabComponents>::Data>>()
+            .insert("wheel", prefab);
+        PrefabReloadSystem::<Infallible, (Pos, Target)>::new().run_now(&world);
+        assert_eq!(world.read_storage::<Pos>().get(wheel), Some(&Pos(5)));
+        assert_eq!(world.read_storage::<Target>().get(wheel).unwrap().0, car[0]);
+    }
+
+    #[test]
+    fn reload_keeps_nested_instances_of_deleted_parents() {
+        let mut world = setup();
+        world.register::<PrefabSource>();
+
+        let mut library = PrefabLibrary::new();
+        let mut wheel = Prefab::new();
+        wheel.add((Some(Pos(1)), None));
+        library.insert("wheel", wheel);
+        let mut car = Prefab::new();
+        car.add((Some(Pos(0)), None));
+        let seat = car.add((Some(Pos(2)), None));
+        car.add_child("wheel", Some((None, Some(seat))));
+        library.insert("car", car);
+        let car = library
+            .instantiate::<Infallible, Storages>(&world, "car", Vec::new())
+            .unwrap();
+        world.insert(library);
+        world.maintain();
+        let wheel = (&world.entities(), &world.read_storage::<PrefabSource>())
+            .join()
+            .find(|(_, source)| source.prefab == "wheel")
+            .unwrap()
+            .0;
+        let reload = |world: &mut World, pos: i32| {
+            let mut prefab = Prefab::new();
+            prefab.add((Some(Pos(pos)), None));
+            world
+                .write_resource::<PrefabLibrary<<(Pos, Target) as PrefabComponents>::Data>>()
+                .insert("wheel", prefab);
+            PrefabReloadSystem::<Infallible, (Pos, Target)>::new().run_now(world);
+            world.maintain();
+        };
+
+        // The patch still finds the seat when an earlier entity is gone.
+        world.delete_entity(car[0]).unwrap();
+        world.maintain();
+        reload(&mut world, 5);
+        assert_eq!(world.read_storage::<Pos>().get(wheel), Some(&Pos(5)));
+        assert_eq!(world.read_storage::<Target>().get(wheel).unwrap().0, car[1]);
+
+        world.delete_entity(car[1]).unwrap();
+        world.maintain();
+        reload(&mut world, 6);
+        assert_eq!(world.read_storage::<Pos>().get(wheel), Some(&Pos(6)));
+    }
+
+    #[test]
+    fn recursive_prefab_fails() {
+        let mut world = setup();
+        world.register::<PrefabSource>();
+
+        let mut library = PrefabLibrary::new();
+        let mut prefab = Prefab::new();
+        prefab.add((Some(Pos(0)), None));
+        prefab.add_child("loop", None);
+        library.insert("loop", prefab);
+        let e = library
+            .instantiate::<Infallible, Storages>(&world, "loop", Vec::new())
+            .unwrap_err();
+        assert!(e.to_string().contains("prefab `loop` contains itself"));
+
+        world.maintain();
+        assert_eq!((&world.entities()).join().count(), 0);
+    }
+
+    #[test]
+    fn unknown_child_prefab_fails() {
+        let mut world = setup();
+        world.register::<PrefabSource>();
+
+        let mut library = PrefabLibrary::new();
+        let mut prefab = Prefab::new();
+        prefab.add((Some(Pos(0)), None));
+        prefab.add_child("missing", None);
+        library.insert("outer", prefab);
+        let e = library
+            .instantiate::<Infallible, Storages>(&world, "outer", Vec::new())
+            .unwrap_err();
+        assert!(e.to_string().contains("no prefab is called `missing`"));
+
+        world.maintain();
+        assert_eq!((&world.entities()).join().count(), 0);
+    }
+
+    #[test]
+    fn prefab_from_ron() {
+        let mut world = setup();
+        let prefab: Prefab<<Storages as DeserializeComponents<Infallible, PrefabMarker>>::Data> =
+            ron::de::from_str(
+                "(entities: [(marker: (0), components: (Some((3)), None)), \
+                 (marker: (1), components: (None, Some((0))))])",
+            )
+            .unwrap();
+        assert_eq!(prefab.len(), 2);
+
+        let entities = world.instantiate::<Infallible, Storages>(&prefab).unwrap();
+        world.maintain();
+        assert_eq!(world.read_storage::<Pos>().get(entities[0]), Some(&Pos(3)));
+        assert_eq!(
+            world.read_storage::<Target>().get(entities[1]).unwrap().0,
+            entities[0]
+        );
+    }
+    #[test]
+    fn templates_extend_their_bases() {
+        let mut templates = TemplateLibrary::new();
+        templates.insert("base", Template::new((Some(1), Some(2), None)));
+        templates.insert("middle", Template::extend("base", (None, Some(3), Some(4))));
+        templates.insert("leaf", Template::extend("middle", (Some(5), None, None)).without(2));
+
+        assert_eq!(templates.resolve("middle").unwrap(), (Some(1), Some(3), Some(4)));
+        assert_eq!(templates.resolve("leaf").unwrap(), (Some(5), Some(3), None));
+    }
+
+    #[test]
+    fn overrides_win_over_removals() {
+        let mut templates = TemplateLibrary::new();
+        templates.insert("base", Template::new((Some(1),)));
+        templates.insert("leaf", Template::extend("base", (Some(2),)).without(0));
+
+        assert_eq!(templates.resolve("leaf").unwrap(), (Some(2),));
+    }
+
+    #[test]
+    fn map_templates_from_ron() {
+        let mut templates = TemplateLibrary::<BTreeMap<String, i32>>::new();
+        templates.insert(
+            "base",
+            ron::de::from_str(r#"(components: {"hp": 5, "speed": 2})"#).unwrap(),
+        );
+        templates.insert(
+            "leaf",
+            ron::de::from_str(r#"(base: Some("base"), components: {"hp": 8}, remove: ["speed"])"#)
+                .unwrap(),
+        );
+
+        let resolved = templates.resolve("leaf").unwrap();
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved["hp"], 8);
+    }
+
+    #[test]
+    fn templates_are_resolved_on_instantiation() {
+        let mut world = setup();
+        let mut templates = TemplateLibrary::new();
+        templates.insert("base", Template::new((Some(Pos(1)), None)));
+        templates.insert("leaf", Template::extend("base", (None, None)));
+        templates.insert("base", Template::new((Some(Pos(2)), None)));
+
+        let entity = templates
+            .instantiate::<Infallible, Storages>(&world, "leaf")
+            .unwrap();
+        world.maintain();
+        assert_eq!(world.read_storage::<Pos>().get(entity), Some(&Pos(2)));
+    }
+
+    #[test]
+    fn template_cycles_fail() {
+        let mut templates = TemplateLibrary::new();
+        templates.insert("a", Template::extend("b", (Some(1),)));
+        templates.insert("b", Template::extend("a", (None,)));
+        let e = templates.resolve("a").unwrap_err();
+        assert!(e.to_string().contains("template `a` extends itself"));
+    }
+
+    #[test]
+    fn unknown_template_bases_fail() {
+        let world = setup();
+        let mut templates = TemplateLibrary::new();
+        templates.insert("leaf", Template::extend("missing", (Some(Pos(1)), None)));
+
+        let e = templates
+            .instantiate::<Infallible, Storages>(&world, "leaf")
+            .unwrap_err();
+        assert!(e.to_string().contains("no template is called `missing`"));
+    }
+}
+
+#[cfg(all(feature = "ron", feature = "serde_json"))]
+mod scene_test {
+    use super::{common::*, *};
+
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+    struct Pos {
+        x: i32,
+        y: i32,
+    }
+
+    impl Component for Pos {
+        type Storage = VecStorage<Self>;
+    }
+
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+    struct Name(String);
+
+    impl Component for Name {
+        type Storage = VecStorage<Self>;
+    }
+
+    fn setup() -> (World, SaveloadRegistry<SaveMarker>) {
+        let mut world = common::setup();
+        world.register::<Pos>();
+        world.register::<Name>();
+
+        let mut registry = SaveloadRegistry::new();
+        registry.register::<Pos>("pos");
+        registry.register::<Name>("name");
+
+        (world, registry)
+    }
+
+    #[test]
+    fn hand_written_scene_loads() {
+        let (mut world, registry) = setup();
+        let scene = r#"(
+            entities: [
+                (
+                    marker: (0),
+                    components: {
+                        "pos": Pos(x: 1, y: 2),
+                        "name": "player",
+                    },
+                ),
+                (marker: (1), components: {"pos": (x: 3, y: 4)}),
+            ],
+        )"#;
+        read_scene(&world, &registry, scene.as_bytes()).unwrap();
+        world.maintain();
+
+        let pos = world.read_storage::<Pos>();
+        let names = world.read_storage::<Name>();
+        let mut loaded: Vec<_> = (&pos, names.maybe()).join().collect();
+        loaded.sort_by_key(|(pos, _)| pos.x);
+        assert_eq!(loaded, vec![
+            (&Pos { x: 1, y: 2 }, Some(&Name("player".to_owned()))),
+            (&Pos { x: 3, y: 4 }, None),
+        ]);
+    }
+
+    #[test]
+    fn written_scene_round_trips() {
+        let (mut world, registry) = setup();
+        world
+            .create_entity()
+            .with(Pos { x: 5, y: 6 })
+            .with(Name("door".to_owned()))
+            .marked::<SaveMarker>()
+            .build();
+        let mut written = Vec::new();
+        write_scene(&world, &registry, &mut written).unwrap();
+
+        let (mut copy, registry) = setup();
+        read_scene(&copy, &registry, &written[..]).unwrap();
+        copy.maintain();
+        assert_eq!(
+            (&copy.read_storage::<Pos>(), &copy.read_storage::<Name>())
+                .join()
+                .map(|(pos, name)| (pos.clone(), name.clone()))
+                .collect::<Vec<_>>(),
+            vec![(Pos { x: 5, y: 6 }, Name("door".to_owned()))]
+        );
+    }
+
+    #[test]
+    fn unknown_component_is_rejected() {
+        let (world, registry) = setup();
+        let scene = r#"(entities: [(marker: (0), components: {"postion": (x: 1, y: 2)})])"#;
+        match read_scene(&world, &registry, scene.as_bytes()) {
+            Err(SceneError::UnknownComponent(UnknownComponent(name))) => {
+                assert_eq!(name, "postion")
+            }
+            _ => panic!("expected an unknown component"),
+        }
+        assert!(world.read_storage::<SaveMarker>().is_empty());
+    }
+
+    #[test]
+    fn editors_round_trip_unknown_components() {
+        let mut editor = World::new();
+        editor.register::<Pos>();
+        editor.register::<SaveMarker>();
+        editor.register::<UnknownComponents>();
+        editor.insert(SimpleMarkerAllocator::<Save>::new());
+        let mut editor_registry = SaveloadRegistry::<SaveMarker>::new();
+        editor_registry.register::<Pos>("pos");
+        editor_registry.preserve_unknown(true);
+
+        let scene = r#"(
+            entities: [(marker: (0), components: {"pos": (x: 1, y: 2), "name": "door"})],
+        )"#;
+        read_scene(&editor, &editor_registry, scene.as_bytes()).unwrap();
+        editor.maintain();
+        for pos in (&mut editor.write_storage::<Pos>()).join() {
+            pos.x = 7;
+        }
+        let mut written = Vec::new();
+        write_scene(&editor, &editor_registry, &mut written).unwrap();
+
+        let (mut world, registry) = setup();
+        read_scene(&world, &registry, &written[..]).unwrap();
+        world.maintain();
+        assert_eq!(
+            (&world.read_storage::<Pos>(), &world.read_storage::<Name>())
+                .join()
+                .map(|(pos, name)| (pos.clone(), name.clone()))
+                .collect::<Vec<_>>(),
+            vec![(Pos { x: 7, y: 2 }, Name("door".to_owned()))]
+        );
+    }
+
+    fn named(world: &World, name: &str) -> Entity {
+        (&world.entities(), &world.read_storage::<Name>())
+            .join()
+            .find(|(_, n)| n.0 == name)
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn hierarchies_are_nested() {
+        use crate::hierarchy::Parent;
+
+        let (mut world, registry) = setup();
+        world.register::<Parent>();
+        let mut build = |name: &str, parent: Option<Entity>| {
+            let mut builder = world.create_entity().with(Name(name.to_owned()));
+            if let Some(parent) = parent {
+                builder = builder.with(Parent(parent));
+            }
+            builder.marked::<SaveMarker>().build()
+        };
+        let wheel = build("wheel", None);
+        let car = build("car", None);
+        let bolt = build("bolt", Some(wheel));
+        let a = build("a", Some(bolt));
+        build("b", Some(a));
+        world.write_storage::<Parent>().insert(wheel, Parent(car)).unwrap();
+        world.write_storage::<Parent>().insert(bolt, Parent(a)).unwrap();
+        let unmarked = world.create_entity().build();
+        world
+            .create_entity()
+            .with(Name("orphan".to_owned()))
+            .with(Parent(unmarked))
+            .marked::<SaveMarker>()
+            .build();
+
+        let mut written = Vec::new();
+        write_scene(&world, &registry, &mut written).unwrap();
+        let written = String::from_utf8(written).unwrap();
+        let scene: Scene<SaveMarker> = ron::de::from_str(&written).unwrap();
+        let tops: Vec<_> = scene.entities.iter().map(|e| e.children.len()).collect();
+        assert_eq!(tops.len(), 3);
+        assert!(!written.contains("\"parent\""));
+
+        let (mut copy, registry) = setup();
+        copy.register::<Parent>();
+        read_scene(&copy, &registry, written.as_bytes()).unwrap();
+        copy.maintain();
+        let parent = |name| copy.read_storage::<Parent>().get(named(&copy, name)).cloned();
+        assert_eq!(parent("car"), None);
+        assert_eq!(parent("wheel"), Some(Parent(named(&copy, "car"))));
+        assert_eq!(parent("b"), Some(Parent(named(&copy, "a"))));
+        assert_eq!(parent("orphan"), None);
+        // The cycle is broken where it was written at the top.
+        let (a, bolt) = (named(&copy, "a"), named(&copy, "bolt"));
+        let cycle = (parent("a"), parent("bolt"));
+        assert!(cycle == (Some(Parent(bolt)), None) || cycle == (None, Some(Parent(a))));
+    }
+
+    #[test]
+    fn nested_scene_needs_parent() {
+        let (world, registry) = setup();
+        let scene = r#"(
+            entities: [
+                (
+                    marker: (0),
+                    components: {"name": "car"},
+                    children: [(marker: (1), components: {"name": "wheel"})],
+                ),
+            ],
+        )"#;
+        match read_scene(&world, &registry, scene.as_bytes()) {
+            Err(SceneError::ParentNotRegistered) => {}
+            _ => panic!("expected `Parent` to be missing"),
+        }
+        let top = r#"(entities: [(marker: (0), components: {"name": "car"})])"#;
+        let parent = world.entities().create();
+        match read_scene_into(&world, &registry, top.as_bytes(), parent) {
+            Err(SceneError::ParentNotRegistered) => {}
+            _ => panic!("expected `Parent` to be missing"),
+        }
+        assert!(world.read_storage::<SaveMarker>().is_empty());
+    }
+
+    #[test]
+    fn subtrees_load_under_other_parents() {
+        use crate::hierarchy::Parent;
+
+        let (mut world, registry) = setup();
+        world.register::<Parent>();
+        let car = world
+            .create_entity()
+            .with(Name("car".to_owned()))
+            .marked::<SaveMarker>()
+            .build();
+        let wheel = world
+            .create_entity()
+            .with(Name("wheel".to_owned()))
+            .with(Parent(car))
+            .marked::<SaveMarker>()
+            .build();
+        world
+            .create_entity()
+            .with(Name("road".to_owned()))
+            .marked::<SaveMarker>()
+            .build();
+        let unmarked = world.create_entity().build();
+        match write_subtree(&world, &registry, unmarked, Vec::new()) {
+            Err(SceneError::Unmarked(e)) => assert_eq!(e, unmarked),
+            _ => panic!("expected an unmarked root"),
+        }
+
+        let mut written = Vec::new();
+        write_subtree(&world, &registry, wheel, &mut written).unwrap();
+        read_scene_into(&world, &registry, &written[..], unmarked).unwrap();
+        assert_eq!(world.read_storage::<Parent>().get(wheel), Some(&Parent(unmarked)));
+
+        let mut written = Vec::new();
+        write_subtree(&world, &registry, car, &mut written).unwrap();
+        let written = String::from_utf8(written).unwrap();
+        assert!(!written.contains("road"));
+        assert!(!written.contains("wheel"));
+
+        let (mut copy, registry) = setup();
+        copy.register::<Parent>();
+        let garage = copy.create_entity().build();
+        read_scene_into(&copy, &registry, written.as_bytes(), garage).unwrap();
+        copy.maintain();
+        assert_eq!(copy.read_storage::<Name>().count(), 1);
+        assert_eq!(
+            copy.read_storage::<Parent>().get(named(&copy, "car")),
+            Some(&Parent(garage))
+        );
+    }
+}
+
+#[cfg(feature = "serde_json")]
+mod world_diff_test {
+    use super::{common::*, *};
+
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+    struct Tag;
+
+    impl Component for Tag {
+        type Storage = VecStorage<Self>;
+    }
+
+    struct Net;
+
+    type NetMarker = SimpleMarker<Net>;
+
+    fn world(components: &[(Option<i32>, bool)]) -> World {
+        let mut world = World::new();
+        world.register::<Pos>();
+        world.register::<Tag>();
+        world.register::<NetMarker>();
+        world.insert(SimpleMarkerAllocator::<Net>::new());
+        for &(pos, tag) in components {
+            let mut builder = world.create_entity();
+            if let Some(pos) = pos {
+                builder = builder.with(Pos(pos));
+            }
+            if tag {
+                builder = builder.with(Tag);
+            }
+            builder.marked::<NetMarker>().build();
+        }
+
+        world
+    }
+
+    fn registry() -> SaveloadRegistry<NetMarker> {
+        let mut registry = SaveloadRegistry::new();
+        registry.register::<Pos>("pos");
+        registry.register::<Tag>("tag");
+
+        registry
+    }
+
+    #[test]
+    fn equal_worlds_have_no_diff() {
+        let a = world(&[(Some(1), true), (None, false)]);
+        let b = world(&[(Some(1), true), (None, false)]);
+        assert!(diff(&a, &b, &registry()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn changes_are_listed_per_component() {
+        let a = world(&[(Some(1), true), (Some(2), false)]);
+        let mut b = world(&[(Some(1), false), (Some(5), true)]);
+        let first = b.entities().entity(0);
+        b.delete_entity(first).unwrap();
+        b.maintain();
+        b.create_entity().with(Pos(4)).marked::<NetMarker>().build();
+
+        let changes = diff(&a, &b, &registry()).unwrap();
+        assert_eq!(changes.removed.iter().map(|m| m.id()).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(changes.added.iter().map(|m| m.id()).collect::<Vec<_>>(), vec![2]);
+        let mut changed: Vec<_> = changes
+            .changed
+            .iter()
+            .map(|change| {
+                (
+                    change.marker.id(),
+                    change.component.as_str(),
+                    change.before.clone(),
+                    change.after.clone(),
+                )
+            })
+            .collect();
+        changed.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+        assert_eq!(changed, vec![
+            (1, "pos", Some(2.into()), Some(5.into())),
+            (1, "tag", None, Some(serde_json::Value::Null)),
+        ]);
+    }
+}
+
+mod merge_test {
+    use super::{common::*, *};
+
+    struct Net;
+
+    type NetMarker = SimpleMarker<Net>;
+
+    type Storages<'a> = (WriteStorage<'a, Pos>, WriteStorage<'a, Target>);
+
+    type Save = Vec<EntityData<NetMarker, (Option<Pos>, Option<NetMarker>)>>;
+
+    fn setup() -> World {
+        let mut world = World::new();
+        world.register::<Pos>();
+        world.register::<Target>();
+        world.register::<NetMarker>();
+        world.insert(SimpleMarkerAllocator::<Net>::new());
+        world.create_entity().with(Pos(1)).marked::<NetMarker>().build();
+        world.create_entity().with(Pos(2)).marked::<NetMarker>().build();
+
+        world
+    }
+
+    /// Two entities targeting each other, the first one with a marker the
+    /// world has already.
+    fn save() -> Save {
+        vec![
+            EntityData {
+                marker: NetMarker::new(1),
+                components: (Some(Pos(10)), Some(NetMarker::new(2))),
+            },
+            EntityData {
+                marker: NetMarker::new(2),
+                components: (Some(Pos(20)), Some(NetMarker::new(1))),
+            },
+        ]
+    }
+
+    fn merge(world: &mut World, policy: ConflictPolicy) -> MergeReport<NetMarker> {
+        let report = world
+            .merge_from::<Infallible, NetMarker, Storages>(save(), policy)
+            .unwrap();
+        world.maintain();
+
+        report
+    }
+
+    /// The `Pos` of the entity with marker `id` and of the one it targets.
+    fn lookup(world: &World, id: u64) -> (i32, Option<i32>) {
+        let entity = world
+            .read_resource::<SimpleMarkerAllocator<Net>>()
+            .retrieve_entity_internal(id)
+            .unwrap();
+        let positions = world.read_storage::<Pos>();
+        let target = world
+            .read_storage::<Target>()
+            .get(entity)
+            .map(|target| positions.get(target.0).unwrap().0);
+
+        (positions.get(entity).unwrap().0, target)
+    }
+
+    #[test]
+    fn rename_keeps_references_within_the_save() {
+        let mut world = setup();
+        let report = merge(&mut world, ConflictPolicy::Rename);
+
+        assert_eq!(report.entities, 2);
+        assert_eq!(report.conflicts, vec![NetMarker::new(1)]);
+        assert_eq!(report.renamed, vec![(NetMarker::new(1), NetMarker::new(3))]);
+        assert_eq!(lookup(&world, 0), (1, None));
+        assert_eq!(lookup(&world, 1), (2, None));
+        assert_eq!(lookup(&world, 2), (20, Some(10)));
+        assert_eq!(lookup(&world, 3), (10, Some(20)));
+    }
+
+    #[test]
+    fn prefer_local_skips_conflicts() {
+        let mut world = setup();
+        let report = merge(&mut world, ConflictPolicy::PreferLocal);
+
+        assert_eq!(report.entities, 1);
+        assert!(report.renamed.is_empty());
+        assert_eq!(lookup(&world, 1), (2, None));
+        assert_eq!(lookup(&world, 2), (20, Some(2)));
+    }
+
+    #[test]
+    fn prefer_incoming_overwrites_conflicts() {
+        let mut world = setup();
+        let report = merge(&mut world, ConflictPolicy::PreferIncoming);
+
+        assert_eq!(report.entities, 2);
+        assert_eq!(report.conflicts, vec![NetMarker::new(1)]);
+        assert_eq!(lookup(&world, 1), (10, Some(20)));
+        assert_eq!(lookup(&world, 2), (20, Some(10)));
+        assert_eq!(world.read_storage::<NetMarker>().join().count(), 3);
+    }
+}
+
+mod arity_test {
+    use super::{common::*, *};
+
+    macro_rules! components {
+        ($($comp:ident),*) => {
+            $(
+                #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+                struct $comp(i32);
+
+                impl Component for $comp {
+                    type Storage = VecStorage<Self>;
+                }
+            )*
+
+            type Reads<'a> = ($(ReadStorage<'a, $comp>,)*);
+
+            type Writes<'a> = ($(WriteStorage<'a, $comp>,)*);
+
+            fn setup() -> World {
+                let mut world = common::setup();
+                $(world.register::<$comp>();)*
+
+                world
+            }
+
+            fn build(world: &mut World, value: i32) {
+                world
+                    .create_entity()
+                    $(.with($comp(value)))*
+                    .marked::<SaveMarker>()
+                    .build();
+            }
+
+            fn values(world: &World) -> Vec<Vec<i32>> {
+                let mut values = Vec::new();
+                $(values.push(world.read_storage::<$comp>().join().map(|c| c.0).collect());)*
+
+                values
+            }
+        };
+    }
+
+    components!(C0, C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11, C12, C13, C14, C15);
+
+    #[test]
+    fn sixteen_storages_round_trip() {
+        let mut world = setup();
+        build(&mut world, 1);
+        build(&mut world, 2);
+        let serial = save::<Reads>(&world);
+
+        let loaded = setup();
+        load::<Writes>(&loaded, &serial, &DeserializeOptions::new()).unwrap();
+        assert_eq!(values(&loaded), vec![vec![1, 2]; 16]);
+    }
+}
@@ -5,9 +5,9 @@ use uuid::Uuid;
 
 use crate::{
     join::Join,
-    saveload::{Marker, MarkerAllocator},
+    saveload::{Marker, MarkerAllocator, PersistentAllocator},
     storage::{ReadStorage, VecStorage},
-    world::{Component, EntitiesRes, Entity},
+    world::{Component, EntitiesRes, Entity, Reset},
 };
 
 /// Basic marker uuid implementation usable for saving and loading.
@@ -68,6 +68,12 @@ impl UuidMarkerAllocator {
     }
 }
 
+impl Reset for UuidMarkerAllocator {
+    fn reset(&mut self) {
+        self.mapping.clear();
+    }
+}
+
 impl MarkerAllocator<UuidMarker> for UuidMarkerAllocator {
     fn allocate(&mut self, entity: Entity, id: Option<Uuid>) -> UuidMarker {
         let marker = if let Some(id) = id {
@@ -84,6 +90,10 @@ impl MarkerAllocator<UuidMarker> for UuidMarkerAllocator {
         self.mapping.get(&id).cloned()
     }
 
+    fn retire(&mut self, marker: &UuidMarker) {
+        self.mapping.remove(&marker.uuid());
+    }
+
     fn maintain(&mut self, entities: &EntitiesRes, storage: &ReadStorage<UuidMarker>) {
         // FIXME: may be too slow
         self.mapping = (entities, storage)
@@ -92,3 +102,12 @@ impl MarkerAllocator<UuidMarker> for UuidMarkerAllocator {
             .collect();
     }
 }
+
+impl PersistentAllocator<UuidMarker> for UuidMarkerAllocator {
+    /// Random uuids don't collide, so there's nothing to save.
+    type State = ();
+
+    fn state(&self) {}
+
+    fn restore(&mut self, _state: ()) {}
+}
@@ -0,0 +1,166 @@
+//! Comparing the marked entities of two worlds.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::{
+    join::Join,
+    saveload::{
+        error::Error,
+        registry::{ComponentMap, SaveloadRegistry},
+        Marker, SerializeComponents,
+    },
+    world::{World, WorldExt},
+};
+
+/// The differences between the marked entities of two worlds, returned by
+/// `diff`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorldDiff<M> {
+    /// The markers of the entities only the second world has.
+    pub added: Vec<M>,
+    /// The markers of the entities only the first world has.
+    pub removed: Vec<M>,
+    /// The components which differ between entities both worlds have.
+    pub changed: Vec<ComponentChange<M>>,
+}
+
+impl<M> WorldDiff<M> {
+    /// Returns `true` if the worlds have the same marked entities with the
+    /// same components.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// A component which differs between the entities with the same marker in two
+/// worlds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComponentChange<M> {
+    /// The marker of the entity.
+    pub marker: M,
+    /// The registered name of the component.
+    pub component: String,
+    /// The component in the first world, if the entity has it there.
+    pub before: Option<Value>,
+    /// The component in the second world, if the entity has it there.
+    pub after: Option<Value>,
+}
+
+/// Compares the entities marked with `M` in `a` and `b`, matching them by
+/// marker.
+///
+/// All components of `registry` are compared by their serialized form, so
+/// entity references are equal if they point to entities with the same
+/// marker. Entities are listed in the order they are joined in `a`, followed
+/// by the ones only `b` has.
+///
+/// ## Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use specs::{
+///     prelude::*,
+///     saveload::{diff, MarkedBuilder, SaveloadRegistry, SimpleMarker, SimpleMarkerAllocator},
+/// };
+///
+/// #[derive(Clone, Deserialize, Serialize)]
+/// struct Health(u32);
+///
+/// impl Component for Health {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// struct Net;
+/// type NetMarker = SimpleMarker<Net>;
+///
+/// fn world(health: u32) -> World {
+///     let mut world = World::new();
+///     world.register::<Health>();
+///     world.register::<NetMarker>();
+///     world.insert(SimpleMarkerAllocator::<Net>::new());
+///     world.create_entity().with(Health(health)).marked::<NetMarker>().build();
+///     world
+/// }
+///
+/// let mut registry = SaveloadRegistry::<NetMarker>::new();
+/// registry.register::<Health>("health");
+///
+/// let changes = diff(&world(10), &world(7), &registry).unwrap();
+/// assert_eq!(changes.changed.len(), 1);
+/// assert_eq!(changes.changed[0].component, "health");
+/// assert!(diff(&world(10), &world(10), &registry).unwrap().is_empty());
+/// ```
+pub fn diff<M: Marker>(
+    a: &World,
+    b: &World,
+    registry: &SaveloadRegistry<M>,
+) -> Result<WorldDiff<M>, Error<String>> {
+    let entities_a = collect(a, registry)?;
+    let entities_b = collect(b, registry)?;
+    let mut order_b: Vec<M> = entities_b.iter().map(|(marker, _)| marker.clone()).collect();
+    let mut components_b: HashMap<M, ComponentMap> = entities_b.into_iter().collect();
+
+    let mut diff = WorldDiff {
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+    };
+    for (marker, before) in entities_a {
+        let mut after = match components_b.remove(&marker) {
+            Some(after) => after,
+            None => {
+                diff.removed.push(marker);
+                continue;
+            }
+        };
+
+        for (component, value) in before {
+            match after.remove(&component) {
+                Some(ref other) if *other == value => {}
+                other => diff.changed.push(ComponentChange {
+                    marker: marker.clone(),
+                    component,
+                    before: Some(value),
+                    after: other,
+                }),
+            }
+        }
+        for (component, value) in after {
+            diff.changed.push(ComponentChange {
+                marker: marker.clone(),
+                component,
+                before: None,
+                after: Some(value),
+            });
+        }
+    }
+    order_b.retain(|marker| components_b.contains_key(marker));
+    diff.added = order_b;
+
+    Ok(diff)
+}
+
+/// Serializes the registered components of all marked entities of `world`.
+fn collect<M: Marker>(
+    world: &World,
+    registry: &SaveloadRegistry<M>,
+) -> Result<Vec<(M, ComponentMap)>, Error<String>> {
+    let names: Vec<&str> = registry.names().collect();
+    let storages = registry
+        .serializer(world, &names)
+        .map_err(|e| Error::new(e.to_string()))?;
+    let entities = world.entities();
+    let markers = world.read_storage::<M>();
+
+    (&entities, &markers)
+        .join()
+        .map(|(entity, marker)| {
+            storages
+                .serialize_entity(entity, |entity| markers.get(entity).cloned())
+                .map(|components| (marker.clone(), components))
+                .map_err(|e| Error::new(e).with_marker(marker))
+        })
+        .collect()
+}
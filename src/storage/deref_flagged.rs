@@ -92,6 +92,32 @@ impl<C: Component, T: UnprotectedStorage<C>> UnprotectedStorage<C> for DerefFlag
         }
         self.storage.remove(id)
     }
+
+    unsafe fn shrink_to_fit<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        self.storage.shrink_to_fit(has);
+    }
+
+    unsafe fn maintain<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        self.storage.maintain(has);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.storage.reserve(additional);
+    }
+
+    fn capacity(&self, len: usize) -> usize {
+        self.storage.capacity(len)
+    }
+
+    fn allocated_bytes(&self, len: usize) -> usize {
+        self.storage.allocated_bytes(len)
+    }
 }
 
 impl<C, T> Tracked for DerefFlaggedStorage<C, T> {
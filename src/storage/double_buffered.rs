@@ -0,0 +1,157 @@
+use std::marker::PhantomData;
+
+use hibitset::BitSetLike;
+
+use crate::{
+    storage::{DenseVecStorage, DistinctStorage, TryDefault, UnprotectedStorage},
+    world::{Component, Index},
+};
+
+/// Wrapper storage which keeps the values of the previous frame around.
+///
+/// Immutable accesses, like `get` or joining over `&storage`, read the values
+/// the components had at the last `World::maintain`. Mutable accesses, like
+/// `get_mut` or joining over `&mut storage`, go to a second buffer which is
+/// copied over the first one at the next `maintain`. That way, all systems of
+/// a frame see the same values no matter in which order they run, e.g. in a
+/// simulation where every entity reacts to the others' positions.
+///
+/// Inserting a component writes it to both buffers, so it can be read right
+/// away. Every `maintain` clones all components of the storage.
+///
+/// **Note:** A component read with `get` after it was changed through
+/// `get_mut` still has its old value until the next `maintain`.
+///
+/// # Examples
+///
+/// ```
+/// use specs::{prelude::*, storage::DoubleBufferedStorage};
+///
+/// #[derive(Clone)]
+/// struct Heat(f32);
+///
+/// impl Component for Heat {
+///     type Storage = DoubleBufferedStorage<Self, VecStorage<Self>>;
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Heat>();
+/// let hot = world.create_entity().with(Heat(10.0)).build();
+/// let cold = world.create_entity().with(Heat(0.0)).build();
+///
+/// {
+///     // Both entities move towards the average heat of last frame.
+///     let mut heat = world.write_storage::<Heat>();
+///     let average = (&heat).join().map(|heat| heat.0).sum::<f32>() / 2.0;
+///     for heat in (&mut heat).join() {
+///         heat.0 = (heat.0 + average) / 2.0;
+///     }
+///     // The old values are still visible until the next `maintain`.
+///     assert_eq!(heat.get(hot).unwrap().0, 10.0);
+/// }
+///
+/// world.maintain();
+/// assert_eq!(world.read_storage::<Heat>().get(hot).unwrap().0, 7.5);
+/// assert_eq!(world.read_storage::<Heat>().get(cold).unwrap().0, 2.5);
+/// ```
+pub struct DoubleBufferedStorage<C, T = DenseVecStorage<C>> {
+    // The values of the previous frame, which are read.
+    front: T,
+    // The values of the next frame, which are written.
+    back: T,
+    phantom: PhantomData<C>,
+}
+
+impl<C, T> Default for DoubleBufferedStorage<C, T>
+where
+    T: TryDefault,
+{
+    fn default() -> Self {
+        DoubleBufferedStorage {
+            front: T::unwrap_default(),
+            back: T::unwrap_default(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<C, T> UnprotectedStorage<C> for DoubleBufferedStorage<C, T>
+where
+    C: Component + Clone,
+    T: UnprotectedStorage<C>,
+{
+    #[cfg(feature = "nightly")]
+    type AccessMut<'a> where T: 'a = <T as UnprotectedStorage<C>>::AccessMut<'a>;
+
+    unsafe fn clean<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        self.front.clean(&has);
+        self.back.clean(has);
+    }
+
+    unsafe fn get(&self, id: Index) -> &C {
+        self.front.get(id)
+    }
+
+    #[cfg(feature = "nightly")]
+    unsafe fn get_mut(&mut self, id: Index) -> <T as UnprotectedStorage<C>>::AccessMut<'_> {
+        self.back.get_mut(id)
+    }
+
+    #[cfg(not(feature = "nightly"))]
+    unsafe fn get_mut(&mut self, id: Index) -> &mut C {
+        self.back.get_mut(id)
+    }
+
+    unsafe fn insert(&mut self, id: Index, comp: C) {
+        self.front.insert(id, comp.clone());
+        self.back.insert(id, comp);
+    }
+
+    unsafe fn remove(&mut self, id: Index) -> C {
+        self.front.drop(id);
+        self.back.remove(id)
+    }
+
+    unsafe fn drop(&mut self, id: Index) {
+        self.front.drop(id);
+        self.back.drop(id);
+    }
+
+    unsafe fn shrink_to_fit<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        self.front.shrink_to_fit(&has);
+        self.back.shrink_to_fit(has);
+    }
+
+    unsafe fn maintain<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        for id in (&has).iter() {
+            C::clone_from(&mut *self.front.get_mut(id), self.back.get(id));
+        }
+        self.front.maintain(&has);
+        self.back.maintain(has);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.front.reserve(additional);
+        self.back.reserve(additional);
+    }
+
+    fn capacity(&self, len: usize) -> usize {
+        self.back.capacity(len)
+    }
+
+    fn allocated_bytes(&self, len: usize) -> usize {
+        self.front.allocated_bytes(len) + self.back.allocated_bytes(len)
+    }
+}
+
+// SAFETY: `get_mut` only accesses the back buffer, at the same index.
+unsafe impl<C, T> DistinctStorage for DoubleBufferedStorage<C, T> where T: DistinctStorage {}
@@ -73,4 +73,46 @@ mod tests {
 
         assert_eq!((&comps).join().count(), 0);
     }
+
+    #[test]
+    fn drain_joined() {
+        use crate::{
+            join::Join,
+            storage::{DenseVecStorage, VecStorage},
+            world::{Builder, Component, World, WorldExt},
+        };
+
+        #[derive(Debug, PartialEq)]
+        struct Comp(u32);
+
+        impl Component for Comp {
+            type Storage = DenseVecStorage<Self>;
+        }
+
+        struct Filter;
+
+        impl Component for Filter {
+            type Storage = VecStorage<Self>;
+        }
+
+        let mut world = World::new();
+        world.register::<Comp>();
+        world.register::<Filter>();
+
+        let a = world.create_entity().with(Comp(1)).with(Filter).build();
+        let b = world.create_entity().with(Comp(2)).build();
+        let c = world.create_entity().with(Comp(3)).with(Filter).build();
+
+        let mut comps = world.write_storage::<Comp>();
+        let filter = world.read_storage::<Filter>();
+        let entities = world.entities();
+
+        let drained: Vec<_> = (&entities, comps.drain(), &filter)
+            .join()
+            .map(|(entity, comp, _)| (entity, comp))
+            .collect();
+
+        assert_eq!(drained, vec![(a, Comp(1)), (c, Comp(3))]);
+        assert_eq!((&entities, &comps).join().collect::<Vec<_>>(), vec![(b, &Comp(2))]);
+    }
 }
@@ -206,9 +206,8 @@ where
     }
 
     /// Inserts a value into the storage and returns the old one.
-    pub fn insert(&mut self, mut component: T) -> T {
-        std::mem::swap(&mut component, self.get_mut().deref_mut());
-        component
+    pub fn insert(&mut self, component: T) -> T {
+        self.storage.data.replace(self.id, component)
     }
 
     /// Removes the component from the storage and returns it.
@@ -231,12 +230,9 @@ where
 {
     /// Inserts a value into the storage.
     pub fn insert(self, component: T) -> AccessMutReturn<'a, T> {
-        self.storage.data.mask.add(self.id);
+        self.storage.data.insert(self.id, component);
         // SAFETY: This is safe since we added `self.id` to the mask.
-        unsafe {
-            self.storage.data.inner.insert(self.id, component);
-            self.storage.data.inner.get_mut(self.id)
-        }
+        unsafe { self.storage.data.inner.get_mut(self.id) }
     }
 }
 
@@ -284,4 +280,51 @@ where
             StorageEntry::Vacant(vacant) => vacant.insert(default()),
         }
     }
+    /// Inserts the default value of the component if the entity does not have
+    /// it already.
+    pub fn or_default(self) -> AccessMutReturn<'a, T>
+    where
+        T: Default,
+    {
+        self.or_insert_with(T::default)
+    }
+
+    /// Modifies the component if the entity has it, before any of the
+    /// `or_insert` methods.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use specs::prelude::*;
+    /// struct Hits(u32);
+    ///
+    /// impl Component for Hits {
+    ///     type Storage = DenseVecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Hits>();
+    /// let entity = world.create_entity().build();
+    /// let mut hits = world.write_storage::<Hits>();
+    ///
+    /// for _ in 0..3 {
+    ///     hits.entry(entity)
+    ///         .unwrap()
+    ///         .and_modify(|hits| hits.0 += 1)
+    ///         .or_insert(Hits(1));
+    /// }
+    /// assert_eq!(hits.get(entity).unwrap().0, 3);
+    /// ```
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut T),
+    {
+        match self {
+            StorageEntry::Occupied(mut occupied) => {
+                f(occupied.get_mut().deref_mut());
+                StorageEntry::Occupied(occupied)
+            }
+            vacant => vacant,
+        }
+    }
 }
@@ -12,6 +12,10 @@ use shrev::EventChannel;
 /// Wrapper storage that tracks modifications, insertions, and removals of
 /// components through an `EventChannel`.
 ///
+/// `Storage::read_changes` collects the events into the `BitSet`s of a
+/// `ComponentChanges`, which can be joined over to process only the changed
+/// components.
+///
 /// **Note:** Joining over all components of a `FlaggedStorage`
 /// mutably will flag all components.
 ///
@@ -243,6 +247,32 @@ impl<C: Component, T: UnprotectedStorage<C>> UnprotectedStorage<C> for FlaggedSt
         }
         self.storage.remove(id)
     }
+
+    unsafe fn shrink_to_fit<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        self.storage.shrink_to_fit(has);
+    }
+
+    unsafe fn maintain<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        self.storage.maintain(has);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.storage.reserve(additional);
+    }
+
+    fn capacity(&self, len: usize) -> usize {
+        self.storage.capacity(len)
+    }
+
+    fn allocated_bytes(&self, len: usize) -> usize {
+        self.storage.allocated_bytes(len)
+    }
 }
 
 impl<C, T> Tracked for FlaggedStorage<C, T> {
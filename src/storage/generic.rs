@@ -90,6 +90,9 @@ pub trait GenericWriteStorage {
     #[cfg(feature = "nightly")]
     type AccessMut<'a>: DerefMut<Target=Self::Component> where Self: 'a;
 
+    /// Returns `true` if the storage has a component for this entity.
+    fn contains(&self, entity: Entity) -> bool;
+
     /// Get mutable access to an `Entity`s component
     fn get_mut(&mut self, entity: Entity) -> Option<AccessMutReturn<'_, Self::Component>>;
 
@@ -101,6 +104,18 @@ pub trait GenericWriteStorage {
     where
         Self::Component: Default;
 
+    /// Get mutable access to an `Entity`s component. If the component does not
+    /// exist, it is created by calling `f`.
+    ///
+    /// Returns None if the entity is dead.
+    fn get_mut_or_insert_with<F>(
+        &mut self,
+        entity: Entity,
+        f: F,
+    ) -> Option<AccessMutReturn<'_, Self::Component>>
+    where
+        F: FnOnce() -> Self::Component;
+
     /// Insert a component for an `Entity`
     fn insert(&mut self, entity: Entity, comp: Self::Component) -> InsertResult<Self::Component>;
 
@@ -119,6 +134,10 @@ where
     #[cfg(feature = "nightly")]
     type AccessMut<'b> where Self: 'b = <<T as Component>::Storage as UnprotectedStorage<T>>::AccessMut<'b>;
 
+    fn contains(&self, entity: Entity) -> bool {
+        WriteStorage::contains(self, entity)
+    }
+
     fn get_mut(&mut self, entity: Entity) -> Option<AccessMutReturn<'_, T>> {
         WriteStorage::get_mut(self, entity)
     }
@@ -127,13 +146,16 @@ where
     where
         Self::Component: Default,
     {
-        if !self.contains(entity) {
-            self.insert(entity, Default::default())
-                .ok()
-                .and_then(move |_| self.get_mut(entity))
-        } else {
-            self.get_mut(entity)
-        }
+        self.get_mut_or_insert_with(entity, Default::default)
+    }
+
+    fn get_mut_or_insert_with<F>(&mut self, entity: Entity, f: F) -> Option<AccessMutReturn<'_, T>>
+    where
+        F: FnOnce() -> T,
+    {
+        WriteStorage::entry(self, entity)
+            .ok()
+            .map(|entry| entry.or_insert_with(f))
     }
 
     fn insert(&mut self, entity: Entity, comp: Self::Component) -> InsertResult<Self::Component> {
@@ -157,6 +179,10 @@ where
     #[cfg(feature = "nightly")]
     type AccessMut<'c> where Self: 'c = <<T as Component>::Storage as UnprotectedStorage<T>>::AccessMut<'c>;
 
+    fn contains(&self, entity: Entity) -> bool {
+        WriteStorage::contains(*self, entity)
+    }
+
     fn get_mut(&mut self, entity: Entity) -> Option<AccessMutReturn<'_, T>> {
         WriteStorage::get_mut(*self, entity)
     }
@@ -165,13 +191,16 @@ where
     where
         Self::Component: Default,
     {
-        if !self.contains(entity) {
-            self.insert(entity, Default::default())
-                .ok()
-                .and_then(move |_| self.get_mut(entity))
-        } else {
-            self.get_mut(entity)
-        }
+        self.get_mut_or_insert_with(entity, Default::default)
+    }
+
+    fn get_mut_or_insert_with<F>(&mut self, entity: Entity, f: F) -> Option<AccessMutReturn<'_, T>>
+    where
+        F: FnOnce() -> T,
+    {
+        WriteStorage::entry(*self, entity)
+            .ok()
+            .map(|entry| entry.or_insert_with(f))
     }
 
     fn insert(&mut self, entity: Entity, comp: Self::Component) -> InsertResult<Self::Component> {
@@ -0,0 +1,218 @@
+use std::{
+    ops::DerefMut,
+    slice::IterMut,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use hibitset::{BitSet, BitSetLike};
+
+use crate::{
+    storage::{storages::SliceAccess, MaskedStorage, PackedStorage, Storage},
+    world::{Component, Index},
+};
+
+/// A group of `PackedStorage`s whose components are kept in lockstep, e.g.
+/// `(&mut positions, &mut velocities)`.
+///
+/// Packing moves the components of the entities which have all components
+/// of the group to the front of every storage, in the same order. The first
+/// `len` elements of the storages' slices then belong to the same entities,
+/// so a hot loop can walk them linearly instead of joining.
+///
+/// Packing only does work if components were inserted or removed since the
+/// last time, so it's cheap to call every frame. A storage can be part of
+/// several groups, but packing it for one of them breaks the order of the
+/// others, which are packed again the next time they are used.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::{prelude::*, storage::{PackedGroup, PackedStorage}};
+///
+/// struct Pos(f32);
+///
+/// impl Component for Pos {
+///     type Storage = PackedStorage<Self>;
+/// }
+///
+/// struct Vel(f32);
+///
+/// impl Component for Vel {
+///     type Storage = PackedStorage<Self>;
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Pos>();
+/// world.register::<Vel>();
+/// world.create_entity().with(Pos(0.0)).build();
+/// let moving = world.create_entity().with(Pos(1.0)).with(Vel(2.0)).build();
+///
+/// let mut pos = world.write_storage::<Pos>();
+/// let mut vel = world.write_storage::<Vel>();
+/// let len = (&mut pos, &mut vel).pack();
+/// for (pos, vel) in pos.as_mut_slice()[..len].iter_mut().zip(&vel.as_slice()[..len]) {
+///     pos.0 += vel.0;
+/// }
+///
+/// assert_eq!(len, 1);
+/// assert_eq!(pos.get(moving).unwrap().0, 3.0);
+/// ```
+pub trait PackedGroup {
+    /// The iterators over the packed components of every storage.
+    type Slices;
+
+    /// Packs the storages if necessary and returns the number of entities
+    /// which have all components of the group.
+    fn pack(self) -> usize;
+
+    /// Packs the storages if necessary and returns an iterator over the
+    /// components of the entities which have all components of the group,
+    /// walking the storages in lockstep.
+    ///
+    /// This yields the same components as joining over the storages, in a
+    /// different order, but without any bitset work. `join` can't do that
+    /// by itself, because it doesn't know the storages are grouped.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::{prelude::*, storage::{PackedGroup, PackedStorage}};
+    ///
+    /// struct Pos(f32);
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = PackedStorage<Self>;
+    /// }
+    ///
+    /// struct Vel(f32);
+    ///
+    /// impl Component for Vel {
+    ///     type Storage = PackedStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// world.register::<Vel>();
+    /// world.create_entity().with(Pos(0.0)).build();
+    /// let moving = world.create_entity().with(Pos(1.0)).with(Vel(2.0)).build();
+    ///
+    /// let mut pos = world.write_storage::<Pos>();
+    /// let mut vel = world.write_storage::<Vel>();
+    /// for (pos, vel) in (&mut pos, &mut vel).grouped() {
+    ///     pos.0 += vel.0;
+    /// }
+    ///
+    /// assert_eq!(pos.get(moving).unwrap().0, 3.0);
+    /// ```
+    fn grouped(self) -> GroupedJoin<Self::Slices>;
+}
+
+/// The id of the next group of storages which is packed.
+static NEXT_GROUP: AtomicUsize = AtomicUsize::new(0);
+
+/// An `Iterator` over the components of a `PackedGroup`, created by
+/// `PackedGroup::grouped`.
+#[must_use]
+pub struct GroupedJoin<T> {
+    slices: T,
+    len: usize,
+}
+
+macro_rules! packed_group {
+    () => {};
+    ($($comp:ident => $data:ident,)*) => {
+        impl<'a, 'e, $($comp, $data,)*> PackedGroup for ($(&'a mut Storage<'e, $comp, $data>,)*)
+        where
+            $(
+                $comp: Component<Storage = PackedStorage<$comp>>,
+                $data: DerefMut<Target = MaskedStorage<$comp>>,
+            )*
+        {
+            type Slices = ($(IterMut<'a, $comp>,)*);
+
+            fn pack(self) -> usize {
+                self.grouped().len()
+            }
+
+            #[allow(unused)]
+            fn grouped(self) -> GroupedJoin<Self::Slices> {
+                #[allow(bad_style)]
+                let ($($data,)*) = self;
+                // The storages are still packed for this group if they were
+                // all packed together, for a group of the same size, and
+                // haven't changed since.
+                let groups = [$($data.data.inner.group(),)*];
+                let packed = match groups[0] {
+                    Some((_, size)) => {
+                        size == groups.len() && groups.iter().all(|&g| g == groups[0])
+                    }
+                    None => false,
+                };
+                let len = if packed {
+                    [$($data.data.inner.group_len(),)*][0]
+                } else {
+                    let masks: &[&BitSet] = &[$(&$data.data.mask,)*];
+                    let members: Vec<Index> = masks[0]
+                        .iter()
+                        .filter(|&id| masks.iter().all(|mask| mask.contains(id)))
+                        .collect();
+                    let group = (NEXT_GROUP.fetch_add(1, Ordering::Relaxed), groups.len());
+                    $(
+                        $data.data.inner.pack(&members, group);
+                    )*
+
+                    members.len()
+                };
+
+                GroupedJoin {
+                    slices: ($($data.data.inner.as_mut_slice()[..len].iter_mut(),)*),
+                    len,
+                }
+            }
+        }
+
+        impl<'a, $($comp,)*> Iterator for GroupedJoin<($(IterMut<'a, $comp>,)*)> {
+            type Item = ($(&'a mut $comp,)*);
+
+            fn next(&mut self) -> Option<Self::Item> {
+                #[allow(bad_style)]
+                let ($(ref mut $data,)*) = self.slices;
+                let item = ($($data.next()?,)*);
+                self.len -= 1;
+
+                Some(item)
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (self.len, Some(self.len))
+            }
+        }
+
+        impl<'a, $($comp,)*> ExactSizeIterator for GroupedJoin<($(IterMut<'a, $comp>,)*)> {}
+
+        packed_group!(@pop $($comp => $data,)*);
+    };
+    (@pop) => {};
+    (@pop $head:ident => $hdata:ident, $($tail:ident => $tdata:ident,)*) => {
+        packed_group!($($tail => $tdata,)*);
+    };
+}
+
+packed_group!(
+    CA => DA,
+    CB => DB,
+    CC => DC,
+    CD => DD,
+    CE => DE,
+    CF => DF,
+    CG => DG,
+    CH => DH,
+    CI => DI,
+    CJ => DJ,
+    CK => DK,
+    CL => DL,
+    CM => DM,
+    CN => DN,
+    CO => DO,
+    CP => DP,
+);
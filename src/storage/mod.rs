@@ -2,17 +2,23 @@
 
 pub use self::{
     data::{ReadStorage, WriteStorage},
+    double_buffered::DoubleBufferedStorage,
     entry::{Entries, OccupiedEntry, StorageEntry, VacantEntry},
     flagged::FlaggedStorage,
     generic::{GenericReadStorage, GenericWriteStorage},
+    group::{GroupedJoin, PackedGroup},
     restrict::{
         ImmutableParallelRestriction, MutableParallelRestriction, RestrictedStorage,
         SequentialRestriction, PairedStorage
     },
     storages::{
-        BTreeStorage, DefaultVecStorage, DenseVecStorage, HashMapStorage, NullStorage, VecStorage,
+        BTreeStorage, DefaultVecStorage, DenseVecStorage, HashMapStorage, NullStorage,
+        PackedStorage, PagedStorage, VecStorage,
     },
-    track::{ComponentEvent, Tracked},
+    sorted::{Sorted, SortedStorage},
+    ticked::{ChangeTick, Ticked, TickedStorage},
+    track::{ComponentChanges, ComponentEvent, Tracked},
+    with_entities::WithEntities,
 };
 #[cfg(feature = "nightly")]
 pub use self::deref_flagged::{DerefFlaggedStorage, FlaggedAccessMut};
@@ -39,17 +45,22 @@ use crate::{
 use self::drain::Drain;
 
 mod data;
+mod double_buffered;
 mod drain;
 mod entry;
 mod flagged;
 #[cfg(feature = "nightly")]
 mod deref_flagged;
 mod generic;
+mod group;
 mod restrict;
+mod sorted;
 mod storages;
 #[cfg(test)]
 mod tests;
+mod ticked;
 mod track;
+mod with_entities;
 
 #[cfg(feature = "nightly")]
 type AccessMutReturn<'a, T> = <<T as Component>::Storage as UnprotectedStorage<T>>::AccessMut<'a>;
@@ -85,6 +96,35 @@ unsafe impl<'a> ParJoin for AntiStorage<'a> {}
 pub trait AnyStorage {
     /// Drop components of given entities.
     fn drop(&mut self, entities: &[Entity]);
+
+    /// Drops the components of all entities, which are given in `entities`,
+    /// for `WorldExt::delete_all`.
+    /// Defaults to calling `drop`.
+    fn drop_all(&mut self, entities: &[Entity]) {
+        self.drop(entities);
+    }
+
+    /// Releases memory which isn't needed for the current components.
+    /// Defaults to doing nothing.
+    fn shrink_to_fit(&mut self) {}
+
+    /// Called by `World::maintain` before the components of deleted entities
+    /// are dropped. Drops the components retained from entities deleted
+    /// earlier, see `Storage::retain_removed`, and calls
+    /// `UnprotectedStorage::maintain`.
+    /// Defaults to doing nothing.
+    fn maintain(&mut self) {}
+
+    /// Drops all components, including the ones retained from deleted
+    /// entities, for `WorldExt::clear`.
+    /// Defaults to doing nothing.
+    fn reset(&mut self) {}
+
+    /// Returns the memory used by this storage, for `WorldExt::memory_report`.
+    /// Defaults to `None`, leaving the storage out of the report.
+    fn memory(&self) -> Option<ComponentMemory> {
+        None
+    }
 }
 
 unsafe impl<T> CastFrom<T> for dyn AnyStorage
@@ -105,10 +145,102 @@ where
     T: Component,
 {
     fn drop(&mut self, entities: &[Entity]) {
-        for entity in entities {
-            MaskedStorage::drop(self, entity.id());
+        if self.removed.is_some() {
+            for &entity in entities {
+                if let Some(component) = self.remove(entity.id()) {
+                    if let Some(ref mut removed) = self.removed {
+                        removed.insert(entity, component);
+                    }
+                }
+            }
+        } else {
+            for entity in entities {
+                MaskedStorage::drop(self, entity.id());
+            }
+        }
+    }
+
+    fn drop_all(&mut self, entities: &[Entity]) {
+        if self.removed.is_some() {
+            AnyStorage::drop(self, entities);
+        } else {
+            // Every component belongs to one of the entities, so there's no
+            // need to look them up one by one.
+            self.clear();
         }
     }
+
+    fn shrink_to_fit(&mut self) {
+        MaskedStorage::shrink_to_fit(self);
+    }
+
+    fn maintain(&mut self) {
+        if let Some(ref mut removed) = self.removed {
+            removed.entities.clear();
+            removed.storage.clear();
+        }
+        // SAFETY: `self.mask` tracks the components of `self.inner`.
+        unsafe {
+            self.inner.maintain(&self.mask);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.clear();
+        if let Some(ref mut removed) = self.removed {
+            removed.entities.clear();
+            removed.storage.clear();
+        }
+    }
+
+    fn memory(&self) -> Option<ComponentMemory> {
+        Some(ComponentMemory {
+            component: std::any::type_name::<T>(),
+            stats: self.stats(),
+        })
+    }
+}
+
+/// A dynamic storage whose components can be cloned, see
+/// `WorldExt::register_cloneable`.
+pub trait CloneStorage {
+    /// Clones the component at `from`, if any, to `to`, replacing the
+    /// component there. Returns `true` if there was a component to clone.
+    fn clone_component(&mut self, from: Index, to: Index) -> bool;
+}
+
+unsafe impl<T> CastFrom<T> for dyn CloneStorage
+where
+    T: CloneStorage + 'static,
+{
+    fn cast(t: &T) -> &Self {
+        t
+    }
+
+    fn cast_mut(t: &mut T) -> &mut Self {
+        t
+    }
+}
+
+impl<T> CloneStorage for MaskedStorage<T>
+where
+    T: Component + Clone,
+{
+    fn clone_component(&mut self, from: Index, to: Index) -> bool {
+        if !self.mask.contains(from) {
+            return false;
+        }
+
+        // SAFETY: We checked the mask.
+        let component = unsafe { self.inner.get(from) }.clone();
+        if self.mask.contains(to) {
+            self.replace(to, component);
+        } else {
+            self.insert(to, component);
+        }
+
+        true
+    }
 }
 
 /// This is a marker trait which requires you to uphold the following guarantee:
@@ -131,6 +263,12 @@ where
 ///
 /// Implementing this trait marks the storage safe for concurrent mutation (of
 /// distinct elements), thus allows `join_par()`.
+///
+/// # Safety
+///
+/// `get` and `get_mut` of the storage may be called concurrently for
+/// distinct indices, so they must not access any memory other than the
+/// elements of these indices.
 pub unsafe trait DistinctStorage {}
 
 /// The status of an `insert()`ion into a storage.
@@ -138,11 +276,118 @@ pub unsafe trait DistinctStorage {}
 /// contain the component that was replaced (if any).
 pub type InsertResult<T> = Result<Option<T>, Error>;
 
+/// The population and memory usage of a storage, returned by
+/// `Storage::stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StorageStats {
+    /// The number of components in the storage.
+    pub len: usize,
+    /// The number of components the storage can hold without reallocating,
+    /// see `UnprotectedStorage::capacity`.
+    pub capacity: usize,
+    /// The number of bytes the storage has allocated on the heap, including
+    /// index maps like the one of `DenseVecStorage`. This is an estimate for
+    /// storages which don't know their exact allocations.
+    pub bytes: usize,
+    /// The number of bytes used by the mask of the storage, which grows with
+    /// the highest entity id that ever had a component.
+    pub mask_bytes: usize,
+}
+
+impl StorageStats {
+    /// Returns the number of bytes used by the storage and its mask.
+    pub fn total_bytes(&self) -> usize {
+        self.bytes + self.mask_bytes
+    }
+}
+
+/// The memory used by the storage of one component type, part of a
+/// `MemoryReport`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComponentMemory {
+    /// The type name of the component.
+    pub component: &'static str,
+    /// The population and memory usage of the storage.
+    pub stats: StorageStats,
+}
+
+/// The memory used by all storages of a `World`, returned by
+/// `WorldExt::memory_report`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    /// The storages of all registered components, the largest first.
+    pub components: Vec<ComponentMemory>,
+}
+
+impl MemoryReport {
+    /// Returns the number of bytes used by all storages.
+    pub fn total_bytes(&self) -> usize {
+        self.components
+            .iter()
+            .map(|component| component.stats.total_bytes())
+            .sum()
+    }
+}
+
 /// The `UnprotectedStorage` together with the `BitSet` that knows
 /// about which elements are stored, and which are not.
 pub struct MaskedStorage<T: Component> {
     mask: BitSet,
     inner: T::Storage,
+    len: usize,
+    removed: Option<Box<Removed<T>>>,
+    hooks: Hooks<T>,
+}
+
+type Hook<T> = Box<dyn FnMut(Index, &T) + Send + Sync>;
+
+/// The callbacks registered with `Storage::on_insert` and
+/// `Storage::on_remove`.
+struct Hooks<T> {
+    on_insert: Vec<Hook<T>>,
+    on_remove: Vec<Hook<T>>,
+}
+
+impl<T> Default for Hooks<T> {
+    fn default() -> Self {
+        Hooks {
+            on_insert: Vec::new(),
+            on_remove: Vec::new(),
+        }
+    }
+}
+
+impl<T> Hooks<T> {
+    fn inserted(&mut self, id: Index, component: &T) {
+        for hook in &mut self.on_insert {
+            hook(id, component);
+        }
+    }
+
+    fn removed(&mut self, id: Index, component: &T) {
+        for hook in &mut self.on_remove {
+            hook(id, component);
+        }
+    }
+}
+
+/// The components of deleted entities kept by `Storage::retain_removed`.
+struct Removed<T: Component> {
+    entities: Vec<Entity>,
+    storage: MaskedStorage<T>,
+}
+
+impl<T: Component> Removed<T> {
+    fn insert(&mut self, entity: Entity, component: T) {
+        let id = entity.id();
+        if self.storage.mask.contains(id) {
+            // The id was reused and deleted again since the last maintain.
+            self.entities.retain(|e| e.id() != id);
+            self.storage.drop(id);
+        }
+        self.storage.insert(id, component);
+        self.entities.push(entity);
+    }
 }
 
 impl<T: Component> Default for MaskedStorage<T>
@@ -153,6 +398,9 @@ where
         Self {
             mask: Default::default(),
             inner: Default::default(),
+            len: 0,
+            removed: None,
+            hooks: Hooks::default(),
         }
     }
 }
@@ -164,6 +412,9 @@ impl<T: Component> MaskedStorage<T> {
         MaskedStorage {
             mask: BitSet::new(),
             inner,
+            len: 0,
+            removed: None,
+            hooks: Hooks::default(),
         }
     }
 
@@ -171,20 +422,89 @@ impl<T: Component> MaskedStorage<T> {
         (&self.mask, &mut self.inner)
     }
 
+    /// Returns the component at `id`, even if its entity was just deleted.
+    pub(crate) fn get(&self, id: Index) -> Option<&T> {
+        if self.mask.contains(id) {
+            // SAFETY: We checked the mask.
+            Some(unsafe { self.inner.get(id) })
+        } else {
+            None
+        }
+    }
+
     /// Clear the contents of this storage.
     pub fn clear(&mut self) {
+        if !self.hooks.on_remove.is_empty() {
+            for id in (&self.mask).iter() {
+                // SAFETY: We checked the mask.
+                self.hooks.removed(id, unsafe { self.inner.get(id) });
+            }
+        }
         // SAFETY: `self.mask` is the correct mask as specified.
         unsafe {
             self.inner.clean(&self.mask);
         }
         self.mask.clear();
+        self.len = 0;
+    }
+
+    /// Returns the number of components and the memory used by this storage.
+    pub fn stats(&self) -> StorageStats {
+        let mask_bytes = (self.mask.layer0_as_slice().len()
+            + self.mask.layer1_as_slice().len()
+            + self.mask.layer2_as_slice().len())
+            * std::mem::size_of::<usize>();
+
+        StorageStats {
+            len: self.len,
+            capacity: self.inner.capacity(self.len),
+            bytes: self.inner.allocated_bytes(self.len),
+            mask_bytes,
+        }
+    }
+
+    /// Releases memory the storage doesn't need for its current components.
+    pub fn shrink_to_fit(&mut self) {
+        // SAFETY: `self.mask` is the correct mask as specified.
+        unsafe {
+            self.inner.shrink_to_fit(&self.mask);
+        }
+    }
+
+    /// Adds an index to the mask.
+    fn add(&mut self, id: Index) {
+        if !self.mask.add(id) {
+            self.len += 1;
+        }
+    }
+
+    /// Inserts a component at an index which isn't in the mask.
+    fn insert(&mut self, id: Index, component: T) {
+        self.hooks.inserted(id, &component);
+        self.add(id);
+        // SAFETY: The index wasn't in the mask.
+        unsafe { self.inner.insert(id, component) };
+    }
+
+    /// Replaces the component at an index which is in the mask.
+    fn replace(&mut self, id: Index, mut component: T) -> T {
+        // SAFETY: The index is in the mask.
+        std::mem::swap(&mut component, unsafe { self.inner.get_mut(id).deref_mut() });
+        self.hooks.removed(id, &component);
+        // SAFETY: The index is in the mask.
+        self.hooks.inserted(id, unsafe { self.inner.get(id) });
+
+        component
     }
 
     /// Remove an element by a given index.
     pub fn remove(&mut self, id: Index) -> Option<T> {
         if self.mask.remove(id) {
+            self.len -= 1;
             // SAFETY: We checked the mask (`remove` returned `true`)
-            Some(unsafe { self.inner.remove(id) })
+            let component = unsafe { self.inner.remove(id) };
+            self.hooks.removed(id, &component);
+            Some(component)
         } else {
             None
         }
@@ -193,8 +513,12 @@ impl<T: Component> MaskedStorage<T> {
     /// Drop an element by a given index.
     pub fn drop(&mut self, id: Index) {
         if self.mask.remove(id) {
+            self.len -= 1;
             // SAFETY: We checked the mask (`remove` returned `true`)
             unsafe {
+                if !self.hooks.on_remove.is_empty() {
+                    self.hooks.removed(id, self.inner.get(id));
+                }
                 self.inner.drop(id);
             }
         }
@@ -256,11 +580,83 @@ where
         }
     }
 
-    /// Computes the number of elements this `Storage` contains by counting the
-    /// bits in the bit set. This operation will never be performed in
-    /// constant time.
+    /// Returns the number of elements this `Storage` contains. This operation
+    /// is very cheap, the count is kept up to date on insertion and removal.
     pub fn count(&self) -> usize {
-        self.mask().iter().count()
+        self.data.len
+    }
+
+    /// Returns the components kept from deleted entities in the order they
+    /// were deleted, if `retain_removed` is enabled.
+    ///
+    /// Entities deleted with `Entities::delete` are removed by
+    /// `World::maintain`, so their components can be read until the
+    /// following `maintain`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Pos(f32, f32);
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// world.write_storage::<Pos>().retain_removed(true);
+    /// let enemy = world.create_entity().with(Pos(3.0, 4.0)).build();
+    ///
+    /// world.entities().delete(enemy).unwrap();
+    /// world.maintain();
+    ///
+    /// // Play the death animation at the last position of the enemy.
+    /// let pos = world.read_storage::<Pos>();
+    /// assert_eq!(pos.removed().collect::<Vec<_>>(), vec![(enemy, &Pos(3.0, 4.0))]);
+    /// drop(pos);
+    ///
+    /// world.maintain();
+    /// assert_eq!(world.read_storage::<Pos>().removed().count(), 0);
+    /// ```
+    pub fn removed(&self) -> impl Iterator<Item = (Entity, &T)> + '_ {
+        self.data.removed.iter().flat_map(|removed| {
+            removed.entities.iter().map(move |&entity| {
+                // SAFETY: Every entity of `removed` has a component in it.
+                (entity, unsafe { removed.storage.inner.get(entity.id()) })
+            })
+        })
+    }
+
+    /// Returns the number of components and the memory used by this
+    /// `Storage`, e.g. for showing them in a debug overlay.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// struct Pos(f32, f32);
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = DenseVecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// for i in 0..10 {
+    ///     world.create_entity().with(Pos(i as f32, 0.0)).build();
+    /// }
+    ///
+    /// let stats = world.read_storage::<Pos>().stats();
+    /// assert_eq!(stats.len, 10);
+    /// assert!(stats.capacity >= 10);
+    /// assert!(stats.bytes >= 10 * std::mem::size_of::<Pos>());
+    /// ```
+    pub fn stats(&self) -> StorageStats {
+        self.data.stats()
     }
 
     /// Checks whether this `Storage` is empty. This operation is very cheap.
@@ -290,12 +686,85 @@ where
     /// Returns the component data as a slice.
     ///
     /// The indices of this slice may not correspond to anything in particular.
-    /// Check the underlying storage documentation for details.
+    /// Check the underlying storage documentation for details. For storages
+    /// indexed by entity id, `mask()` tells which slots hold a component; for
+    /// `DenseVecStorage`, `slice_entity_ids()` does.
     pub fn as_slice(&self) -> &[<T::Storage as SliceAccess<T>>::Element] {
         self.data.inner.as_slice()
     }
 }
 
+impl<'e, T, D> Storage<'e, T, D>
+where
+    T: Component<Storage = DenseVecStorage<T>>,
+    D: Deref<Target = MaskedStorage<T>>,
+{
+    /// Returns the entity ids of the components in `as_slice()`, so the
+    /// component at `as_slice()[i]` belongs to the entity with the id
+    /// `slice_entity_ids()[i]`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// struct Mass(f32);
+    ///
+    /// impl Component for Mass {
+    ///     type Storage = DenseVecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Mass>();
+    /// world.create_entity().build();
+    /// let heavy = world.create_entity().with(Mass(10.0)).build();
+    ///
+    /// let masses = world.read_storage::<Mass>();
+    /// assert_eq!(masses.as_slice().len(), 1);
+    /// assert_eq!(masses.slice_entity_ids(), &[heavy.id()]);
+    /// ```
+    pub fn slice_entity_ids(&self) -> &[Index] {
+        self.data.inner.entity_ids()
+    }
+}
+
+impl<'e, T, D> Storage<'e, T, D>
+where
+    T: Component<Storage = DenseVecStorage<T>>,
+    D: DerefMut<Target = MaskedStorage<T>>,
+{
+    /// Sorts the components by entity id and releases unused memory, see
+    /// `DenseVecStorage::compact`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// struct Mass(f32);
+    ///
+    /// impl Component for Mass {
+    ///     type Storage = DenseVecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Mass>();
+    /// let entities: Vec<Entity> = (0..4)
+    ///     .map(|i| world.create_entity().with(Mass(i as f32)).build())
+    ///     .collect();
+    ///
+    /// let mut masses = world.write_storage::<Mass>();
+    /// masses.remove(entities[0]);
+    /// assert_eq!(masses.slice_entity_ids(), &[3, 1, 2]);
+    ///
+    /// masses.compact();
+    /// assert_eq!(masses.slice_entity_ids(), &[1, 2, 3]);
+    /// ```
+    pub fn compact(&mut self) {
+        self.data.inner.compact();
+    }
+}
+
 impl<'e, T, D> Storage<'e, T, D>
 where
     T: Component,
@@ -343,17 +812,13 @@ where
     /// If a component already existed for the given `Entity`, then it will
     /// be overwritten with the new component. If it did overwrite, then the
     /// result will contain `Some(T)` where `T` is the previous component.
-    pub fn insert(&mut self, e: Entity, mut v: T) -> InsertResult<T> {
+    pub fn insert(&mut self, e: Entity, v: T) -> InsertResult<T> {
         if self.entities.is_alive(e) {
             let id = e.id();
             if self.data.mask.contains(id) {
-                // SAFETY: We checked the mask, so all invariants are met.
-                std::mem::swap(&mut v, unsafe { self.data.inner.get_mut(id).deref_mut() });
-                Ok(Some(v))
+                Ok(Some(self.data.replace(id, v)))
             } else {
-                self.data.mask.add(id);
-                // SAFETY: The mask was previously empty, so it is safe to insert.
-                unsafe { self.data.inner.insert(id, v) };
+                self.data.insert(id, v);
                 Ok(None)
             }
         } else {
@@ -365,6 +830,46 @@ where
         }
     }
 
+    /// Inserts the components of many entities, reserving space for them
+    /// first, e.g. for the entities of `Entities::create_many`.
+    ///
+    /// Like `insert`, this overwrites existing components. If one of the
+    /// entities is dead, this stops and returns the error, keeping the
+    /// components inserted before it.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// struct Pos(f32);
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    ///
+    /// let entities = world.entities();
+    /// let mut pos = world.write_storage::<Pos>();
+    /// pos.insert_batch(entities.create_many(100).zip((0..100).map(|i| Pos(i as f32))))
+    ///     .unwrap();
+    /// assert_eq!(pos.count(), 100);
+    /// ```
+    pub fn insert_batch<I>(&mut self, iter: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = (Entity, T)>,
+    {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for (e, v) in iter {
+            self.insert(e, v)?;
+        }
+
+        Ok(())
+    }
+
     /// Removes the data associated with an `Entity`.
     pub fn remove(&mut self, e: Entity) -> Option<T> {
         if self.entities.is_alive(e) {
@@ -379,8 +884,143 @@ where
         self.data.clear();
     }
 
+    /// Reserves capacity for at least `additional` more components, e.g.
+    /// before loading a level with a known number of entities.
+    ///
+    /// Storages which can't allocate ahead of time, like `BTreeStorage`,
+    /// ignore this.
+    pub fn reserve(&mut self, additional: usize) {
+        self.data.inner.reserve(additional);
+    }
+
+    /// Sets whether the components of deleted entities are kept until the
+    /// next `World::maintain` instead of being dropped right away, so they
+    /// can be read with `removed`. Disabling it drops the kept components.
+    ///
+    /// Only components dropped because their entity was deleted are kept,
+    /// `remove` gives the component to the caller instead.
+    pub fn retain_removed(&mut self, retain: bool) {
+        match (retain, self.data.removed.is_some()) {
+            (true, false) => {
+                self.data.removed = Some(Box::new(Removed {
+                    entities: Vec::new(),
+                    storage: MaskedStorage::new(T::Storage::unwrap_default()),
+                }))
+            }
+            (false, true) => self.data.removed = None,
+            _ => {}
+        }
+    }
+
+    /// Registers a callback which is run with the index and the component
+    /// whenever a component is inserted into this storage, no matter how.
+    ///
+    /// Replacing a component runs the `on_remove` callbacks with the old
+    /// component and then the `on_insert` callbacks with the new one.
+    /// Modifying a component through `get_mut` or a join runs neither.
+    ///
+    /// Callbacks can't access the `World`, so they usually record the
+    /// changes somewhere shared, e.g. in a channel or behind a mutex.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// use specs::{prelude::*, world::Index};
+    ///
+    /// struct Collider(f32);
+    ///
+    /// impl Component for Collider {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// let broadphase: Arc<Mutex<Vec<Index>>> = Default::default();
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Collider>();
+    /// {
+    ///     let mut colliders = world.write_storage::<Collider>();
+    ///     let added = broadphase.clone();
+    ///     colliders.on_insert(move |id, _| added.lock().unwrap().push(id));
+    ///     let removed = broadphase.clone();
+    ///     colliders.on_remove(move |id, _| removed.lock().unwrap().retain(|&i| i != id));
+    /// }
+    ///
+    /// let ball = world.create_entity().with(Collider(1.0)).build();
+    /// assert_eq!(*broadphase.lock().unwrap(), vec![ball.id()]);
+    ///
+    /// world.delete_entity(ball).unwrap();
+    /// assert!(broadphase.lock().unwrap().is_empty());
+    /// ```
+    pub fn on_insert<F>(&mut self, hook: F)
+    where
+        F: FnMut(Index, &T) + Send + Sync + 'static,
+    {
+        self.data.hooks.on_insert.push(Box::new(hook));
+    }
+
+    /// Registers a callback which is run with the index and the component
+    /// whenever a component is removed from this storage, including when
+    /// its entity is deleted, the storage is cleared or drained, and when
+    /// the storage itself is dropped. See `on_insert` for an example.
+    pub fn on_remove<F>(&mut self, hook: F)
+    where
+        F: FnMut(Index, &T) + Send + Sync + 'static,
+    {
+        self.data.hooks.on_remove.push(Box::new(hook));
+    }
+
+    /// Releases memory the storage doesn't need for its current components,
+    /// e.g. after deleting many entities.
+    ///
+    /// Storages keep their peak memory otherwise, which is usually what you
+    /// want if it will be needed again.
+    pub fn shrink_to_fit(&mut self) {
+        self.data.shrink_to_fit();
+    }
+
     /// Creates a draining storage wrapper which can be `.join`ed
     /// to get a draining iterator.
+    ///
+    /// Components are removed as the join visits them, so joining it with
+    /// other storages only drains the components of the entities which have
+    /// all of them, and the components left after stopping early stay in
+    /// the storage. Joining it with `&entities` yields `(Entity, T)`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// struct Damage(u32);
+    ///
+    /// impl Component for Damage {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// struct Health(u32);
+    ///
+    /// impl Component for Health {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Damage>();
+    /// world.register::<Health>();
+    /// let player = world.create_entity().with(Health(10)).with(Damage(3)).build();
+    ///
+    /// let entities = world.entities();
+    /// let mut damage = world.write_storage::<Damage>();
+    /// let mut health = world.write_storage::<Health>();
+    /// for (entity, damage, health) in (&entities, damage.drain(), &mut health).join() {
+    ///     health.0 -= damage.0;
+    ///     assert_eq!(entity, player);
+    /// }
+    ///
+    /// assert_eq!(health.get(player).unwrap().0, 7);
+    /// assert!(damage.is_empty());
+    /// ```
     pub fn drain(&mut self) -> Drain<T> {
         Drain {
             data: &mut self.data,
@@ -504,6 +1144,91 @@ where
 }
 
 /// Used by the framework to quickly join components.
+///
+/// This is the storage named by `Component::Storage`. It's wrapped in a
+/// `MaskedStorage`, whose mask keeps track of the indices which have a
+/// component, so the storage itself doesn't have to check anything. Any
+/// type implementing it can be used as a storage, and works with `Join`,
+/// `Entry` and saveload like the storages of this crate.
+///
+/// If accessing distinct indices from multiple threads doesn't alias,
+/// `DistinctStorage` can be implemented as well to allow `par_join`ing
+/// mutably.
+///
+/// ## Examples
+///
+/// A storage which keeps the components in a `Vec<Option<T>>`:
+///
+/// ```
+/// use specs::{
+///     hibitset::BitSetLike,
+///     prelude::*,
+///     storage::{DistinctStorage, UnprotectedStorage},
+///     world::Index,
+/// };
+///
+/// struct OptionStorage<T>(Vec<Option<T>>);
+///
+/// impl<T> Default for OptionStorage<T> {
+///     fn default() -> Self {
+///         OptionStorage(Vec::new())
+///     }
+/// }
+///
+/// impl<T> UnprotectedStorage<T> for OptionStorage<T> {
+///     unsafe fn clean<B>(&mut self, _has: B)
+///     where
+///         B: BitSetLike,
+///     {
+///         self.0.clear();
+///     }
+///
+///     unsafe fn get(&self, id: Index) -> &T {
+///         self.0[id as usize].as_ref().unwrap()
+///     }
+///
+///     unsafe fn get_mut(&mut self, id: Index) -> &mut T {
+///         self.0[id as usize].as_mut().unwrap()
+///     }
+///
+///     unsafe fn insert(&mut self, id: Index, value: T) {
+///         let id = id as usize;
+///         if self.0.len() <= id {
+///             self.0.resize_with(id + 1, || None);
+///         }
+///         self.0[id] = Some(value);
+///     }
+///
+///     unsafe fn remove(&mut self, id: Index) -> T {
+///         self.0[id as usize].take().unwrap()
+///     }
+/// }
+///
+/// // Distinct indices are distinct elements of the `Vec`.
+/// unsafe impl<T> DistinctStorage for OptionStorage<T> {}
+///
+/// struct Health(u32);
+///
+/// impl Component for Health {
+///     type Storage = OptionStorage<Self>;
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Health>();
+/// let entity = world.create_entity().with(Health(10)).build();
+/// world.create_entity().build();
+///
+/// let mut health = world.write_storage::<Health>();
+/// for health in (&mut health).join() {
+///     health.0 -= 1;
+/// }
+/// assert_eq!(health.get(entity).unwrap().0, 9);
+/// assert_eq!(health.remove(entity).unwrap().0, 9);
+/// assert_eq!((&health).join().count(), 0);
+/// ```
+///
+/// With the `nightly` feature, `get_mut` returns `Self::AccessMut`, which
+/// can be set to `&'a mut T`.
 pub trait UnprotectedStorage<T>: TryDefault {
     /// The wrapper through with mutable access of a component is performed.
     #[cfg(feature = "nightly")]
@@ -592,6 +1317,54 @@ pub trait UnprotectedStorage<T>: TryDefault {
     unsafe fn drop(&mut self, id: Index) {
         self.remove(id);
     }
+
+    /// Reserves capacity for at least `additional` more components, so they
+    /// can be inserted without reallocating.
+    /// Defaults to doing nothing, for storages which can't allocate ahead.
+    fn reserve(&mut self, _additional: usize) {}
+
+    /// Releases memory the storage doesn't need for its current components,
+    /// e.g. after many of them were removed.
+    /// Defaults to doing nothing.
+    ///
+    /// # Safety
+    ///
+    /// May only be called with the mask which keeps track of the elements
+    /// existing in this storage.
+    unsafe fn shrink_to_fit<B>(&mut self, _has: B)
+    where
+        B: BitSetLike,
+    {
+    }
+
+    /// Called once per frame by `World::maintain`, before the components of
+    /// deleted entities are dropped.
+    /// Defaults to doing nothing.
+    ///
+    /// # Safety
+    ///
+    /// May only be called with the mask which keeps track of the elements
+    /// existing in this storage.
+    unsafe fn maintain<B>(&mut self, _has: B)
+    where
+        B: BitSetLike,
+    {
+    }
+
+    /// Returns the number of components the storage can hold without
+    /// reallocating, given that it holds `len` of them. For storages indexed
+    /// by entity id, this is the number of ids they can hold.
+    /// Defaults to `len`.
+    fn capacity(&self, len: usize) -> usize {
+        len
+    }
+
+    /// Returns the number of bytes the storage has allocated on the heap,
+    /// given that it holds `len` components.
+    /// Defaults to the size of `len` components.
+    fn allocated_bytes(&self, len: usize) -> usize {
+        len * std::mem::size_of::<T>()
+    }
 }
 
 #[cfg(test)]
@@ -10,7 +10,7 @@ use shred::Fetch;
 use crate::join::Join;
 
 #[cfg(feature = "parallel")]
-use crate::join::ParJoin;
+use crate::{join::ParJoin, storage::DistinctStorage};
 use crate::{
     storage::{MaskedStorage, Storage, UnprotectedStorage, AccessMutReturn},
     world::{Component, EntitiesRes, Entity, Index},
@@ -79,21 +79,27 @@ where
     phantom: PhantomData<(C, Restrict)>,
 }
 
+// SAFETY: A `MutableParallelRestriction` entry can only access the component
+// of its own entity, and `DistinctStorage` guarantees that accessing distinct
+// indices from different threads doesn't alias.
 #[cfg(feature = "parallel")]
 unsafe impl<'rf, 'st: 'rf, C, S, B> ParJoin
     for &'rf mut RestrictedStorage<'rf, 'st, C, S, B, MutableParallelRestriction>
 where
     C: Component,
+    C::Storage: Sync + DistinctStorage,
     S: BorrowMut<C::Storage> + 'rf,
     B: Borrow<BitSet> + 'rf,
 {
 }
 
+// SAFETY: Entries which can only read components may alias freely.
 #[cfg(feature = "parallel")]
 unsafe impl<'rf, 'st: 'rf, C, S, B, Restrict> ParJoin
     for &'rf RestrictedStorage<'rf, 'st, C, S, B, Restrict>
 where
     C: Component,
+    C::Storage: Sync,
     S: Borrow<C::Storage> + 'rf,
     B: Borrow<BitSet> + 'rf,
     Restrict: ImmutableAliasing,
@@ -204,6 +210,36 @@ where
     /// Builds a mutable, parallel `RestrictedStorage`,
     /// does not allow mutably getting other components
     /// aside from the current iteration.
+    ///
+    /// It can be used with `par_join` if the storage of `T` implements
+    /// `DistinctStorage`, so every thread mutates its own entities.
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// struct Health(u32);
+    ///
+    /// impl Component for Health {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Health>();
+    /// for i in 0..100 {
+    ///     world.create_entity().with(Health(i)).build();
+    /// }
+    ///
+    /// let mut health = world.write_storage::<Health>();
+    /// # #[cfg(feature = "parallel")]
+    /// (&mut health.par_restrict_mut())
+    ///     .par_join()
+    ///     .for_each(|mut entry| {
+    ///         if entry.get_unchecked().0 < 50 {
+    ///             entry.get_mut_unchecked().0 += 10;
+    ///         }
+    ///     });
+    /// # #[cfg(feature = "parallel")]
+    /// assert!(health.join().all(|health| health.0 >= 10));
+    /// ```
     pub fn par_restrict_mut<'rf>(
         &'rf mut self,
     ) -> RestrictedStorage<'rf, 'st, T, &mut T::Storage, &BitSet, MutableParallelRestriction> {
@@ -0,0 +1,224 @@
+use std::{borrow::Cow, marker::PhantomData, ops::Deref};
+
+use hibitset::{BitSet, BitSetLike};
+
+use crate::{
+    storage::{DenseVecStorage, MaskedStorage, Storage, TryDefault, UnprotectedStorage},
+    world::{Component, Index},
+};
+
+/// `UnprotectedStorage`s which keep the indices of their components in
+/// ascending order of the components.
+pub trait Sorted {
+    /// Returns the indices of all components in ascending order of the
+    /// components, ties broken by index.
+    ///
+    /// # Safety
+    ///
+    /// May only be called with the mask which keeps track of the elements
+    /// existing in this storage.
+    unsafe fn sorted_ids(&self, has: &BitSet) -> Cow<'_, [Index]>;
+}
+
+/// Wrapper storage which keeps an index of its components sorted by their
+/// `Ord` implementation, e.g. for drawing sprites by render layer.
+///
+/// The index is sorted again by `World::maintain` if components were
+/// inserted, removed or accessed mutably. Until then, `Join::sorted_join`
+/// sorts a copy of it, so the order is right either way, but only free if
+/// the storage didn't change since the last `maintain`.
+///
+/// # Examples
+///
+/// ```
+/// use specs::{prelude::*, storage::SortedStorage};
+///
+/// #[derive(PartialEq, Eq, PartialOrd, Ord)]
+/// struct Layer(u8);
+///
+/// impl Component for Layer {
+///     type Storage = SortedStorage<Self>;
+/// }
+///
+/// struct Sprite(&'static str);
+///
+/// impl Component for Sprite {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Layer>();
+/// world.register::<Sprite>();
+/// world.create_entity().with(Layer(2)).with(Sprite("player")).build();
+/// world.create_entity().with(Layer(0)).with(Sprite("sky")).build();
+/// world.create_entity().with(Layer(1)).with(Sprite("tree")).build();
+/// world.maintain();
+///
+/// let layers = world.read_storage::<Layer>();
+/// let sprites = world.read_storage::<Sprite>();
+/// let order: Vec<&str> = (&sprites).sorted_join(&layers).map(|sprite| sprite.0).collect();
+/// assert_eq!(order, vec!["sky", "tree", "player"]);
+/// ```
+pub struct SortedStorage<C, T = DenseVecStorage<C>> {
+    storage: T,
+    // The indices of the components, sorted unless `dirty` is set. May
+    // contain removed and duplicate indices while it's dirty.
+    order: Vec<Index>,
+    dirty: bool,
+    phantom: PhantomData<C>,
+}
+
+impl<C, T> SortedStorage<C, T>
+where
+    C: Ord,
+    T: UnprotectedStorage<C>,
+{
+    /// Sorts `order` by component, dropping indices which aren't in `has`.
+    ///
+    /// # Safety
+    ///
+    /// May only be called with the mask of the storage.
+    unsafe fn sort<B>(&self, order: &mut Vec<Index>, has: B)
+    where
+        B: BitSetLike,
+    {
+        order.retain(|&id| has.contains(id));
+        order.sort_unstable_by(|&a, &b| {
+            self.storage
+                .get(a)
+                .cmp(self.storage.get(b))
+                .then(a.cmp(&b))
+        });
+        order.dedup();
+    }
+}
+
+impl<C, T> Default for SortedStorage<C, T>
+where
+    T: TryDefault,
+{
+    fn default() -> Self {
+        SortedStorage {
+            storage: T::unwrap_default(),
+            order: Vec::new(),
+            dirty: false,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<C, T> UnprotectedStorage<C> for SortedStorage<C, T>
+where
+    C: Component + Ord,
+    T: UnprotectedStorage<C>,
+{
+    #[cfg(feature = "nightly")]
+    type AccessMut<'a> where T: 'a = <T as UnprotectedStorage<C>>::AccessMut<'a>;
+
+    unsafe fn clean<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        self.order.clear();
+        self.dirty = false;
+        self.storage.clean(has);
+    }
+
+    unsafe fn get(&self, id: Index) -> &C {
+        self.storage.get(id)
+    }
+
+    #[cfg(feature = "nightly")]
+    unsafe fn get_mut(&mut self, id: Index) -> <T as UnprotectedStorage<C>>::AccessMut<'_> {
+        self.dirty = true;
+        self.storage.get_mut(id)
+    }
+
+    #[cfg(not(feature = "nightly"))]
+    unsafe fn get_mut(&mut self, id: Index) -> &mut C {
+        self.dirty = true;
+        self.storage.get_mut(id)
+    }
+
+    unsafe fn insert(&mut self, id: Index, comp: C) {
+        self.order.push(id);
+        self.dirty = true;
+        self.storage.insert(id, comp);
+    }
+
+    unsafe fn remove(&mut self, id: Index) -> C {
+        // The index is dropped from `order` when sorting.
+        self.dirty = true;
+        self.storage.remove(id)
+    }
+
+    unsafe fn drop(&mut self, id: Index) {
+        self.dirty = true;
+        self.storage.drop(id);
+    }
+
+    unsafe fn shrink_to_fit<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        self.order.retain(|&id| has.contains(id));
+        self.order.dedup();
+        self.order.shrink_to_fit();
+        self.storage.shrink_to_fit(has);
+    }
+
+    unsafe fn maintain<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        if self.dirty {
+            let mut order = std::mem::take(&mut self.order);
+            self.sort(&mut order, &has);
+            self.order = order;
+            self.dirty = false;
+        }
+        self.storage.maintain(has);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.order.reserve(additional);
+        self.storage.reserve(additional);
+    }
+
+    fn capacity(&self, len: usize) -> usize {
+        self.storage.capacity(len)
+    }
+
+    fn allocated_bytes(&self, len: usize) -> usize {
+        self.storage.allocated_bytes(len) + self.order.capacity() * std::mem::size_of::<Index>()
+    }
+}
+
+impl<C, T> Sorted for SortedStorage<C, T>
+where
+    C: Ord,
+    T: UnprotectedStorage<C>,
+{
+    unsafe fn sorted_ids(&self, has: &BitSet) -> Cow<'_, [Index]> {
+        if self.dirty {
+            let mut order = self.order.clone();
+            self.sort(&mut order, has);
+            Cow::Owned(order)
+        } else {
+            Cow::Borrowed(&self.order)
+        }
+    }
+}
+
+impl<'e, T, D> Storage<'e, T, D>
+where
+    T: Component,
+    T::Storage: Sorted,
+    D: Deref<Target = MaskedStorage<T>>,
+{
+    /// Returns the indices of the components in ascending order.
+    pub(crate) fn sorted_ids(&self) -> Cow<'_, [Index]> {
+        // SAFETY: `self.data.mask` is the mask of the storage.
+        unsafe { self.data.inner.sorted_ids(&self.data.mask) }
+    }
+}
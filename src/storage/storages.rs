@@ -1,6 +1,9 @@
 //! Different types of storages you can use for your components.
 
-use std::{collections::BTreeMap, mem::MaybeUninit};
+use std::{
+    collections::BTreeMap,
+    mem::{size_of, MaybeUninit},
+};
 
 use hashbrown::HashMap;
 use hibitset::BitSetLike;
@@ -22,7 +25,12 @@ pub trait SliceAccess<T> {
     fn as_mut_slice(&mut self) -> &mut [Self::Element];
 }
 
-/// BTreeMap-based storage.
+/// BTreeMap-based storage. Suited for sparse components.
+///
+/// Unlike `HashMapStorage`, it doesn't depend on a hasher and keeps its
+/// components ordered by entity id, so it behaves the same on every run. Like
+/// with every storage, joins visit the components in the order of their
+/// entity ids.
 pub struct BTreeStorage<T>(BTreeMap<Index, T>);
 
 impl<T> Default for BTreeStorage<T> {
@@ -39,7 +47,7 @@ impl<T> UnprotectedStorage<T> for BTreeStorage<T> {
     where
         B: BitSetLike,
     {
-        // nothing to do
+        self.0.clear();
     }
 
     unsafe fn get(&self, id: Index) -> &T {
@@ -57,6 +65,10 @@ impl<T> UnprotectedStorage<T> for BTreeStorage<T> {
     unsafe fn remove(&mut self, id: Index) -> T {
         self.0.remove(&id).unwrap()
     }
+
+    fn allocated_bytes(&self, len: usize) -> usize {
+        len * size_of::<(Index, T)>()
+    }
 }
 
 unsafe impl<T> DistinctStorage for BTreeStorage<T> {}
@@ -80,7 +92,7 @@ impl<T> UnprotectedStorage<T> for HashMapStorage<T> {
     where
         B: BitSetLike,
     {
-        //nothing to do
+        self.0.clear();
     }
 
     unsafe fn get(&self, id: Index) -> &T {
@@ -98,6 +110,25 @@ impl<T> UnprotectedStorage<T> for HashMapStorage<T> {
     unsafe fn remove(&mut self, id: Index) -> T {
         self.0.remove(&id).unwrap()
     }
+
+    fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional);
+    }
+
+    unsafe fn shrink_to_fit<B>(&mut self, _has: B)
+    where
+        B: BitSetLike,
+    {
+        self.0.shrink_to_fit();
+    }
+
+    fn capacity(&self, _len: usize) -> usize {
+        self.0.capacity()
+    }
+
+    fn allocated_bytes(&self, _len: usize) -> usize {
+        self.0.capacity() * size_of::<(Index, T)>()
+    }
 }
 
 unsafe impl<T> DistinctStorage for HashMapStorage<T> {}
@@ -130,6 +161,40 @@ impl<T> Default for DenseVecStorage<T> {
     }
 }
 
+impl<T> DenseVecStorage<T> {
+    /// Returns the entity ids of the components in `as_slice()`, in the same
+    /// order.
+    pub fn entity_ids(&self) -> &[Index] {
+        &self.entity_id
+    }
+
+    /// Sorts the components by entity id and releases unused memory.
+    ///
+    /// Removing a component moves the last one into its place, so after many
+    /// removals the components are scattered in no particular order. Joins
+    /// visit them by entity id, so compacting makes them walk the components
+    /// linearly again.
+    pub fn compact(&mut self) {
+        let mut components: Vec<(Index, T)> =
+            self.entity_id.drain(..).zip(self.data.drain(..)).collect();
+        components.sort_unstable_by_key(|&(id, _)| id);
+        for (did, (id, component)) in components.into_iter().enumerate() {
+            self.data_id[id as usize] = MaybeUninit::new(did as Index);
+            self.entity_id.push(id);
+            self.data.push(component);
+        }
+        self.shrink();
+    }
+
+    fn shrink(&mut self) {
+        let len = self.entity_id.iter().max().map_or(0, |&id| id as usize + 1);
+        self.data_id.truncate(len);
+        self.data_id.shrink_to_fit();
+        self.entity_id.shrink_to_fit();
+        self.data.shrink_to_fit();
+    }
+}
+
 impl<T> SliceAccess<T> for DenseVecStorage<T> {
     type Element = T;
 
@@ -160,7 +225,8 @@ impl<T> UnprotectedStorage<T> for DenseVecStorage<T> {
     where
         B: BitSetLike,
     {
-        // nothing to do
+        self.data.clear();
+        self.entity_id.clear();
     }
 
     unsafe fn get(&self, id: Index) -> &T {
@@ -198,10 +264,198 @@ impl<T> UnprotectedStorage<T> for DenseVecStorage<T> {
         self.entity_id.swap_remove(did as usize);
         self.data.swap_remove(did as usize)
     }
+
+    fn reserve(&mut self, additional: usize) {
+        self.data.reserve(additional);
+        self.entity_id.reserve(additional);
+        self.data_id.reserve(additional);
+    }
+
+    unsafe fn shrink_to_fit<B>(&mut self, _has: B)
+    where
+        B: BitSetLike,
+    {
+        self.shrink();
+    }
+
+    fn capacity(&self, _len: usize) -> usize {
+        self.data.capacity()
+    }
+
+    fn allocated_bytes(&self, _len: usize) -> usize {
+        self.data.capacity() * size_of::<T>()
+            + self.entity_id.capacity() * size_of::<Index>()
+            + self.data_id.capacity() * size_of::<MaybeUninit<Index>>()
+    }
 }
 
 unsafe impl<T> DistinctStorage for DenseVecStorage<T> {}
 
+/// Dense vector storage which can be packed together with the storages of
+/// other components, see `PackedGroup`.
+///
+/// Apart from packing, it works like `DenseVecStorage`: `as_slice()` and
+/// `as_mut_slice()` indices are local to this storage, and a particular
+/// entity's position within the slice changes when components are removed
+/// or the storage is packed.
+pub struct PackedStorage<T> {
+    data: Vec<T>,
+    entity_id: Vec<Index>,
+    data_id: Vec<Index>,
+    group_len: usize,
+    /// The id of the group the storage was last packed for, and the number
+    /// of storages in it.
+    group: Option<(usize, usize)>,
+    dirty: bool,
+}
+
+impl<T> PackedStorage<T> {
+    /// Returns the entity ids of the components in `as_slice()`, in the same
+    /// order.
+    pub fn entity_ids(&self) -> &[Index] {
+        &self.entity_id
+    }
+
+    /// Returns the id of the group the storage was last packed for and its
+    /// number of storages, or `None` if components were inserted or removed
+    /// since.
+    pub(crate) fn group(&self) -> Option<(usize, usize)> {
+        if self.dirty {
+            None
+        } else {
+            self.group
+        }
+    }
+
+    /// The number of components which were packed the last time.
+    pub(crate) fn group_len(&self) -> usize {
+        self.group_len
+    }
+
+    /// Moves the components of `ids` to the front of the storage, in the
+    /// order of `ids`, for `group`. Every id has to have a component.
+    pub(crate) fn pack(&mut self, ids: &[Index], group: (usize, usize)) {
+        for (pos, &id) in ids.iter().enumerate() {
+            let did = self.data_id[id as usize] as usize;
+            if did != pos {
+                self.data.swap(did, pos);
+                self.entity_id.swap(did, pos);
+                self.data_id[self.entity_id[did] as usize] = did as Index;
+                self.data_id[id as usize] = pos as Index;
+            }
+        }
+        self.group_len = ids.len();
+        self.group = Some(group);
+        self.dirty = false;
+    }
+}
+
+impl<T> Default for PackedStorage<T> {
+    fn default() -> Self {
+        Self {
+            data: Default::default(),
+            entity_id: Default::default(),
+            data_id: Default::default(),
+            group_len: 0,
+            group: None,
+            dirty: false,
+        }
+    }
+}
+
+impl<T> SliceAccess<T> for PackedStorage<T> {
+    type Element = T;
+
+    /// Returns a slice of all the components in this storage.
+    ///
+    /// After packing, the components of the group come first.
+    #[inline]
+    fn as_slice(&self) -> &[Self::Element] {
+        self.data.as_slice()
+    }
+
+    /// Returns a mutable slice of all the components in this storage.
+    ///
+    /// After packing, the components of the group come first.
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [Self::Element] {
+        self.data.as_mut_slice()
+    }
+}
+
+impl<T> UnprotectedStorage<T> for PackedStorage<T> {
+    #[cfg(feature = "nightly")]
+    type AccessMut<'a> where T: 'a = &'a mut T;
+
+    unsafe fn clean<B>(&mut self, _has: B)
+    where
+        B: BitSetLike,
+    {
+        self.data.clear();
+        self.entity_id.clear();
+        self.group_len = 0;
+        self.group = None;
+    }
+
+    unsafe fn get(&self, id: Index) -> &T {
+        let did = *self.data_id.get_unchecked(id as usize);
+        self.data.get_unchecked(did as usize)
+    }
+
+    unsafe fn get_mut(&mut self, id: Index) -> &mut T {
+        let did = *self.data_id.get_unchecked(id as usize);
+        self.data.get_unchecked_mut(did as usize)
+    }
+
+    unsafe fn insert(&mut self, id: Index, v: T) {
+        let id = id as usize;
+        if self.data_id.len() <= id {
+            self.data_id.resize(id + 1, 0);
+        }
+        self.data_id[id] = self.data.len() as Index;
+        self.entity_id.push(id as Index);
+        self.data.push(v);
+        self.dirty = true;
+    }
+
+    unsafe fn remove(&mut self, id: Index) -> T {
+        let did = *self.data_id.get_unchecked(id as usize);
+        let last = *self.entity_id.last().unwrap();
+        self.data_id[last as usize] = did;
+        self.entity_id.swap_remove(did as usize);
+        self.dirty = true;
+        self.data.swap_remove(did as usize)
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.data.reserve(additional);
+        self.entity_id.reserve(additional);
+        self.data_id.reserve(additional);
+    }
+
+    unsafe fn shrink_to_fit<B>(&mut self, _has: B)
+    where
+        B: BitSetLike,
+    {
+        let len = self.entity_id.iter().max().map_or(0, |&id| id as usize + 1);
+        self.data_id.truncate(len);
+        self.data_id.shrink_to_fit();
+        self.entity_id.shrink_to_fit();
+        self.data.shrink_to_fit();
+    }
+
+    fn capacity(&self, _len: usize) -> usize {
+        self.data.capacity()
+    }
+
+    fn allocated_bytes(&self, _len: usize) -> usize {
+        self.data.capacity() * size_of::<T>()
+            + (self.entity_id.capacity() + self.data_id.capacity()) * size_of::<Index>()
+    }
+}
+
+unsafe impl<T> DistinctStorage for PackedStorage<T> {}
+
 /// A null storage type, used for cases where the component
 /// doesn't contain any data and instead works as a simple flag.
 pub struct NullStorage<T>(T);
@@ -321,10 +575,169 @@ impl<T> UnprotectedStorage<T> for VecStorage<T> {
         use std::ptr;
         ptr::read(self.get(id))
     }
+
+    fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional);
+    }
+
+    unsafe fn shrink_to_fit<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        // Only the slots up to the last component are needed, and the ones
+        // after it are uninitialized, so they can be cut off without dropping.
+        let len = has.iter().last().map_or(0, |id| id as usize + 1);
+        self.0.truncate(len);
+        self.0.shrink_to_fit();
+    }
+
+    fn capacity(&self, _len: usize) -> usize {
+        self.0.capacity()
+    }
+
+    fn allocated_bytes(&self, _len: usize) -> usize {
+        self.0.capacity() * size_of::<T>()
+    }
 }
 
 unsafe impl<T> DistinctStorage for VecStorage<T> {}
 
+/// The number of components in a page of a `PagedStorage`.
+const PAGE_SIZE: usize = 1024;
+
+/// A page of a `PagedStorage` and the number of components in it.
+struct Page<T> {
+    slots: Box<[MaybeUninit<T>]>,
+    len: usize,
+}
+
+/// Paged vector storage. Like `VecStorage`, components are stored at their
+/// entity id, but the address range is split into pages of 1024 components
+/// which are only allocated while they hold a component.
+///
+/// Best suited for components of worlds with huge entity ids and patchy
+/// occupancy, which would make a `VecStorage` allocate the whole range.
+pub struct PagedStorage<T> {
+    pages: Vec<Option<Page<T>>>,
+}
+
+impl<T> PagedStorage<T> {
+    /// Returns the number of allocated pages.
+    pub fn page_count(&self) -> usize {
+        self.pages.iter().filter(|page| page.is_some()).count()
+    }
+
+    unsafe fn slot(&self, id: Index) -> &MaybeUninit<T> {
+        let id = id as usize;
+        match *self.pages.get_unchecked(id / PAGE_SIZE) {
+            Some(ref page) => page.slots.get_unchecked(id % PAGE_SIZE),
+            None => unreachable!("component of a missing page"),
+        }
+    }
+
+    unsafe fn slot_mut(&mut self, id: Index) -> &mut MaybeUninit<T> {
+        let id = id as usize;
+        match *self.pages.get_unchecked_mut(id / PAGE_SIZE) {
+            Some(ref mut page) => page.slots.get_unchecked_mut(id % PAGE_SIZE),
+            None => unreachable!("component of a missing page"),
+        }
+    }
+}
+
+impl<T> Default for PagedStorage<T> {
+    fn default() -> Self {
+        Self {
+            pages: Default::default(),
+        }
+    }
+}
+
+impl<T> UnprotectedStorage<T> for PagedStorage<T> {
+    #[cfg(feature = "nightly")]
+    type AccessMut<'a> where T: 'a = &'a mut T;
+
+    unsafe fn clean<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        use std::ptr;
+        for (index, page) in self.pages.iter_mut().enumerate() {
+            if let Some(ref mut page) = *page {
+                for (offset, v) in page.slots.iter_mut().enumerate() {
+                    if has.contains((index * PAGE_SIZE + offset) as u32) {
+                        // drop in place
+                        ptr::drop_in_place(&mut *v.as_mut_ptr());
+                    }
+                }
+            }
+        }
+        self.pages.clear();
+    }
+
+    unsafe fn get(&self, id: Index) -> &T {
+        &*self.slot(id).as_ptr()
+    }
+
+    unsafe fn get_mut(&mut self, id: Index) -> &mut T {
+        &mut *self.slot_mut(id).as_mut_ptr()
+    }
+
+    unsafe fn insert(&mut self, id: Index, v: T) {
+        let index = id as usize / PAGE_SIZE;
+        if self.pages.len() <= index {
+            self.pages.resize_with(index + 1, || None);
+        }
+        let page = self.pages[index].get_or_insert_with(|| Page {
+            slots: (0..PAGE_SIZE).map(|_| MaybeUninit::uninit()).collect(),
+            len: 0,
+        });
+        page.len += 1;
+        // Write the value without reading or dropping
+        // the (currently uninitialized) memory.
+        *page.slots.get_unchecked_mut(id as usize % PAGE_SIZE) = MaybeUninit::new(v);
+    }
+
+    unsafe fn remove(&mut self, id: Index) -> T {
+        use std::ptr;
+        let v = ptr::read(self.get(id));
+        let index = id as usize / PAGE_SIZE;
+        let empty = match self.pages[index] {
+            Some(ref mut page) => {
+                page.len -= 1;
+                page.len == 0
+            }
+            None => false,
+        };
+        // Release the page once it's empty; `MaybeUninit` doesn't drop the
+        // components that were read out of it.
+        if empty {
+            self.pages[index] = None;
+        }
+        v
+    }
+
+    unsafe fn shrink_to_fit<B>(&mut self, _has: B)
+    where
+        B: BitSetLike,
+    {
+        while let Some(None) = self.pages.last() {
+            self.pages.pop();
+        }
+        self.pages.shrink_to_fit();
+    }
+
+    fn capacity(&self, _len: usize) -> usize {
+        self.page_count() * PAGE_SIZE
+    }
+
+    fn allocated_bytes(&self, _len: usize) -> usize {
+        self.page_count() * PAGE_SIZE * size_of::<T>()
+            + self.pages.capacity() * size_of::<Option<Page<T>>>()
+    }
+}
+
+unsafe impl<T> DistinctStorage for PagedStorage<T> {}
+
 /// Vector storage, like `VecStorage`, but allows safe access to the
 /// interior slices because unused slots are always initialized.
 ///
@@ -385,6 +798,27 @@ where
         // return the old value
         v
     }
+
+    fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional);
+    }
+
+    unsafe fn shrink_to_fit<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        let len = has.iter().last().map_or(0, |id| id as usize + 1);
+        self.0.truncate(len);
+        self.0.shrink_to_fit();
+    }
+
+    fn capacity(&self, _len: usize) -> usize {
+        self.0.capacity()
+    }
+
+    fn allocated_bytes(&self, _len: usize) -> usize {
+        self.0.capacity() * size_of::<T>()
+    }
 }
 
 unsafe impl<T> DistinctStorage for DefaultVecStorage<T> {}
@@ -169,6 +169,49 @@ mod test {
         type Storage = FlaggedStorage<Self, VecStorage<Self>>;
     }
 
+    #[derive(PartialEq, Eq, Debug)]
+    struct TickedCvec(u32);
+    impl From<u32> for TickedCvec {
+        fn from(v: u32) -> TickedCvec {
+            TickedCvec(v)
+        }
+    }
+    impl AsMut<u32> for TickedCvec {
+        fn as_mut(&mut self) -> &mut u32 {
+            &mut self.0
+        }
+    }
+    impl Component for TickedCvec {
+        type Storage = TickedStorage<Self, VecStorage<Self>>;
+    }
+
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
+    struct SortedCvec(u32);
+    impl From<u32> for SortedCvec {
+        fn from(v: u32) -> SortedCvec {
+            SortedCvec(v)
+        }
+    }
+    impl AsMut<u32> for SortedCvec {
+        fn as_mut(&mut self) -> &mut u32 {
+            &mut self.0
+        }
+    }
+    impl Component for SortedCvec {
+        type Storage = SortedStorage<Self, VecStorage<Self>>;
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    struct DoubleBufferedCvec(u32);
+    impl From<u32> for DoubleBufferedCvec {
+        fn from(v: u32) -> DoubleBufferedCvec {
+            DoubleBufferedCvec(v)
+        }
+    }
+    impl Component for DoubleBufferedCvec {
+        type Storage = DoubleBufferedStorage<Self, VecStorage<Self>>;
+    }
+
     #[derive(PartialEq, Eq, Debug)]
     struct Cmap(u32);
     impl From<u32> for Cmap {
@@ -185,6 +228,71 @@ mod test {
         type Storage = HashMapStorage<Self>;
     }
 
+    #[derive(PartialEq, Eq, Debug)]
+    struct CdenseVec(u32);
+    impl From<u32> for CdenseVec {
+        fn from(v: u32) -> CdenseVec {
+            CdenseVec(v)
+        }
+    }
+    impl Component for CdenseVec {
+        type Storage = DenseVecStorage<Self>;
+    }
+
+    #[derive(PartialEq, Eq, Debug)]
+    struct Cpacked(u32);
+    impl From<u32> for Cpacked {
+        fn from(v: u32) -> Cpacked {
+            Cpacked(v)
+        }
+    }
+    impl AsMut<u32> for Cpacked {
+        fn as_mut(&mut self) -> &mut u32 {
+            &mut self.0
+        }
+    }
+    impl Component for Cpacked {
+        type Storage = PackedStorage<Self>;
+    }
+
+    #[derive(PartialEq, Eq, Debug)]
+    struct CpackedB(u32);
+    impl From<u32> for CpackedB {
+        fn from(v: u32) -> CpackedB {
+            CpackedB(v)
+        }
+    }
+    impl Component for CpackedB {
+        type Storage = PackedStorage<Self>;
+    }
+
+    #[derive(PartialEq, Eq, Debug)]
+    struct CpackedC(u32);
+    impl From<u32> for CpackedC {
+        fn from(v: u32) -> CpackedC {
+            CpackedC(v)
+        }
+    }
+    impl Component for CpackedC {
+        type Storage = PackedStorage<Self>;
+    }
+
+    #[derive(PartialEq, Eq, Debug)]
+    struct Cpaged(u32);
+    impl From<u32> for Cpaged {
+        fn from(v: u32) -> Cpaged {
+            Cpaged(v)
+        }
+    }
+    impl AsMut<u32> for Cpaged {
+        fn as_mut(&mut self) -> &mut u32 {
+            &mut self.0
+        }
+    }
+    impl Component for Cpaged {
+        type Storage = PagedStorage<Self>;
+    }
+
     #[derive(PartialEq, Eq, Debug)]
     struct CBtree(u32);
     impl From<u32> for CBtree {
@@ -413,6 +521,79 @@ mod test {
         }
     }
 
+    fn test_reserve<T: Component + From<u32> + Debug + Eq>()
+    where
+        T::Storage: Default,
+    {
+        let mut w = World::new();
+        w.register_with_capacity::<T>(100);
+        let mut s: Storage<T, _> = w.write_storage();
+        s.reserve(100);
+
+        for i in 0..200 {
+            if let Err(err) = s.insert(Entity::new(i, Generation::new(1)), (i + 10).into()) {
+                panic!("Failed to insert component into entity! {:?}", err);
+            }
+        }
+
+        for i in 0..200 {
+            assert_eq!(s.get(Entity::new(i, Generation::new(1))), Some(&(i + 10).into()));
+        }
+    }
+
+    fn test_insert_batch<T: Component + From<u32> + Debug + Eq>()
+    where
+        T::Storage: Default,
+    {
+        let mut w = World::new();
+        let mut s: Storage<T, _> = create(&mut w);
+
+        let batch = (0..200).map(|i| (Entity::new(i, Generation::new(1)), (i + 10).into()));
+        s.insert_batch(batch).unwrap();
+        for i in 0..200 {
+            assert_eq!(s.get(Entity::new(i, Generation::new(1))), Some(&(i + 10).into()));
+        }
+
+        // Stops at the first dead entity.
+        let batch = vec![
+            (Entity::new(300, Generation::new(1)), 1.into()),
+            (Entity::new(301, Generation::new(2)), 2.into()),
+            (Entity::new(302, Generation::new(1)), 3.into()),
+        ];
+        assert!(s.insert_batch(batch).is_err());
+        assert!(s.contains(Entity::new(300, Generation::new(1))));
+        assert!(!s.contains(Entity::new(302, Generation::new(1))));
+    }
+
+    fn test_shrink_to_fit<T: Component + From<u32> + Debug + Eq>()
+    where
+        T::Storage: Default,
+    {
+        let mut w = World::new();
+        let mut s: Storage<T, _> = create(&mut w);
+
+        for i in 0..1_000 {
+            if let Err(err) = s.insert(Entity::new(i, Generation::new(1)), (i + 10).into()) {
+                panic!("Failed to insert component into entity! {:?}", err);
+            }
+        }
+        for i in 10..1_000 {
+            s.remove(Entity::new(i, Generation::new(1)));
+        }
+        s.shrink_to_fit();
+
+        for i in 0..10 {
+            assert_eq!(s.get(Entity::new(i, Generation::new(1))), Some(&(i + 10).into()));
+        }
+        for i in 10..20 {
+            assert!(s.get(Entity::new(i, Generation::new(1))).is_none());
+            if let Err(err) = s.insert(Entity::new(i, Generation::new(1)), (i + 10).into()) {
+                panic!("Failed to insert component into entity! {:?}", err);
+            }
+        }
+        assert_eq!((&s).join().count(), 20);
+    }
+
     fn test_anti<T: Component + From<u32> + Debug + Eq>()
     where
         T::Storage: Default,
@@ -489,6 +670,19 @@ mod test {
         test_get_mut_or_default::<Cvec>();
     }
     #[test]
+    fn vec_test_get_mut_or_insert_with() {
+        let mut w = World::new();
+        let mut s: Storage<Cvec, _> = create(&mut w);
+        let e = Entity::new(3, Generation::new(1));
+
+        s.get_mut_or_insert_with(e, || Cvec(5)).unwrap().0 += 1;
+        s.get_mut_or_insert_with(e, || panic!("component exists already")).unwrap().0 += 1;
+        assert_eq!(s.get(e), Some(&Cvec(7)));
+        assert!(s
+            .get_mut_or_insert_with(Entity::new(3, Generation::new(2)), || Cvec(0))
+            .is_none());
+    }
+    #[test]
     fn vec_test_add_gen() {
         test_add_gen::<Cvec>();
     }
@@ -501,6 +695,18 @@ mod test {
         test_clear::<Cvec>();
     }
     #[test]
+    fn vec_test_reserve() {
+        test_reserve::<Cvec>();
+    }
+    #[test]
+    fn vec_test_insert_batch() {
+        test_insert_batch::<Cvec>();
+    }
+    #[test]
+    fn vec_test_shrink_to_fit() {
+        test_shrink_to_fit::<Cvec>();
+    }
+    #[test]
     fn vec_test_anti() {
         test_anti::<Cvec>();
     }
@@ -557,6 +763,14 @@ mod test {
         test_clear::<CdefaultVec>();
     }
     #[test]
+    fn default_vec_test_reserve() {
+        test_reserve::<CdefaultVec>();
+    }
+    #[test]
+    fn default_vec_test_shrink_to_fit() {
+        test_shrink_to_fit::<CdefaultVec>();
+    }
+    #[test]
     fn default_vec_test_anti() {
         test_anti::<CdefaultVec>();
     }
@@ -565,6 +779,54 @@ mod test {
         test_slice_access::<CdefaultVec>();
     }
 
+    #[test]
+    fn dense_vec_test_reserve() {
+        test_reserve::<CdenseVec>();
+    }
+    #[test]
+    fn dense_vec_test_shrink_to_fit() {
+        test_shrink_to_fit::<CdenseVec>();
+    }
+
+    #[test]
+    fn dense_vec_test_compact() {
+        let mut w = World::new();
+        let mut s: Storage<CdenseVec, _> = create(&mut w);
+
+        for i in 0..100 {
+            s.insert(Entity::new(i, Generation::new(1)), i.into()).unwrap();
+        }
+        for i in (0..100).filter(|i| i % 3 != 0) {
+            s.remove(Entity::new(i, Generation::new(1)));
+        }
+        s.compact();
+
+        let ids: Vec<Index> = (0..100).filter(|i| i % 3 == 0).collect();
+        assert_eq!(s.slice_entity_ids(), &ids[..]);
+        assert!(s.as_slice().iter().map(|c| c.0).eq(ids.iter().cloned()));
+        for &i in &ids {
+            assert_eq!(s.get(Entity::new(i, Generation::new(1))), Some(&CdenseVec(i)));
+        }
+    }
+
+    #[test]
+    fn dense_vec_test_slice_entity_ids() {
+        let mut w = World::new();
+        let mut s: Storage<CdenseVec, _> = create(&mut w);
+
+        for i in (0..10).rev() {
+            s.insert(Entity::new(i * 2, Generation::new(1)), i.into())
+                .unwrap();
+        }
+        s.remove(Entity::new(18, Generation::new(1)));
+
+        assert_eq!(s.as_slice().len(), 9);
+        assert_eq!(s.slice_entity_ids().len(), 9);
+        for (component, &id) in s.as_slice().iter().zip(s.slice_entity_ids()) {
+            assert_eq!(id, component.0 * 2);
+        }
+    }
+
     #[test]
     fn default_vec_test_defaults() {
         let mut w = World::new();
@@ -600,6 +862,244 @@ mod test {
         );
     }
 
+    #[test]
+    fn packed_test_add() {
+        test_add::<Cpacked>();
+    }
+    #[test]
+    fn packed_test_sub() {
+        test_sub::<Cpacked>();
+    }
+    #[test]
+    fn packed_test_get_mut() {
+        test_get_mut::<Cpacked>();
+    }
+    #[test]
+    fn packed_test_add_gen() {
+        test_add_gen::<Cpacked>();
+    }
+    #[test]
+    fn packed_test_sub_gen() {
+        test_sub_gen::<Cpacked>();
+    }
+    #[test]
+    fn packed_test_clear() {
+        test_clear::<Cpacked>();
+    }
+    #[test]
+    fn packed_test_reserve() {
+        test_reserve::<Cpacked>();
+    }
+    #[test]
+    fn packed_test_insert_batch() {
+        test_insert_batch::<Cpacked>();
+    }
+    #[test]
+    fn packed_test_shrink_to_fit() {
+        test_shrink_to_fit::<Cpacked>();
+    }
+    #[test]
+    fn packed_test_slice_access() {
+        test_slice_access::<Cpacked>();
+    }
+
+    #[test]
+    fn packed_group_lockstep() {
+        let mut w = World::new();
+        w.register::<Cpacked>();
+        w.register::<CpackedB>();
+        let mut a: Storage<Cpacked, _> = w.write_storage();
+        let mut b: Storage<CpackedB, _> = w.write_storage();
+
+        // `a` has every entity, `b` every third one, inserted in reverse.
+        for i in 0..30 {
+            a.insert(Entity::new(i, Generation::new(1)), i.into())
+                .unwrap();
+        }
+        for i in (0..30).rev().filter(|i| i % 3 == 0) {
+            b.insert(Entity::new(i, Generation::new(1)), i.into())
+                .unwrap();
+        }
+
+        let check = |a: &Storage<Cpacked, _>, b: &Storage<CpackedB, _>, len: usize| {
+            let a = &a.as_slice()[..len];
+            let b = &b.as_slice()[..len];
+            for (a, b) in a.iter().zip(b) {
+                assert_eq!(a.0, b.0);
+            }
+        };
+
+        assert_eq!((&mut a, &mut b).pack(), 10);
+        check(&a, &b, 10);
+
+        b.remove(Entity::new(0, Generation::new(1)));
+        a.remove(Entity::new(3, Generation::new(1)));
+        b.insert(Entity::new(4, Generation::new(1)), 4.into())
+            .unwrap();
+        assert_eq!((&mut a, &mut b).pack(), 9);
+        check(&a, &b, 9);
+        assert_eq!((&mut a, &mut b).pack(), 9);
+        assert_eq!(a.get(Entity::new(4, Generation::new(1))), Some(&Cpacked(4)));
+        assert_eq!(b.get(Entity::new(4, Generation::new(1))), Some(&CpackedB(4)));
+    }
+
+    #[test]
+    fn packed_group_grouped() {
+        let mut w = World::new();
+        w.register::<Cpacked>();
+        w.register::<CpackedB>();
+        let mut a: Storage<Cpacked, _> = w.write_storage();
+        let mut b: Storage<CpackedB, _> = w.write_storage();
+
+        for i in 0..30 {
+            a.insert(Entity::new(i, Generation::new(1)), i.into())
+                .unwrap();
+            if i % 2 == 0 {
+                b.insert(Entity::new(i, Generation::new(1)), (i * 10).into())
+                    .unwrap();
+            }
+        }
+
+        let grouped = (&mut a, &mut b).grouped();
+        assert_eq!(grouped.len(), 15);
+        for (a, b) in grouped {
+            assert_eq!(a.0 * 10, b.0);
+            a.0 += b.0;
+        }
+
+        let mut joined: Vec<_> = (&a, &b).join().map(|(a, b)| (a.0, b.0)).collect();
+        joined.sort();
+        assert_eq!(
+            joined,
+            (0..30).step_by(2).map(|i| (i * 11, i * 10)).collect::<Vec<_>>()
+        );
+
+        // Grouping again doesn't need to pack, but still sees new inserts.
+        assert_eq!((&mut a, &mut b).grouped().len(), 15);
+        b.insert(Entity::new(1, Generation::new(1)), 10.into())
+            .unwrap();
+        assert_eq!((&mut a, &mut b).grouped().count(), 16);
+    }
+
+    #[test]
+    fn packed_group_shared_storage() {
+        let mut w = World::new();
+        w.register::<Cpacked>();
+        w.register::<CpackedB>();
+        w.register::<CpackedC>();
+        let mut a: Storage<Cpacked, _> = w.write_storage();
+        let mut b: Storage<CpackedB, _> = w.write_storage();
+        let mut c: Storage<CpackedC, _> = w.write_storage();
+
+        // `b` and `c` have the same number of components, but on different
+        // entities, so `a` is packed differently for each group.
+        for i in 0..30 {
+            a.insert(Entity::new(i, Generation::new(1)), i.into())
+                .unwrap();
+            if i % 2 == 0 {
+                b.insert(Entity::new(i, Generation::new(1)), i.into())
+                    .unwrap();
+            } else {
+                c.insert(Entity::new(i, Generation::new(1)), i.into())
+                    .unwrap();
+            }
+        }
+
+        for _ in 0..2 {
+            let grouped = (&mut a, &mut b).grouped();
+            assert_eq!(grouped.len(), 15);
+            for (a, b) in grouped {
+                assert_eq!(a.0, b.0);
+            }
+            let grouped = (&mut a, &mut c).grouped();
+            assert_eq!(grouped.len(), 15);
+            for (a, c) in grouped {
+                assert_eq!(a.0, c.0);
+            }
+        }
+
+        // A group which is part of another one is packed by itself.
+        (&mut a, &mut b).pack();
+        assert_eq!((&mut a,).grouped().len(), 30);
+        assert_eq!((&mut a, &mut b).grouped().len(), 15);
+        for (a, b) in (&mut a, &mut b).grouped() {
+            assert_eq!(a.0, b.0);
+        }
+    }
+
+    #[test]
+    fn paged_test_add() {
+        test_add::<Cpaged>();
+    }
+    #[test]
+    fn paged_test_sub() {
+        test_sub::<Cpaged>();
+    }
+    #[test]
+    fn paged_test_get_mut() {
+        test_get_mut::<Cpaged>();
+    }
+    #[test]
+    fn paged_test_add_gen() {
+        test_add_gen::<Cpaged>();
+    }
+    #[test]
+    fn paged_test_sub_gen() {
+        test_sub_gen::<Cpaged>();
+    }
+    #[test]
+    fn paged_test_clear() {
+        test_clear::<Cpaged>();
+    }
+    #[test]
+    fn paged_test_shrink_to_fit() {
+        test_shrink_to_fit::<Cpaged>();
+    }
+    #[test]
+    fn paged_test_anti() {
+        test_anti::<Cpaged>();
+    }
+
+    #[test]
+    fn paged_pages_are_allocated_on_demand() {
+        let mut w = World::new();
+        let mut s: Storage<Cpaged, _> = create(&mut w);
+
+        let far = Entity::new(3_000_000, Generation::new(1));
+        let near = Entity::new(5, Generation::new(1));
+        s.insert(far, 1.into()).unwrap();
+        s.insert(near, 2.into()).unwrap();
+        s.insert(Entity::new(6, Generation::new(1)), 3.into())
+            .unwrap();
+        assert_eq!(s.unprotected_storage().page_count(), 2);
+        assert_eq!(s.get(far), Some(&Cpaged(1)));
+
+        s.remove(far);
+        s.remove(near);
+        assert_eq!(s.unprotected_storage().page_count(), 1);
+        assert_eq!(s.get(Entity::new(6, Generation::new(1))), Some(&Cpaged(3)));
+    }
+
+    #[test]
+    fn paged_arc() {
+        use std::sync::Arc;
+
+        let counter = Arc::new(());
+        let mut storage = PagedStorage::<Arc<()>>::default();
+        let mut bitset = BitSet::new();
+
+        unsafe {
+            for i in (0..5000).filter(|i| i % 2 != 0) {
+                storage.insert(i, counter.clone());
+                bitset.add(i);
+            }
+            drop(storage.remove(1));
+            bitset.remove(1);
+            storage.clean(&bitset);
+        }
+        assert_eq!(Arc::strong_count(&counter), 1);
+    }
+
     #[test]
     fn hash_test_add() {
         test_add::<Cmap>();
@@ -624,6 +1124,14 @@ mod test {
     fn hash_test_clear() {
         test_clear::<Cmap>();
     }
+    #[test]
+    fn hash_test_reserve() {
+        test_reserve::<Cmap>();
+    }
+    #[test]
+    fn hash_test_shrink_to_fit() {
+        test_shrink_to_fit::<Cmap>();
+    }
 
     #[test]
     fn btree_test_add() {
@@ -649,6 +1157,14 @@ mod test {
     fn btree_test_clear() {
         test_clear::<CBtree>();
     }
+    #[test]
+    fn btree_test_reserve() {
+        test_reserve::<CBtree>();
+    }
+    #[test]
+    fn btree_test_shrink_to_fit() {
+        test_shrink_to_fit::<CBtree>();
+    }
 
     #[test]
     fn dummy_test_clear() {
@@ -773,6 +1289,7 @@ mod test {
 
         let e7 = w.create_entity().build();
         let e8 = w.create_entity().with(Cvec(10)).build();
+        let e9 = w.create_entity().build();
 
         let mut s1 = w.write_storage::<Cvec>();
 
@@ -849,6 +1366,164 @@ mod test {
             assert_eq!(s1.get(e7), Some(&Cvec(9001)));
             assert_eq!(s1.get(e8), Some(&Cvec(11)));
         }
+
+        // `and_modify` only touches occupied entries, `or_default` vacant ones.
+        {
+            s1.entry(e8).unwrap().and_modify(|c| c.0 += 1).or_default();
+            s1.entry(e9).unwrap().and_modify(|c| c.0 += 1).or_default();
+            assert_eq!(s1.get(e8), Some(&Cvec(12)));
+            assert_eq!(s1.get(e9), Some(&Cvec(0)));
+        }
+    }
+
+    #[test]
+    fn storage_hooks() {
+        use std::sync::{Arc, Mutex};
+
+        let mut w = World::new();
+        w.register::<Cvec>();
+        let log: Arc<Mutex<Vec<(&str, Index, u32)>>> = Default::default();
+        {
+            let mut s = w.write_storage::<Cvec>();
+            let inserted = log.clone();
+            s.on_insert(move |id, c| inserted.lock().unwrap().push(("insert", id, c.0)));
+            let removed = log.clone();
+            s.on_remove(move |id, c| removed.lock().unwrap().push(("remove", id, c.0)));
+        }
+        let take = || std::mem::take(&mut *log.lock().unwrap());
+
+        let e1 = w.create_entity().with(Cvec(1)).build();
+        let e2 = w.create_entity().build();
+        let e3 = w.create_entity().with(Cvec(3)).build();
+        assert_eq!(take(), vec![("insert", e1.id(), 1), ("insert", e3.id(), 3)]);
+
+        {
+            let mut s = w.write_storage::<Cvec>();
+            s.insert(e1, Cvec(10)).unwrap();
+            assert_eq!(take(), vec![("remove", e1.id(), 1), ("insert", e1.id(), 10)]);
+
+            s.entry(e2).unwrap().or_insert(Cvec(2));
+            s.get_mut(e2).unwrap().0 = 20;
+            assert_eq!(take(), vec![("insert", e2.id(), 2)]);
+
+            s.remove(e1);
+            for _ in s.drain().join() {}
+            assert_eq!(
+                take(),
+                vec![("remove", e1.id(), 10), ("remove", e2.id(), 20), ("remove", e3.id(), 3)]
+            );
+
+            s.insert(e1, Cvec(1)).unwrap();
+            s.insert(e2, Cvec(2)).unwrap();
+            take();
+            s.clear();
+            assert_eq!(take(), vec![("remove", e1.id(), 1), ("remove", e2.id(), 2)]);
+            s.insert(e3, Cvec(3)).unwrap();
+        }
+
+        w.entities().delete(e3).unwrap();
+        w.maintain();
+        assert_eq!(take(), vec![("insert", e3.id(), 3), ("remove", e3.id(), 3)]);
+    }
+
+    #[test]
+    fn storage_with_entities() {
+        use crate::join::Join;
+
+        let mut w = World::new();
+        w.register::<Cvec>();
+        let e1 = w.create_entity().with(Cvec(1)).build();
+        w.delete_entity(e1).unwrap();
+        // Reuses the id of `e1` with a new generation.
+        let e2 = w.create_entity().with(Cvec(2)).build();
+        let e3 = w.create_entity().with(Cvec(3)).build();
+        assert_eq!(e1.id(), e2.id());
+
+        let mut s = w.write_storage::<Cvec>();
+        for (entity, c) in s.with_entities_mut().join() {
+            c.0 += entity.id() * 10;
+        }
+        let joined: Vec<_> = s.with_entities().join().collect();
+        assert_eq!(joined, vec![(e2, &Cvec(2)), (e3, &Cvec(13))]);
+
+        #[cfg(feature = "parallel")]
+        {
+            use crate::join::ParJoin;
+            use rayon::iter::ParallelIterator;
+
+            s.with_entities_mut()
+                .par_join()
+                .for_each(|(entity, c)| c.0 = entity.id());
+            assert_eq!(s.get(e3), Some(&Cvec(1)));
+        }
+    }
+
+    #[test]
+    fn storage_count() {
+        let mut w = World::new();
+        w.register::<Cvec>();
+
+        let entities: Vec<Entity> = (0..10)
+            .map(|i| w.create_entity().with(Cvec(i)).build())
+            .collect();
+        let extra = w.create_entity().build();
+        let mut s = w.write_storage::<Cvec>();
+        assert_eq!(s.count(), 10);
+
+        // Replacing a component doesn't change the count.
+        s.insert(entities[0], Cvec(100)).unwrap();
+        assert_eq!(s.count(), 10);
+
+        s.remove(entities[0]);
+        s.remove(entities[0]);
+        assert_eq!(s.count(), 9);
+
+        s.entry(extra).unwrap().or_insert(Cvec(5));
+        assert_eq!(s.count(), 10);
+
+        let mut only = BitSet::new();
+        only.add(entities[1].id());
+        for _ in (s.drain(), &only).join() {}
+        assert_eq!(s.count(), 9);
+        assert_eq!(s.count(), s.mask().iter().count());
+
+        s.clear();
+        assert_eq!(s.count(), 0);
+        drop(s);
+
+        w.write_storage::<Cvec>().insert(extra, Cvec(1)).unwrap();
+        w.delete_entity(extra).unwrap();
+        w.maintain();
+        assert_eq!(w.read_storage::<Cvec>().count(), 0);
+    }
+
+    #[test]
+    fn storage_stats() {
+        let mut w = World::new();
+        w.register::<CdenseVec>();
+        w.register::<CMarker>();
+        let mut dense = w.write_storage::<CdenseVec>();
+        assert_eq!(dense.stats(), StorageStats::default());
+
+        for i in 0..10 {
+            dense.insert(Entity::new(i, Generation::new(1)), i.into()).unwrap();
+        }
+        let stats = dense.stats();
+        assert_eq!(stats.len, 10);
+        assert!(stats.capacity >= 10);
+        assert!(stats.bytes >= 10 * (std::mem::size_of::<CdenseVec>() + 8));
+
+        let mut null = w.write_storage::<CMarker>();
+        null.insert(Entity::new(3, Generation::new(1)), CMarker).unwrap();
+        assert_eq!(
+            null.stats(),
+            StorageStats {
+                len: 1,
+                capacity: 1,
+                bytes: 0,
+                mask_bytes: 3 * std::mem::size_of::<usize>(),
+            }
+        );
     }
 
     #[test]
@@ -876,6 +1551,39 @@ mod test {
         assert_eq!((s1.mask()).join().count(), 50);
     }
 
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn par_join_tuple() {
+        use crate::join::ParJoin;
+        use rayon::iter::ParallelIterator;
+
+        let mut w = World::new();
+        w.register::<Cvec>();
+        w.register::<CdenseVec>();
+        let mut s1: Storage<Cvec, _> = w.write_storage();
+        let mut s2: Storage<CdenseVec, _> = w.write_storage();
+
+        for i in 0..10_000 {
+            let entity = Entity::new(i, Generation::new(1));
+            s1.insert(entity, i.into()).unwrap();
+            if i % 3 == 0 {
+                s2.insert(entity, 0.into()).unwrap();
+            }
+        }
+
+        (&s1, &mut s2).par_join().for_each(|(a, b)| b.0 = a.0 + 1);
+
+        assert_eq!((&s2).par_join().count(), 3334);
+        for i in 0..10_000 {
+            let entity = Entity::new(i, Generation::new(1));
+            if i % 3 == 0 {
+                assert_eq!(s2.get(entity), Some(&CdenseVec(i + 1)));
+            } else {
+                assert_eq!(s2.get(entity), None);
+            }
+        }
+    }
+
     #[test]
     #[cfg(feature = "parallel")]
     fn par_storage_mask() {
@@ -988,6 +1696,263 @@ mod test {
         }
     }
 
+    #[test]
+    fn ticked_test_add() {
+        test_add::<TickedCvec>();
+    }
+    #[test]
+    fn ticked_test_sub() {
+        test_sub::<TickedCvec>();
+    }
+    #[test]
+    fn ticked_test_get_mut() {
+        test_get_mut::<TickedCvec>();
+    }
+    #[test]
+    fn ticked_test_clear() {
+        test_clear::<TickedCvec>();
+    }
+    #[test]
+    fn ticked_test_shrink_to_fit() {
+        test_shrink_to_fit::<TickedCvec>();
+    }
+
+    #[test]
+    fn ticked() {
+        let mut w = World::new();
+        w.register::<TickedCvec>();
+
+        let mut s1: Storage<TickedCvec, _> = w.write_storage();
+        let start = s1.change_tick();
+        let entities: Vec<Entity> = (0..10)
+            .map(|i| {
+                let entity = w.entities().create();
+                s1.insert(entity, i.into()).unwrap();
+                entity
+            })
+            .collect();
+        assert_eq!(s1.added(start).iter().count(), 10);
+        assert_eq!(s1.changed(start).iter().count(), 10);
+
+        let first_run = s1.change_tick();
+        assert!(s1.added(first_run).is_empty());
+        assert!(s1.changed(first_run).is_empty());
+
+        s1.get_mut(entities[3]).unwrap().0 += 1;
+        s1.insert(entities[4], 40.into()).unwrap();
+        s1.remove(entities[5]);
+        let late = w.entities().create();
+        s1.insert(late, 100.into()).unwrap();
+
+        let changed: Vec<Index> = s1.changed(first_run).iter().collect();
+        assert_eq!(changed, vec![entities[3].id(), entities[4].id(), late.id()]);
+        let added: Vec<Index> = s1.added(first_run).iter().collect();
+        assert_eq!(added, vec![late.id()]);
+
+        // Removed components aren't reported.
+        s1.remove(late);
+        assert_eq!(s1.added(first_run).iter().count(), 0);
+        assert_eq!(s1.changed(start).iter().count(), 9);
+    }
+
+    #[test]
+    fn sorted_test_add() {
+        test_add::<SortedCvec>();
+    }
+    #[test]
+    fn sorted_test_sub() {
+        test_sub::<SortedCvec>();
+    }
+    #[test]
+    fn sorted_test_get_mut() {
+        test_get_mut::<SortedCvec>();
+    }
+    #[test]
+    fn sorted_test_clear() {
+        test_clear::<SortedCvec>();
+    }
+    #[test]
+    fn sorted_test_shrink_to_fit() {
+        test_shrink_to_fit::<SortedCvec>();
+    }
+
+    #[test]
+    fn sorted() {
+        use crate::join::Join;
+
+        let mut w = World::new();
+        w.register::<SortedCvec>();
+        w.register::<Cvec>();
+        let entities: Vec<Entity> = [5, 3, 8, 1, 3]
+            .iter()
+            .map(|&i| w.create_entity().with(SortedCvec(i)).with(Cvec(i)).build())
+            .collect();
+        let order = |w: &World| -> Vec<u32> {
+            let keys = w.read_storage::<SortedCvec>();
+            let values = w.read_storage::<Cvec>();
+            (&values).sorted_join(&keys).map(|c| c.0).collect()
+        };
+
+        // The index isn't sorted before the first maintain.
+        assert_eq!(order(&w), vec![1, 3, 3, 5, 8]);
+        w.maintain();
+        assert_eq!(order(&w), vec![1, 3, 3, 5, 8]);
+
+        {
+            let mut keys = w.write_storage::<SortedCvec>();
+            let mut values = w.write_storage::<Cvec>();
+            keys.get_mut(entities[2]).unwrap().0 = 0;
+            values.get_mut(entities[2]).unwrap().0 = 0;
+            keys.remove(entities[0]);
+            // Reinserting must not yield the entity twice.
+            keys.remove(entities[1]);
+            keys.insert(entities[1], SortedCvec(3)).unwrap();
+            // Entities without a key are skipped.
+            values.remove(entities[3]);
+        }
+        assert_eq!(order(&w), vec![0, 3, 3]);
+        w.maintain();
+        assert_eq!(order(&w), vec![0, 3, 3]);
+
+        // Mutable joins in key order.
+        {
+            let keys = w.read_storage::<SortedCvec>();
+            let mut values = w.write_storage::<Cvec>();
+            for (i, value) in (&mut values).sorted_join(&keys).enumerate() {
+                value.0 = i as u32;
+            }
+        }
+        let values = w.read_storage::<Cvec>();
+        assert_eq!(values.get(entities[2]), Some(&Cvec(0)));
+        assert_eq!(values.get(entities[1]), Some(&Cvec(1)));
+        assert_eq!(values.get(entities[4]), Some(&Cvec(2)));
+    }
+
+    #[test]
+    fn double_buffered_test_add() {
+        test_add::<DoubleBufferedCvec>();
+    }
+    #[test]
+    fn double_buffered_test_sub() {
+        test_sub::<DoubleBufferedCvec>();
+    }
+    #[test]
+    fn double_buffered_test_clear() {
+        test_clear::<DoubleBufferedCvec>();
+    }
+    #[test]
+    fn double_buffered_test_shrink_to_fit() {
+        test_shrink_to_fit::<DoubleBufferedCvec>();
+    }
+
+    #[test]
+    fn double_buffered() {
+        let mut w = World::new();
+        w.register::<DoubleBufferedCvec>();
+        let e1 = w.create_entity().with(DoubleBufferedCvec(1)).build();
+        let e2 = w.create_entity().with(DoubleBufferedCvec(2)).build();
+
+        {
+            let mut s1 = w.write_storage::<DoubleBufferedCvec>();
+            s1.get_mut(e1).unwrap().0 = 10;
+            assert_eq!(s1.get(e1), Some(&DoubleBufferedCvec(1)));
+            assert_eq!(s1.get_mut(e1), Some(&mut DoubleBufferedCvec(10)));
+
+            // Replacing a component only changes the next frame, too.
+            assert_eq!(
+                s1.insert(e2, DoubleBufferedCvec(20)).unwrap(),
+                Some(DoubleBufferedCvec(2))
+            );
+            assert_eq!(s1.get(e2), Some(&DoubleBufferedCvec(2)));
+        }
+
+        w.maintain();
+        {
+            let mut s1 = w.write_storage::<DoubleBufferedCvec>();
+            assert_eq!(s1.get(e1), Some(&DoubleBufferedCvec(10)));
+            assert_eq!(s1.get(e2), Some(&DoubleBufferedCvec(20)));
+            // Writes build on the latest values.
+            s1.get_mut(e2).unwrap().0 += 1;
+            assert_eq!(s1.remove(e1), Some(DoubleBufferedCvec(10)));
+        }
+
+        w.maintain();
+        let s1 = w.read_storage::<DoubleBufferedCvec>();
+        assert_eq!(s1.get(e1), None);
+        assert_eq!(s1.get(e2), Some(&DoubleBufferedCvec(21)));
+    }
+
+    #[test]
+    fn flagged_readers_are_independent() {
+        let mut w = World::new();
+        w.register::<FlaggedCvec>();
+
+        let mut s1: Storage<FlaggedCvec, _> = w.write_storage();
+        let mut first = s1.register_reader();
+        let mut second = s1.register_reader();
+
+        let entity = w.entities().create();
+        s1.insert(entity, 1.into()).unwrap();
+        let ids: Vec<Index> = s1.channel().read(&mut first).map(|e| e.id()).collect();
+        assert_eq!(ids, vec![entity.id()]);
+
+        s1.get_mut(entity).unwrap().0 += 1;
+        s1.remove(entity);
+
+        // Each reader gets every event exactly once.
+        let events: Vec<ComponentEvent> = s1.channel().read(&mut first).cloned().collect();
+        assert_eq!(
+            events,
+            vec![
+                ComponentEvent::Modified(entity.id()),
+                ComponentEvent::Removed(entity.id())
+            ]
+        );
+        assert_eq!(s1.channel().read(&mut first).count(), 0);
+        assert_eq!(s1.channel().read(&mut second).count(), 3);
+        assert_eq!(s1.channel().read(&mut second).count(), 0);
+    }
+
+    #[test]
+    fn flagged_changes() {
+        use crate::{join::Join, storage::ComponentChanges};
+
+        let mut w = World::new();
+        w.register::<FlaggedCvec>();
+
+        let mut s1: Storage<FlaggedCvec, _> = w.write_storage();
+        let mut reader_id = s1.register_reader();
+        let mut changes = ComponentChanges::new();
+
+        let entities: Vec<_> = (0..4).map(|_| w.entities().create()).collect();
+        for (i, &entity) in entities.iter().enumerate() {
+            s1.insert(entity, (i as u32).into()).unwrap();
+        }
+        s1.get_mut(entities[1]).unwrap().0 += 1;
+        s1.remove(entities[2]);
+        s1.read_changes(&mut reader_id, &mut changes);
+
+        assert_eq!((&w.entities(), &changes.inserted).join().count(), 4);
+        assert_eq!(
+            (&w.entities(), &changes.modified)
+                .join()
+                .map(|(entity, _)| entity)
+                .collect::<Vec<_>>(),
+            vec![entities[1]]
+        );
+        assert_eq!(
+            (&w.entities(), &changes.removed)
+                .join()
+                .map(|(entity, _)| entity)
+                .collect::<Vec<_>>(),
+            vec![entities[2]]
+        );
+
+        changes.clear();
+        s1.read_changes(&mut reader_id, &mut changes);
+        assert!(changes.is_empty());
+    }
+
     #[test]
     fn entries() {
         use crate::{join::Join, storage::WriteStorage, world::Entities};
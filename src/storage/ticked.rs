@@ -0,0 +1,256 @@
+use std::{marker::PhantomData, ops::Deref};
+
+use hibitset::{BitSet, BitSetLike};
+
+use crate::{
+    storage::{DenseVecStorage, MaskedStorage, Storage, TryDefault, UnprotectedStorage},
+    world::{Component, Index},
+};
+
+/// A point in the history of a `TickedStorage`, compared to the ticks at which
+/// components were added and changed.
+///
+/// Ticks are counted per storage, so a tick of one storage means nothing to
+/// another one.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ChangeTick(u64);
+
+/// `UnprotectedStorage`s that know when their components were added and last
+/// changed.
+pub trait Ticked {
+    /// Returns the tick of the latest change to the storage.
+    fn change_tick(&self) -> ChangeTick;
+
+    /// Returns the tick at which the component at `id` was added.
+    ///
+    /// May return anything if the storage has no component at `id`.
+    fn added_tick(&self, id: Index) -> ChangeTick;
+
+    /// Returns the tick at which the component at `id` was last inserted or
+    /// accessed mutably.
+    ///
+    /// May return anything if the storage has no component at `id`.
+    fn changed_tick(&self, id: Index) -> ChangeTick;
+}
+
+/// Wrapper storage that stamps components with the tick at which they were
+/// added and last accessed mutably.
+///
+/// Unlike `FlaggedStorage`, it doesn't buffer any events: a system remembers
+/// the storage's `change_tick()` when it runs, and the next time asks for the
+/// components `added` or `changed` since then. Finding them needs a pass over
+/// the storage's mask, but nothing piles up if a system doesn't run for a
+/// while.
+///
+/// **Note:** Like with `FlaggedStorage`, joining over all components of a
+/// `TickedStorage` mutably marks all of them as changed.
+///
+/// # Examples
+///
+/// ```
+/// use specs::{
+///     prelude::*,
+///     storage::{ChangeTick, TickedStorage},
+/// };
+///
+/// struct Transform(f32);
+///
+/// impl Component for Transform {
+///     type Storage = TickedStorage<Self>;
+/// }
+///
+/// #[derive(Default)]
+/// struct Bounds(f32);
+///
+/// impl Component for Bounds {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// #[derive(Default)]
+/// struct UpdateBounds {
+///     last_run: ChangeTick,
+///     updated: usize,
+/// }
+///
+/// impl<'a> System<'a> for UpdateBounds {
+///     type SystemData = (ReadStorage<'a, Transform>, WriteStorage<'a, Bounds>);
+///
+///     fn run(&mut self, (transforms, mut bounds): Self::SystemData) {
+///         let changed = transforms.changed(self.last_run);
+///         for (transform, bounds, _) in (&transforms, &mut bounds, &changed).join() {
+///             bounds.0 = transform.0 + 1.0;
+///             self.updated += 1;
+///         }
+///         self.last_run = transforms.change_tick();
+///     }
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Transform>();
+/// world.register::<Bounds>();
+/// let moving = world.create_entity().with(Transform(0.0)).with(Bounds(0.0)).build();
+/// world.create_entity().with(Transform(5.0)).with(Bounds(0.0)).build();
+///
+/// let mut system = UpdateBounds::default();
+/// system.run_now(&world);
+/// assert_eq!(system.updated, 2);
+///
+/// world.write_storage::<Transform>().get_mut(moving).unwrap().0 = 2.0;
+/// system.run_now(&world);
+/// assert_eq!(system.updated, 3);
+/// assert_eq!(world.read_storage::<Bounds>().get(moving).unwrap().0, 3.0);
+/// ```
+pub struct TickedStorage<C, T = DenseVecStorage<C>> {
+    storage: T,
+    tick: u64,
+    // The ticks at which the components were added and changed, by index.
+    ticks: Vec<(u64, u64)>,
+    phantom: PhantomData<C>,
+}
+
+impl<C, T> TickedStorage<C, T> {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+
+        self.tick
+    }
+}
+
+impl<C, T> Default for TickedStorage<C, T>
+where
+    T: TryDefault,
+{
+    fn default() -> Self {
+        TickedStorage {
+            storage: T::unwrap_default(),
+            tick: 0,
+            ticks: Vec::new(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<C: Component, T: UnprotectedStorage<C>> UnprotectedStorage<C> for TickedStorage<C, T> {
+    #[cfg(feature = "nightly")]
+    type AccessMut<'a> where T: 'a = <T as UnprotectedStorage<C>>::AccessMut<'a>;
+
+    unsafe fn clean<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        self.storage.clean(has);
+    }
+
+    unsafe fn get(&self, id: Index) -> &C {
+        self.storage.get(id)
+    }
+
+    #[cfg(feature = "nightly")]
+    unsafe fn get_mut(&mut self, id: Index) -> <T as UnprotectedStorage<C>>::AccessMut<'_> {
+        let tick = self.next_tick();
+        self.ticks.get_unchecked_mut(id as usize).1 = tick;
+        self.storage.get_mut(id)
+    }
+
+    #[cfg(not(feature = "nightly"))]
+    unsafe fn get_mut(&mut self, id: Index) -> &mut C {
+        let tick = self.next_tick();
+        self.ticks.get_unchecked_mut(id as usize).1 = tick;
+        self.storage.get_mut(id)
+    }
+
+    unsafe fn insert(&mut self, id: Index, comp: C) {
+        let tick = self.next_tick();
+        let index = id as usize;
+        if self.ticks.len() <= index {
+            self.ticks.resize(index + 1, (0, 0));
+        }
+        self.ticks[index] = (tick, tick);
+        self.storage.insert(id, comp);
+    }
+
+    unsafe fn remove(&mut self, id: Index) -> C {
+        self.storage.remove(id)
+    }
+
+    unsafe fn drop(&mut self, id: Index) {
+        self.storage.drop(id);
+    }
+
+    unsafe fn shrink_to_fit<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        let len = (&has).iter().last().map_or(0, |id| id as usize + 1);
+        self.ticks.truncate(len);
+        self.ticks.shrink_to_fit();
+        self.storage.shrink_to_fit(has);
+    }
+
+    unsafe fn maintain<B>(&mut self, has: B)
+    where
+        B: BitSetLike,
+    {
+        self.storage.maintain(has);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.storage.reserve(additional);
+    }
+
+    fn capacity(&self, len: usize) -> usize {
+        self.storage.capacity(len)
+    }
+
+    fn allocated_bytes(&self, len: usize) -> usize {
+        self.storage.allocated_bytes(len)
+            + self.ticks.capacity() * std::mem::size_of::<(u64, u64)>()
+    }
+}
+
+impl<C, T> Ticked for TickedStorage<C, T> {
+    fn change_tick(&self) -> ChangeTick {
+        ChangeTick(self.tick)
+    }
+
+    fn added_tick(&self, id: Index) -> ChangeTick {
+        ChangeTick(self.ticks.get(id as usize).map_or(0, |ticks| ticks.0))
+    }
+
+    fn changed_tick(&self, id: Index) -> ChangeTick {
+        ChangeTick(self.ticks.get(id as usize).map_or(0, |ticks| ticks.1))
+    }
+}
+
+impl<'e, T, D> Storage<'e, T, D>
+where
+    T: Component,
+    T::Storage: Ticked,
+    D: Deref<Target = MaskedStorage<T>>,
+{
+    /// Returns the tick of the latest change to this storage. Passing it to
+    /// `added` or `changed` later on returns the components which were added
+    /// or changed in between.
+    pub fn change_tick(&self) -> ChangeTick {
+        self.data.inner.change_tick()
+    }
+
+    /// Returns the indices of the components which were added after `since`.
+    pub fn added(&self, since: ChangeTick) -> BitSet {
+        let inner = &self.data.inner;
+        self.mask()
+            .iter()
+            .filter(|&id| inner.added_tick(id) > since)
+            .collect()
+    }
+
+    /// Returns the indices of the components which were added or accessed
+    /// mutably after `since`.
+    pub fn changed(&self, since: ChangeTick) -> BitSet {
+        let inner = &self.data.inner;
+        self.mask()
+            .iter()
+            .filter(|&id| inner.changed_tick(id) > since)
+            .collect()
+    }
+}
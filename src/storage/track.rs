@@ -1,5 +1,6 @@
 use std::ops::{Deref, DerefMut};
 
+use hibitset::{BitSet, BitSetLike};
 use shrev::{EventChannel, ReaderId};
 
 use crate::{
@@ -42,6 +43,65 @@ pub enum ComponentEvent {
     Removed(Index),
 }
 
+impl ComponentEvent {
+    /// Returns the index of the entity the event is about.
+    pub fn id(&self) -> Index {
+        match *self {
+            ComponentEvent::Inserted(id)
+            | ComponentEvent::Modified(id)
+            | ComponentEvent::Removed(id) => id,
+        }
+    }
+}
+
+/// The indices of the components inserted, modified and removed according
+/// to the `ComponentEvent`s read from a tracked storage, see
+/// `Storage::read_changes`.
+///
+/// An index is added to every set it has an event for, so a component which
+/// was inserted and removed again since the last read is in both `inserted`
+/// and `removed`.
+#[derive(Clone, Debug, Default)]
+pub struct ComponentChanges {
+    /// The indices of inserted components.
+    pub inserted: BitSet,
+    /// The indices of modified components.
+    pub modified: BitSet,
+    /// The indices of removed components.
+    pub removed: BitSet,
+}
+
+impl ComponentChanges {
+    /// Creates empty sets.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Clears all sets, e.g. before reading the events of the next frame.
+    pub fn clear(&mut self) {
+        self.inserted.clear();
+        self.modified.clear();
+        self.removed.clear();
+    }
+
+    /// Returns `true` if there are no changes.
+    pub fn is_empty(&self) -> bool {
+        self.inserted.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl<'a> Extend<&'a ComponentEvent> for ComponentChanges {
+    fn extend<I: IntoIterator<Item = &'a ComponentEvent>>(&mut self, events: I) {
+        for event in events {
+            match *event {
+                ComponentEvent::Inserted(id) => self.inserted.add(id),
+                ComponentEvent::Modified(id) => self.modified.add(id),
+                ComponentEvent::Removed(id) => self.removed.add(id),
+            };
+        }
+    }
+}
+
 impl<'e, T, D> Storage<'e, T, D>
 where
     T: Component,
@@ -53,6 +113,47 @@ where
         unsafe { self.open() }.1.channel()
     }
 
+    /// Reads the events `reader` hasn't seen yet into `changes`, which can
+    /// then be joined over instead of scanning the whole storage.
+    ///
+    /// `changes` isn't cleared, so changes can be accumulated over several
+    /// reads.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::{prelude::*, storage::ComponentChanges};
+    ///
+    /// struct Transform(f32);
+    ///
+    /// impl Component for Transform {
+    ///     type Storage = FlaggedStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Transform>();
+    /// let mut reader = world.write_storage::<Transform>().register_reader();
+    /// let moved = world.create_entity().with(Transform(0.0)).build();
+    /// world.create_entity().with(Transform(0.0)).build();
+    ///
+    /// let mut changes = ComponentChanges::new();
+    /// world.read_storage::<Transform>().read_changes(&mut reader, &mut changes);
+    /// assert_eq!((&world.entities(), &changes.inserted).join().count(), 2);
+    ///
+    /// changes.clear();
+    /// world.write_storage::<Transform>().get_mut(moved).unwrap().0 = 1.0;
+    /// world.read_storage::<Transform>().read_changes(&mut reader, &mut changes);
+    /// let transforms = world.read_storage::<Transform>();
+    /// assert_eq!((&transforms, &changes.modified).join().count(), 1);
+    /// ```
+    pub fn read_changes(
+        &self,
+        reader: &mut ReaderId<ComponentEvent>,
+        changes: &mut ComponentChanges,
+    ) {
+        changes.extend(self.channel().read(reader));
+    }
+
     /// Returns the actual state of the event emission.
     #[cfg(feature = "storage-event-control")]
     pub fn event_emission(&self) -> bool {
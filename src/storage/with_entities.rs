@@ -0,0 +1,183 @@
+use std::{
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use hibitset::BitSet;
+
+#[cfg(feature = "parallel")]
+use crate::{join::ParJoin, storage::DistinctStorage};
+use crate::{
+    join::Join,
+    storage::{AccessMutReturn, MaskedStorage, Storage, UnprotectedStorage},
+    world::{Component, EntitiesRes, Entity, Index},
+};
+
+/// A `Join`-able view of a storage which yields the entities together with
+/// their components, created by `Storage::with_entities` and
+/// `Storage::with_entities_mut`.
+pub struct WithEntities<'a, T, S> {
+    entities: &'a EntitiesRes,
+    mask: &'a BitSet,
+    storage: S,
+    phantom: PhantomData<T>,
+}
+
+impl<'e, T, D> Storage<'e, T, D>
+where
+    T: Component,
+    D: Deref<Target = MaskedStorage<T>>,
+{
+    /// Returns a `Join`-able view of this storage which yields each entity
+    /// together with its component, without fetching `Entities`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// struct Pos(f32);
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// struct Vel(f32);
+    ///
+    /// impl Component for Vel {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// world.register::<Vel>();
+    /// let still = world.create_entity().with(Pos(1.0)).build();
+    /// let moving = world.create_entity().with(Pos(2.0)).with(Vel(3.0)).build();
+    ///
+    /// let pos = world.read_storage::<Pos>();
+    /// let vel = world.read_storage::<Vel>();
+    /// let all: Vec<_> = pos.with_entities().join().map(|(e, pos)| (e, pos.0)).collect();
+    /// assert_eq!(all, vec![(still, 1.0), (moving, 2.0)]);
+    ///
+    /// for ((entity, pos), vel) in (pos.with_entities(), &vel).join() {
+    ///     assert_eq!((entity, pos.0, vel.0), (moving, 2.0, 3.0));
+    /// }
+    /// ```
+    pub fn with_entities(&self) -> WithEntities<'_, T, &T::Storage> {
+        WithEntities {
+            entities: &self.entities,
+            mask: &self.data.mask,
+            storage: &self.data.inner,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'e, T, D> Storage<'e, T, D>
+where
+    T: Component,
+    D: DerefMut<Target = MaskedStorage<T>>,
+{
+    /// Returns a `Join`-able view of this storage which yields each entity
+    /// together with its component, mutably. See `with_entities`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// struct Health(u32);
+    ///
+    /// impl Component for Health {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Health>();
+    /// let dying = world.create_entity().with(Health(0)).build();
+    /// let alive = world.create_entity().with(Health(5)).build();
+    ///
+    /// let mut health = world.write_storage::<Health>();
+    /// let mut dead = Vec::new();
+    /// for (entity, health) in health.with_entities_mut().join() {
+    ///     if health.0 == 0 {
+    ///         dead.push(entity);
+    ///     } else {
+    ///         health.0 -= 1;
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(dead, vec![dying]);
+    /// assert_eq!(health.get(alive).unwrap().0, 4);
+    /// ```
+    pub fn with_entities_mut(&mut self) -> WithEntities<'_, T, &mut T::Storage> {
+        let (mask, storage) = self.data.open_mut();
+
+        WithEntities {
+            entities: &self.entities,
+            mask,
+            storage,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Join for WithEntities<'a, T, &'a T::Storage>
+where
+    T: Component,
+{
+    type Mask = &'a BitSet;
+    type Type = (Entity, &'a T);
+    type Value = (&'a EntitiesRes, &'a T::Storage);
+
+    // SAFETY: No unsafe code and no invariants.
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        (self.mask, (self.entities, self.storage))
+    }
+
+    // SAFETY: Since we require that the mask was checked, an element for `i` must
+    // have been inserted without being removed.
+    unsafe fn get((entities, storage): &mut Self::Value, i: Index) -> Self::Type {
+        (entities.entity(i), storage.get(i))
+    }
+}
+
+// SAFETY: `get` only reads the storage, which is `Sync`.
+#[cfg(feature = "parallel")]
+unsafe impl<'a, T> ParJoin for WithEntities<'a, T, &'a T::Storage>
+where
+    T: Component,
+    T::Storage: Sync,
+{
+}
+
+impl<'a, T> Join for WithEntities<'a, T, &'a mut T::Storage>
+where
+    T: Component,
+{
+    type Mask = &'a BitSet;
+    type Type = (Entity, AccessMutReturn<'a, T>);
+    type Value = (&'a EntitiesRes, &'a mut T::Storage);
+
+    // SAFETY: No unsafe code and no invariants.
+    unsafe fn open(self) -> (Self::Mask, Self::Value) {
+        (self.mask, (self.entities, self.storage))
+    }
+
+    // SAFETY: Since we require that the mask was checked, an element for `i` must
+    // have been inserted without being removed. Like the join over
+    // `&mut Storage`, this relies on every index being yielded once.
+    unsafe fn get((entities, storage): &mut Self::Value, i: Index) -> Self::Type {
+        let storage: *mut T::Storage = *storage as *mut T::Storage;
+        (entities.entity(i), (*storage).get_mut(i))
+    }
+}
+
+// SAFETY: This is safe because of the `DistinctStorage` guarantees.
+#[cfg(feature = "parallel")]
+unsafe impl<'a, T> ParJoin for WithEntities<'a, T, &'a mut T::Storage>
+where
+    T: Component,
+    T::Storage: Sync + DistinctStorage,
+{
+}
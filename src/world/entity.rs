@@ -1,11 +1,14 @@
 use std::{
-    fmt,
+    collections::VecDeque,
+    fmt, mem,
     num::NonZeroI32,
+    ops::Range,
     sync::atomic::{AtomicUsize, Ordering},
 };
 
 use hibitset::{AtomicBitSet, BitSet, BitSetOr};
 use shred::Read;
+use shrev::{EventChannel, EventIterator, ReaderId};
 
 #[cfg(feature = "parallel")]
 use crate::join::ParJoin;
@@ -14,6 +17,23 @@ use crate::{error::WrongGeneration, join::Join, storage::WriteStorage, world::Co
 /// An index is basically the id of an `Entity`.
 pub type Index = u32;
 
+/// The number of indices a `BitSet` can hold: 2^24 on 64-bit targets and
+/// 2^20 on 32-bit ones.
+const CAPACITY: usize = BitSet::BITS_PER_USIZE
+    * BitSet::BITS_PER_USIZE
+    * BitSet::BITS_PER_USIZE
+    * BitSet::BITS_PER_USIZE;
+
+/// Panics if the indices below `end` don't fit into a `BitSet`.
+fn check_capacity(end: usize) {
+    assert!(
+        end <= CAPACITY,
+        "entity index space exhausted: index {} doesn't fit into the {} indices of a `BitSet`",
+        end - 1,
+        CAPACITY
+    );
+}
+
 /// A wrapper for a read `Entities` resource.
 /// Note that this is just `Read<Entities>`, so
 /// you can easily use it in your system:
@@ -56,6 +76,41 @@ pub(crate) struct Allocator {
     killed: AtomicBitSet,
     cache: EntityCache,
     max_id: AtomicUsize,
+
+    policy: ReusePolicy,
+    /// Indices freed in the current frame, reused after the `Delayed` policy
+    /// has aged them, or all indices freed with the `Never` policy.
+    freed: Vec<Index>,
+    /// The indices freed in each past frame, the oldest first.
+    quarantine: VecDeque<Vec<Index>>,
+}
+
+/// How the indices of deleted entities are reused for new entities, see
+/// `EntitiesRes::set_reuse_policy`.
+///
+/// Reusing an index raises its `Generation`, so stale `Entity` handles are
+/// never confused with new ones. Delaying the reuse still helps if indices
+/// are stored elsewhere, e.g. in a physics engine or a network packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReusePolicy {
+    /// Indices are reused as soon as their entities are deleted.
+    Immediate,
+    /// The indices of entities deleted in a frame, which ends with
+    /// `World::maintain`, are reused after this many more frames.
+    Delayed(u32),
+    /// Indices are never reused, so every entity gets a new one.
+    ///
+    /// This uses up the index space: creating more than 2^24 entities in
+    /// total, 2^20 on 32-bit targets, panics, no matter how many of them
+    /// are alive. The indices of deleted entities are kept aside, so they
+    /// are reused after switching to another policy.
+    Never,
+}
+
+impl Default for ReusePolicy {
+    fn default() -> Self {
+        ReusePolicy::Immediate
+    }
 }
 
 impl Allocator {
@@ -80,7 +135,7 @@ impl Allocator {
             self.generations[id].die();
         }
 
-        self.cache.extend(delete.iter().map(|e| e.0));
+        self.free(delete.iter().map(|e| e.0));
 
         Ok(())
     }
@@ -117,6 +172,19 @@ impl Allocator {
             }
     }
 
+    /// Returns `true` if the entity is alive, but was killed atomically.
+    pub fn is_pending_deletion(&self, e: Entity) -> bool {
+        self.killed.contains(e.id()) && self.is_alive(e)
+    }
+
+    /// Returns the entities killed atomically, which are deleted by the next
+    /// `merge`.
+    pub fn pending_deletions(&self) -> Vec<Entity> {
+        use hibitset::BitSetLike;
+
+        (&self.killed).iter().map(|id| self.entity(id)).collect()
+    }
+
     /// Returns the `Generation` of the given `Index`, if any.
     pub fn generation(&self, id: Index) -> Option<Generation> {
         self.generations
@@ -136,10 +204,21 @@ impl Allocator {
         Entity(id, gen)
     }
 
+    /// Returns the alive entity with the given `Index`, if any.
+    pub fn live_entity(&self, id: Index) -> Option<Entity> {
+        if self.alive.contains(id) || self.raised.contains(id) {
+            Some(self.entity(id))
+        } else {
+            None
+        }
+    }
+
     /// Allocate a new entity
     pub fn allocate_atomic(&self) -> Entity {
         let id = self.cache.pop_atomic().unwrap_or_else(|| {
-            atomic_increment(&self.max_id).expect("No entity left to allocate") as Index
+            let id = atomic_increment(&self.max_id).expect("No entity left to allocate");
+            check_capacity(id + 1);
+            id as Index
         });
 
         self.raised.add_atomic(id);
@@ -150,11 +229,34 @@ impl Allocator {
         Entity(id, gen)
     }
 
+    /// Allocates `n` new entities, taking the indices of deleted entities
+    /// first and a block of consecutive new indices for the rest.
+    pub fn allocate_many_atomic(&self, n: usize) -> (Vec<Index>, Range<Index>) {
+        let mut reused = Vec::new();
+        while reused.len() < n {
+            match self.cache.pop_atomic() {
+                Some(id) => reused.push(id),
+                None => break,
+            }
+        }
+
+        let fresh = n - reused.len();
+        let start = atomic_add(&self.max_id, fresh).expect("No entity left to allocate");
+        check_capacity(start + fresh);
+        let ids = start as Index..(start + fresh) as Index;
+        for id in reused.iter().cloned().chain(ids.clone()) {
+            self.raised.add_atomic(id);
+        }
+
+        (reused, ids)
+    }
+
     /// Allocate a new entity
     pub fn allocate(&mut self) -> Entity {
         let id = self.cache.pop().unwrap_or_else(|| {
             let id = *self.max_id.get_mut();
             *self.max_id.get_mut() = id.checked_add(1).expect("No entity left to allocate");
+            check_capacity(id + 1);
             id as Index
         });
 
@@ -190,11 +292,58 @@ impl Allocator {
         }
         self.killed.clear();
 
-        self.cache.extend(deleted.iter().map(|e| e.0));
+        self.free(deleted.iter().map(|e| e.0));
+        if let ReusePolicy::Delayed(frames) = self.policy {
+            self.quarantine.push_back(mem::take(&mut self.freed));
+            while self.quarantine.len() > frames as usize {
+                let ids = self.quarantine.pop_front().unwrap();
+                self.cache.extend(ids);
+            }
+        }
 
         deleted
     }
 
+    /// Makes the indices of deleted entities available according to the
+    /// policy.
+    fn free<I: IntoIterator<Item = Index>>(&mut self, ids: I) {
+        match self.policy {
+            ReusePolicy::Immediate => self.cache.extend(ids),
+            ReusePolicy::Delayed(_) | ReusePolicy::Never => self.freed.extend(ids),
+        }
+    }
+
+    /// Sets how the indices of deleted entities are reused.
+    pub fn set_reuse_policy(&mut self, policy: ReusePolicy) {
+        self.policy = policy;
+        match policy {
+            ReusePolicy::Immediate => {
+                let freed = mem::take(&mut self.freed);
+                for ids in self.quarantine.drain(..).chain(Some(freed)) {
+                    self.cache.extend(ids);
+                }
+            }
+            ReusePolicy::Delayed(_) => {}
+            ReusePolicy::Never => {
+                let cached = self.cache.take();
+                self.freed.extend(cached);
+            }
+        }
+    }
+
+    /// Adds `n` new indices to the ones reused first, so creating the next
+    /// `n` entities doesn't grow the generations.
+    pub fn reserve_indices(&mut self, n: usize) {
+        let start = *self.max_id.get_mut();
+        let end = start.checked_add(n).expect("No entity left to allocate");
+        check_capacity(end);
+        *self.max_id.get_mut() = end;
+        if n > 0 {
+            self.update_generation_length(end - 1);
+        }
+        self.cache.extend((start as Index..end as Index).rev());
+    }
+
     fn update_generation_length(&mut self, i: usize) {
         if self.generations.len() <= i as usize {
             self.generations
@@ -218,6 +367,33 @@ impl<'a> Iterator for CreateIterAtomic<'a> {
     }
 }
 
+/// An iterator over a batch of entities created at once.
+///
+/// Returned from `Entities::create_many`.
+pub struct CreateManyAtomic<'a> {
+    alloc: &'a Allocator,
+    reused: std::vec::IntoIter<Index>,
+    ids: Range<Index>,
+}
+
+impl<'a> Iterator for CreateManyAtomic<'a> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Entity> {
+        self.reused
+            .next()
+            .or_else(|| self.ids.next())
+            .map(|id| self.alloc.entity(id))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.reused.len() + self.ids.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a> ExactSizeIterator for CreateManyAtomic<'a> {}
+
 /// `Entity` type, as seen by the user.
 #[derive(Clone, Copy, Debug, Hash, Eq, Ord, PartialEq, PartialOrd)]
 pub struct Entity(Index, Generation);
@@ -252,6 +428,63 @@ impl Entity {
 ///
 /// You need to call `World::maintain` after creating / deleting
 /// entities with this struct.
+///
+/// ## Joining
+///
+/// `&EntitiesRes` can be joined over to get the entities together with their
+/// components. It's `ParJoin` as well if the `parallel` feature is enabled,
+/// which it is by default:
+///
+/// ```
+/// # #[cfg(feature = "parallel")]
+/// # {
+/// use specs::prelude::*;
+///
+/// struct Owner(Option<Entity>);
+///
+/// impl Component for Owner {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Owner>();
+/// for _ in 0..100 {
+///     world.create_entity().with(Owner(None)).build();
+/// }
+///
+/// let entities = world.entities();
+/// let mut owners = world.write_storage::<Owner>();
+/// (&entities, &mut owners).par_join().for_each(|(entity, owner)| {
+///     owner.0 = Some(entity);
+/// });
+///
+/// for (entity, owner) in (&entities, &owners).join() {
+///     assert_eq!(owner.0, Some(entity));
+/// }
+/// # }
+/// ```
+///
+/// ## Limits
+///
+/// Every `Index` has to fit into a `BitSet`, which holds 2^24 indices on
+/// 64-bit targets and 2^20 on 32-bit ones. Creating an entity beyond that
+/// panics with "entity index space exhausted".
+///
+/// With the default `ReusePolicy::Immediate`, `create` and `create_many`
+/// reuse the indices of deleted entities, with their `Generation` raised, so
+/// churning entities doesn't use up the index space and only the number of
+/// entities alive at the same time is limited. With `ReusePolicy::Delayed`,
+/// the entities deleted in the delayed frames count as well. With
+/// `ReusePolicy::Never`, every index is used once, so the limit applies to
+/// all entities ever created. Indices reserved with `reserve_indices` count
+/// until they are taken.
+///
+/// The number of layers of the bitsets and the `Index` type are fixed: they
+/// are defined by `hibitset`, whose `BitSet` is used by every storage and
+/// join, so they can't be configured per `World`.
+///
+/// Every index can be reused about 2^31 times before its generation
+/// overflows, which panics.
 #[derive(Debug, Default)]
 pub struct EntitiesRes {
     pub(crate) alloc: Allocator,
@@ -280,6 +513,35 @@ impl EntitiesRes {
         CreateIterAtomic(&self.alloc)
     }
 
+    /// Creates `n` new entities atomically, e.g. for spawning particles.
+    /// They will be persistent as soon as you call `World::maintain`.
+    ///
+    /// The entities are created right away, even if the returned iterator
+    /// isn't consumed. They take the indices of deleted entities first, like
+    /// `create` does, and a block of consecutive new indices, reserved at
+    /// once, for the rest.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// let mut world = World::new();
+    /// let particles: Vec<Entity> = world.entities().create_many(1000).collect();
+    /// world.maintain();
+    ///
+    /// assert_eq!(particles[999].id(), particles[0].id() + 999);
+    /// assert!(particles.iter().all(|&e| world.is_alive(e)));
+    /// ```
+    pub fn create_many(&self, n: usize) -> CreateManyAtomic<'_> {
+        let (reused, ids) = self.alloc.allocate_many_atomic(n);
+        CreateManyAtomic {
+            alloc: &self.alloc,
+            reused: reused.into_iter(),
+            ids,
+        }
+    }
+
     /// Similar to the `create` method above this
     /// creates an entity atomically, and then returns a
     /// builder which can be used to insert components into
@@ -301,16 +563,122 @@ impl EntitiesRes {
     }
 
     /// Returns an entity with a given `id`. There's no guarantee for validity,
-    /// meaning the entity could be not alive.
+    /// meaning the entity could be not alive, see `live_entity`.
     pub fn entity(&self, id: Index) -> Entity {
         self.alloc.entity(id)
     }
 
+    /// Returns the alive entity with a given `id`, with its current
+    /// generation, or `None` if no entity with that index is alive. Use this
+    /// to get an entity back from an index stored somewhere else, e.g. in a
+    /// physics engine or a network packet.
+    ///
+    /// Like `is_alive`, this includes the entities created with `create` and
+    /// the ones deleted with `delete` until the next `World::maintain`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// let mut world = World::new();
+    /// let e = world.create_entity().build();
+    /// let body_id = e.id();
+    ///
+    /// assert_eq!(world.entities().live_entity(body_id), Some(e));
+    /// world.delete_entity(e).unwrap();
+    /// assert_eq!(world.entities().live_entity(body_id), None);
+    /// ```
+    pub fn live_entity(&self, id: Index) -> Option<Entity> {
+        self.alloc.live_entity(id)
+    }
+
     /// Returns `true` if the specified entity is alive.
+    ///
+    /// Entities created with `create` are alive right away, and entities
+    /// deleted with `delete` stay alive until the next `World::maintain`,
+    /// see `is_pending_deletion`. This is cheap enough to validate entities
+    /// stored in resources or components before every use.
     #[inline]
     pub fn is_alive(&self, e: Entity) -> bool {
         self.alloc.is_alive(e)
     }
+
+    /// Sets how the indices of deleted entities are reused, see
+    /// `ReusePolicy`. Defaults to `ReusePolicy::Immediate`.
+    ///
+    /// Switching to `Immediate` makes the indices waiting for a `Delayed`
+    /// policy or kept aside by `Never` available right away. Switching to
+    /// `Never` keeps all free indices aside until the policy changes again.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::{prelude::*, world::ReusePolicy};
+    ///
+    /// let mut world = World::new();
+    /// world.entities_mut().set_reuse_policy(ReusePolicy::Delayed(1));
+    ///
+    /// let a = world.create_entity().build();
+    /// world.delete_entity(a).unwrap();
+    /// world.maintain();
+    /// assert_ne!(world.create_entity().build().id(), a.id());
+    ///
+    /// world.maintain();
+    /// assert_eq!(world.create_entity().build().id(), a.id());
+    /// ```
+    pub fn set_reuse_policy(&mut self, policy: ReusePolicy) {
+        self.alloc.set_reuse_policy(policy);
+    }
+
+    /// Returns how the indices of deleted entities are reused.
+    pub fn reuse_policy(&self) -> ReusePolicy {
+        self.alloc.policy
+    }
+
+    /// Reserves `n` unused indices, which are taken by the next entities
+    /// created before any other, so creating them doesn't grow the
+    /// allocator. They are taken in increasing order.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// let mut world = World::new();
+    /// world.entities_mut().reserve_indices(100);
+    ///
+    /// let first = world.create_entity().build();
+    /// let second = world.entities().create();
+    /// assert_eq!(second.id(), first.id() + 1);
+    /// ```
+    pub fn reserve_indices(&mut self, n: usize) {
+        self.alloc.reserve_indices(n);
+    }
+
+    /// Returns `true` if the specified entity is alive, but was deleted with
+    /// `delete` and will be deleted on the next `World::maintain`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// let mut world = World::new();
+    /// let e = world.create_entity().build();
+    ///
+    /// world.entities().delete(e).unwrap();
+    /// assert!(world.entities().is_alive(e));
+    /// assert!(world.entities().is_pending_deletion(e));
+    ///
+    /// world.maintain();
+    /// assert!(!world.entities().is_alive(e));
+    /// assert!(!world.entities().is_pending_deletion(e));
+    /// ```
+    #[inline]
+    pub fn is_pending_deletion(&self, e: Entity) -> bool {
+        self.alloc.is_pending_deletion(e)
+    }
 }
 
 impl<'a> Join for &'a EntitiesRes {
@@ -411,7 +779,8 @@ impl Generation {
     /// Panics if it is alive.
     fn raised(self) -> Generation {
         assert!(!self.is_alive());
-        unsafe { Generation(NonZeroI32::new_unchecked(1 - self.id())) }
+        let gen = 1i32.checked_sub(self.id()).expect("generation overflow");
+        unsafe { Generation(NonZeroI32::new_unchecked(gen)) }
     }
 }
 
@@ -466,6 +835,56 @@ impl ZeroableGeneration {
     }
 }
 
+/// Publishes the entities which were deleted, when their components are
+/// dropped: by `WorldExt::maintain` for the ones deleted through `Entities`,
+/// or right away by `WorldExt::delete_entity` and the like.
+///
+/// This lets systems clean up after deleted entities, e.g. remove them from
+/// a spatial index, without tracking which ones they've seen themselves.
+/// The resource is added by `WorldExt::new`.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::{prelude::*, world::DeletedEntities};
+///
+/// let mut world = World::new();
+/// let mut reader = world.write_resource::<DeletedEntities>().register_reader();
+///
+/// let a = world.create_entity().build();
+/// let b = world.create_entity().build();
+/// world.delete_entity(a).unwrap();
+/// world.entities().delete(b).unwrap();
+/// world.maintain();
+///
+/// let deleted = world.read_resource::<DeletedEntities>();
+/// assert_eq!(deleted.read(&mut reader).collect::<Vec<_>>(), vec![&a, &b]);
+/// ```
+#[derive(Debug, Default)]
+pub struct DeletedEntities {
+    channel: EventChannel<Entity>,
+}
+
+impl DeletedEntities {
+    /// Starts reading deleted entities. Like the readers of tracked
+    /// storages, it should be read every frame, otherwise the deleted
+    /// entities pile up waiting for it.
+    pub fn register_reader(&mut self) -> ReaderId<Entity> {
+        self.channel.register_reader()
+    }
+
+    /// Returns the entities deleted since `reader` was last read.
+    pub fn read(&self, reader: &mut ReaderId<Entity>) -> EventIterator<'_, Entity> {
+        self.channel.read(reader)
+    }
+
+    pub(crate) fn publish(&mut self, deleted: &[Entity]) {
+        if self.channel.would_write() {
+            self.channel.iter_write(deleted.iter().cloned());
+        }
+    }
+}
+
 #[derive(Default, Debug)]
 struct EntityCache {
     cache: Vec<Index>,
@@ -487,6 +906,13 @@ impl EntityCache {
     fn maintain(&mut self) {
         self.cache.truncate(*(self.len.get_mut()));
     }
+
+    /// Removes all indices from the cache.
+    fn take(&mut self) -> Vec<Index> {
+        self.maintain();
+        *self.len.get_mut() = 0;
+        mem::take(&mut self.cache)
+    }
 }
 
 impl Extend<Index> for EntityCache {
@@ -501,10 +927,16 @@ impl Extend<Index> for EntityCache {
 /// Resembles a `fetch_add(1, Ordering::Relaxed)` with
 /// checked overflow, returning `None` instead.
 fn atomic_increment(i: &AtomicUsize) -> Option<usize> {
-    use std::usize;
+    atomic_add(i, 1)
+}
+
+/// Adds `n` to `i` atomically without wrapping on overflow.
+/// Resembles a `fetch_add(n, Ordering::Relaxed)` with
+/// checked overflow, returning `None` instead.
+fn atomic_add(i: &AtomicUsize, n: usize) -> Option<usize> {
     let mut prev = i.load(Ordering::Relaxed);
-    while prev != usize::MAX {
-        match i.compare_exchange_weak(prev, prev + 1, Ordering::Relaxed, Ordering::Relaxed) {
+    while let Some(next) = prev.checked_add(n) {
+        match i.compare_exchange_weak(prev, next, Ordering::Relaxed, Ordering::Relaxed) {
             Ok(x) => return Some(x),
             Err(next_prev) => prev = next_prev,
         }
@@ -552,6 +984,169 @@ mod tests {
         assert_eq!(allocator.merge(), vec![entity]);
     }
 
+    #[test]
+    fn churn_reuses_indices() {
+        let mut allocator = Allocator::default();
+        for i in 1..10_000 {
+            let entity = allocator.allocate();
+            assert_eq!((entity.id(), entity.gen().id()), (0, i));
+            allocator.kill(&[entity]).unwrap();
+        }
+    }
+
+    #[test]
+    fn live_entity() {
+        let mut allocator = Allocator::default();
+        assert_eq!(allocator.live_entity(0), None);
+
+        let old = allocator.allocate();
+        allocator.kill(&[old]).unwrap();
+        assert_eq!(allocator.live_entity(old.id()), None);
+
+        let atomic = allocator.allocate_atomic();
+        assert_eq!(atomic.id(), old.id());
+        assert_eq!(allocator.live_entity(old.id()), Some(atomic));
+        allocator.merge();
+        assert_eq!(allocator.live_entity(old.id()), Some(atomic));
+        assert_eq!(allocator.live_entity(1), None);
+    }
+
+    #[test]
+    fn pending_deletion() {
+        let mut allocator = Allocator::default();
+        let old = allocator.allocate();
+        allocator.kill(&[old]).unwrap();
+        let entity = allocator.allocate();
+        assert_eq!(entity.id(), old.id());
+
+        allocator.kill_atomic(entity).unwrap();
+        assert!(allocator.is_pending_deletion(entity));
+        assert!(!allocator.is_pending_deletion(old));
+
+        allocator.merge();
+        assert!(!allocator.is_pending_deletion(entity));
+    }
+
+    #[test]
+    fn create_many_reuses_indices_first() {
+        let mut allocator = Allocator::default();
+        let first = allocator.allocate();
+        allocator.kill(&[first]).unwrap();
+
+        let (reused, ids) = allocator.allocate_many_atomic(100);
+        assert_eq!(reused, vec![first.id()]);
+        assert_eq!(ids, 1..100);
+        assert_eq!(allocator.entity(first.id()).gen().id(), first.gen().id() + 1);
+
+        allocator.merge();
+        let all: Vec<_> = reused.into_iter().chain(ids).collect();
+        assert!(all.iter().all(|&id| allocator.is_alive(allocator.entity(id))));
+        assert_eq!(allocator.allocate_many_atomic(0), (vec![], 100..100));
+
+        // Churning batches doesn't use up the index space.
+        for _ in 0..10 {
+            let entities: Vec<_> = all.iter().map(|&id| allocator.entity(id)).collect();
+            allocator.kill(&entities).unwrap();
+            let (reused, ids) = allocator.allocate_many_atomic(100);
+            assert_eq!((reused.len(), ids), (100, 100..100));
+            allocator.merge();
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "entity index space exhausted")]
+    fn create_many_checks_capacity() {
+        Allocator::default().allocate_many_atomic(CAPACITY + 1);
+    }
+
+    #[test]
+    fn delayed_reuse() {
+        let mut allocator = Allocator::default();
+        allocator.set_reuse_policy(ReusePolicy::Delayed(2));
+
+        let a = allocator.allocate();
+        allocator.kill(&[a]).unwrap();
+        allocator.merge();
+        assert_ne!(allocator.allocate().id(), a.id());
+        allocator.merge();
+        assert_ne!(allocator.allocate_atomic().id(), a.id());
+        allocator.merge();
+        assert_eq!(allocator.allocate().id(), a.id());
+    }
+
+    #[test]
+    fn never_reuse() {
+        let mut allocator = Allocator::default();
+        allocator.set_reuse_policy(ReusePolicy::Delayed(5));
+        let a = allocator.allocate();
+        allocator.kill(&[a]).unwrap();
+        allocator.merge();
+        let c = allocator.allocate();
+        allocator.set_reuse_policy(ReusePolicy::Immediate);
+        allocator.kill(&[c]).unwrap();
+        allocator.set_reuse_policy(ReusePolicy::Never);
+
+        let b = allocator.allocate();
+        allocator.kill_atomic(b).unwrap();
+        allocator.merge();
+        for _ in 0..10 {
+            allocator.merge();
+            let id = allocator.allocate().id();
+            assert!(id != a.id() && id != b.id() && id != c.id());
+        }
+
+        allocator.set_reuse_policy(ReusePolicy::Immediate);
+        let mut reused: Vec<_> = (0..3).map(|_| allocator.allocate().id()).collect();
+        reused.sort();
+        let mut freed = vec![a.id(), b.id(), c.id()];
+        freed.sort();
+        assert_eq!(reused, freed);
+    }
+
+    #[test]
+    #[should_panic(expected = "entity index space exhausted")]
+    fn allocate_checks_capacity() {
+        let mut allocator = Allocator::default();
+        allocator.set_reuse_policy(ReusePolicy::Never);
+        *allocator.max_id.get_mut() = CAPACITY;
+        allocator.allocate();
+    }
+
+    #[test]
+    #[should_panic(expected = "entity index space exhausted")]
+    fn reserve_indices_checks_capacity() {
+        Allocator::default().reserve_indices(CAPACITY + 1);
+    }
+
+    #[test]
+    fn immediate_reuse_flushes_quarantine() {
+        let mut allocator = Allocator::default();
+        allocator.set_reuse_policy(ReusePolicy::Delayed(5));
+        let a = allocator.allocate();
+        allocator.kill(&[a]).unwrap();
+        allocator.set_reuse_policy(ReusePolicy::Immediate);
+
+        assert_eq!(allocator.allocate().id(), a.id());
+    }
+
+    #[test]
+    fn reserve_indices() {
+        let mut allocator = Allocator::default();
+        let a = allocator.allocate();
+        allocator.reserve_indices(3);
+        assert_eq!(allocator.generations.len(), 4);
+
+        let ids: Vec<_> = (0..4).map(|_| allocator.allocate().id()).collect();
+        assert_eq!(ids, vec![1, 2, 3, 4]);
+        assert!(allocator.is_alive(a));
+    }
+
+    #[test]
+    #[should_panic(expected = "generation overflow")]
+    fn generation_overflow() {
+        Generation::new(-i32::max_value()).raised();
+    }
+
     #[test]
     fn kill_atomic_kill_now_create_merge() {
         let mut allocator = Allocator::default();
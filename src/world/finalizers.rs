@@ -0,0 +1,136 @@
+use std::{collections::HashMap, mem};
+
+use crate::{
+    storage::MaskedStorage,
+    world::{Component, Entity, World},
+};
+
+type DeleteFinalizer = Box<dyn Fn(&World, &[Entity]) + Send + Sync>;
+type EntityFinalizer = Box<dyn FnOnce(&World, Entity) + Send + Sync>;
+
+/// Callbacks run when entities are deleted, before their components are
+/// dropped, e.g. to release GPU handles or remove bodies from a physics
+/// engine.
+///
+/// They run in `WorldExt::maintain` for entities deleted with
+/// `Entities::delete`, and right away for the ones deleted with
+/// `WorldExt::delete_entity`, `delete_entities` or `delete_all`, while the
+/// deleted entities are still alive. The finalizers get the `World`, so they
+/// can fetch resources and storages, but they can't fetch the storage of the
+/// component they finalize mutably. Entities deleted by finalizers in
+/// `maintain` are finalized and deleted as well. The resource is added by
+/// `WorldExt::new`.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::{prelude::*, world::Finalizers};
+///
+/// struct Mesh(u32);
+///
+/// impl Component for Mesh {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// #[derive(Default)]
+/// struct FreedMeshes(Vec<u32>);
+///
+/// let mut world = World::new();
+/// world.register::<Mesh>();
+/// world.insert(FreedMeshes::default());
+/// world
+///     .write_resource::<Finalizers>()
+///     .on_component::<Mesh, _>(|world, _, mesh| {
+///         world.write_resource::<FreedMeshes>().0.push(mesh.0);
+///     });
+///
+/// let e = world.create_entity().with(Mesh(7)).build();
+/// world.entities().delete(e).unwrap();
+/// world.maintain();
+///
+/// assert_eq!(world.read_resource::<FreedMeshes>().0, vec![7]);
+/// ```
+#[derive(Default)]
+pub struct Finalizers {
+    deleted: Vec<DeleteFinalizer>,
+    entities: HashMap<Entity, Vec<EntityFinalizer>>,
+}
+
+impl Finalizers {
+    /// Creates a registry without finalizers.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds a finalizer which is called with the deleted entities, which
+    /// are deleted together. It can delete more entities with
+    /// `Entities::delete`, e.g. the children of the deleted ones, which are
+    /// finalized as well before `maintain` returns.
+    pub fn on_delete<F>(&mut self, f: F)
+    where
+        F: Fn(&World, &[Entity]) + Send + Sync + 'static,
+    {
+        self.deleted.push(Box::new(f));
+    }
+
+    /// Adds a finalizer which is called with every deleted entity which has
+    /// a component of type `T`, and the component.
+    pub fn on_component<T, F>(&mut self, f: F)
+    where
+        T: Component,
+        F: Fn(&World, Entity, &T) + Send + Sync + 'static,
+    {
+        self.deleted.push(Box::new(move |world, deleted| {
+            let storage = match world.try_fetch::<MaskedStorage<T>>() {
+                Some(storage) => storage,
+                None => return,
+            };
+            for &entity in deleted {
+                if let Some(component) = storage.get(entity.id()) {
+                    f(world, entity, component);
+                }
+            }
+        }));
+    }
+
+    /// Adds a finalizer which is called once when `entity` is deleted.
+    pub fn on_entity<F>(&mut self, entity: Entity, f: F)
+    where
+        F: FnOnce(&World, Entity) + Send + Sync + 'static,
+    {
+        self.entities.entry(entity).or_default().push(Box::new(f));
+    }
+
+    /// Returns `true` if there are no finalizers.
+    pub fn is_empty(&self) -> bool {
+        self.deleted.is_empty() && self.entities.is_empty()
+    }
+}
+
+/// Runs the finalizers of the `deleted` entities. The finalizers are taken
+/// out of the world meanwhile, so they can add new ones.
+pub(crate) fn run(world: &World, deleted: &[Entity]) {
+    let (deleted_finalizers, entities) = match world.try_fetch_mut::<Finalizers>() {
+        Some(ref mut finalizers) if !finalizers.is_empty() => {
+            let entities: Vec<_> = deleted
+                .iter()
+                .filter_map(|e| finalizers.entities.remove(e).map(|f| (*e, f)))
+                .collect();
+            (mem::take(&mut finalizers.deleted), entities)
+        }
+        _ => return,
+    };
+
+    for (entity, finalizers) in entities {
+        for f in finalizers {
+            f(world, entity);
+        }
+    }
+    for f in &deleted_finalizers {
+        f(world, deleted);
+    }
+
+    let mut finalizers = world.fetch_mut::<Finalizers>();
+    let added = mem::replace(&mut finalizers.deleted, deleted_finalizers);
+    finalizers.deleted.extend(added);
+}
@@ -78,6 +78,42 @@ macro_rules! parallel_feature {
 /// lazily, meaning on `maintain`.
 /// If you need those components to exist immediately,
 /// you have to insert them into the storages yourself.
+///
+/// Since it only needs `Entities` and `LazyUpdate`, which can both be fetched
+/// immutably, systems running in parallel can spawn complete entities without
+/// waiting for each other's write storages.
+///
+/// ## Examples
+///
+/// ```
+/// # use specs::prelude::*;
+/// #
+/// struct Pos(f32, f32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// #[derive(Default)]
+/// struct Bullet;
+///
+/// impl Component for Bullet {
+///     type Storage = NullStorage<Self>;
+/// }
+///
+/// struct Fire;
+///
+/// impl<'a> System<'a> for Fire {
+///     type SystemData = (Entities<'a>, Read<'a, LazyUpdate>);
+///
+///     fn run(&mut self, (entities, lazy): Self::SystemData) {
+///         lazy.create_entity(&entities)
+///             .with(Pos(0.0, 0.0))
+///             .with(Bullet)
+///             .build();
+///     }
+/// }
+/// ```
 #[must_use = "Please call .build() on this to finish building it."]
 pub struct LazyBuilder<'a> {
     /// The entity that we're inserting components for.
@@ -375,6 +411,49 @@ impl LazyUpdate {
         LazyBuilder { entity, lazy: self }
     }
 
+    /// Lazily deletes an entity and its components.
+    ///
+    /// Unlike `Entities::delete`, the deletion is queued with the other lazy
+    /// updates, so updates requested before it still apply to the entity.
+    /// If the entity is already dead at that point, a warning is logged.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # use specs::prelude::*;
+    /// #
+    /// struct Health(u32);
+    ///
+    /// impl Component for Health {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// struct Reaper;
+    ///
+    /// impl<'a> System<'a> for Reaper {
+    ///     type SystemData = (Entities<'a>, ReadStorage<'a, Health>, Read<'a, LazyUpdate>);
+    ///
+    ///     fn run(&mut self, (ent, health, lazy): Self::SystemData) {
+    ///         for (entity, health) in (&ent, &health).join() {
+    ///             if health.0 == 0 {
+    ///                 lazy.delete(entity);
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn delete(&self, e: Entity) {
+        self.exec_mut(move |world| {
+            // Deleting a dead entity would drop the components of the one
+            // which reused its index.
+            if world.is_alive(e) {
+                world.delete_entity(e).expect("entity checked to be alive");
+            } else {
+                log::warn!("Lazy deletion failed because {:?} was already dead.", e);
+            }
+        });
+    }
+
     pub(super) fn clone(&self) -> Self {
         Self {
             queue: self.queue.clone(),
@@ -5,19 +5,28 @@ pub use shred::World;
 pub use self::{
     comp::Component,
     entity::{
-        CreateIterAtomic, Entities, EntitiesRes, Entity, EntityResBuilder, Generation, Index,
+        CreateIterAtomic, CreateManyAtomic, DeletedEntities, Entities, EntitiesRes, Entity,
+        EntityResBuilder, Generation, Index, ReusePolicy,
     },
+    finalizers::Finalizers,
     lazy::{LazyBuilder, LazyUpdate},
+    names::Names,
+    reflect::{ComponentInfo, ComponentRegistry},
+    stats::WorldStats,
     world_ext::WorldExt,
 };
 
-use shred::{FetchMut, SystemData};
+use shred::{CastFrom, FetchMut, SystemData};
 
 use crate::storage::WriteStorage;
 
 mod comp;
 mod entity;
+mod finalizers;
 mod lazy;
+mod names;
+mod reflect;
+mod stats;
 #[cfg(test)]
 mod tests;
 mod world_ext;
@@ -99,10 +108,97 @@ pub trait Builder {
         }
     }
 
+    /// Appends all components of `bundle`, see `Bundle`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the components hasn't been `register()`ed in the
+    /// `World`.
+    fn with_bundle<T: Bundle>(self, bundle: T) -> Self
+    where
+        Self: Sized,
+    {
+        bundle.add_to_entity(self)
+    }
+
     /// Finishes the building and returns the entity.
     fn build(self) -> Entity;
 }
 
+/// A group of components which are added to an entity together with
+/// `Builder::with_bundle`, instead of repeating the same `with` calls
+/// wherever such an entity is created.
+///
+/// With the `specs-derive` feature, `#[derive(Bundle)]` implements this for
+/// a struct by adding each of its fields as a component.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::prelude::*;
+///
+/// struct Pos(f32, f32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// struct Health(u32);
+///
+/// impl Component for Health {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// struct PlayerBundle {
+///     pos: Pos,
+///     health: Health,
+/// }
+///
+/// impl Bundle for PlayerBundle {
+///     fn add_to_entity<B: Builder>(self, builder: B) -> B {
+///         builder.with(self.pos).with(self.health)
+///     }
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Pos>();
+/// world.register::<Health>();
+///
+/// let player = world
+///     .create_entity()
+///     .with_bundle(PlayerBundle {
+///         pos: Pos(0.0, 0.0),
+///         health: Health(100),
+///     })
+///     .build();
+/// assert!(world.read_storage::<Health>().contains(player));
+/// ```
+pub trait Bundle {
+    /// Adds the components of this bundle to the entity built by `builder`.
+    fn add_to_entity<B: Builder>(self, builder: B) -> B;
+}
+
+/// A resource which is reset by `WorldExt::clear`, e.g. a marker allocator.
+/// Resources are reset only if they were registered with
+/// `WorldExt::register_reset`.
+pub trait Reset {
+    /// Resets this resource to its initial state.
+    fn reset(&mut self);
+}
+
+unsafe impl<T> CastFrom<T> for dyn Reset
+where
+    T: Reset + 'static,
+{
+    fn cast(t: &T) -> &Self {
+        t
+    }
+
+    fn cast_mut(t: &mut T) -> &mut Self {
+        t
+    }
+}
+
 /// The entity builder, allowing to
 /// build an entity together with its components.
 ///
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use crate::world::Entity;
+
+/// Maps names to entities, giving scripts and debugging tools stable,
+/// human-readable handles.
+///
+/// Every entity has at most one name and every name belongs to at most one
+/// entity. Deleted entities lose their name when their components are
+/// dropped, see `DeletedEntities`. The resource is added by `WorldExt::new`.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::{prelude::*, world::Names};
+///
+/// let mut world = World::new();
+/// let player = world.create_entity().build();
+/// world.write_resource::<Names>().insert("player", player);
+///
+/// assert_eq!(world.entity_by_name("player"), Some(player));
+/// assert_eq!(world.read_resource::<Names>().name(player), Some("player"));
+///
+/// world.delete_entity(player).unwrap();
+/// assert_eq!(world.entity_by_name("player"), None);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Names {
+    entities: HashMap<String, Entity>,
+    names: HashMap<Entity, String>,
+}
+
+impl Names {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Gives `entity` the name `name`, replacing its previous name.
+    /// Returns the entity which had the name before, if any.
+    pub fn insert<S: Into<String>>(&mut self, name: S, entity: Entity) -> Option<Entity> {
+        let name = name.into();
+        if let Some(old) = self.names.remove(&entity) {
+            self.entities.remove(&old);
+        }
+        let previous = self.entities.insert(name.clone(), entity);
+        if let Some(previous) = previous {
+            self.names.remove(&previous);
+        }
+        self.names.insert(entity, name);
+
+        previous
+    }
+
+    /// Returns the entity named `name`.
+    pub fn get(&self, name: &str) -> Option<Entity> {
+        self.entities.get(name).cloned()
+    }
+
+    /// Returns the name of `entity`.
+    pub fn name(&self, entity: Entity) -> Option<&str> {
+        self.names.get(&entity).map(String::as_str)
+    }
+
+    /// Removes the name `name`, returning the entity which had it.
+    pub fn remove(&mut self, name: &str) -> Option<Entity> {
+        let entity = self.entities.remove(name)?;
+        self.names.remove(&entity);
+
+        Some(entity)
+    }
+
+    /// Returns the number of named entities.
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Returns `true` if no entity has a name.
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Returns an iterator over all names and their entities.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Entity)> {
+        self.entities
+            .iter()
+            .map(|(name, &entity)| (name.as_str(), entity))
+    }
+
+    pub(crate) fn forget(&mut self, deleted: &[Entity]) {
+        if self.names.is_empty() {
+            return;
+        }
+
+        for entity in deleted {
+            if let Some(name) = self.names.remove(entity) {
+                self.entities.remove(&name);
+            }
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use std::{any::TypeId, collections::HashMap, fmt};
+
+#[cfg(all(feature = "serde", feature = "serde_json"))]
+use serde::{de::DeserializeOwned, Serialize};
+#[cfg(all(feature = "serde", feature = "serde_json"))]
+use serde_json::Value;
+
+use crate::world::{Component, Entity, World, WorldExt};
+
+/// The component types registered in a `World`, for inspecting their
+/// storages at runtime without knowing the types.
+///
+/// Components are added by `WorldExt::register` under their type name,
+/// which can be replaced with `set_name`, e.g. to keep save files or network
+/// schemas stable when a type is moved. With the `serde` and `serde_json`
+/// features enabled, `add_serde` makes the components of a type convertible
+/// to and from `serde_json::Value`s. The resource is added by
+/// `WorldExt::new`.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::{prelude::*, world::ComponentRegistry};
+///
+/// struct Pos(i32, i32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Pos>();
+/// world.write_resource::<ComponentRegistry>().set_name::<Pos>("pos");
+/// let e = world.create_entity().with(Pos(1, 2)).build();
+///
+/// let registry = world.read_resource::<ComponentRegistry>();
+/// let pos = registry.get("pos").unwrap();
+/// assert_eq!(pos.count(&world), 1);
+/// assert!(pos.contains(&world, e));
+/// assert!(registry.get(std::any::type_name::<Pos>()).is_none());
+/// ```
+#[derive(Debug, Default)]
+pub struct ComponentRegistry {
+    components: Vec<ComponentInfo>,
+    types: HashMap<TypeId, usize>,
+}
+
+impl ComponentRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds `T` under its type name, if it isn't in the registry yet.
+    /// Called by `WorldExt::register`.
+    pub fn add<T: Component>(&mut self) -> &mut ComponentInfo {
+        let components = &mut self.components;
+        let index = *self.types.entry(TypeId::of::<T>()).or_insert_with(|| {
+            components.push(ComponentInfo::new::<T>());
+            components.len() - 1
+        });
+
+        &mut self.components[index]
+    }
+
+    /// Adds `T` like `add` and makes its components convertible to and from
+    /// `serde_json::Value`s, see `ComponentInfo::serialize`.
+    #[cfg(all(feature = "serde", feature = "serde_json"))]
+    pub fn add_serde<T>(&mut self) -> &mut ComponentInfo
+    where
+        T: Component + Serialize + DeserializeOwned,
+    {
+        let info = self.add::<T>();
+        info.serde = Some(SerdeVtable {
+            serialize: serialize::<T>,
+            deserialize: deserialize::<T>,
+        });
+        info
+    }
+
+    /// Renames `T`, which has to be in the registry, replacing the name it
+    /// had.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` isn't in the registry.
+    pub fn set_name<T: Component>(&mut self, name: &'static str) {
+        let index = self.types[&TypeId::of::<T>()];
+        self.components[index].name = name;
+    }
+
+    /// Returns the component type with the name `name`.
+    pub fn get(&self, name: &str) -> Option<&ComponentInfo> {
+        self.components.iter().find(|info| info.name == name)
+    }
+
+    /// Returns the component type with the id `id`.
+    pub fn get_by_type_id(&self, id: TypeId) -> Option<&ComponentInfo> {
+        self.types.get(&id).map(|&index| &self.components[index])
+    }
+
+    /// Returns all component types, in the order they were registered.
+    pub fn iter(&self) -> impl Iterator<Item = &ComponentInfo> {
+        self.components.iter()
+    }
+
+    /// Returns the number of component types.
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    /// Returns `true` if there are no component types.
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+}
+
+/// A component type in a `ComponentRegistry`, with functions accessing its
+/// storage in a `World`.
+#[derive(Clone)]
+pub struct ComponentInfo {
+    name: &'static str,
+    type_id: TypeId,
+    count: fn(&World) -> usize,
+    contains: fn(&World, Entity) -> bool,
+    remove: fn(&World, Entity) -> bool,
+    #[cfg(all(feature = "serde", feature = "serde_json"))]
+    serde: Option<SerdeVtable>,
+}
+
+#[cfg(all(feature = "serde", feature = "serde_json"))]
+#[derive(Clone)]
+struct SerdeVtable {
+    serialize: fn(&World, Entity) -> Option<Result<Value, String>>,
+    deserialize: fn(&World, Entity, Value) -> Result<(), String>,
+}
+
+impl fmt::Debug for ComponentInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ComponentInfo")
+            .field("name", &self.name)
+            .field("type_id", &self.type_id)
+            .finish()
+    }
+}
+
+impl ComponentInfo {
+    fn new<T: Component>() -> Self {
+        ComponentInfo {
+            name: std::any::type_name::<T>(),
+            type_id: TypeId::of::<T>(),
+            count: |world| world.read_storage::<T>().count(),
+            contains: |world, e| world.read_storage::<T>().contains(e),
+            remove: |world, e| world.write_storage::<T>().remove(e).is_some(),
+            #[cfg(all(feature = "serde", feature = "serde_json"))]
+            serde: None,
+        }
+    }
+
+    /// Returns the name of the component type.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the `TypeId` of the component type.
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    /// Returns the number of components in `world`.
+    pub fn count(&self, world: &World) -> usize {
+        (self.count)(world)
+    }
+
+    /// Returns `true` if `e` has a component in `world`.
+    pub fn contains(&self, world: &World, e: Entity) -> bool {
+        (self.contains)(world, e)
+    }
+
+    /// Removes the component of `e` in `world`, returning `true` if it had
+    /// one.
+    pub fn remove(&self, world: &World, e: Entity) -> bool {
+        (self.remove)(world, e)
+    }
+
+    /// Returns `true` if the components can be converted to and from
+    /// `serde_json::Value`s, see `ComponentRegistry::add_serde`.
+    #[cfg(all(feature = "serde", feature = "serde_json"))]
+    pub fn is_serde(&self) -> bool {
+        self.serde.is_some()
+    }
+
+    /// Converts the component of `e` in `world` to a `serde_json::Value`.
+    /// Returns `None` if `e` doesn't have one or the component type wasn't
+    /// added with `ComponentRegistry::add_serde`.
+    #[cfg(all(feature = "serde", feature = "serde_json"))]
+    pub fn serialize(&self, world: &World, e: Entity) -> Option<Result<Value, String>> {
+        self.serde
+            .as_ref()
+            .and_then(|serde| (serde.serialize)(world, e))
+    }
+
+    /// Inserts a component converted from `value` for `e` in `world`.
+    /// Fails if the component type wasn't added with
+    /// `ComponentRegistry::add_serde`.
+    #[cfg(all(feature = "serde", feature = "serde_json"))]
+    pub fn deserialize(&self, world: &World, e: Entity, value: Value) -> Result<(), String> {
+        match self.serde {
+            Some(ref serde) => (serde.deserialize)(world, e, value),
+            None => Err(format!("component {} can't be deserialized", self.name)),
+        }
+    }
+}
+
+#[cfg(all(feature = "serde", feature = "serde_json"))]
+fn serialize<T>(world: &World, e: Entity) -> Option<Result<Value, String>>
+where
+    T: Component + Serialize,
+{
+    world
+        .read_storage::<T>()
+        .get(e)
+        .map(|component| serde_json::to_value(component).map_err(|e| e.to_string()))
+}
+
+#[cfg(all(feature = "serde", feature = "serde_json"))]
+fn deserialize<T>(world: &World, e: Entity, value: Value) -> Result<(), String>
+where
+    T: Component + DeserializeOwned,
+{
+    let component = serde_json::from_value::<T>(value).map_err(|e| e.to_string())?;
+    world
+        .write_storage::<T>()
+        .insert(e, component)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,68 @@
+use std::collections::BTreeMap;
+
+/// Statistics about a `World`, updated by `WorldExt::maintain`, e.g. for a
+/// debug overlay.
+///
+/// A frame is the time between two calls of `maintain`. The resource is
+/// added by `WorldExt::new`.
+///
+/// ## Examples
+///
+/// ```
+/// use specs::{prelude::*, world::WorldStats};
+///
+/// struct Pos(i32, i32);
+///
+/// impl Component for Pos {
+///     type Storage = VecStorage<Self>;
+/// }
+///
+/// let mut world = World::new();
+/// world.register::<Pos>();
+/// let a = world.create_entity().with(Pos(1, 2)).build();
+/// world.create_entity().build();
+/// world.maintain();
+///
+/// world.delete_entity(a).unwrap();
+/// world.create_entity().build();
+/// world.maintain();
+///
+/// let stats = world.read_resource::<WorldStats>();
+/// assert_eq!(stats.entities, 2);
+/// assert_eq!((stats.created, stats.deleted), (1, 1));
+/// assert_eq!(stats.components[std::any::type_name::<Pos>()], 0);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WorldStats {
+    /// The number of live entities.
+    pub entities: usize,
+    /// The number of entities created in the last frame, including the ones
+    /// deleted in the same frame.
+    pub created: usize,
+    /// The number of entities deleted in the last frame.
+    pub deleted: usize,
+    /// The number of components of each type in the `ComponentRegistry`,
+    /// keyed by its name.
+    pub components: BTreeMap<&'static str, usize>,
+    deleted_in_frame: usize,
+}
+
+impl WorldStats {
+    /// Counts entities deleted before the next `update`.
+    pub(crate) fn count_deleted(&mut self, deleted: usize) {
+        self.deleted_in_frame += deleted;
+    }
+
+    /// Ends the frame, with `entities` live entities.
+    pub(crate) fn update(
+        &mut self,
+        entities: usize,
+        components: BTreeMap<&'static str, usize>,
+    ) {
+        self.deleted = self.deleted_in_frame;
+        self.created = (entities + self.deleted).saturating_sub(self.entities);
+        self.entities = entities;
+        self.components = components;
+        self.deleted_in_frame = 0;
+    }
+}
@@ -1,3 +1,8 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
 use super::{WorldExt, *};
 use crate::{join::Join, storage::VecStorage};
 
@@ -32,6 +37,273 @@ fn delete_all() {
     assert!(world.read_storage::<Pos>().get(b).is_none());
 }
 
+macro_rules! drop_counters {
+    ($($name:ident: $storage:ident),*) => {
+        $(
+            struct $name(Arc<AtomicUsize>);
+
+            impl Drop for $name {
+                fn drop(&mut self) {
+                    self.0.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+
+            impl Component for $name {
+                type Storage = crate::storage::$storage<Self>;
+            }
+        )*
+    };
+}
+
+drop_counters!(
+    VecDrops: VecStorage,
+    DenseVecDrops: DenseVecStorage,
+    HashMapDrops: HashMapStorage,
+    BTreeDrops: BTreeStorage,
+    PackedDrops: PackedStorage,
+    PagedDrops: PagedStorage
+);
+
+/// Creates components with `new` and removes them all with `remove` three
+/// times, checking that every component is dropped.
+fn check_drops<T, F, R>(new: F, remove: R)
+where
+    T: Component + Send + Sync,
+    T::Storage: Default,
+    F: Fn(Arc<AtomicUsize>) -> T,
+    R: Fn(&mut World),
+{
+    let drops = Arc::new(AtomicUsize::new(0));
+    let mut world = World::new();
+    world.register::<T>();
+    for round in 1..4 {
+        for _ in 0..100 {
+            world.create_entity().with(new(drops.clone())).build();
+        }
+        remove(&mut world);
+        assert_eq!(world.read_storage::<T>().count(), 0);
+        assert_eq!(drops.load(Ordering::SeqCst), round * 100);
+    }
+}
+
+#[test]
+fn delete_all_drops_components() {
+    let delete_all = |world: &mut World| world.delete_all();
+    check_drops(VecDrops, delete_all);
+    check_drops(DenseVecDrops, delete_all);
+    check_drops(HashMapDrops, delete_all);
+    check_drops(BTreeDrops, delete_all);
+    check_drops(PackedDrops, delete_all);
+    check_drops(PagedDrops, delete_all);
+
+    let mut world = World::new();
+    world.register::<DenseVecDrops>();
+    let drops = Arc::new(AtomicUsize::new(0));
+    world.create_entity().with(DenseVecDrops(drops)).build();
+    world.delete_all();
+    assert!(world.read_storage::<DenseVecDrops>().as_slice().is_empty());
+}
+
+#[test]
+fn delete_entities_checks_all_first() {
+    let mut world = World::new();
+    world.register::<Pos>();
+
+    let a = world.create_entity().with(Pos).build();
+    let dead = world.create_entity().build();
+    world.delete_entity(dead).unwrap();
+    let reused = world.create_entity().with(Pos).build();
+    assert_eq!(reused.id(), dead.id());
+
+    assert!(world.delete_entities(&[a, dead]).is_err());
+    assert!(world.is_alive(a));
+    assert!(world.read_storage::<Pos>().get(a).is_some());
+    assert!(world.read_storage::<Pos>().get(reused).is_some());
+}
+
+#[test]
+fn delete_all_keeps_generations() {
+    let mut world = World::new();
+    world.register::<Pos>();
+
+    let old: Vec<_> = (0..10).map(|_| world.create_entity().with(Pos).build()).collect();
+    world.delete_all();
+    assert_eq!(world.read_storage::<Pos>().count(), 0);
+
+    let new = world.create_entity().with(Pos).build();
+    assert!(old.iter().all(|&e| !world.is_alive(e)));
+    assert!(world.read_storage::<Pos>().get(new).is_some());
+    assert_eq!(world.read_storage::<Pos>().count(), 1);
+}
+
+#[test]
+fn deleted_entities() {
+    let mut world = World::new();
+    let mut reader = world.write_resource::<DeletedEntities>().register_reader();
+
+    let a = world.create_entity().build();
+    let b = world.create_entity().build();
+    let c = world.create_entity().build();
+    world.read_resource::<LazyUpdate>().delete(a);
+    world.maintain();
+    world.delete_all();
+
+    let deleted = world.read_resource::<DeletedEntities>();
+    let deleted: Vec<_> = deleted.read(&mut reader).cloned().collect();
+    assert_eq!(deleted, vec![a, b, c]);
+}
+
+#[test]
+fn names() {
+    let mut world = World::new();
+    let a = world.create_entity().build();
+    let b = world.create_entity().build();
+    {
+        let mut names = world.write_resource::<Names>();
+        assert_eq!(names.insert("first", a), None);
+        assert_eq!(names.insert("second", a), None);
+        assert_eq!(names.get("first"), None);
+        assert_eq!(names.insert("second", b), Some(a));
+        assert_eq!(names.name(a), None);
+        names.insert("first", a);
+        assert_eq!(names.len(), 2);
+    }
+
+    world.entities().delete(a).unwrap();
+    assert_eq!(world.entity_by_name("first"), Some(a));
+    world.maintain();
+    assert_eq!(world.entity_by_name("first"), None);
+    assert_eq!(world.entity_by_name("second"), Some(b));
+
+    assert_eq!(world.write_resource::<Names>().remove("second"), Some(b));
+    assert!(world.read_resource::<Names>().is_empty());
+}
+
+#[test]
+fn exec_and_system_data() {
+    use crate::storage::{ReadStorage, WriteStorage};
+
+    let mut world = World::new();
+    let count = world.exec(|(entities, mut pos): (Entities, WriteStorage<Pos>)| {
+        for _ in 0..3 {
+            pos.insert(entities.create(), Pos).unwrap();
+        }
+        pos.count()
+    });
+    assert_eq!(count, 3);
+
+    let (entities, pos): (Entities, ReadStorage<Pos>) = world.system_data();
+    assert_eq!((&entities, &pos).join().count(), 3);
+}
+
+#[test]
+fn register_with_storage_keeps_existing() {
+    let mut world = World::new();
+    world.register::<Pos>();
+    let e = world.create_entity().with(Pos).build();
+
+    world.register_with_storage::<_, Pos>(|| panic!("storage created twice"));
+    assert!(world.read_storage::<Pos>().contains(e));
+}
+
+#[test]
+fn shrink_storages() {
+    let mut world = World::new();
+
+    world.register::<Pos>();
+    world.register::<Vel>();
+
+    let entities: Vec<Entity> = (0..100)
+        .map(|_| world.create_entity().with(Pos).with(Vel).build())
+        .collect();
+    world.delete_entities(&entities[1..]).unwrap();
+    world.maintain();
+    world.shrink_storages();
+
+    assert_eq!((&world.read_storage::<Pos>(), &world.read_storage::<Vel>()).join().count(), 1);
+    assert!(world.read_storage::<Pos>().get(entities[0]).is_some());
+}
+
+#[derive(Debug, PartialEq)]
+struct Health(u32);
+
+impl Component for Health {
+    type Storage = crate::storage::DenseVecStorage<Self>;
+}
+
+#[test]
+fn retain_removed() {
+    let mut world = World::new();
+    world.register::<Health>();
+    world.write_storage::<Health>().retain_removed(true);
+
+    let a = world.create_entity().with(Health(1)).build();
+    let b = world.create_entity().with(Health(2)).build();
+    world.create_entity().with(Health(3)).build();
+
+    // Deleting right away keeps the components until the next maintain.
+    world.delete_entity(b).unwrap();
+    assert_eq!(
+        world.read_storage::<Health>().removed().collect::<Vec<_>>(),
+        vec![(b, &Health(2))]
+    );
+
+    world.entities().delete(a).unwrap();
+    world.maintain();
+    {
+        let health = world.read_storage::<Health>();
+        let removed: Vec<_> = health.removed().collect();
+        assert_eq!(removed, vec![(a, &Health(1))]);
+        assert_eq!(health.count(), 1);
+    }
+
+    // The id of `a` is reused and deleted again before the next maintain.
+    let c = world.create_entity().with(Health(4)).build();
+    assert_eq!(c.id(), a.id());
+    world.delete_entity(c).unwrap();
+    {
+        let health = world.read_storage::<Health>();
+        let removed: Vec<_> = health.removed().collect();
+        assert_eq!(removed, vec![(c, &Health(4))]);
+    }
+
+    world.maintain();
+    assert_eq!(world.read_storage::<Health>().removed().count(), 0);
+
+    // Disabled storages drop the components right away.
+    world.write_storage::<Health>().retain_removed(false);
+    world.delete_all();
+    assert_eq!(world.read_storage::<Health>().removed().count(), 0);
+}
+
+#[test]
+fn clone_entity() {
+    #[derive(Clone, Debug, PartialEq)]
+    struct Name(&'static str);
+
+    impl Component for Name {
+        type Storage = VecStorage<Self>;
+    }
+
+    let mut world = World::new();
+    world.register_cloneable::<Name>();
+    world.register::<Pos>();
+
+    let original = world.create_entity().with(Name("tree")).with(Pos).build();
+    let bare = world.create_entity().build();
+    let copy = world.clone_entity(original).unwrap();
+    let bare_copy = world.clone_entity(bare).unwrap();
+
+    assert_ne!(copy, original);
+    assert_eq!(world.read_storage::<Name>().get(copy), Some(&Name("tree")));
+    assert!(world.read_storage::<Pos>().get(copy).is_none());
+    assert!(world.is_alive(bare_copy));
+    assert!(world.read_storage::<Name>().get(bare_copy).is_none());
+
+    world.delete_entity(original).unwrap();
+    assert!(world.clone_entity(original).is_err());
+}
+
 #[test]
 fn lazy_insertion() {
     let mut world = World::new();
@@ -71,6 +343,30 @@ fn lazy_removal() {
     assert!(world.read_storage::<Pos>().get(e).is_none());
 }
 
+#[test]
+fn lazy_deletion() {
+    let mut world = World::new();
+    world.register::<Pos>();
+    world.register::<Vel>();
+
+    let e = world.create_entity().with(Pos).build();
+    let dead = world.create_entity().build();
+    world.delete_entity(dead).unwrap();
+    let reused = world.create_entity().with(Pos).build();
+    {
+        let lazy = world.read_resource::<LazyUpdate>();
+        lazy.insert(e, Vel);
+        lazy.delete(e);
+        lazy.delete(dead);
+    }
+
+    assert!(world.is_alive(e));
+    world.maintain();
+    assert!(!world.is_alive(e));
+    assert!(world.read_storage::<Pos>().get(e).is_none());
+    assert!(world.read_storage::<Pos>().get(reused).is_some());
+}
+
 #[test]
 fn super_lazy_execution() {
     let mut world = World::new();
@@ -164,3 +460,196 @@ fn delete_and_lazy() {
 
     world.delete_all();
 }
+
+#[test]
+fn memory_report() {
+    let mut world = World::new();
+    assert!(world.memory_report().components.is_empty());
+
+    world.register::<Pos>();
+    world.register::<Vel>();
+    for _ in 0..100 {
+        world.create_entity().with(Pos).build();
+    }
+
+    let report = world.memory_report();
+    assert_eq!(report.components.len(), 2);
+    assert!(report.components[0].component.ends_with("Pos"));
+    assert_eq!(report.components[0].stats.len, 100);
+    assert_eq!(report.components[1].stats.total_bytes(), 0);
+    assert_eq!(report.total_bytes(), report.components[0].stats.mask_bytes);
+}
+
+#[test]
+fn component_registry() {
+    let mut world = World::new();
+    world.register::<Pos>();
+    world.register::<Vel>();
+    world.register::<Pos>();
+    let e = world.create_entity().with(Pos).build();
+
+    let registry = world.read_resource::<ComponentRegistry>();
+    let names: Vec<_> = registry.iter().map(ComponentInfo::name).collect();
+    assert_eq!(
+        names,
+        vec![std::any::type_name::<Pos>(), std::any::type_name::<Vel>()]
+    );
+
+    let pos = registry
+        .get_by_type_id(std::any::TypeId::of::<Pos>())
+        .unwrap();
+    assert!(pos.contains(&world, e));
+    assert!(pos.remove(&world, e));
+    assert_eq!(pos.count(&world), 0);
+}
+
+#[cfg(all(feature = "serde", feature = "serde_json"))]
+#[test]
+fn component_registry_serde() {
+    #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+    struct Health(u32);
+
+    impl Component for Health {
+        type Storage = VecStorage<Self>;
+    }
+
+    let mut world = World::new();
+    world.register::<Health>();
+    world.register::<Pos>();
+    world
+        .write_resource::<ComponentRegistry>()
+        .add_serde::<Health>();
+    let a = world.create_entity().with(Health(3)).build();
+    let b = world.create_entity().with(Pos).build();
+
+    let registry = world.read_resource::<ComponentRegistry>();
+    let health = registry.get(std::any::type_name::<Health>()).unwrap();
+    let value = health.serialize(&world, a).unwrap().unwrap();
+    health.deserialize(&world, b, value).unwrap();
+    assert_eq!(world.read_storage::<Health>().get(b), Some(&Health(3)));
+
+    let pos = registry.get(std::any::type_name::<Pos>()).unwrap();
+    assert!(!pos.is_serde());
+    assert!(pos.deserialize(&world, b, serde_json::Value::Null).is_err());
+}
+
+#[test]
+fn world_stats() {
+    let mut world = World::new();
+    world.register::<Pos>();
+    let a = world.create_entity().with(Pos).build();
+    world.entities().create();
+    world.maintain();
+    assert_eq!(world.read_resource::<WorldStats>().entities, 2);
+    assert_eq!(world.read_resource::<WorldStats>().created, 2);
+
+    world.delete_entity(a).unwrap();
+    let b = world.create_entity().build();
+    world.entities().delete(b).unwrap();
+    world.maintain();
+    {
+        let stats = world.read_resource::<WorldStats>();
+        assert_eq!((stats.entities, stats.created, stats.deleted), (1, 1, 2));
+        assert_eq!(stats.components[std::any::type_name::<Pos>()], 0);
+    }
+
+    world.maintain();
+    let stats = world.read_resource::<WorldStats>();
+    assert_eq!((stats.entities, stats.created, stats.deleted), (1, 0, 0));
+}
+
+#[test]
+fn finalizers() {
+    #[derive(Default)]
+    struct Finalized(Vec<(&'static str, Entity)>);
+
+    let mut world = World::new();
+    world.register::<Pos>();
+    world.insert(Finalized::default());
+    world
+        .write_resource::<Finalizers>()
+        .on_component::<Pos, _>(|world, e, _| {
+            assert!(world.read_storage::<Vel>().get(e).is_some());
+            world.write_resource::<Finalized>().0.push(("pos", e));
+        });
+    world.register::<Vel>();
+
+    let a = world.create_entity().with(Pos).with(Vel).build();
+    let b = world.create_entity().with(Vel).build();
+    let c = world.create_entity().with(Pos).with(Vel).build();
+    let d = world.create_entity().with(Pos).with(Vel).build();
+    world
+        .write_resource::<Finalizers>()
+        .on_entity(b, move |world, e| {
+            world.write_resource::<Finalized>().0.push(("b", e));
+            world.entities().delete(d).unwrap();
+        });
+
+    world.entities().delete(a).unwrap();
+    world.entities().delete(b).unwrap();
+    world.maintain();
+    assert_eq!(
+        world.read_resource::<Finalized>().0,
+        vec![("b", b), ("pos", a), ("pos", d)]
+    );
+    assert!(!world.is_alive(d));
+    assert_eq!(world.read_storage::<Pos>().count(), 1);
+
+    world.delete_entity(c).unwrap();
+    assert_eq!(world.read_resource::<Finalized>().0.len(), 4);
+    world.maintain();
+    assert_eq!(world.read_resource::<Finalized>().0.len(), 4);
+}
+
+#[test]
+fn clear() {
+    #[derive(Default)]
+    struct Counter(u32);
+
+    impl Reset for Counter {
+        fn reset(&mut self) {
+            self.0 = 0;
+        }
+    }
+
+    let mut world = World::new();
+    world.register::<Pos>();
+    world.write_storage::<Pos>().retain_removed(true);
+    world.insert(Counter(5));
+    world.register_reset::<Counter>();
+
+    let a = world.create_entity().with(Pos).build();
+    world.create_entity().with(Pos).build();
+    world.entities().delete(a).unwrap();
+    world.maintain();
+    assert_eq!(world.read_storage::<Pos>().removed().count(), 1);
+
+    world.clear();
+    assert_eq!(world.entities().join().count(), 0);
+    assert_eq!(world.read_storage::<Pos>().count(), 0);
+    assert_eq!(world.read_storage::<Pos>().removed().count(), 0);
+    assert_eq!(world.read_resource::<Counter>().0, 0);
+
+    let b = world.create_entity().with(Pos).build();
+    world.delete_entity(b).unwrap();
+    assert_eq!(world.read_storage::<Pos>().removed().count(), 1);
+}
+
+#[test]
+fn clear_drops_components() {
+    check_drops(DenseVecDrops, |world| world.clear());
+
+    let mut world = World::new();
+    world.register::<DenseVecDrops>();
+    world.write_storage::<DenseVecDrops>().retain_removed(true);
+    let drops = Arc::new(AtomicUsize::new(0));
+    let a = world.create_entity().with(DenseVecDrops(drops.clone())).build();
+    world.create_entity().with(DenseVecDrops(drops.clone())).build();
+    world.entities().delete(a).unwrap();
+    world.maintain();
+    assert_eq!(drops.load(Ordering::SeqCst), 0);
+
+    world.clear();
+    assert_eq!(drops.load(Ordering::SeqCst), 2);
+    assert!(world.read_storage::<DenseVecDrops>().as_slice().is_empty());
+}
@@ -1,13 +1,18 @@
+use std::cmp::Reverse;
+
 use super::{
     comp::Component,
-    entity::{Allocator, EntitiesRes, Entity},
-    CreateIter, EntityBuilder, LazyUpdate,
+    entity::{Allocator, DeletedEntities, EntitiesRes, Entity},
+    finalizers, ComponentRegistry, CreateIter, EntityBuilder, Finalizers, LazyUpdate, Names,
+    Reset, WorldStats,
 };
 
 use crate::{
-    error::WrongGeneration,
-    storage::{AnyStorage, MaskedStorage},
-    ReadStorage, WriteStorage,
+    error::{Error, WrongGeneration},
+    hierarchy::{subtree, Parent},
+    relation::{forget_objects, Relations},
+    storage::{AnyStorage, CloneStorage, MaskedStorage, MemoryReport},
+    BitSet, ReadStorage, WriteStorage,
 };
 use shred::{Fetch, FetchMut, MetaTable, Read, Resource, SystemData, World};
 
@@ -135,11 +140,159 @@ pub trait WorldExt {
     /// Registers a new component with a given storage.
     ///
     /// Does nothing if the component was already registered.
+    ///
+    /// This allows configuring the storage at runtime, e.g. depending on a
+    /// level file. The type of the storage is still `Component::Storage`,
+    /// and `storage` is only called if the component wasn't registered yet.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::{prelude::*, storage::UnprotectedStorage};
+    ///
+    /// struct Pos(f32, f32);
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = DenseVecStorage<Self>;
+    /// }
+    ///
+    /// let expected_entities = 5_000;
+    /// let mut world = World::new();
+    /// world.register_with_storage::<_, Pos>(|| {
+    ///     let mut storage = DenseVecStorage::default();
+    ///     storage.reserve(expected_entities);
+    ///     storage
+    /// });
+    /// assert!(world.read_storage::<Pos>().stats().capacity >= expected_entities);
+    /// ```
     fn register_with_storage<F, T>(&mut self, storage: F)
     where
         F: FnOnce() -> T::Storage,
         T: Component;
 
+    /// Registers a new component and reserves space for `capacity`
+    /// components in its storage, so inserting them doesn't reallocate.
+    ///
+    /// If the component was already registered, this only reserves.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// struct Pos(f32, f32);
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register_with_capacity::<Pos>(10_000);
+    /// for i in 0..10_000 {
+    ///     world.create_entity().with(Pos(i as f32, 0.0)).build();
+    /// }
+    /// ```
+    fn register_with_capacity<T: Component>(&mut self, capacity: usize)
+    where
+        T::Storage: Default;
+
+    /// Registers a new component like `register`, and allows
+    /// `clone_entity` to clone it.
+    ///
+    /// If the component was already registered, this only allows cloning
+    /// it.
+    fn register_cloneable<T: Component + Clone>(&mut self)
+    where
+        T::Storage: Default;
+
+    /// Makes `clear` reset the resource `R`, e.g. a marker allocator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `R` hasn't been inserted.
+    fn register_reset<R: Resource + Reset>(&mut self);
+
+    /// Registers the `relation::Relations<R>` component, and removes the
+    /// relations to deleted entities with a finalizer, see
+    /// `Finalizers::on_delete`.
+    ///
+    /// Registering a relation again only registers the component.
+    fn register_relation<R: Send + Sync + 'static>(&mut self);
+
+    /// Relates `subject` to `object` with the default value of `R`,
+    /// returning the value it replaced. Fails if either entity is dead.
+    ///
+    /// Use `Storage::relate` on the `Relations<R>` storage to relate
+    /// entities with other values.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::{prelude::*, relation::Relations};
+    ///
+    /// #[derive(Default)]
+    /// struct Likes;
+    ///
+    /// let mut world = World::new();
+    /// world.register_relation::<Likes>();
+    ///
+    /// let a = world.create_entity().build();
+    /// let b = world.create_entity().build();
+    /// world.relate::<Likes>(a, b).unwrap();
+    /// assert!(world.read_storage::<Relations<Likes>>().related(a, b).is_some());
+    ///
+    /// world.delete_entity(b).unwrap();
+    /// assert!(world.read_storage::<Relations<Likes>>().is_empty());
+    /// ```
+    fn relate<R: Default + Send + Sync + 'static>(
+        &self,
+        subject: Entity,
+        object: Entity,
+    ) -> Result<Option<R>, Error>;
+
+    /// Releases the memory all storages don't need for their current
+    /// components.
+    ///
+    /// Storages keep their peak memory, so this is worth calling after
+    /// `maintain` deleted many entities which won't be replaced soon.
+    fn shrink_storages(&mut self);
+
+    /// Returns the memory used by the storages of all registered components,
+    /// the largest first, e.g. for finding the components which bloat a
+    /// world.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// struct Pos(f32, f32);
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// #[derive(Default)]
+    /// struct Enemy;
+    ///
+    /// impl Component for Enemy {
+    ///     type Storage = NullStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// world.register::<Enemy>();
+    /// for i in 0..100 {
+    ///     world.create_entity().with(Pos(i as f32, 0.0)).with(Enemy).build();
+    /// }
+    ///
+    /// let report = world.memory_report();
+    /// assert_eq!(report.components.len(), 2);
+    /// assert!(report.components[0].component.ends_with("Pos"));
+    /// assert!(report.total_bytes() >= 100 * std::mem::size_of::<Pos>());
+    /// ```
+    fn memory_report(&self) -> MemoryReport;
+
     /// Adds a resource to the world.
     ///
     /// If the resource already exists it will be overwritten.
@@ -259,11 +412,128 @@ pub trait WorldExt {
     fn delete_entity(&mut self, entity: Entity) -> Result<(), WrongGeneration>;
 
     /// Deletes the specified entities and their components.
+    ///
+    /// If one of the entities is dead, returns an error without deleting
+    /// any of them.
     fn delete_entities(&mut self, delete: &[Entity]) -> Result<(), WrongGeneration>;
 
+    /// Deletes `root` with its children, their children and so on, like
+    /// `delete_entities`. The children are found with their
+    /// `hierarchy::Parent` components, even if the `Hierarchy` isn't up to
+    /// date yet.
+    ///
+    /// Returns an error if `root` is dead, without deleting anything.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::{hierarchy::Parent, prelude::*};
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Parent>();
+    ///
+    /// let vehicle = world.create_entity().build();
+    /// let wheel = world.create_entity().with(Parent(vehicle)).build();
+    /// let bolt = world.create_entity().with(Parent(wheel)).build();
+    /// let other = world.create_entity().build();
+    ///
+    /// world.delete_hierarchy(vehicle).unwrap();
+    /// assert!(!world.is_alive(wheel));
+    /// assert!(!world.is_alive(bolt));
+    /// assert!(world.is_alive(other));
+    /// ```
+    fn delete_hierarchy(&mut self, root: Entity) -> Result<(), WrongGeneration>;
+
     /// Deletes all entities and their components.
+    ///
+    /// Storages are cleared at once instead of looking up the components of
+    /// every entity, unless they retain removed components. The indices of
+    /// the entities are reused afterwards, but their generations are kept,
+    /// so the deleted entities stay dead.
     fn delete_all(&mut self);
 
+    /// Deletes all entities like `delete_all` and resets the world for a
+    /// fresh start, e.g. when restarting a level, without building a new
+    /// `World` and `Dispatcher`.
+    ///
+    /// Storages drop all components, including the ones retained from
+    /// deleted entities, but stay registered with their configuration, and
+    /// the resources registered with `register_reset` are reset. Other
+    /// resources are kept as they are.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "serde")]
+    /// # {
+    /// use specs::{
+    ///     prelude::*,
+    ///     saveload::{MarkedBuilder, Marker, SimpleMarker, SimpleMarkerAllocator},
+    /// };
+    ///
+    /// struct Pos(i32, i32);
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// struct Level(u32);
+    /// struct Net;
+    ///
+    /// let mut world = World::new();
+    /// world.register::<Pos>();
+    /// world.register::<SimpleMarker<Net>>();
+    /// world.insert(SimpleMarkerAllocator::<Net>::new());
+    /// world.register_reset::<SimpleMarkerAllocator<Net>>();
+    /// world.insert(Level(3));
+    ///
+    /// let e = world
+    ///     .create_entity()
+    ///     .with(Pos(1, 2))
+    ///     .marked::<SimpleMarker<Net>>()
+    ///     .build();
+    /// world.clear();
+    ///
+    /// assert!(!world.is_alive(e));
+    /// assert!(world.read_storage::<Pos>().is_empty());
+    /// assert_eq!(world.read_resource::<Level>().0, 3);
+    ///
+    /// let e = world.create_entity().marked::<SimpleMarker<Net>>().build();
+    /// assert_eq!(world.read_storage::<SimpleMarker<Net>>().get(e).unwrap().id(), 0);
+    /// # }
+    /// ```
+    fn clear(&mut self);
+
+    /// Creates a new entity with clones of all components of `entity` which
+    /// were registered with `register_cloneable`, e.g. for duplicating an
+    /// object in an editor. Other components aren't copied.
+    ///
+    /// Returns an error if `entity` is dead.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use specs::prelude::*;
+    ///
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// struct Pos(f32, f32);
+    ///
+    /// impl Component for Pos {
+    ///     type Storage = VecStorage<Self>;
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// world.register_cloneable::<Pos>();
+    ///
+    /// let original = world.create_entity().with(Pos(1.0, 2.0)).build();
+    /// let copy = world.clone_entity(original).unwrap();
+    /// assert_eq!(world.read_storage::<Pos>().get(copy), Some(&Pos(1.0, 2.0)));
+    /// ```
+    fn clone_entity(&mut self, entity: Entity) -> Result<Entity, WrongGeneration>;
+
+    /// Returns the entity named `name` in the `Names` resource.
+    fn entity_by_name(&self, name: &str) -> Option<Entity>;
+
     /// Checks if an entity is alive.
     /// Please note that atomically created or deleted entities
     /// (the ones created / deleted with the `Entities` struct)
@@ -285,6 +555,10 @@ pub trait WorldExt {
     /// Also removes all the abandoned components.
     ///
     /// Additionally, `LazyUpdate` will be merged.
+    ///
+    /// The `Finalizers` of the deleted entities run before their components
+    /// are dropped. The deleted entities are published to `DeletedEntities`,
+    /// and `WorldStats` is updated last.
     fn maintain(&mut self);
 
     #[doc(hidden)]
@@ -297,6 +571,11 @@ impl WorldExt for World {
         world.insert(EntitiesRes::default());
         world.insert(MetaTable::<dyn AnyStorage>::default());
         world.insert(LazyUpdate::default());
+        world.insert(DeletedEntities::default());
+        world.insert(Names::default());
+        world.insert(Finalizers::default());
+        world.insert(ComponentRegistry::default());
+        world.insert(WorldStats::default());
 
         world
     }
@@ -319,6 +598,80 @@ impl WorldExt for World {
             .or_insert_with(Default::default);
         self.fetch_mut::<MetaTable<dyn AnyStorage>>()
             .register(&*self.fetch::<MaskedStorage<T>>());
+        self.entry::<ComponentRegistry>()
+            .or_insert_with(Default::default)
+            .add::<T>();
+    }
+
+    fn register_with_capacity<T: Component>(&mut self, capacity: usize)
+    where
+        T::Storage: Default,
+    {
+        self.register::<T>();
+        self.write_storage::<T>().reserve(capacity);
+    }
+
+    fn register_cloneable<T: Component + Clone>(&mut self)
+    where
+        T::Storage: Default,
+    {
+        self.register::<T>();
+        self.entry::<MetaTable<dyn CloneStorage>>()
+            .or_insert_with(Default::default);
+        self.fetch_mut::<MetaTable<dyn CloneStorage>>()
+            .register(&*self.fetch::<MaskedStorage<T>>());
+    }
+
+    fn register_reset<R: Resource + Reset>(&mut self) {
+        self.entry::<MetaTable<dyn Reset>>()
+            .or_insert_with(Default::default);
+        self.fetch_mut::<MetaTable<dyn Reset>>()
+            .register(&*self.fetch::<R>());
+    }
+
+    fn register_relation<R: Send + Sync + 'static>(&mut self) {
+        let registered = self.has_value::<MaskedStorage<Relations<R>>>();
+        self.register::<Relations<R>>();
+        if !registered {
+            self.entry::<Finalizers>()
+                .or_insert_with(Default::default)
+                .on_delete(forget_objects::<R>);
+        }
+    }
+
+    fn relate<R: Default + Send + Sync + 'static>(
+        &self,
+        subject: Entity,
+        object: Entity,
+    ) -> Result<Option<R>, Error> {
+        self.write_storage::<Relations<R>>()
+            .relate(subject, object, R::default())
+    }
+
+    fn shrink_storages(&mut self) {
+        self.entry::<MetaTable<dyn AnyStorage>>()
+            .or_insert_with(Default::default);
+        for storage in self
+            .fetch_mut::<MetaTable<dyn AnyStorage>>()
+            .iter_mut(self)
+        {
+            storage.shrink_to_fit();
+        }
+    }
+
+    fn memory_report(&self) -> MemoryReport {
+        let mut report = MemoryReport::default();
+        if let Some(storages) = self.try_fetch::<MetaTable<dyn AnyStorage>>() {
+            report.components = storages
+                .iter(self)
+                .filter_map(|storage| storage.memory())
+                .collect();
+        }
+        report
+            .components
+            .sort_by_key(|component| Reverse(component.stats.total_bytes()));
+
+        report
     }
 
     fn add_resource<T: Resource>(&mut self, res: T) {
@@ -372,20 +725,95 @@ impl WorldExt for World {
     }
 
     fn delete_entities(&mut self, delete: &[Entity]) -> Result<(), WrongGeneration> {
+        // Check all entities first, so the components of the entity which
+        // reused the index of a dead one aren't dropped.
+        {
+            let alloc = &self.entities().alloc;
+            if let Some(&dead) = delete.iter().find(|&&e| !alloc.is_alive(e)) {
+                return alloc.del_err(dead);
+            }
+        }
+
+        finalizers::run(self, delete);
         self.delete_components(delete);
+        self.entities_mut().alloc.kill(delete)?;
+        forget_deleted(self, delete);
+
+        Ok(())
+    }
+
+    fn delete_hierarchy(&mut self, root: Entity) -> Result<(), WrongGeneration> {
+        let mut delete = vec![root];
+        if self.entities().is_alive(root) && self.has_value::<MaskedStorage<Parent>>() {
+            let descendants = subtree(&self.entities(), &self.read_storage::<Parent>(), &delete);
+            delete.extend(descendants);
+        }
 
-        self.entities_mut().alloc.kill(delete)
+        self.delete_entities(&delete)
     }
 
     fn delete_all(&mut self) {
         use crate::join::Join;
 
         let entities: Vec<_> = self.entities().join().collect();
+        finalizers::run(self, &entities);
 
-        self.delete_entities(&entities).expect(
+        self.entry::<MetaTable<dyn AnyStorage>>()
+            .or_insert_with(Default::default);
+        for storage in self
+            .fetch_mut::<MetaTable<dyn AnyStorage>>()
+            .iter_mut(self)
+        {
+            storage.drop_all(&entities);
+        }
+
+        self.entities_mut().alloc.kill(&entities).expect(
             "Bug: previously collected entities are not valid \
              even though access should be exclusive",
         );
+        forget_deleted(self, &entities);
+    }
+
+    fn clear(&mut self) {
+        self.delete_all();
+
+        for storage in self
+            .fetch_mut::<MetaTable<dyn AnyStorage>>()
+            .iter_mut(self)
+        {
+            storage.reset();
+        }
+        if let Some(resources) = self.try_fetch_mut::<MetaTable<dyn Reset>>() {
+            for resource in resources.iter_mut(self) {
+                resource.reset();
+            }
+        }
+    }
+
+    fn clone_entity(&mut self, entity: Entity) -> Result<Entity, WrongGeneration> {
+        if !self.is_alive(entity) {
+            return Err(WrongGeneration {
+                action: "clone",
+                actual_gen: self.entities().entity(entity.id()).gen(),
+                entity,
+            });
+        }
+
+        let clone = self.entities_mut().alloc.allocate();
+        self.entry::<MetaTable<dyn CloneStorage>>()
+            .or_insert_with(Default::default);
+        for storage in self
+            .fetch_mut::<MetaTable<dyn CloneStorage>>()
+            .iter_mut(self)
+        {
+            storage.clone_component(entity.id(), clone.id());
+        }
+
+        Ok(clone)
+    }
+
+    fn entity_by_name(&self, name: &str) -> Option<Entity> {
+        self.try_fetch::<Names>().and_then(|names| names.get(name))
     }
 
     fn is_alive(&self, e: Entity) -> bool {
@@ -396,13 +824,42 @@ impl WorldExt for World {
     }
 
     fn maintain(&mut self) {
+        self.entry::<MetaTable<dyn AnyStorage>>()
+            .or_insert_with(Default::default);
+        for storage in self
+            .fetch_mut::<MetaTable<dyn AnyStorage>>()
+            .iter_mut(self)
+        {
+            storage.maintain();
+        }
+
+        // Finalize the entities while they are alive, including the ones
+        // deleted by finalizers.
+        let mut finalized = BitSet::new();
+        loop {
+            let pending: Vec<_> = self
+                .entities()
+                .alloc
+                .pending_deletions()
+                .into_iter()
+                .filter(|e| !finalized.add(e.id()))
+                .collect();
+            if pending.is_empty() {
+                break;
+            }
+            finalizers::run(self, &pending);
+        }
+
         let deleted = self.entities_mut().alloc.merge();
         if !deleted.is_empty() {
             self.delete_components(&deleted);
+            forget_deleted(self, &deleted);
         }
 
         let lazy = self.write_resource::<LazyUpdate>().clone();
         lazy.maintain(self);
+
+        update_stats(self);
     }
 
     fn delete_components(&mut self, delete: &[Entity]) {
@@ -416,3 +873,37 @@ impl WorldExt for World {
         }
     }
 }
+
+fn forget_deleted(world: &World, deleted: &[Entity]) {
+    if let Some(mut names) = world.try_fetch_mut::<Names>() {
+        names.forget(deleted);
+    }
+    #[cfg(feature = "serde_json")]
+    crate::dynamic::forget(world, deleted);
+    if let Some(mut stats) = world.try_fetch_mut::<WorldStats>() {
+        stats.count_deleted(deleted.len());
+    }
+    if let Some(mut channel) = world.try_fetch_mut::<DeletedEntities>() {
+        channel.publish(deleted);
+    }
+}
+
+fn update_stats(world: &World) {
+    use crate::join::Join;
+
+    let mut stats = match world.try_fetch_mut::<WorldStats>() {
+        Some(stats) => stats,
+        None => return,
+    };
+    let components = world
+        .try_fetch::<ComponentRegistry>()
+        .map(|registry| {
+            registry
+                .iter()
+                .map(|info| (info.name(), info.count(world)))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    stats.update(world.entities().join().len(), components);
+}
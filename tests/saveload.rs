@@ -13,8 +13,10 @@ extern crate specs_derive;
 mod tests {
     #[cfg(feature = "uuid_entity")]
     use spocs::saveload::UuidMarker;
+    use std::collections::HashMap;
+
     use spocs::{
-        saveload::{ConvertSaveload, Marker, SimpleMarker},
+        saveload::{ConvertEntities, ConvertSaveload, Marker, SimpleMarker},
         Builder, Entity, World, WorldExt,
     };
 
@@ -104,6 +106,29 @@ mod tests {
         B(NamedContainsSerdeType)
     }
 
+    #[derive(ConvertSaveload)]
+    struct EntityContainers {
+        e: Entity,
+        #[convert_save_load_entities]
+        parent: Option<Entity>,
+        #[convert_save_load_entities]
+        children: Vec<Entity>,
+        #[convert_save_load_entities]
+        named: HashMap<String, Entity>,
+    }
+
+    #[derive(ConvertSaveload)]
+    struct EntityContainersTuple(#[convert_save_load_entities] Vec<Entity>, u32);
+
+    #[derive(ConvertSaveload)]
+    enum EntityContainersEnum {
+        A(#[convert_save_load_entities] Vec<Entity>),
+        B {
+            #[convert_save_load_entities]
+            parent: Option<Entity>,
+        },
+    }
+
     #[derive(ConvertSaveload)]
     struct Generic<E: EntityLike>(E);
 
@@ -139,6 +164,15 @@ mod tests {
         // so no need to test anything but unit
         black_box::<M, _>(AnEnum::Unit);
         black_box::<M, _>(Generic(entity));
+        black_box::<M, _>(EntityContainers {
+            e: entity,
+            parent: Some(entity),
+            children: vec![entity],
+            named: HashMap::new(),
+        });
+        black_box::<M, _>(EntityContainersTuple(vec![entity], 5));
+        black_box::<M, _>(EntityContainersEnum::A(vec![entity]));
+        black_box::<M, _>(EntityContainersEnum::B { parent: None });
     }
 
     fn black_box<M, T: ConvertSaveload<M>>(_item: T) {}
@@ -395,6 +395,659 @@ fn par_join_two_components() {
     );
 }
 
+#[test]
+fn join_with_maybe() {
+    let mut world = create_world();
+    let e1 = world
+        .create_entity()
+        .with(CompInt(1))
+        .with(CompBool(false))
+        .build();
+    let e2 = world.create_entity().with(CompInt(2)).build();
+    world.create_entity().with(CompBool(true)).build();
+
+    let int = world.read_storage::<CompInt>();
+    let mut boolean = world.write_storage::<CompBool>();
+    let joined: Vec<_> = (&int, boolean.maybe())
+        .join()
+        .map(|(int, boolean)| (int.0, boolean.map(|c| c.0)))
+        .collect();
+    assert_eq!(joined, vec![(1, Some(false)), (2, None)]);
+
+    for (int, boolean) in (&int, (&mut boolean).maybe()).join() {
+        if let Some(boolean) = boolean {
+            boolean.0 = int.0 % 2 == 1;
+        }
+    }
+    assert_eq!(boolean.get(e1), Some(&CompBool(true)));
+    assert_eq!(boolean.get(e2), None);
+}
+
+#[test]
+fn join_adapters() {
+    let mut world = create_world();
+    for i in 0..100 {
+        let builder = world.create_entity().with(CompInt(i));
+        if i % 2 == 0 {
+            builder.with(CompBool(i % 4 == 0)).build();
+        } else {
+            builder.build();
+        }
+    }
+
+    let int = world.read_storage::<CompInt>();
+    let boolean = world.read_storage::<CompBool>();
+    let small = (&int).filter_ids(|id| id < 50).map_join(|int| int.0);
+    let flags = (&boolean).map_join(|boolean| boolean.0).filter_ids(|id| id % 10 == 0);
+    let joined: Vec<_> = (small, flags).join().collect();
+    assert_eq!(
+        joined,
+        vec![(0, true), (10, false), (20, true), (30, false), (40, true)]
+    );
+
+    // Ids which aren't in the mask are never passed to the filter.
+    let visited = std::cell::Cell::new(0);
+    let count = (&boolean)
+        .filter_ids(|_| {
+            visited.set(visited.get() + 1);
+            true
+        })
+        .join()
+        .count();
+    assert_eq!((count, visited.get()), (50, 50));
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::iter::ParallelIterator;
+
+        let sum: i32 = (&int)
+            .map_join(|int| i32::from(int.0))
+            .filter_ids(|id| id >= 90)
+            .par_join()
+            .sum();
+        assert_eq!(sum, (90..100).sum::<i32>());
+    }
+}
+
+#[test]
+fn join_many_storages() {
+    macro_rules! join_many {
+        ($($comp:ident => $sto:ident,)*) => {{
+            $(
+                struct $comp(u32);
+
+                impl Component for $comp {
+                    type Storage = VecStorage<Self>;
+                }
+            )*
+
+            let mut world = World::new();
+            $(world.register::<$comp>();)*
+            for i in 0..3 {
+                world.create_entity()$(.with($comp(i)))*.build();
+            }
+            // This entity is missing the last component.
+            world.create_entity().with(C0(3)).build();
+
+            let ($($sto,)*) = ($(world.read_storage::<$comp>(),)*);
+            let sums: Vec<u32> = ($(&$sto,)*)
+                .join()
+                .map(|($($sto,)*)| 0 $(+ $sto.0)*)
+                .collect();
+            sums
+        }};
+    }
+
+    let sixteen = join_many!(
+        C0 => s0,
+        C1 => s1,
+        C2 => s2,
+        C3 => s3,
+        C4 => s4,
+        C5 => s5,
+        C6 => s6,
+        C7 => s7,
+        C8 => s8,
+        C9 => s9,
+        C10 => s10,
+        C11 => s11,
+        C12 => s12,
+        C13 => s13,
+        C14 => s14,
+        C15 => s15,
+    );
+    assert_eq!(sixteen, vec![0, 16, 32]);
+    let seventeen = join_many!(
+        C0 => s0,
+        C1 => s1,
+        C2 => s2,
+        C3 => s3,
+        C4 => s4,
+        C5 => s5,
+        C6 => s6,
+        C7 => s7,
+        C8 => s8,
+        C9 => s9,
+        C10 => s10,
+        C11 => s11,
+        C12 => s12,
+        C13 => s13,
+        C14 => s14,
+        C15 => s15,
+        C16 => s16,
+    );
+    assert_eq!(seventeen, vec![0, 17, 34]);
+}
+
+#[test]
+fn bitset_in_place_ops() {
+    use specs::bitset::BitSetLike;
+
+    let ids = |set: &BitSet| set.iter().collect::<Vec<_>>();
+    let mut set: BitSet = (0..100_000).filter(|id| id % 7 == 0).collect();
+    let other: BitSet = (50_000..200_000).collect();
+
+    let mut expected: Vec<_> = ids(&set).into_iter().filter(|id| other.contains(*id)).collect();
+    set.intersect_with(&other);
+    assert_eq!(ids(&set), expected);
+
+    set.clear_range(60_000..99_000);
+    expected.retain(|&id| !(60_000..99_000).contains(&id));
+    assert_eq!(ids(&set), expected);
+
+    set.union_with(&(0..10).collect());
+    expected.splice(0..0, 0..10);
+    assert_eq!(ids(&set), expected);
+
+    // Emptying the set clears the upper layers too.
+    set.difference_with(&other);
+    set.clear_range(0..5);
+    assert_eq!(ids(&set), vec![5, 6, 7, 8, 9]);
+    set.clear_range(0..!0);
+    assert!(set.is_empty());
+}
+
+#[test]
+fn bitset_ranges() {
+    use specs::bitset::{BitSetLike, BitSetRange};
+
+    let mut set = BitSet::new();
+    set.add_range(10..300_000);
+    set.add_range(5..5);
+    assert_eq!((&set).iter().count(), 300_000 - 10);
+    assert!(!set.contains(9) && set.contains(10) && set.contains(299_999));
+    assert!(!set.contains(300_000));
+
+    assert!(set.remove_range(4_000..260_000));
+    assert!(!set.remove_range(4_000..260_000));
+    let expected: Vec<_> = (10..4_000).chain(260_000..300_000).collect();
+    assert_eq!((&set).iter().collect::<Vec<_>>(), expected);
+
+    let in_range: Vec<_> = set.iter_range(3_990..260_005).collect();
+    assert_eq!(
+        in_range,
+        (3_990..4_000).chain(260_000..260_005).collect::<Vec<_>>()
+    );
+    assert_eq!(set.iter_range(100_000..200_000).count(), 0);
+    assert_eq!((&set, BitSetRange(0..20)).join().count(), 10);
+
+    set.remove_range(0..!0);
+    assert!(set.is_empty());
+}
+
+#[test]
+fn bitset_split_ranges() {
+    use specs::bitset::{split_ranges, BitSetOr, BitSetRange};
+
+    let a: BitSet = (0..1_000).chain(500_000..501_000).collect();
+    let b: BitSet = (5_000_000..5_000_100).collect();
+    let set = BitSetOr(&a, &b);
+    let all: Vec<_> = (&set).join().collect();
+
+    for parts in 1..10 {
+        let ranges = split_ranges(&set, parts);
+        assert!(!ranges.is_empty() && ranges.len() <= parts);
+        assert!(ranges.windows(2).all(|w| w[0].end <= w[1].start));
+
+        let joined: Vec<_> = ranges
+            .into_iter()
+            .flat_map(|range| (&set, BitSetRange(range)).join().map(|(id, _)| id))
+            .collect();
+        assert_eq!(joined, all);
+    }
+
+    assert!(split_ranges(&BitSet::new(), 4).is_empty());
+}
+
+#[test]
+fn counted_bitset() {
+    use specs::bitset::CountedBitSet;
+
+    let set: BitSet = (0..100_000).filter(|id| id % 3 == 0).collect();
+    let mut counted = CountedBitSet::from(set);
+    assert_eq!(counted.len(), 33_334);
+
+    assert!(!counted.add(1));
+    assert!(counted.add(3));
+    assert!(counted.remove(0));
+    assert!(!counted.remove(0));
+    assert_eq!(counted.len(), (&counted).join().count());
+
+    counted.clear();
+    assert!(counted.is_empty());
+    let counted: CountedBitSet = vec![7, 7, 8].into_iter().collect();
+    assert_eq!(counted.len(), 2);
+}
+
+#[test]
+fn cow_bitset_snapshots() {
+    use specs::bitset::CowBitSet;
+
+    let mut set: CowBitSet = (0..100_000).filter(|id| id % 3 == 0).collect();
+    let before = set.snapshot();
+    assert!(set.remove(3));
+    assert!(!set.remove(3));
+    assert!(!set.add(99_999 + 1));
+    assert!(set.add(0));
+
+    assert!(before.contains(3) && !before.contains(100_000));
+    assert!(!set.contains(3) && set.contains(100_000));
+    assert_eq!((&before).join().count(), 33_334);
+    assert_eq!((&set).join().count(), 33_334);
+
+    // Emptying a block clears the upper layers, too.
+    let mut small: CowBitSet = vec![5, 70_000].into_iter().collect();
+    let snapshot = small.snapshot();
+    small.remove(70_000);
+    assert_eq!((&small).join().collect::<Vec<_>>(), vec![5]);
+    assert_eq!((&snapshot).join().collect::<Vec<_>>(), vec![5, 70_000]);
+    small.clear();
+    assert_eq!((&small).join().count(), 0);
+}
+
+#[test]
+fn lazy_builder_from_systems() {
+    struct Spawn(i8);
+
+    impl<'a> System<'a> for Spawn {
+        type SystemData = (Entities<'a>, Read<'a, LazyUpdate>);
+
+        fn run(&mut self, (entities, lazy): Self::SystemData) {
+            for _ in 0..10 {
+                lazy.create_entity(&entities)
+                    .with(CompInt(self.0))
+                    .with(CompBool(self.0 > 0))
+                    .build();
+            }
+        }
+    }
+
+    let mut world = create_world();
+    let mut dispatcher = DispatcherBuilder::new()
+        .with(Spawn(-1), "spawn_a", &[])
+        .with(Spawn(1), "spawn_b", &[])
+        .build();
+    dispatcher.dispatch(&world);
+    assert_eq!(world.read_storage::<CompInt>().count(), 0);
+
+    world.maintain();
+    let int = world.read_storage::<CompInt>();
+    let boolean = world.read_storage::<CompBool>();
+    let spawned: Vec<_> = (&int, &boolean).join().map(|(i, b)| (i.0, b.0)).collect();
+    assert_eq!(spawned.len(), 20);
+    assert!(spawned.iter().all(|&(i, b)| b == (i > 0)));
+}
+
+#[test]
+fn derived_bundles() {
+    #[derive(specs_derive::Bundle)]
+    struct Named {
+        int: CompInt,
+        boolean: CompBool,
+    }
+
+    #[derive(specs_derive::Bundle)]
+    struct Tuple(CompInt, #[bundle] Named);
+
+    let mut world = create_world();
+    let named = world
+        .create_entity()
+        .with_bundle(Named {
+            int: CompInt(1),
+            boolean: CompBool(true),
+        })
+        .build();
+    let nested = world
+        .create_entity()
+        .with_bundle(Tuple(
+            CompInt(2),
+            Named {
+                int: CompInt(3),
+                boolean: CompBool(false),
+            },
+        ))
+        .build();
+
+    let int = world.read_storage::<CompInt>();
+    let boolean = world.read_storage::<CompBool>();
+    assert_eq!(int.get(named), Some(&CompInt(1)));
+    assert_eq!(boolean.get(named), Some(&CompBool(true)));
+    // Later components overwrite earlier ones, like with `with`.
+    assert_eq!(int.get(nested), Some(&CompInt(3)));
+    assert_eq!(boolean.get(nested), Some(&CompBool(false)));
+}
+
+#[test]
+fn hierarchy_tracks_parents() {
+    use specs::hierarchy::{Hierarchy, HierarchyEvent, HierarchyMaintenanceSystem, Parent};
+
+    let mut world = create_world();
+    let mut system = HierarchyMaintenanceSystem::new();
+    System::setup(&mut system, &mut world);
+    let mut reader = world.write_resource::<Hierarchy>().register_reader();
+
+    let a = world.create_entity().build();
+    let b = world.create_entity().build();
+    let c = world.create_entity().with(Parent(b)).build();
+    world.write_storage::<Parent>().insert(b, Parent(a)).unwrap();
+    system.run_now(&world);
+    {
+        let hierarchy = world.read_resource::<Hierarchy>();
+        assert_eq!(hierarchy.all(), &[b, c]);
+        assert_eq!(
+            hierarchy.read(&mut reader).cloned().collect::<Vec<_>>(),
+            vec![HierarchyEvent::Modified(c), HierarchyEvent::Modified(b)]
+        );
+    }
+
+    // Reparenting `c` and removing the parent of `b`.
+    world.write_storage::<Parent>().insert(c, Parent(a)).unwrap();
+    world.write_storage::<Parent>().remove(b);
+    system.run_now(&world);
+    {
+        let hierarchy = world.read_resource::<Hierarchy>();
+        assert_eq!(hierarchy.children(a), &[c]);
+        assert!(hierarchy.children(b).is_empty());
+        assert_eq!(hierarchy.parent(b), None);
+        assert_eq!(
+            hierarchy.read(&mut reader).cloned().collect::<Vec<_>>(),
+            vec![HierarchyEvent::Modified(c), HierarchyEvent::Removed(b)]
+        );
+    }
+
+    // Deleted children leave the hierarchy, even if their index is reused.
+    world.entities().delete(c).unwrap();
+    world.maintain();
+    let d = world.create_entity().with(Parent(b)).build();
+    assert_eq!(d.id(), c.id());
+    system.run_now(&world);
+    let hierarchy = world.read_resource::<Hierarchy>();
+    assert_eq!(hierarchy.parent(c), None);
+    assert_eq!(hierarchy.parent(d), Some(b));
+    assert!(hierarchy.children(a).is_empty());
+    assert_eq!(hierarchy.all(), &[d]);
+    assert_eq!(
+        hierarchy.read(&mut reader).cloned().collect::<Vec<_>>(),
+        vec![HierarchyEvent::Removed(c), HierarchyEvent::Modified(d)]
+    );
+}
+
+#[test]
+fn hierarchy_skips_cycles() {
+    use specs::hierarchy::{Hierarchy, HierarchyMaintenanceSystem, Parent};
+
+    let mut world = create_world();
+    let mut system = HierarchyMaintenanceSystem::new();
+    System::setup(&mut system, &mut world);
+
+    let a = world.create_entity().build();
+    let b = world.create_entity().with(Parent(a)).build();
+    world.write_storage::<Parent>().insert(a, Parent(b)).unwrap();
+    let c = world.create_entity().build();
+    let d = world.create_entity().with(Parent(c)).build();
+    system.run_now(&world);
+
+    let hierarchy = world.read_resource::<Hierarchy>();
+    assert_eq!(hierarchy.all(), &[d]);
+    assert_eq!(hierarchy.parent(a), Some(b));
+}
+
+#[test]
+fn hierarchy_cascade_deletion() {
+    use specs::hierarchy::{Hierarchy, HierarchyMaintenanceSystem, Parent};
+
+    let mut world = create_world();
+    let mut system = HierarchyMaintenanceSystem::new().cascade_deletion(true);
+    System::setup(&mut system, &mut world);
+
+    let a = world.create_entity().build();
+    let b = world.create_entity().with(Parent(a)).with(CompInt(1)).build();
+    let c = world.create_entity().with(Parent(b)).build();
+    let d = world.create_entity().with(Parent(a)).build();
+    let e = world.create_entity().build();
+    system.run_now(&world);
+    assert_eq!(world.read_resource::<Hierarchy>().descendants(a), vec![b, d, c]);
+
+    world.entities().delete(b).unwrap();
+    world.maintain();
+    assert!(!world.is_alive(b) && !world.is_alive(c));
+    assert!(world.is_alive(a) && world.is_alive(d));
+    assert!(world.read_storage::<CompInt>().is_empty());
+
+    // Deleting right away deletes the descendants with the next maintain.
+    world.delete_entity(a).unwrap();
+    assert!(world.is_alive(d));
+    world.maintain();
+    assert!(!world.is_alive(d));
+    assert!(world.is_alive(e));
+
+    system.run_now(&world);
+    assert!(world.read_resource::<Hierarchy>().all().is_empty());
+}
+
+#[test]
+fn delete_hierarchy() {
+    use specs::hierarchy::{Hierarchy, HierarchyMaintenanceSystem, Parent};
+
+    let mut world = create_world();
+    let mut system = HierarchyMaintenanceSystem::new();
+    System::setup(&mut system, &mut world);
+
+    let a = world.create_entity().build();
+    let b = world.create_entity().with(Parent(a)).build();
+    system.run_now(&world);
+    // Not in the `Hierarchy` yet.
+    let c = world.create_entity().with(Parent(b)).build();
+    // A cycle below `a`.
+    let d = world.create_entity().with(Parent(c)).build();
+    world.write_storage::<Parent>().insert(c, Parent(d)).unwrap();
+    world.write_storage::<Parent>().insert(d, Parent(b)).unwrap();
+
+    world.delete_hierarchy(a).unwrap();
+    assert!([a, b, c, d].iter().all(|&e| !world.is_alive(e)));
+    assert!(world.delete_hierarchy(a).is_err());
+
+    system.run_now(&world);
+    let hierarchy = world.read_resource::<Hierarchy>();
+    assert!(hierarchy.all().is_empty());
+    assert!(hierarchy.descendants(a).is_empty());
+}
+
+#[test]
+fn hierarchy_propagation() {
+    use specs::hierarchy::{HierarchyMaintenanceSystem, Parent, PropagationSystem};
+
+    struct Local(i32);
+
+    impl Component for Local {
+        type Storage = FlaggedStorage<Self>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Global(i32);
+
+    impl Component for Global {
+        type Storage = VecStorage<Self>;
+    }
+
+    let mut world = create_world();
+    let mut hierarchy = HierarchyMaintenanceSystem::new();
+    let mut propagation = PropagationSystem::new(|parent: Option<&Global>, local: &Local| {
+        Global(parent.map_or(0, |p| p.0) * 10 + local.0)
+    });
+    System::setup(&mut hierarchy, &mut world);
+    System::setup(&mut propagation, &mut world);
+    let mut run = |world: &World| {
+        hierarchy.run_now(world);
+        propagation.run_now(world);
+    };
+
+    let a = world.create_entity().with(Local(1)).build();
+    let b = world.create_entity().with(Local(2)).with(Parent(a)).build();
+    let c = world.create_entity().with(Local(3)).with(Parent(b)).build();
+    let d = world.create_entity().with(Local(4)).build();
+    run(&world);
+    let globals = |world: &World| -> Vec<Option<i32>> {
+        let globals = world.read_storage::<Global>();
+        [a, b, c, d].iter().map(|&e| globals.get(e).map(|g| g.0)).collect()
+    };
+    assert_eq!(globals(&world), vec![Some(1), Some(12), Some(123), Some(4)]);
+
+    // Only the dirty subtree is recomputed.
+    world.write_storage::<Global>().insert(d, Global(0)).unwrap();
+    world.write_storage::<Local>().get_mut(b).unwrap().0 = 5;
+    run(&world);
+    assert_eq!(globals(&world), vec![Some(1), Some(15), Some(153), Some(0)]);
+
+    // Reparenting and removing locals.
+    world.write_storage::<Parent>().insert(c, Parent(d)).unwrap();
+    world.write_storage::<Local>().remove(a);
+    run(&world);
+    assert_eq!(globals(&world), vec![None, Some(5), Some(3), Some(0)]);
+
+    world.write_storage::<Parent>().remove(b);
+    run(&world);
+    assert_eq!(globals(&world), vec![None, Some(5), Some(3), Some(0)]);
+}
+
+#[test]
+fn relations() {
+    use specs::relation::Relations;
+
+    let mut world = create_world();
+    world.register_relation::<i8>();
+
+    let a = world.create_entity().with(CompInt(1)).build();
+    let b = world.create_entity().with(CompBool(true)).build();
+    let c = world.create_entity().build();
+    {
+        let mut rel = world.write_storage::<Relations<i8>>();
+        assert_eq!(rel.relate(a, b, 1).unwrap(), None);
+        assert_eq!(rel.relate(a, b, 2).unwrap(), Some(1));
+        rel.relate(a, c, 3).unwrap();
+        rel.relate(c, b, 4).unwrap();
+    }
+
+    {
+        let rel = world.read_storage::<Relations<i8>>();
+        let bools = world.read_storage::<CompBool>();
+        let pairs: Vec<_> = rel
+            .pairs()
+            .filter(|&(_, object, _)| bools.contains(object))
+            .map(|(subject, object, &v)| (subject, object, v))
+            .collect();
+        assert_eq!(pairs, vec![(a, b, 2), (c, b, 4)]);
+        assert_eq!(rel.targets(a).collect::<Vec<_>>(), vec![b, c]);
+        assert_eq!(rel.sources(b).collect::<Vec<_>>(), vec![a, c]);
+        assert_eq!(rel.related(c, b), Some(&4));
+        assert_eq!(rel.related(b, c), None);
+    }
+
+    assert_eq!(world.write_storage::<Relations<i8>>().unrelate(c, b), Some(4));
+    assert!(!world.read_storage::<Relations<i8>>().contains(c));
+
+    world.entities().delete(b).unwrap();
+    world.maintain();
+    let mut rel = world.write_storage::<Relations<i8>>();
+    assert_eq!(rel.get(a).unwrap().edges(), &[(c, 3)]);
+    assert!(rel.relate(a, b, 5).is_err());
+}
+
+#[test]
+fn relation_traversal() {
+    use specs::relation::Relations;
+
+    #[derive(Default)]
+    struct Chains(Vec<Vec<Entity>>);
+
+    struct Resolve(Vec<Entity>);
+
+    impl<'a> System<'a> for Resolve {
+        type SystemData = (ReadStorage<'a, Relations<()>>, Write<'a, Chains>);
+
+        fn run(&mut self, (rel, mut chains): Self::SystemData) {
+            chains.0 = self.0.iter().map(|&e| rel.depth_first(e).collect()).collect();
+        }
+    }
+
+    let mut world = create_world();
+    world.register_relation::<()>();
+    let e: Vec<_> = (0..5).map(|_| world.create_entity().build()).collect();
+    for &(a, b) in &[(0, 1), (1, 2), (2, 0), (0, 3), (3, 2)] {
+        world.relate::<()>(e[a], e[b]).unwrap();
+    }
+
+    {
+        let rel = world.read_storage::<Relations<()>>();
+        assert_eq!(rel.breadth_first(e[0]).collect::<Vec<_>>(), vec![e[1], e[3], e[2]]);
+        assert_eq!(rel.depth_first(e[0]).collect::<Vec<_>>(), vec![e[1], e[2], e[3]]);
+        assert_eq!(rel.depth_first(e[4]).count(), 0);
+
+        let reachable = rel.reachable(e[2]);
+        assert!(!reachable.contains(e[2].id()));
+        assert_eq!(
+            (&world.entities(), &reachable).join().map(|(e, _)| e).collect::<Vec<_>>(),
+            vec![e[0], e[1], e[3]]
+        );
+    }
+
+    world.delete_entity(e[1]).unwrap();
+    world.insert(Chains::default());
+    Resolve(vec![e[0], e[3]]).run_now(&world);
+    assert_eq!(
+        world.read_resource::<Chains>().0,
+        vec![vec![e[3], e[2]], vec![e[2], e[0]]]
+    );
+}
+
+#[test]
+fn join_size_hint() {
+    let mut world = create_world();
+    let entities: Vec<_> = (0..10_000)
+        .map(|i| world.create_entity().with(CompInt(i as i8)).build())
+        .collect();
+    for (i, &entity) in entities.iter().enumerate() {
+        if i % 3 == 0 || (1000..5000).contains(&i) {
+            world.write_storage::<CompBool>().insert(entity, CompBool(true)).unwrap();
+        }
+    }
+
+    let int = world.read_storage::<CompInt>();
+    let boolean = world.read_storage::<CompBool>();
+    let mut join = (&int, &boolean).join();
+    let expected = (0..10_000).filter(|i| i % 3 == 0 || (1000..5000).contains(i)).count();
+    assert_eq!(join.size_hint(), (expected, Some(expected)));
+    for left in (expected - 100..expected).rev() {
+        join.next().unwrap();
+        assert_eq!(join.len(), left);
+    }
+    assert_eq!(join.len(), join.count());
+
+    let mut empty = (&int, !&int).join();
+    assert_eq!(empty.len(), 0);
+    assert!(empty.next().is_none());
+}
+
 #[test]
 #[cfg(feature = "parallel")]
 fn par_join_with_maybe() {